@@ -13,6 +13,16 @@ fn stress_integer(iterations: u64, accumulator: &mut u64) {
     }
 }
 
+#[inline(always)]
+fn stress_integer_latency(iterations: u64, accumulator: &mut u64) {
+    let multiplier = std_black_box(0x9e3779b97f4a7c15_u64);
+    let mut x = std_black_box(*accumulator | 1);
+    for _ in 0..iterations {
+        x = x.wrapping_mul(multiplier);
+    }
+    *accumulator = std_black_box(x);
+}
+
 #[inline(always)]
 fn stress_float(iterations: u64, accumulator: &mut f64) {
     for i in 0..iterations {
@@ -24,6 +34,39 @@ fn stress_float(iterations: u64, accumulator: &mut f64) {
     }
 }
 
+#[inline(always)]
+fn stress_float_simple(iterations: u64, accumulator: &mut f64) {
+    for i in 0..iterations {
+        let x = std_black_box(i as f64 + 1.0);
+        let y = x.sqrt() * 1.618033988749895;
+        let w = y * 0.5 + 0.25;
+        *accumulator = std_black_box(*accumulator + w);
+    }
+}
+
+/// `stress_float_simple` two lanes at a time, as float and mixed run it on
+/// aarch64.
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+fn stress_float_simple_neon(iterations: u64, accumulator: &mut f64) {
+    use std::arch::aarch64::*;
+
+    #[allow(unused_unsafe)]
+    let lanes = unsafe {
+        let phi = vdupq_n_f64(1.618033988749895);
+        let (half, quarter, two) = (vdupq_n_f64(0.5), vdupq_n_f64(0.25), vdupq_n_f64(2.0));
+        let mut x = vld1q_f64([1.0, 2.0].as_ptr());
+        let mut acc = vdupq_n_f64(0.0);
+        for _ in 0..iterations / 2 {
+            let y = vmulq_f64(vsqrtq_f64(std_black_box(x)), phi);
+            acc = std_black_box(vaddq_f64(acc, vfmaq_f64(quarter, y, half)));
+            x = vaddq_f64(x, two);
+        }
+        vaddvq_f64(acc)
+    };
+    *accumulator = std_black_box(*accumulator + lanes);
+}
+
 #[inline(always)]
 fn stress_memory(iterations: u64, buffer: &mut [u64]) {
     if buffer.is_empty() {
@@ -41,6 +84,96 @@ fn stress_memory(iterations: u64, buffer: &mut [u64]) {
     }
 }
 
+/// `stress_memory` with the remainder swapped for a mask; `buffer.len()`
+/// must be a power of two.
+#[inline(always)]
+fn stress_memory_masked(iterations: u64, buffer: &mut [u64]) {
+    if buffer.is_empty() {
+        return;
+    }
+
+    let mask = buffer.len() - 1;
+    let mut index = 0usize;
+
+    for i in 0..iterations {
+        let value = std_black_box(buffer[index]);
+        let new_value = value.wrapping_mul(6364136223846793005_u64).wrapping_add(i);
+        buffer[index] = std_black_box(new_value);
+        index = std_black_box(((new_value >> 17) ^ i) as usize & mask);
+    }
+}
+
+const STREAMS: usize = 8;
+const LCG_MULTS: [u64; STREAMS] = [
+    6364136223846793005,
+    2862933555777941757,
+    3202034522624059733,
+    7046029254386353087,
+    5495735621104509439,
+    1865811235122147685,
+    8121734705789632447,
+    4976774832059184573,
+];
+
+/// The --prefetch bandwidth kernel; `buffer.len()` must be a power of two.
+/// Distance 0 walks the same precomputed addresses without issuing hints, so
+/// the comparison is the prefetch alone.
+#[inline(always)]
+fn stress_bandwidth_prefetch(iterations: u64, buffer: &mut [u64], distance: usize) {
+    let mask = buffer.len() - 1;
+    let depth = distance.max(1);
+    let spacing = (buffer.len() / STREAMS) as u64;
+    let mut states: [u64; STREAMS] = std::array::from_fn(|i| spacing * i as u64);
+    let mut step = 0u64;
+    let mut advance = |states: &mut [u64; STREAMS]| {
+        let next = std::array::from_fn(|s| {
+            states[s] = states[s].wrapping_mul(LCG_MULTS[s]).wrapping_add(step);
+            (states[s] >> 17) as usize & mask
+        });
+        step += 1;
+        next
+    };
+
+    let mut ahead: Vec<[usize; STREAMS]> = (0..depth).map(|_| advance(&mut states)).collect();
+    let mut slot = 0;
+    for iter in 0..iterations {
+        let current = ahead[slot];
+        ahead[slot] = advance(&mut states);
+        if distance > 0 {
+            for &index in &ahead[slot] {
+                prefetch(&buffer[index]);
+            }
+        }
+        for (s, &index) in current.iter().enumerate() {
+            let value = std_black_box(buffer[index]);
+            buffer[index] = std_black_box(value.wrapping_mul(LCG_MULTS[s]).wrapping_add(iter));
+        }
+        slot = if slot + 1 == depth { 0 } else { slot + 1 };
+    }
+}
+
+#[inline(always)]
+fn prefetch(word: &u64) {
+    #[cfg(target_arch = "x86_64")]
+    #[allow(unused_unsafe)]
+    unsafe {
+        use std::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+        _mm_prefetch::<_MM_HINT_T0>((word as *const u64).cast());
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        std::arch::asm!(
+            "prfm pldl1keep, [{addr}]",
+            addr = in(reg) word as *const u64,
+            options(nostack, readonly, preserves_flags)
+        );
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    let _ = word;
+}
+
 fn bench_integer_workload(c: &mut Criterion) {
     c.bench_function("stress_integer_10k", |b| {
         b.iter(|| {
@@ -49,6 +182,14 @@ fn bench_integer_workload(c: &mut Criterion) {
             acc
         });
     });
+
+    c.bench_function("stress_integer_latency_10k", |b| {
+        b.iter(|| {
+            let mut acc = 0u64;
+            stress_integer_latency(std_black_box(10_000), &mut acc);
+            acc
+        });
+    });
 }
 
 fn bench_float_workload(c: &mut Criterion) {
@@ -61,6 +202,27 @@ fn bench_float_workload(c: &mut Criterion) {
     });
 }
 
+/// The scalar simple kernel everywhere, and the NEON one next to it when
+/// built for aarch64.
+fn bench_float_simple(c: &mut Criterion) {
+    c.bench_function("stress_float_simple_10k", |b| {
+        b.iter(|| {
+            let mut acc = 0.0f64;
+            stress_float_simple(std_black_box(10_000), &mut acc);
+            acc
+        });
+    });
+
+    #[cfg(target_arch = "aarch64")]
+    c.bench_function("stress_float_simple_neon_10k", |b| {
+        b.iter(|| {
+            let mut acc = 0.0f64;
+            stress_float_simple_neon(std_black_box(10_000), &mut acc);
+            acc
+        });
+    });
+}
+
 fn bench_memory_workload(c: &mut Criterion) {
     c.bench_function("stress_memory_10k", |b| {
         let mut buffer = vec![0u64; 128 * 1024].into_boxed_slice();
@@ -81,10 +243,38 @@ fn bench_memory_workload(c: &mut Criterion) {
     });
 }
 
+/// Same L1-resident buffer both ways, so the gap is the cost of the 64-bit
+/// remainder rather than of the memory access it indexes.
+fn bench_memory_indexing(c: &mut Criterion) {
+    c.bench_function("stress_memory_l1_modulo", |b| {
+        let mut buffer = vec![0u64; 4096].into_boxed_slice();
+        b.iter(|| stress_memory(std_black_box(10_000), &mut buffer));
+    });
+
+    c.bench_function("stress_memory_l1_mask", |b| {
+        let mut buffer = vec![0u64; 4096].into_boxed_slice();
+        b.iter(|| stress_memory_masked(std_black_box(10_000), &mut buffer));
+    });
+}
+
+/// A 64 MB buffer, past most last-level caches, so every access is a DRAM
+/// miss the hint can get started early.
+fn bench_bandwidth_prefetch(c: &mut Criterion) {
+    let mut buffer = vec![0u64; 8 * 1024 * 1024].into_boxed_slice();
+    for distance in [0, 4, 16] {
+        c.bench_function(&format!("stress_bandwidth_prefetch_{}", distance), |b| {
+            b.iter(|| stress_bandwidth_prefetch(std_black_box(10_000), &mut buffer, distance));
+        });
+    }
+}
+
 criterion_group!(
     benches,
     bench_integer_workload,
     bench_float_workload,
-    bench_memory_workload
+    bench_float_simple,
+    bench_memory_workload,
+    bench_memory_indexing,
+    bench_bandwidth_prefetch
 );
 criterion_main!(benches);
@@ -0,0 +1,71 @@
+//! Embeds what `locus --version --verbose` and the JSON reports need to say
+//! which binary produced a result. Anything that can't be found (no git, a
+//! crates.io tarball) is recorded as "unknown" rather than failing the build.
+
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    let git_dir = Path::new(".git");
+    for tracked in ["HEAD", "index"] {
+        let path = git_dir.join(tracked);
+        // A missing path would rerun the script on every build
+        if path.exists() {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+    // Edits change the dirty flag without touching the index
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let commit = output_of("git", &["rev-parse", "--short=12", "HEAD"]);
+    let status = output_of("git", &["status", "--porcelain", "--untracked-files=no"]);
+    let dirty = match commit.as_ref().and(status) {
+        Some(status) if status.is_empty() => "false",
+        Some(_) => "true",
+        None => "",
+    };
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+
+    // Cargo also exports these to `cargo run` and `cargo test`, where a
+    // LOCUS_<NAME> key would be read as the --<name> option
+    set("LOCUS_BUILD_GIT_COMMIT", commit.as_deref().unwrap_or("unknown"));
+    set("LOCUS_BUILD_GIT_DIRTY", dirty);
+    set(
+        "LOCUS_BUILD_RUSTC",
+        &output_of(&rustc, &["--version"]).unwrap_or_else(|| "unknown".into()),
+    );
+    set("LOCUS_BUILD_TARGET", &env_or_unknown("TARGET"));
+    set("LOCUS_BUILD_PROFILE", &env_or_unknown("PROFILE"));
+    set("LOCUS_BUILD_OPT_LEVEL", &env_or_unknown("OPT_LEVEL"));
+    set("LOCUS_BUILD_FEATURES", &enabled_features().join(","));
+}
+
+fn set(key: &str, value: &str) {
+    println!("cargo:rustc-env={}={}", key, value);
+}
+
+fn env_or_unknown(key: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Trimmed stdout, or None if the command is missing or fails.
+fn output_of(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Cargo sets CARGO_FEATURE_<NAME> for every enabled feature.
+fn enabled_features() -> Vec<String> {
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|name| name.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+    features
+}
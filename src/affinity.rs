@@ -0,0 +1,245 @@
+use clap::ValueEnum;
+
+/// Which end of the allowed CPUs worker 0 takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreOrder {
+    Ascending,
+    /// Highest CPU first, for targeting E-cores or a second CCD that sit
+    /// at the top of the numbering
+    Descending,
+}
+
+/// How pinned workers share physical cores (`--smt`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SmtPolicy {
+    /// One worker per physical core before any core gets a second
+    Spread,
+    /// Both siblings of a core before the next core
+    Compact,
+}
+
+impl SmtPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Spread => "spread",
+            Self::Compact => "compact",
+        }
+    }
+}
+
+/// How workers are pinned: from which end, and, when SMT siblings are
+/// known, how they're used (None = CPU numbering order).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pinning {
+    pub order: CoreOrder,
+    pub smt:   Option<SmtPolicy>,
+}
+
+/// `cpus` reordered for `policy`. Spread takes the first allowed thread of
+/// every core, then every core's second; compact keeps each core's allowed
+/// threads together. CPUs no group mentions go last, in their own order.
+pub fn smt_order(cpus: &[usize], siblings: &[Vec<usize>], policy: SmtPolicy) -> Vec<usize> {
+    let cores: Vec<Vec<usize>> = siblings
+        .iter()
+        .map(|group| group.iter().copied().filter(|cpu| cpus.contains(cpu)).collect())
+        .filter(|group: &Vec<usize>| !group.is_empty())
+        .collect();
+
+    let mut ordered: Vec<usize> = match policy {
+        SmtPolicy::Compact => cores.concat(),
+        SmtPolicy::Spread => {
+            let deepest = cores.iter().map(Vec::len).max().unwrap_or(0);
+            (0..deepest)
+                .flat_map(|thread| cores.iter().filter_map(move |core| core.get(thread)))
+                .copied()
+                .collect()
+        },
+    };
+    let placed = ordered.clone();
+    ordered.extend(cpus.iter().filter(|cpu| !placed.contains(cpu)));
+    ordered
+}
+
+/// The CPU per worker for `pinning`, from the allowed `cpus` and the SMT
+/// `siblings` if they could be read.
+pub fn pinned_cores(
+    num_workers: usize,
+    cpus: &[usize],
+    siblings: Option<&[Vec<usize>]>,
+    pinning: Pinning,
+) -> Vec<usize> {
+    match (pinning.smt, siblings) {
+        (Some(policy), Some(siblings)) => {
+            worker_cores(num_workers, &smt_order(cpus, siblings, policy), pinning.order)
+        },
+        _ => worker_cores(num_workers, cpus, pinning.order),
+    }
+}
+
+/// Worker `id` runs on the `id`-th allowed CPU from the `order` end,
+/// wrapping when oversubscribed.
+pub fn worker_cores(num_workers: usize, cpus: &[usize], order: CoreOrder) -> Vec<usize> {
+    if cpus.is_empty() {
+        return Vec::new();
+    }
+    (0..num_workers)
+        .map(|id| match order {
+            CoreOrder::Ascending => cpus[id % cpus.len()],
+            CoreOrder::Descending => cpus[cpus.len() - 1 - id % cpus.len()],
+        })
+        .collect()
+}
+
+/// Highest-numbered allowed CPU no worker is pinned to. The reporter and the
+/// main monitoring loop go there so they don't steal time from a worker;
+/// when every CPU has a worker they stay unpinned.
+pub fn reporter_core(worker_cores: &[usize], cpus: &[usize]) -> Option<usize> {
+    cpus.iter()
+        .rev()
+        .copied()
+        .find(|cpu| !worker_cores.contains(cpu))
+}
+
+/// "0-3,6"
+pub fn describe_cores(cores: &[usize]) -> String {
+    let mut sorted = cores.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut ranges: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < sorted.len() {
+        let first = sorted[i];
+        let mut last = first;
+        while i + 1 < sorted.len() && sorted[i + 1] == last + 1 {
+            i += 1;
+            last = sorted[i];
+        }
+        ranges.push(if first == last {
+            first.to_string()
+        } else {
+            format!("{}-{}", first, last)
+        });
+        i += 1;
+    }
+    ranges.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpus(n: usize) -> Vec<usize> {
+        (0..n).collect()
+    }
+
+    const UP: CoreOrder = CoreOrder::Ascending;
+    const DOWN: CoreOrder = CoreOrder::Descending;
+
+    #[test]
+    fn test_worker_cores_wrap() {
+        assert_eq!(worker_cores(3, &cpus(8), UP), [0, 1, 2]);
+        assert_eq!(worker_cores(5, &cpus(4), UP), [0, 1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn test_worker_cores_follow_allowed_set() {
+        assert_eq!(worker_cores(3, &[4, 5, 6, 7], UP), [4, 5, 6]);
+    }
+
+    #[test]
+    fn test_descending_worker_cores_start_at_the_top() {
+        assert_eq!(worker_cores(3, &cpus(8), DOWN), [7, 6, 5]);
+        assert_eq!(worker_cores(5, &cpus(4), DOWN), [3, 2, 1, 0, 3]);
+        assert_eq!(worker_cores(2, &[0, 2, 9, 11], DOWN), [11, 9]);
+        assert!(worker_cores(2, &[], DOWN).is_empty());
+    }
+
+    /// 4 cores with SMT, Linux-style numbering: siblings are N and N + 4.
+    fn smt_pairs() -> Vec<Vec<usize>> {
+        (0..4).map(|core| vec![core, core + 4]).collect()
+    }
+
+    #[test]
+    fn test_smt_spread_fills_every_core_first() {
+        let order = smt_order(&cpus(8), &smt_pairs(), SmtPolicy::Spread);
+        assert_eq!(order, [0, 1, 2, 3, 4, 5, 6, 7]);
+        // Windows-style numbering puts siblings next to each other
+        let adjacent: Vec<Vec<usize>> = (0..4)
+            .map(|core| vec![2 * core, 2 * core + 1])
+            .collect();
+        let order = smt_order(&cpus(8), &adjacent, SmtPolicy::Spread);
+        assert_eq!(order, [0, 2, 4, 6, 1, 3, 5, 7]);
+    }
+
+    #[test]
+    fn test_smt_compact_pairs_siblings() {
+        let order = smt_order(&cpus(8), &smt_pairs(), SmtPolicy::Compact);
+        assert_eq!(order, [0, 4, 1, 5, 2, 6, 3, 7]);
+        let pinning = Pinning {
+            order: DOWN,
+            smt:   Some(SmtPolicy::Compact),
+        };
+        assert_eq!(
+            pinned_cores(2, &cpus(8), Some(&smt_pairs()), pinning),
+            [7, 3]
+        );
+    }
+
+    #[test]
+    fn test_smt_order_keeps_to_the_allowed_cpus() {
+        // taskset 0-2,4: core 3 and CPUs 5-7 are off limits
+        let allowed = [0, 1, 2, 4];
+        assert_eq!(
+            smt_order(&allowed, &smt_pairs(), SmtPolicy::Spread),
+            [0, 1, 2, 4]
+        );
+        assert_eq!(
+            smt_order(&allowed, &smt_pairs(), SmtPolicy::Compact),
+            [0, 4, 1, 2]
+        );
+        // A CPU the topology missed still gets used, last
+        assert_eq!(
+            smt_order(&[0, 4, 9], &smt_pairs(), SmtPolicy::Compact),
+            [0, 4, 9]
+        );
+        // Without siblings the policy has nothing to go on
+        let pinning = Pinning {
+            order: UP,
+            smt:   Some(SmtPolicy::Compact),
+        };
+        assert_eq!(pinned_cores(3, &cpus(8), None, pinning), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_reporter_core_picks_highest_free_core() {
+        assert_eq!(
+            reporter_core(&worker_cores(6, &cpus(8), UP), &cpus(8)),
+            Some(7)
+        );
+        assert_eq!(reporter_core(&[0, 1, 7], &cpus(8)), Some(6));
+        assert_eq!(
+            reporter_core(&worker_cores(6, &cpus(8), DOWN), &cpus(8)),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_reporter_core_unpinned_when_all_cores_busy() {
+        assert_eq!(
+            reporter_core(&worker_cores(8, &cpus(8), UP), &cpus(8)),
+            None
+        );
+        assert_eq!(
+            reporter_core(&worker_cores(12, &cpus(8), UP), &cpus(8)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_describe_cores() {
+        assert_eq!(describe_cores(&[0, 1, 2, 3, 6]), "0-3,6");
+        assert_eq!(describe_cores(&[5]), "5");
+        assert_eq!(describe_cores(&[]), "");
+    }
+}
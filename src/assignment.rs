@@ -0,0 +1,220 @@
+use crate::registry::{self, WorkloadSpec};
+use crate::reporting;
+
+/// A set of workers that all run the same workload.
+#[derive(Debug, Clone, Copy)]
+pub struct WorkloadGroup {
+    pub spec:    &'static WorkloadSpec,
+    pub threads: usize,
+}
+
+impl WorkloadGroup {
+    /// Compute-only workloads never touch the buffer, so they skip it.
+    pub fn buffer_mb(&self, memory_mb: usize) -> usize {
+        if self.spec.needs_buffer { memory_mb } else { 0 }
+    }
+
+    /// Every thread's buffer together, saturating: -m takes any usize.
+    pub fn total_buffer_mb(&self, memory_mb: usize) -> usize {
+        self.buffer_mb(memory_mb).saturating_mul(self.threads)
+    }
+}
+
+/// Parses `integer=8,memory-bandwidth=8` into (workload, count) pairs.
+pub fn parse_split(s: &str) -> Result<Vec<(&'static WorkloadSpec, usize)>, String> {
+    let mut entries: Vec<(&'static WorkloadSpec, usize)> = Vec::new();
+
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (name, count) = part
+            .split_once('=')
+            .ok_or_else(|| format!("expected WORKLOAD=COUNT, got '{}'", part))?;
+
+        let spec = registry::resolve(name.trim()).ok_or_else(|| {
+            format!(
+                "unknown workload '{}' (available: {})",
+                name.trim(),
+                registry::accepted_names().join(", ")
+            )
+        })?;
+
+        let count: usize = count.trim().parse().map_err(|_| {
+            format!("invalid thread count '{}' for {}", count.trim(), spec.name)
+        })?;
+        if count == 0 {
+            return Err(format!("thread count for {} must be at least 1", spec.name));
+        }
+
+        if entries.iter().any(|(s, _)| s.name == spec.name) {
+            return Err(format!("workload {} listed more than once", spec.name));
+        }
+        entries.push((spec, count));
+    }
+
+    if entries.is_empty() {
+        return Err("no workloads given".to_string());
+    }
+    Ok(entries)
+}
+
+/// Turns parsed counts into groups covering exactly `num_threads` workers.
+/// Counts that sum to less than `num_threads` are treated as proportions.
+pub fn resolve_split(
+    entries: &[(&'static WorkloadSpec, usize)],
+    num_threads: usize,
+) -> Result<Vec<WorkloadGroup>, String> {
+    let requested: usize = entries.iter().map(|(_, count)| count).sum();
+
+    if requested > num_threads {
+        return Err(format!(
+            "split requests {} threads but only {} are available",
+            requested, num_threads
+        ));
+    }
+
+    let mut groups: Vec<WorkloadGroup> = entries
+        .iter()
+        .map(|&(spec, count)| WorkloadGroup {
+            spec,
+            threads: count * num_threads / requested,
+        })
+        .collect();
+
+    // Hand leftover threads to the groups with the largest rounding loss
+    let assigned: usize = groups.iter().map(|g| g.threads).sum();
+    let mut order: Vec<usize> = (0..entries.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse((entries[i].1 * num_threads) % requested));
+    for &i in order.iter().cycle().take(num_threads - assigned) {
+        groups[i].threads += 1;
+    }
+
+    Ok(groups)
+}
+
+/// "integer ×8, memory-bandwidth ×8"
+pub fn describe(groups: &[WorkloadGroup]) -> String {
+    if let [group] = groups {
+        return group.spec.name.to_string();
+    }
+    groups
+        .iter()
+        .map(|g| format!("{} ×{}", g.spec.name, g.threads))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// ["420 MiB per thread, random over the whole buffer"], or one line per
+/// distinct working set, naming the workloads, when they differ.
+pub fn describe_working_set(groups: &[WorkloadGroup], memory_mb: usize) -> Vec<String> {
+    let mut kinds: Vec<(String, Vec<&str>)> = Vec::new();
+    for group in groups {
+        let working_set = group.spec.working_set;
+        let description = match working_set.bytes(group.buffer_mb(memory_mb)) {
+            0 => working_set.pattern().to_string(),
+            bytes => format!(
+                "{} per thread, {}",
+                reporting::format_bytes(bytes),
+                working_set.pattern()
+            ),
+        };
+        match kinds.iter_mut().find(|(d, _)| *d == description) {
+            Some((_, names)) => names.push(group.spec.name),
+            None => kinds.push((description, vec![group.spec.name])),
+        }
+    }
+
+    if let [(description, _)] = kinds.as_slice() {
+        return vec![description.clone()];
+    }
+    kinds
+        .iter()
+        .map(|(description, names)| format!("{}: {}", names.join(", "), description))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(groups: &[WorkloadGroup]) -> Vec<(&str, usize)> {
+        groups.iter().map(|g| (g.spec.name, g.threads)).collect()
+    }
+
+    #[test]
+    fn test_describe_working_set() {
+        let groups = |s: &str| resolve_split(&parse_split(s).unwrap(), 2).unwrap();
+
+        assert_eq!(describe_working_set(&groups("memory-latency=2"), 420), [
+            "420 MiB per thread, random over the whole buffer"
+        ]);
+        assert_eq!(describe_working_set(&groups("integer=1,float=1"), 420), [
+            "registers only"
+        ]);
+        assert_eq!(
+            describe_working_set(&groups("memory-latency=1,memory-bandwidth=1"), 64),
+            ["64 MiB per thread, random over the whole buffer"]
+        );
+        assert_eq!(describe_working_set(&groups("integer=1,mixed=1"), 64), [
+            "integer: registers only",
+            "mixed: 64 MiB per thread, random over the whole buffer",
+        ]);
+    }
+
+    #[test]
+    fn test_parse_split() {
+        let entries = parse_split("integer=8, memory-bandwidth=8").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0.name, "integer");
+        assert_eq!(entries[1].1, 8);
+    }
+
+    #[test]
+    fn test_parse_split_rejects_bad_input() {
+        assert!(parse_split("bogus=4").is_err());
+        assert!(parse_split("integer").is_err());
+        assert!(parse_split("integer=0").is_err());
+        assert!(parse_split("integer=x").is_err());
+        assert!(parse_split("integer=1,integer=2").is_err());
+        assert!(parse_split("").is_err());
+    }
+
+    #[test]
+    fn test_parse_split_canonicalizes_aliases() {
+        let entries = parse_split("memory=2").unwrap();
+        assert_eq!(entries[0].0.name, "memory-latency");
+        // Same workload under two spellings is still a duplicate
+        assert!(parse_split("memory=1,memory-latency=1").is_err());
+    }
+
+    #[test]
+    fn test_resolve_exact_counts() {
+        let entries = parse_split("integer=8,memory-bandwidth=8").unwrap();
+        let groups = resolve_split(&entries, 16).unwrap();
+        assert_eq!(counts(&groups), [("integer", 8), ("memory-bandwidth", 8)]);
+    }
+
+    #[test]
+    fn test_resolve_proportional_counts() {
+        let entries = parse_split("integer=1,memory-bandwidth=1").unwrap();
+        let groups = resolve_split(&entries, 16).unwrap();
+        assert_eq!(counts(&groups), [("integer", 8), ("memory-bandwidth", 8)]);
+
+        let entries = parse_split("integer=2,float=1").unwrap();
+        let groups = resolve_split(&entries, 7).unwrap();
+        assert_eq!(groups.iter().map(|g| g.threads).sum::<usize>(), 7);
+        assert_eq!(counts(&groups), [("integer", 5), ("float", 2)]);
+    }
+
+    #[test]
+    fn test_buffer_only_for_memory_workloads() {
+        let entries = parse_split("integer=1,memory-bandwidth=1").unwrap();
+        let groups = resolve_split(&entries, 2).unwrap();
+        assert_eq!(groups[0].buffer_mb(192), 0);
+        assert_eq!(groups[1].buffer_mb(192), 192);
+    }
+
+    #[test]
+    fn test_resolve_rejects_oversubscription() {
+        let entries = parse_split("integer=8,memory-bandwidth=8").unwrap();
+        assert!(resolve_split(&entries, 12).is_err());
+    }
+}
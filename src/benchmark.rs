@@ -1,188 +1,1877 @@
-use std::io::Write;
+use std::collections::HashMap;
+use std::io::{self, Write};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::thread;
-use std::time::{Duration, Instant};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 
-use crate::reporting::format_number;
-use crate::worker;
+use clap::ValueEnum;
+
+use crate::affinity::Pinning;
+use crate::assignment::{self, WorkloadGroup};
+use crate::error::LocusError;
+use crate::events::EventLog;
+use crate::output::{
+    self, BenchmarkEntry, BenchmarkReport, BufferRounding, InterferenceEntry,
+    InterferenceReport, JunitCase, JunitSuite, ProgressFormat, RunMetadata, ScalingEntry,
+    ScalingReport,
+};
+use crate::registry::{self, Category, WorkloadSpec};
+use crate::reporting::{self, ProgressOptions, format_number};
+use crate::run::{self, RunConfig, RunStats};
+use crate::swap::SwapMonitor;
+use crate::workload::{AccessPattern, FloatMode, InitPattern, KernelParams};
+use crate::{affinity, compare, reference, signal, system, utilization, worker};
 
 #[derive(Debug, Clone)]
 pub struct WorkloadResult {
-    pub name:        String,
-    pub ops_per_sec: u64,
+    pub name:          String,
+    pub ops_per_sec:   u64,
+    pub duration_secs: u64,
+    /// Spawn to release: the workers' buffer allocation, never timed
+    pub setup:         Duration,
+    /// The window the rate was taken over
+    pub measured:      Duration,
+    /// With --adaptive, whether the rate settled before the cap (None =
+    /// fixed duration)
+    pub converged:     Option<bool>,
+    /// None with fewer than two workers or nothing measured
+    pub imbalance:     Option<Imbalance>,
+    /// The kernel's code path, when it has more than one
+    pub path:          Option<&'static str>,
 }
 
-pub fn run_single_workload(
-    workload: &str,
-    num_threads: usize,
-    memory_mb: usize,
-    batch_size: u64,
-    duration_secs: u64,
-    quiet: bool,
-) -> WorkloadResult {
-    if !quiet {
-        println!("\n[→] Running {} workload...", workload);
+/// Imbalance above this gets a line under the benchmark table.
+pub const IMBALANCE_NOTE_PCT: f64 = 10.0;
+
+/// How unevenly one workload's ops split across its threads. Every thread
+/// runs the same kernel for the same window, so a wide spread points at
+/// one core: parked, throttled on its own, or shared with a pinned daemon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Imbalance {
+    /// (max − min) / mean of the per-thread ops
+    pub spread:  f64,
+    /// Worker id with the fewest ops
+    pub slowest: usize,
+}
+
+pub fn thread_imbalance(thread_ops: &[u64]) -> Option<Imbalance> {
+    if thread_ops.len() < 2 {
+        return None;
+    }
+    let total: u64 = thread_ops.iter().sum();
+    if total == 0 {
+        return None;
     }
+    let mean = total as f64 / thread_ops.len() as f64;
+    let max = *thread_ops.iter().max()?;
+    let (slowest, &min) = thread_ops.iter().enumerate().min_by_key(|(_, ops)| **ops)?;
+    Some(Imbalance {
+        spread: (max - min) as f64 / mean,
+        slowest,
+    })
+}
+
+/// Settings shared by every workload in a benchmark run.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchmarkConfig {
+    pub num_threads:   usize,
+    pub memory_mb:     usize,
+    /// None = each workload's default batch
+    pub batch_size:    Option<u64>,
+    pub duration_secs: u64,
+    pub quiet:         bool,
+    /// None = unpinned
+    pub pin_threads:   Option<Pinning>,
+    pub stagger_ms:    u64,
+    /// Worker stack size in bytes (None = platform default)
+    pub stack_size:    Option<usize>,
+    /// Unmeasured run time before each workload's timer starts
+    pub warmup:        Duration,
+    /// Pointer chains for memory-latency
+    pub chains:        usize,
+    pub float_mode:    FloatMode,
+    /// Iterations ahead memory-bandwidth prefetches (0 = off)
+    pub prefetch:      usize,
+    /// What worker buffers are filled with
+    pub init_pattern:  InitPattern,
+    /// LCG and stride overrides for the memory kernels
+    pub access:        AccessPattern,
+    /// Bytes per cache-thrash pass
+    pub sweep_bytes:   usize,
+    /// Stop each workload once its rate settles; `duration_secs` is the cap
+    pub adaptive:      bool,
+    /// Shrink buffers that can't be allocated (--best-effort-memory)
+    pub best_effort:   bool,
+    /// Warn about cores left idle (--verify-utilization)
+    pub verify_cores:  bool,
+}
+
+impl BenchmarkConfig {
+    /// What every worker's kernel is built from, bar the per-worker seed.
+    pub fn kernel_params(&self) -> KernelParams {
+        KernelParams {
+            seed:       0,
+            chains:     self.chains,
+            float_mode: self.float_mode,
+            prefetch:   self.prefetch,
+            pattern:    self.init_pattern,
+            access:     self.access,
+        }
+    }
+}
 
-    let stop_signal = Arc::new(AtomicBool::new(false));
-    let work_counter = Arc::new(AtomicU64::new(0));
+/// Per-workload cap for --adaptive when --duration isn't given.
+pub const ADAPTIVE_CAP_SECS: u64 = 120;
 
-    let mut handles = Vec::with_capacity(num_threads);
+/// Which workloads `bench` runs (`--benchmark-set`). Every set keeps
+/// mixed, the table's 1.0x baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchmarkSet {
+    /// integer, float, mixed, memory-latency and memory-bandwidth
+    Default,
+    /// Compute workloads and mixed
+    Compute,
+    /// Memory workloads and mixed
+    Memory,
+    /// Every registered workload
+    All,
+}
 
-    for id in 0..num_threads {
-        let stop = Arc::clone(&stop_signal);
-        let counter = Arc::clone(&work_counter);
-        let wl = workload.to_string();
-        let batch = batch_size;
-        let mem_mb = memory_mb;
+const DEFAULT_SET: &[&str] = &[
+    "integer",
+    "float",
+    "mixed",
+    "memory-latency",
+    "memory-bandwidth",
+];
 
-        let handle = thread::spawn(move || {
-            worker::worker_thread(id, stop, counter, &wl, batch, mem_mb);
-        });
-        handles.push(handle);
+impl BenchmarkSet {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Compute => "compute",
+            Self::Memory => "memory",
+            Self::All => "all",
+        }
     }
 
-    let start = Instant::now();
-    let duration_limit = Duration::from_secs(duration_secs);
+    /// Members in registry order.
+    pub fn specs(self) -> Vec<&'static WorkloadSpec> {
+        registry::WORKLOADS
+            .iter()
+            .filter(|spec| self.includes(spec))
+            .collect()
+    }
 
-    if !quiet {
-        let report_stop = Arc::clone(&stop_signal);
-        let report_counter = Arc::clone(&work_counter);
-
-        thread::spawn(move || {
-            let mut last_ops = 0u64;
-
-            loop {
-                thread::sleep(Duration::from_secs(1));
-                if report_stop.load(Ordering::Relaxed) {
-                    break;
-                }
-
-                let current_ops = report_counter.load(Ordering::Relaxed);
-                let ops_per_sec = current_ops.saturating_sub(last_ops);
-                last_ops = current_ops;
-
-                print!(
-                    "\r  [Running] Total ops: {} | Rate: {}/s    ",
-                    format_number(current_ops),
-                    format_number(ops_per_sec)
-                );
-                if let Err(e) = std::io::stdout().flush() {
-                    eprintln!("Warning: failed to flush progress output: {}", e);
-                }
-            }
-        });
+    fn includes(self, spec: &WorkloadSpec) -> bool {
+        match self {
+            Self::Default => DEFAULT_SET.contains(&spec.name),
+            Self::Compute => matches!(spec.category, Category::Compute | Category::Mixed),
+            Self::Memory => matches!(spec.category, Category::Memory | Category::Mixed),
+            Self::All => true,
+        }
     }
+}
+
+/// One full-width group per workload. An empty selection is an error rather
+/// than an empty table, so a filter that drops everything fails up front.
+pub fn benchmark_groups(
+    specs: &[&'static WorkloadSpec],
+    num_threads: usize,
+) -> Result<Vec<WorkloadGroup>, LocusError> {
+    if specs.is_empty() {
+        return Err(LocusError::NoWorkloadsSelected);
+    }
+    Ok(specs
+        .iter()
+        .map(|&spec| WorkloadGroup {
+            spec,
+            threads: num_threads,
+        })
+        .collect())
+}
 
-    loop {
-        thread::sleep(Duration::from_millis(100));
+/// Parses `integer=5,memory-bandwidth=20` into seconds per canonical
+/// workload name. Workloads left out run for --duration.
+pub fn parse_workload_durations(s: &str) -> Result<HashMap<&'static str, u64>, String> {
+    let mut durations = HashMap::new();
 
-        if stop_signal.load(Ordering::Relaxed) {
-            break;
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (name, secs) = part
+            .split_once('=')
+            .ok_or_else(|| format!("expected WORKLOAD=SECONDS, got '{}'", part))?;
+
+        let spec = registry::resolve(name.trim()).ok_or_else(|| {
+            format!(
+                "unknown workload '{}' (available: {})",
+                name.trim(),
+                registry::accepted_names().join(", ")
+            )
+        })?;
+
+        let secs: u64 = secs
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid duration '{}' for {}", secs.trim(), spec.name))?;
+        if secs == 0 {
+            return Err(format!(
+                "duration for {} must be at least 1 second",
+                spec.name
+            ));
         }
 
-        if start.elapsed() >= duration_limit {
-            stop_signal.store(true, Ordering::Release);
-            break;
+        if durations.insert(spec.name, secs).is_some() {
+            return Err(format!("workload {} listed more than once", spec.name));
         }
     }
 
-    for handle in handles {
-        let _ = handle.join();
+    if durations.is_empty() {
+        return Err("no workloads given".to_string());
     }
+    Ok(durations)
+}
 
-    let elapsed = start.elapsed();
-    let total_ops = work_counter.load(Ordering::Relaxed);
-    let ops_per_sec = if elapsed.as_secs() > 0 {
-        total_ops / elapsed.as_secs()
-    } else {
-        total_ops
+/// Per-workload warmup when --bench-warmup isn't given: a tenth of the
+/// duration, capped so long runs don't waste time on it.
+pub fn auto_warmup(duration_secs: u64) -> Duration {
+    Duration::from_millis(duration_secs.saturating_mul(100).min(2000))
+}
+
+/// Where the suite stands as one workload starts, for its "[→]" line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuiteProgress {
+    /// 0-based position of the starting workload
+    pub index: usize,
+    pub count: usize,
+    /// Share of the estimated suite time already behind (0.0-1.0)
+    pub done:  f64,
+    pub eta:   Duration,
+}
+
+impl SuiteProgress {
+    /// "workload 3/5 (memory-latency) — suite 40% — ETA 2m 10s"; under
+    /// --adaptive the plan is every cap, so the ETA is an upper bound.
+    pub fn describe(&self, name: &str, adaptive: bool) -> String {
+        let secs = self.eta.as_secs_f64().round() as u64;
+        let eta = if secs < 60 {
+            format!("{}s", secs)
+        } else {
+            reporting::format_duration(Duration::from_secs(secs))
+        };
+        format!(
+            "workload {}/{} ({}) — suite {:.0}% — ETA {}{}",
+            self.index + 1,
+            self.count,
+            name,
+            self.done * 100.0,
+            if adaptive { "at most " } else { "" },
+            eta
+        )
+    }
+}
+
+/// Progress as workload `taken.len()` starts. `planned` is each workload's
+/// warmup plus duration, `taken` the wall time of those already run and
+/// `setups` their setup time. Each workload still to come is expected to
+/// take its plan plus the mean setup seen so far.
+pub fn suite_progress(
+    planned: &[Duration],
+    taken: &[Duration],
+    setups: &[Duration],
+) -> SuiteProgress {
+    let index = taken.len().min(planned.len());
+    let setup = match u32::try_from(setups.len()) {
+        Ok(0) | Err(_) => Duration::ZERO,
+        Ok(n) => setups.iter().sum::<Duration>() / n,
     };
+    let eta = planned[index..].iter().map(|plan| *plan + setup).sum::<Duration>();
+    let behind = taken.iter().sum::<Duration>();
+    let total = (behind + eta).as_secs_f64();
+    SuiteProgress {
+        index,
+        count: planned.len(),
+        done: if total > 0.0 { behind.as_secs_f64() / total } else { 0.0 },
+        eta,
+    }
+}
+
+pub fn run_single_workload(
+    workload: &'static WorkloadSpec,
+    config: &BenchmarkConfig,
+    suite: SuiteProgress,
+    events: &mut EventLog,
+    swap: &mut SwapMonitor,
+) -> WorkloadResult {
+    let BenchmarkConfig {
+        num_threads,
+        duration_secs,
+        quiet,
+        adaptive,
+        ..
+    } = *config;
+
+    if !quiet {
+        eprintln!("\n[→] Running {}", suite.describe(workload.name, adaptive));
+    }
+
+    let groups = [WorkloadGroup {
+        spec:    workload,
+        threads: num_threads,
+    }];
+    // The caller says why it skips the remaining workloads
+    let stats = run_groups(&groups, config, events, swap);
 
+    let converged = adaptive.then_some(stats.converged.is_some());
     if !quiet {
-        println!(
-            "\r  [✓] Complete: {} ops in {:.2}s               ",
-            format_number(total_ops),
-            elapsed.as_secs_f64()
+        let settled = match converged {
+            Some(true) => " (settled)",
+            Some(false) => " (cap reached, not settled)",
+            None => "",
+        };
+        eprintln!(
+            "\r  [✓] Complete: {} ops, setup {}, measured {}{}               ",
+            format_number(stats.total_ops()),
+            reporting::format_duration(stats.setup),
+            reporting::format_duration(stats.elapsed),
+            settled
         );
     }
+    utilization::warn_on_idle_cores(&stats.core_busy);
 
+    let thread_ops: Vec<u64> = stats.worker_times.iter().map(|(_, t)| t.ops).collect();
     WorkloadResult {
-        name: workload.to_string(),
-        ops_per_sec,
+        name: workload.name.to_string(),
+        // A settled run reports its plateau rather than the ramp into it
+        ops_per_sec: stats.converged.unwrap_or_else(|| stats.ops_per_sec()),
+        duration_secs,
+        setup: stats.setup,
+        measured: stats.elapsed,
+        converged,
+        imbalance: thread_imbalance(&thread_ops),
+        path: workload.implementation(&config.kernel_params()),
+    }
+}
+
+/// Runs `groups` side by side for `config.duration_secs` (or until the
+/// rate settles under `adaptive`), pinned and staggered as configured.
+fn run_groups(
+    groups: &[WorkloadGroup],
+    config: &BenchmarkConfig,
+    events: &mut EventLog,
+    swap: &mut SwapMonitor,
+) -> RunStats {
+    let num_threads = groups.iter().map(|g| g.threads).sum();
+    let worker_cpus = pinned_cpus(config, num_threads);
+    run_groups_on(groups, config, worker_cpus.as_deref(), events, swap)
+}
+
+/// The cores `num_threads` workers are pinned to, in worker order (None =
+/// unpinned).
+fn pinned_cpus(config: &BenchmarkConfig, num_threads: usize) -> Option<Vec<usize>> {
+    config.pin_threads.map(|pinning| {
+        let siblings = pinning.smt.and_then(|_| system::detect_smt_siblings());
+        affinity::pinned_cores(
+            num_threads,
+            &system::available_cpus(),
+            siblings.as_deref(),
+            pinning,
+        )
+    })
+}
+
+/// [`run_groups`] on cores chosen by the caller.
+fn run_groups_on(
+    groups: &[WorkloadGroup],
+    config: &BenchmarkConfig,
+    worker_cpus: Option<&[usize]>,
+    events: &mut EventLog,
+    swap: &mut SwapMonitor,
+) -> RunStats {
+    let BenchmarkConfig {
+        memory_mb,
+        batch_size,
+        duration_secs,
+        quiet,
+        stagger_ms,
+        stack_size,
+        warmup,
+        chains,
+        float_mode,
+        prefetch,
+        init_pattern,
+        access,
+        sweep_bytes,
+        adaptive,
+        best_effort,
+        verify_cores,
+        ..
+    } = *config;
+
+    let num_threads = groups.iter().map(|g| g.threads).sum();
+    let cpus = system::available_cpus();
+    let per_thread_mb: Vec<usize> = groups
+        .iter()
+        .flat_map(|g| std::iter::repeat_n(g.buffer_mb(memory_mb), g.threads))
+        .collect();
+    let watch_cores = verify_cores
+        .then(|| utilization::cores_to_watch(worker_cpus, &cpus, num_threads))
+        .flatten();
+
+    let run_config = RunConfig {
+        groups,
+        per_thread_mb: &per_thread_mb,
+        batch_size,
+        chains,
+        float_mode,
+        prefetch,
+        init_pattern,
+        access,
+        sweep_bytes,
+        stack_size,
+        stagger: Duration::from_millis(stagger_ms),
+        worker_cpus,
+        reporter_cpu: worker_cpus.and_then(|cores| affinity::reporter_core(cores, &cpus)),
+        warmup,
+        duration: Some(Duration::from_secs(duration_secs)),
+        converge: adaptive,
+        ops: None,
+        once: false,
+        best_effort,
+        watch_cores: watch_cores.as_deref(),
+        progress: ProgressOptions {
+            print:              (!quiet).then_some(ProgressFormat::Text),
+            smoothing:          1.0,
+            throttle_alert_pct: 0,
+            indent:             "  ",
+            log_status:         None,
+        },
+    };
+    run::execute_run(
+        &run_config,
+        Arc::new(AtomicBool::new(false)),
+        events,
+        swap,
+        &mut |_| {},
+    )
+}
+
+/// Results in registry order, with any unregistered names at the end.
+fn ordered_results(results: &[WorkloadResult]) -> Vec<&WorkloadResult> {
+    let mut sorted_results: Vec<_> = registry::WORKLOADS
+        .iter()
+        .filter_map(|spec| results.iter().find(|r| r.name == spec.name))
+        .collect();
+
+    for result in results {
+        if !sorted_results.iter().any(|r| r.name == result.name) {
+            sorted_results.push(result);
+        }
     }
+    sorted_results
 }
 
-pub fn display_benchmark_table(results: &[WorkloadResult], num_threads: usize) {
+fn reference_index(results: &[WorkloadResult]) -> Option<f64> {
+    reference::reference_index(results.iter().map(|r| (r.name.as_str(), r.ops_per_sec)))
+}
+
+fn relative_to_mixed(results: &[WorkloadResult], ops_per_sec: u64) -> f64 {
     let mixed_rate = results
         .iter()
         .find(|r| r.name == "mixed")
         .map(|r| r.ops_per_sec)
         .unwrap_or(1);
 
-    println!("\n════════════════════════════════════════════════════════════════════");
-    println!("  BENCHMARK RESULTS");
-    println!("════════════════════════════════════════════════════════════════════");
+    if mixed_rate > 0 {
+        ops_per_sec as f64 / mixed_rate as f64
+    } else {
+        1.0
+    }
+}
 
-    let order = [
-        "integer",
-        "float",
-        "mixed",
-        "memory-latency",
-        "memory-bandwidth",
-    ];
-    let mut sorted_results: Vec<_> = order
+/// Geometric mean of the measured rates, so no single workload dominates
+/// the way it would in a sum. Workloads that measured nothing are left out.
+pub fn composite_score(results: &[WorkloadResult]) -> Option<u64> {
+    let rates: Vec<f64> = results
         .iter()
-        .filter_map(|&name| results.iter().find(|r| r.name == name))
+        .filter(|r| r.ops_per_sec > 0)
+        .map(|r| (r.ops_per_sec as f64).ln())
         .collect();
+    if rates.is_empty() {
+        return None;
+    }
+    Some(
+        (rates.iter().sum::<f64>() / rates.len() as f64)
+            .exp()
+            .round() as u64,
+    )
+}
 
-    for result in results {
-        if !sorted_results.iter().any(|r| r.name == result.name) {
-            sorted_results.push(result);
+/// `--fail-threshold` unless one is given.
+pub const DEFAULT_FAIL_THRESHOLD_PCT: u64 = 10;
+
+/// `--baseline`: a saved bench result each workload is held to. Workloads
+/// the file lacks, or that measured nothing this time, aren't judged.
+pub struct Baseline {
+    pub saved:    compare::Column,
+    /// How far under its saved rate a workload may fall, in percent
+    pub fail_pct: u64,
+}
+
+impl Baseline {
+    fn saved_rate(&self, result: &WorkloadResult) -> Option<u64> {
+        compare::rate(&self.saved, &result.name).filter(|&rate| rate > 0)
+    }
+
+    /// `result`'s change from its saved rate, in percent.
+    pub fn delta_pct(&self, result: &WorkloadResult) -> Option<f64> {
+        let saved = self.saved_rate(result)?;
+        let rate = (result.ops_per_sec > 0).then_some(result.ops_per_sec)?;
+        Some((rate as f64 / saved as f64 - 1.0) * 100.0)
+    }
+
+    /// The "below threshold" message when `result` fell further than
+    /// `fail_pct` under its saved rate.
+    pub fn check(&self, result: &WorkloadResult) -> Result<(), String> {
+        match (self.delta_pct(result), self.saved_rate(result)) {
+            (Some(delta), Some(saved)) if delta < -(self.fail_pct as f64) => Err(format!(
+                "below threshold: {} {}/s is {:.1}% under {}'s {}/s (--fail-threshold {}%)",
+                result.name,
+                format_number(result.ops_per_sec),
+                -delta,
+                self.saved.label,
+                format_number(saved),
+                self.fail_pct
+            )),
+            _ => Ok(()),
         }
     }
+}
+
+pub fn benchmark_report(
+    results: &[WorkloadResult],
+    num_threads: usize,
+    duration_secs: u64,
+    swap_pages: Option<u64>,
+    buffer_rounding: Option<BufferRounding>,
+    events: &EventLog,
+    reference_kernels: bool,
+) -> BenchmarkReport {
+    BenchmarkReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        metadata: output::run_metadata(),
+        build: output::build_info(),
+        virtualization: system::detect_virtualization().describe(),
+        threads: num_threads,
+        duration_secs,
+        results: ordered_results(results)
+            .into_iter()
+            .map(|result| BenchmarkEntry {
+                workload:               result.name.clone(),
+                ops_per_sec:            result.ops_per_sec,
+                relative_to_mixed:      relative_to_mixed(results, result.ops_per_sec),
+                per_thread_ops_per_sec: result.ops_per_sec / num_threads.max(1) as u64,
+                duration_secs:          result.duration_secs,
+                setup_secs:             result.setup.as_secs_f64(),
+                measured_secs:          result.measured.as_secs_f64(),
+                converged:              result.converged,
+                gb_per_sec:             registry::find(&result.name)
+                    .and_then(|spec| spec.bytes_per_op)
+                    .map(|bytes_per_op| result.ops_per_sec as f64 * bytes_per_op as f64 / 1e9),
+                imbalance:              result.imbalance.map(|i| i.spread),
+                slowest_worker:         result
+                    .imbalance
+                    .map(|i| worker::worker_name(i.slowest)),
+                code_path:              result.path.map(str::to_string),
+                percent_of_reference:   reference_kernels
+                    .then(|| reference::percent_of_reference(&result.name, result.ops_per_sec))
+                    .flatten(),
+            })
+            .collect(),
+        score: composite_score(results),
+        index: reference_kernels.then(|| reference_index(results)).flatten(),
+        events: events.reports(),
+        swap_pages,
+        buffer_rounding,
+    }
+}
+
+/// What a benchmark's testcases are judged by, besides a fault.
+#[derive(Default)]
+pub struct JunitGates<'a> {
+    /// --min-score, and the message when the score missed it
+    pub min_score:  Option<u64>,
+    pub score_miss: Option<&'a str>,
+    pub baseline:   Option<&'a Baseline>,
+}
+
+/// The benchmark as JUnit testcases, in the order the workloads ran. A
+/// `fault` (a worker failure or a stop for swapping) fails the workload it
+/// stopped and skips the rest; an interrupt only skips. A workload that
+/// fell past its --baseline fails too, and with --min-score the score is
+/// one more testcase, failing with `score_miss`. So a failed testcase
+/// always comes with a non-zero exit code.
+pub fn junit_suite(
+    results: &[WorkloadResult],
+    groups: &[WorkloadGroup],
+    num_threads: usize,
+    fault: Option<&str>,
+    gates: &JunitGates,
+    reference_kernels: bool,
+) -> JunitSuite {
+    let metadata = output::run_metadata();
+    let mut cases: Vec<JunitCase> = groups
+        .iter()
+        .map(|group| {
+            let Some(at) = results.iter().position(|r| r.name == group.spec.name) else {
+                return JunitCase {
+                    name:       group.spec.name.to_string(),
+                    time_secs:  0.0,
+                    failure:    None,
+                    skipped:    Some(match fault {
+                        Some(_) => "not run: an earlier workload failed".to_string(),
+                        None => "not run: interrupted".to_string(),
+                    }),
+                    system_out: String::new(),
+                };
+            };
+            let result = &results[at];
+            let failure = fault
+                .filter(|_| at + 1 == results.len())
+                .map(str::to_string)
+                .or_else(|| gates.baseline?.check(result).err());
+            // Stopped before its timer started, so there's no rate to judge
+            let skipped = (result.ops_per_sec == 0).then(|| "measured nothing".to_string());
+            JunitCase {
+                name: result.name.clone(),
+                time_secs: result.measured.as_secs_f64(),
+                failure,
+                skipped,
+                system_out: junit_system_out(
+                    result,
+                    num_threads,
+                    reference_kernels,
+                    gates.baseline,
+                ),
+            }
+        })
+        .collect();
+
+    if let Some(minimum) = gates.min_score {
+        cases.push(JunitCase {
+            name:       "score".to_string(),
+            time_secs:  0.0,
+            failure:    gates.score_miss.map(str::to_string),
+            skipped:    None,
+            system_out: match composite_score(results) {
+                Some(score) => format!("score: {} (--min-score {})", score, minimum),
+                None => "score: nothing was measured".to_string(),
+            },
+        });
+    }
 
-    println!("┌──────────────────┬─────────────┬──────────┬─────────────────┐");
-    println!("│ Workload         │    Rate     │ Relative │ Per-Thread Rate │");
-    println!("├──────────────────┼─────────────┼──────────┼─────────────────┤");
+    JunitSuite {
+        name: "locus.bench".to_string(),
+        timestamp: metadata.timestamp,
+        hostname: metadata.hostname,
+        properties: vec![
+            ("run_id".to_string(), metadata.run_id),
+            ("version".to_string(), env!("CARGO_PKG_VERSION").to_string()),
+            ("threads".to_string(), num_threads.to_string()),
+        ],
+        cases,
+    }
+}
+
+/// The measured rate and how it compares with the --baseline, and with the
+/// reference machine when the kernels are the reference ones.
+fn junit_system_out(
+    result: &WorkloadResult,
+    num_threads: usize,
+    reference_kernels: bool,
+    baseline: Option<&Baseline>,
+) -> String {
+    let mut lines = vec![
+        format!("rate: {} ops/s", result.ops_per_sec),
+        format!(
+            "per thread: {} ops/s",
+            result.ops_per_sec / num_threads.max(1) as u64
+        ),
+        format!(
+            "measured: {:.2}s after {:.2}s setup",
+            result.measured.as_secs_f64(),
+            result.setup.as_secs_f64()
+        ),
+    ];
+    if let Some(baseline) = baseline
+        && let Some(delta) = baseline.delta_pct(result)
+    {
+        lines.push(format!(
+            "vs baseline: {:+.1}% ({}; fails under -{}%)",
+            delta, baseline.saved.label, baseline.fail_pct
+        ));
+    }
+    if let Some(percent) = reference::percent_of_reference(&result.name, result.ops_per_sec)
+        && reference_kernels
+    {
+        lines.push(format!(
+            "vs reference: {:+.1}% ({})",
+            percent - 100.0,
+            reference::REFERENCE_LABEL
+        ));
+    }
+    lines.join("\n")
+}
 
-    for result in sorted_results {
+pub fn write_benchmark_table(
+    out: &mut dyn Write,
+    metadata: &RunMetadata,
+    results: &[WorkloadResult],
+    num_threads: usize,
+    swap_pages: Option<u64>,
+    buffer_rounding: Option<BufferRounding>,
+    reference_kernels: bool,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "\n════════════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "  BENCHMARK RESULTS")?;
+    writeln!(
+        out,
+        "════════════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "  Run: {}", metadata.summary())?;
+
+    writeln!(
+        out,
+        "┌──────────────────┬─────────────┬──────────┬─────────────────┐"
+    )?;
+    writeln!(
+        out,
+        "│ Workload         │    Rate     │ Relative │ Per-Thread Rate │"
+    )?;
+    writeln!(
+        out,
+        "├──────────────────┼─────────────┼──────────┼─────────────────┤"
+    )?;
+
+    for result in ordered_results(results) {
         let rate_formatted = format_number(result.ops_per_sec);
         let rate_str = format!("{} /s", rate_formatted);
 
-        let relative = if mixed_rate > 0 {
-            result.ops_per_sec as f64 / mixed_rate as f64
-        } else {
-            1.0
-        };
+        let relative = relative_to_mixed(results, result.ops_per_sec);
         let relative_str = format!("{:5.1}x", relative);
 
         let per_thread = result.ops_per_sec / num_threads.max(1) as u64;
         let per_thread_formatted = format_number(per_thread);
         let per_thread_str = format!("{} /s", per_thread_formatted);
 
-        let workload_name = if result.name == "memory-latency" {
-            "Memory-Latency".to_string()
-        } else if result.name == "memory-bandwidth" {
-            "Memory-Bandwidth".to_string()
-        } else {
-            result
-                .name
-                .chars()
-                .next()
-                .unwrap()
-                .to_uppercase()
-                .to_string()
-                + &result.name[1..]
-        };
+        let workload_name = registry::find(&result.name)
+            .map(|spec| spec.display_name())
+            .unwrap_or_else(|| result.name.clone());
 
-        println!(
+        writeln!(
+            out,
             "│ {:<16} │ {:>11} │ {:>8} │ {:>15} │",
             workload_name, rate_str, relative_str, per_thread_str
+        )?;
+    }
+
+    writeln!(
+        out,
+        "└──────────────────┴─────────────┴──────────┴─────────────────┘"
+    )?;
+    writeln!(out, "\nBaseline: Mixed = 1.0x | Threads: {}", num_threads)?;
+    if let Some(score) = composite_score(results) {
+        writeln!(
+            out,
+            "Score: {}/s (geometric mean of the rates)",
+            format_number(score)
+        )?;
+    }
+    for result in ordered_results(results) {
+        let Some(bytes_per_op) =
+            registry::find(&result.name).and_then(|spec| spec.bytes_per_op)
+        else {
+            continue;
+        };
+        writeln!(
+            out,
+            "Memory BW: {} {} (estimated, {}B per op)",
+            reporting::format_rate_bytes(result.ops_per_sec as f64 * bytes_per_op as f64),
+            result.name,
+            bytes_per_op
+        )?;
+    }
+    let adaptive: Vec<String> = ordered_results(results)
+        .into_iter()
+        .filter_map(|result| {
+            let settled = if result.converged? { "" } else { " (cap, not settled)" };
+            Some(format!(
+                "{} {}{}",
+                result.name,
+                reporting::format_duration(result.measured),
+                settled
+            ))
+        })
+        .collect();
+    if !adaptive.is_empty() {
+        writeln!(out, "Adaptive: {}", adaptive.join(", "))?;
+    }
+    for result in ordered_results(results) {
+        let Some(imbalance) = result.imbalance else {
+            continue;
+        };
+        if imbalance.spread * 100.0 < IMBALANCE_NOTE_PCT {
+            continue;
+        }
+        writeln!(
+            out,
+            "Imbalance: {:.0}% {} (slowest: {})",
+            imbalance.spread * 100.0,
+            result.name,
+            worker::worker_name(imbalance.slowest)
+        )?;
+    }
+    let percents: Vec<String> = ordered_results(results)
+        .into_iter()
+        .filter_map(|result| {
+            let percent = reference::percent_of_reference(&result.name, result.ops_per_sec)?;
+            Some(format!("{} {:.0}%", result.name, percent))
+        })
+        .collect();
+    if !percents.is_empty() && !reference_kernels {
+        writeln!(out, "Index: n/a (non-default kernel options)")?;
+    } else if !percents.is_empty() {
+        writeln!(out, "Reference: {}", percents.join(", "))?;
+        match reference_index(results) {
+            Some(index) => writeln!(
+                out,
+                "Index: {:.0} (100 = {}; see `locus info`)",
+                index,
+                reference::REFERENCE_LABEL
+            )?,
+            None => writeln!(out, "Index: needs the whole default set")?,
+        }
+    }
+    for result in ordered_results(results) {
+        if let Some(path) = result.path {
+            writeln!(out, "Path: {}: {}", result.name, path)?;
+        }
+    }
+    if let Some(pages) = swap_pages {
+        writeln!(out, "Swapping: {}", reporting::describe_swapping(pages))?;
+    }
+    if let Some(rounding) = buffer_rounding {
+        writeln!(out, "Buffer: {}", reporting::describe_buffer_rounding(rounding))?;
+    }
+    Ok(())
+}
+
+/// One side of an `--interference` pair: its rate alone, then next to the
+/// other workload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterferenceResult {
+    pub name:      String,
+    pub threads:   usize,
+    pub solo:      u64,
+    /// None when the run stopped before the contended phase
+    pub contended: Option<u64>,
+}
+
+impl InterferenceResult {
+    /// How much slower it ran next to the other workload, in percent;
+    /// negative when it sped up.
+    pub fn slowdown_pct(&self) -> Option<f64> {
+        let contended = self.contended?;
+        (self.solo > 0).then(|| (1.0 - contended as f64 / self.solo as f64) * 100.0)
+    }
+}
+
+/// Seconds each side of `--interference` runs alone first: a fifth of the
+/// contended phase, like --loaded-latency's idle phase.
+pub fn interference_solo_secs(duration_secs: u64) -> u64 {
+    (duration_secs / 5).max(1)
+}
+
+/// Parses `memory-bandwidth:4,integer:4` into the two groups.
+pub fn parse_interference(s: &str) -> Result<[WorkloadGroup; 2], String> {
+    let groups = s
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|part| {
+            let (name, count) = part
+                .split_once(':')
+                .ok_or_else(|| format!("expected WORKLOAD:THREADS, got '{}'", part))?;
+            let spec = registry::resolve(name.trim()).ok_or_else(|| {
+                format!(
+                    "unknown workload '{}' (available: {})",
+                    name.trim(),
+                    registry::accepted_names().join(", ")
+                )
+            })?;
+            let threads: usize = count.trim().parse().map_err(|_| {
+                format!("invalid thread count '{}' for {}", count.trim(), spec.name)
+            })?;
+            if threads == 0 {
+                return Err(format!("thread count for {} must be at least 1", spec.name));
+            }
+            Ok(WorkloadGroup { spec, threads })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    match groups.as_slice() {
+        [a, b] if a.spec.name == b.spec.name => Err(format!(
+            "both sides are {}; pick two different workloads",
+            a.spec.name
+        )),
+        [a, b] => Ok([*a, *b]),
+        _ => Err(format!("expected two workloads, got {}", groups.len())),
+    }
+}
+
+/// Runs each group alone for `solo_secs`, then both together for
+/// `config.duration_secs` on separate counters. A stop (Ctrl-C, a failed
+/// worker, swapping) skips the phases left; the caller says why.
+pub fn run_interference(
+    pair: &[WorkloadGroup; 2],
+    config: &BenchmarkConfig,
+    solo_secs: u64,
+    events: &mut EventLog,
+    swap: &mut SwapMonitor,
+) -> Vec<InterferenceResult> {
+    let config = BenchmarkConfig {
+        adaptive: false,
+        ..*config
+    };
+    let stopped = |events: &EventLog, swap: &SwapMonitor| {
+        signal::interrupted() || events.has_fatal() || swap.aborted()
+    };
+    let mut results = Vec::new();
+    // Each side's solo phase runs on the cores it gets in the contended
+    // phase, so the slowdown isn't partly a difference between cores
+    let pair_cpus = pinned_cpus(&config, pair.iter().map(|g| g.threads).sum());
+    let side_cpus = pair_cpus.as_deref().map(|cores| side_cores(cores, pair));
+
+    for (side, group) in pair.iter().enumerate() {
+        if !config.quiet {
+            eprintln!(
+                "\n[→] Solo: {} ×{} for {}s",
+                group.spec.name, group.threads, solo_secs
+            );
+        }
+        let solo = BenchmarkConfig {
+            duration_secs: solo_secs,
+            ..config
+        };
+        let cores = side_cpus.map(|sides| sides[side]);
+        let stats = run_groups_on(std::slice::from_ref(group), &solo, cores, events, swap);
+        if !config.quiet {
+            eprintln!(
+                "\r  [✓] Complete: {}/s                              ",
+                format_number(stats.ops_per_sec())
+            );
+        }
+        utilization::warn_on_idle_cores(&stats.core_busy);
+        results.push(InterferenceResult {
+            name:      group.spec.name.to_string(),
+            threads:   group.threads,
+            solo:      stats.ops_per_sec(),
+            contended: None,
+        });
+        if stopped(events, swap) {
+            return results;
+        }
+    }
+
+    if !config.quiet {
+        eprintln!(
+            "\n[→] Contended: {} for {}s",
+            assignment::describe(pair),
+            config.duration_secs
+        );
+    }
+    let stats = run_groups_on(pair, &config, pair_cpus.as_deref(), events, swap);
+    if !config.quiet {
+        eprintln!(
+            "\r  [✓] Complete: {} ops                              ",
+            format_number(stats.total_ops())
+        );
+    }
+    utilization::warn_on_idle_cores(&stats.core_busy);
+    for (result, ops) in results.iter_mut().zip(&stats.group_ops) {
+        result.contended = Some(run::rate_per_sec(*ops, stats.elapsed));
+    }
+    results
+}
+
+/// Each side's share of the pair's cores: its workers come first for side
+/// A, after A's for side B, as [`run::execute_run`] places them.
+fn side_cores<'a>(cores: &'a [usize], pair: &[WorkloadGroup; 2]) -> [&'a [usize]; 2] {
+    let split = pair[0].threads.min(cores.len());
+    [&cores[..split], &cores[split..]]
+}
+
+pub fn write_interference_table(
+    out: &mut dyn Write,
+    metadata: &RunMetadata,
+    results: &[InterferenceResult],
+    solo_secs: u64,
+    duration_secs: u64,
+    swap_pages: Option<u64>,
+    buffer_rounding: Option<BufferRounding>,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "\n════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "  INTERFERENCE")?;
+    writeln!(
+        out,
+        "════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "  Run: {}", metadata.summary())?;
+    writeln!(
+        out,
+        "  {:<18} {:>7} {:>12} {:>12} {:>9}",
+        "Workload", "Threads", "Solo", "Contended", "Slowdown"
+    )?;
+    for result in results {
+        let contended = result
+            .contended
+            .map(|rate| format!("{} /s", format_number(rate)))
+            .unwrap_or_else(|| "n/a".to_string());
+        let slowdown = result
+            .slowdown_pct()
+            .map(|pct| format!("{:.1}%", pct))
+            .unwrap_or_else(|| "n/a".to_string());
+        writeln!(
+            out,
+            "  {:<18} {:>7} {:>12} {:>12} {:>9}",
+            result.name,
+            result.threads,
+            format!("{} /s", format_number(result.solo)),
+            contended,
+            slowdown
+        )?;
+    }
+    writeln!(
+        out,
+        "\nSolo: {}s each | Contended: {}s side by side",
+        solo_secs, duration_secs
+    )?;
+    if let Some(pages) = swap_pages {
+        writeln!(out, "Swapping: {}", reporting::describe_swapping(pages))?;
+    }
+    if let Some(rounding) = buffer_rounding {
+        writeln!(out, "Buffer: {}", reporting::describe_buffer_rounding(rounding))?;
+    }
+    writeln!(
+        out,
+        "════════════════════════════════════════════════════════════"
+    )
+}
+
+pub fn interference_report(
+    results: &[InterferenceResult],
+    solo_secs: u64,
+    duration_secs: u64,
+    swap_pages: Option<u64>,
+    buffer_rounding: Option<BufferRounding>,
+    events: &EventLog,
+) -> InterferenceReport {
+    InterferenceReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        metadata: output::run_metadata(),
+        build: output::build_info(),
+        virtualization: system::detect_virtualization().describe(),
+        solo_secs,
+        duration_secs,
+        results: results
+            .iter()
+            .map(|r| InterferenceEntry {
+                workload:              r.name.clone(),
+                threads:               r.threads,
+                solo_ops_per_sec:      r.solo,
+                contended_ops_per_sec: r.contended,
+                slowdown_pct:          r.slowdown_pct(),
+            })
+            .collect(),
+        events: events.reports(),
+        swap_pages,
+        buffer_rounding,
+    }
+}
+
+/// Share of the best step's bandwidth that counts as saturated: past the
+/// knee, more threads buy less than a tenth more.
+pub const SCALING_KNEE_SHARE: f64 = 0.9;
+
+/// One thread count of `--bandwidth-scaling`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScalingResult {
+    pub threads:       usize,
+    pub bytes_per_sec: u64,
+}
+
+impl ScalingResult {
+    pub fn per_thread(&self) -> u64 {
+        self.bytes_per_sec / self.threads.max(1) as u64
+    }
+
+    /// Against the first step, which runs one thread.
+    pub fn speedup(&self, single: &ScalingResult) -> Option<f64> {
+        (single.bytes_per_sec > 0)
+            .then(|| self.bytes_per_sec as f64 / single.bytes_per_sec as f64)
+    }
+}
+
+/// Thread counts the sweep steps through: doubling from 1, then `max`
+/// itself if the doubling skipped it.
+pub fn scaling_thread_counts(max: usize) -> Vec<usize> {
+    let mut counts: Vec<usize> = std::iter::successors(Some(1usize), |n| n.checked_mul(2))
+        .take_while(|&n| n < max)
+        .collect();
+    counts.push(max.max(1));
+    counts
+}
+
+/// The fewest threads that reach [`SCALING_KNEE_SHARE`] of the best step.
+pub fn scaling_knee(results: &[ScalingResult]) -> Option<&ScalingResult> {
+    let best = results.iter().map(|r| r.bytes_per_sec).max().filter(|&b| b > 0)?;
+    results
+        .iter()
+        .find(|r| r.bytes_per_sec as f64 >= best as f64 * SCALING_KNEE_SHARE)
+}
+
+/// Runs memory-bandwidth at each of [`scaling_thread_counts`] for
+/// `config.duration_secs`, up to `config.num_threads`. A stop (Ctrl-C, a
+/// failed worker, swapping) skips the steps left; the caller says why.
+pub fn run_bandwidth_scaling(
+    config: &BenchmarkConfig,
+    events: &mut EventLog,
+    swap: &mut SwapMonitor,
+) -> Vec<ScalingResult> {
+    let spec = registry::find("memory-bandwidth").expect("memory-bandwidth is registered");
+    let bytes_per_op = spec.bytes_per_op.unwrap_or(0);
+    let counts = scaling_thread_counts(config.num_threads);
+    let mut results = Vec::new();
+
+    for (step, &threads) in counts.iter().enumerate() {
+        if !config.quiet {
+            eprintln!(
+                "\n[→] Step {}/{}: {} ×{} for {}s",
+                step + 1,
+                counts.len(),
+                spec.name,
+                threads,
+                config.duration_secs
+            );
+        }
+        let groups = [WorkloadGroup { spec, threads }];
+        let step_config = BenchmarkConfig {
+            num_threads: threads,
+            adaptive: false,
+            ..*config
+        };
+        let stats = run_groups(&groups, &step_config, events, swap);
+        let result = ScalingResult {
+            threads,
+            bytes_per_sec: stats.ops_per_sec().saturating_mul(bytes_per_op),
+        };
+        if !config.quiet {
+            eprintln!(
+                "\r  [✓] Complete: {}                              ",
+                reporting::format_rate_bytes(result.bytes_per_sec as f64)
+            );
+        }
+        utilization::warn_on_idle_cores(&stats.core_busy);
+        results.push(result);
+        if signal::interrupted() || events.has_fatal() || swap.aborted() {
+            break;
+        }
+    }
+    results
+}
+
+pub fn write_scaling_table(
+    out: &mut dyn Write,
+    metadata: &RunMetadata,
+    results: &[ScalingResult],
+    duration_secs: u64,
+    swap_pages: Option<u64>,
+    buffer_rounding: Option<BufferRounding>,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "\n════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "  BANDWIDTH SCALING")?;
+    writeln!(
+        out,
+        "════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "  Run: {}", metadata.summary())?;
+    writeln!(
+        out,
+        "  {:>7} {:>14} {:>14} {:>9}",
+        "Threads", "Bandwidth", "Per thread", "Scaling"
+    )?;
+    for result in results {
+        let speedup = results
+            .first()
+            .and_then(|single| result.speedup(single))
+            .map(|x| format!("{:.2}x", x))
+            .unwrap_or_else(|| "n/a".to_string());
+        writeln!(
+            out,
+            "  {:>7} {:>14} {:>14} {:>9}",
+            result.threads,
+            reporting::format_rate_bytes(result.bytes_per_sec as f64),
+            reporting::format_rate_bytes(result.per_thread() as f64),
+            speedup
+        )?;
+    }
+    match scaling_knee(results) {
+        Some(knee) => writeln!(
+            out,
+            "\nKnee: {} thread{} reach {:.0}% of the best {}",
+            knee.threads,
+            if knee.threads == 1 { "" } else { "s" },
+            SCALING_KNEE_SHARE * 100.0,
+            reporting::format_rate_bytes(
+                results.iter().map(|r| r.bytes_per_sec).max().unwrap_or(0) as f64
+            )
+        )?,
+        None => writeln!(out, "\nKnee: n/a (nothing measured)")?,
+    }
+    writeln!(out, "Each step: {}s of memory-bandwidth", duration_secs)?;
+    if let Some(pages) = swap_pages {
+        writeln!(out, "Swapping: {}", reporting::describe_swapping(pages))?;
+    }
+    if let Some(rounding) = buffer_rounding {
+        writeln!(out, "Buffer: {}", reporting::describe_buffer_rounding(rounding))?;
+    }
+    writeln!(
+        out,
+        "════════════════════════════════════════════════════════════"
+    )
+}
+
+pub fn scaling_report(
+    results: &[ScalingResult],
+    duration_secs: u64,
+    swap_pages: Option<u64>,
+    buffer_rounding: Option<BufferRounding>,
+    events: &EventLog,
+) -> ScalingReport {
+    ScalingReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        metadata: output::run_metadata(),
+        build: output::build_info(),
+        virtualization: system::detect_virtualization().describe(),
+        duration_secs,
+        results: results
+            .iter()
+            .map(|r| ScalingEntry {
+                threads:               r.threads,
+                gb_per_sec:            r.bytes_per_sec as f64 / 1e9,
+                gb_per_sec_per_thread: r.per_thread() as f64 / 1e9,
+                speedup:               results.first().and_then(|single| r.speedup(single)),
+            })
+            .collect(),
+        knee_threads: scaling_knee(results).map(|knee| knee.threads),
+        events: events.reports(),
+        swap_pages,
+        buffer_rounding,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, ops_per_sec: u64) -> WorkloadResult {
+        WorkloadResult {
+            name: name.to_string(),
+            ops_per_sec,
+            duration_secs: 10,
+            setup: Duration::ZERO,
+            measured: Duration::from_secs(10),
+            converged: None,
+            imbalance: None,
+            path: None,
+        }
+    }
+
+    #[test]
+    fn test_junit_fails_the_stopped_workload_and_skips_the_rest() {
+        let groups: Vec<WorkloadGroup> = ["integer", "float", "mixed"]
+            .into_iter()
+            .map(|name| WorkloadGroup {
+                spec:    registry::find(name).unwrap(),
+                threads: 2,
+            })
+            .collect();
+        let results = [result("integer", 1_920_000_000), result("float", 1_000)];
+
+        let fault = Some("locus-w1 failed: boom");
+        let suite = junit_suite(&results, &groups, 2, fault, &JunitGates::default(), true);
+        let names: Vec<&str> = suite.cases.iter().map(|case| case.name.as_str()).collect();
+        assert_eq!(names, ["integer", "float", "mixed"]);
+        assert_eq!(suite.failures(), 1);
+        assert_eq!(suite.cases[0].failure, None);
+        assert_eq!(suite.cases[0].time_secs, 10.0);
+        assert!(suite.cases[0].system_out.contains("rate: 1920000000 ops/s\n"));
+        assert!(suite.cases[0].system_out.contains("per thread: 960000000 ops/s"));
+        assert!(suite.cases[0].system_out.contains("vs reference: +100.0% (1 thread"));
+        assert_eq!(suite.cases[1].failure.as_deref(), Some("locus-w1 failed: boom"));
+        assert_eq!(
+            suite.cases[2].skipped.as_deref(),
+            Some("not run: an earlier workload failed")
+        );
+
+        // An interrupt fails nothing; --min-score adds the score as a testcase
+        let below = "below threshold: score 44.7K/s is under --min-score 1M/s";
+        let gates = JunitGates {
+            min_score:  Some(1_000_000),
+            score_miss: Some(below),
+            baseline:   None,
+        };
+        let suite = junit_suite(&results, &groups, 2, None, &gates, true);
+        assert_eq!(suite.cases[2].skipped.as_deref(), Some("not run: interrupted"));
+        assert_eq!(suite.cases.len(), 4);
+        assert_eq!(suite.cases[3].name, "score");
+        assert_eq!(suite.cases[3].failure.as_deref(), Some(below));
+        assert_eq!(suite.failures(), 1);
+    }
+
+    #[test]
+    fn test_junit_fails_workloads_past_the_baseline() {
+        let groups: Vec<WorkloadGroup> = ["integer", "float", "mixed"]
+            .into_iter()
+            .map(|name| WorkloadGroup {
+                spec:    registry::find(name).unwrap(),
+                threads: 2,
+            })
+            .collect();
+        let saved = [result("integer", 2_000_000), result("float", 1_000)];
+        let baseline = Baseline {
+            saved:    compare::Column {
+                label:  "desktop".to_string(),
+                report: benchmark_report(&saved, 2, 10, None, None, &EventLog::new(), true),
+            },
+            fail_pct: 10,
+        };
+        let results = [
+            result("integer", 1_700_000),
+            result("float", 950),
+            result("mixed", 500),
+        ];
+        let gates = JunitGates {
+            baseline: Some(&baseline),
+            ..JunitGates::default()
+        };
+        let suite = junit_suite(&results, &groups, 2, None, &gates, true);
+
+        assert_eq!(suite.failures(), 1);
+        let failure = suite.cases[0].failure.as_deref().unwrap();
+        assert!(failure.contains("15.0% under desktop's"), "{}", failure);
+        assert!(failure.contains("(--fail-threshold 10%)"), "{}", failure);
+        assert!(
+            suite.cases[0]
+                .system_out
+                .contains("vs baseline: -15.0% (desktop; fails under -10%)")
+        );
+        // Within the threshold, and not in the baseline at all
+        assert_eq!(suite.cases[1].failure, None);
+        assert!(suite.cases[1].system_out.contains("vs baseline: -5.0%"));
+        assert!(!suite.cases[2].system_out.contains("vs baseline"));
+        assert!(baseline.check(&result("mixed", 1)).is_ok());
+        // Measured nothing this time: skipped, not a regression
+        assert!(baseline.check(&result("integer", 0)).is_ok());
+    }
+
+    #[test]
+    fn test_thread_imbalance_names_the_slowest_thread() {
+        let imbalance = thread_imbalance(&[1000, 1000, 700, 1300]).unwrap();
+        assert!((imbalance.spread - 0.6).abs() < 1e-9);
+        assert_eq!(imbalance.slowest, 2);
+
+        assert_eq!(thread_imbalance(&[500, 500]).unwrap().spread, 0.0);
+        assert_eq!(thread_imbalance(&[500]), None);
+        assert_eq!(thread_imbalance(&[0, 0]), None);
+    }
+
+    #[test]
+    fn test_table_notes_only_large_imbalance() {
+        let mut uneven = result("integer", 1_000_000);
+        uneven.imbalance = thread_imbalance(&[100, 100, 100, 70]);
+        let mut even = result("float", 1_000_000);
+        even.imbalance = thread_imbalance(&[100, 100, 100, 95]);
+        let mut out = Vec::new();
+        write_benchmark_table(
+            &mut out,
+            &RunMetadata::default(),
+            &[uneven, even],
+            4,
+            None,
+            None,
+            true,
+        )
+        .unwrap();
+        let table = String::from_utf8(out).unwrap();
+
+        assert!(
+            table.contains("Imbalance: 32% integer (slowest: locus-w3)"),
+            "{}",
+            table
+        );
+        assert!(!table.contains("Imbalance: 5% float"), "{}", table);
+    }
+
+    #[test]
+    fn test_table_footnotes_dispatched_paths() {
+        let mut prefetched = result("memory-bandwidth", 1_000_000);
+        prefetched.path = Some("precomputed-address kernel, PREFETCHT0");
+        let mut out = Vec::new();
+        let results = [result("integer", 1_000_000), prefetched];
+        write_benchmark_table(
+            &mut out,
+            &RunMetadata::default(),
+            &results,
+            4,
+            None,
+            None,
+            true,
+        )
+        .unwrap();
+        let table = String::from_utf8(out).unwrap();
+
+        assert!(
+            table.contains("Path: memory-bandwidth: precomputed-address kernel"),
+            "{}",
+            table
+        );
+        assert!(!table.contains("Path: integer"), "{}", table);
+    }
+
+    #[test]
+    fn test_table_scores_against_the_reference() {
+        let at_reference: Vec<WorkloadResult> = reference::REFERENCE_RATES
+            .iter()
+            .map(|&(name, ops)| result(name, ops))
+            .collect();
+        let mut out = Vec::new();
+        write_benchmark_table(
+            &mut out,
+            &RunMetadata::default(),
+            &at_reference,
+            1,
+            None,
+            None,
+            true,
+        )
+        .unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert!(table.contains("Reference: integer 100%, float 100%"), "{}", table);
+        assert!(table.contains("Index: 100 (100 = "), "{}", table);
+
+        let mut out = Vec::new();
+        write_benchmark_table(
+            &mut out,
+            &RunMetadata::default(),
+            &at_reference[1..],
+            1,
+            None,
+            None,
+            true,
+        )
+        .unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert!(table.contains("Index: needs the whole default set"), "{}", table);
+
+        // Another kernel would score a different benchmark
+        let mut out = Vec::new();
+        write_benchmark_table(
+            &mut out,
+            &RunMetadata::default(),
+            &at_reference,
+            1,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert!(table.contains("Index: n/a (non-default kernel options)"), "{}", table);
+        assert!(!table.contains("Reference:"), "{}", table);
+        let report =
+            benchmark_report(&at_reference, 1, 10, None, None, &EventLog::new(), false);
+        assert_eq!(report.index, None);
+        assert!(report.results.iter().all(|r| r.percent_of_reference.is_none()));
+    }
+
+    #[test]
+    fn test_table_notes_adaptive_durations() {
+        let mut settled = result("integer", 1_000_000);
+        settled.measured = Duration::from_secs(14);
+        settled.converged = Some(true);
+        let mut capped = result("float", 1_000_000);
+        capped.measured = Duration::from_secs(120);
+        capped.converged = Some(false);
+        let mut out = Vec::new();
+        write_benchmark_table(
+            &mut out,
+            &RunMetadata::default(),
+            &[settled, capped],
+            4,
+            None,
+            None,
+            true,
+        )
+        .unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert!(
+            table.contains("Adaptive: integer 14.00s, float 2m 0s (cap, not settled)"),
+            "{}",
+            table
+        );
+
+        let mut out = Vec::new();
+        let fixed = [result("integer", 1_000_000)];
+        write_benchmark_table(
+            &mut out,
+            &RunMetadata::default(),
+            &fixed,
+            4,
+            None,
+            None,
+            true,
+        )
+        .unwrap();
+        assert!(!String::from_utf8(out).unwrap().contains("Adaptive:"));
+    }
+
+    #[test]
+    fn test_parse_workload_durations() {
+        let durations = parse_workload_durations("integer=5, memory-bandwidth=20").unwrap();
+        assert_eq!(durations.len(), 2);
+        assert_eq!(durations["integer"], 5);
+        assert_eq!(durations["memory-bandwidth"], 20);
+
+        // Aliases land on the canonical name
+        let durations = parse_workload_durations("memory=30").unwrap();
+        assert_eq!(durations["memory-latency"], 30);
+    }
+
+    #[test]
+    fn test_parse_workload_durations_rejects_bad_input() {
+        assert!(parse_workload_durations("bogus=5").is_err());
+        assert!(parse_workload_durations("integer").is_err());
+        assert!(parse_workload_durations("integer=0").is_err());
+        assert!(parse_workload_durations("integer=-5").is_err());
+        assert!(parse_workload_durations("integer=5s").is_err());
+        assert!(parse_workload_durations("memory=5,memory-latency=10").is_err());
+        assert!(parse_workload_durations("").is_err());
+    }
+
+    #[test]
+    fn test_suite_eta_adds_setup_seen_so_far() {
+        let secs = Duration::from_secs;
+        let planned = [secs(60), secs(60), secs(30)];
+
+        let first = suite_progress(&planned, &[], &[]);
+        assert_eq!((first.index, first.count), (0, 3));
+        assert_eq!((first.done, first.eta), (0.0, secs(150)));
+
+        // The first workload took 4s to set up; the rest are expected to too
+        let second = suite_progress(&planned, &[secs(64)], &[secs(4)]);
+        assert_eq!(second.eta, secs(64 + 34));
+        assert!((second.done - 64.0 / 162.0).abs() < 1e-9);
+        assert_eq!(
+            second.describe("float", false),
+            "workload 2/3 (float) — suite 40% — ETA 1m 38s"
+        );
+
+        let last = suite_progress(&planned, &[secs(64), secs(62)], &[secs(4), secs(2)]);
+        assert_eq!(last.eta, secs(33));
+        assert_eq!(
+            last.describe("mixed", true),
+            "workload 3/3 (mixed) — suite 79% — ETA at most 33s"
         );
     }
 
-    println!("└──────────────────┴─────────────┴──────────┴─────────────────┘");
-    println!("\nBaseline: Mixed = 1.0x | Threads: {}", num_threads);
+    #[test]
+    fn test_solo_phases_keep_their_contended_cores() {
+        let pair = ["memory-bandwidth", "integer"].map(|name| WorkloadGroup {
+            spec:    registry::find(name).unwrap(),
+            threads: 2,
+        });
+        let cores = [7, 6, 5, 4];
+        assert_eq!(side_cores(&cores, &pair), [&[7, 6][..], &[5, 4][..]]);
+        assert!(side_cores(&[], &pair).iter().all(|side| side.is_empty()));
+    }
+
+    #[test]
+    fn test_interference_slowdown_against_the_solo_rate() {
+        let side = |solo, contended| InterferenceResult {
+            name: "integer".to_string(),
+            threads: 4,
+            solo,
+            contended,
+        };
+        assert_eq!(side(2_000, Some(1_500)).slowdown_pct(), Some(25.0));
+        // Sharing can help, e.g. when the other side frees a turbo budget
+        let faster = side(1_000, Some(1_100)).slowdown_pct().unwrap();
+        assert!((faster + 10.0).abs() < 1e-9);
+        assert_eq!(side(0, Some(10)).slowdown_pct(), None);
+        assert_eq!(side(1_000, None).slowdown_pct(), None);
+
+        assert_eq!(interference_solo_secs(20), 4);
+        assert_eq!(interference_solo_secs(3), 1);
+    }
+
+    #[test]
+    fn test_scaling_thread_counts_double_up_to_the_cap() {
+        assert_eq!(scaling_thread_counts(1), [1]);
+        assert_eq!(scaling_thread_counts(2), [1, 2]);
+        assert_eq!(scaling_thread_counts(6), [1, 2, 4, 6]);
+        assert_eq!(scaling_thread_counts(16), [1, 2, 4, 8, 16]);
+        assert_eq!(scaling_thread_counts(24), [1, 2, 4, 8, 16, 24]);
+        // -j 0 resolves before this, but a stray 0 still runs one step
+        assert_eq!(scaling_thread_counts(0), [1]);
+    }
+
+    #[test]
+    fn test_scaling_knee_is_the_first_step_near_the_best() {
+        let step = |threads, gb: u64| ScalingResult {
+            threads,
+            bytes_per_sec: gb * 1_000_000_000,
+        };
+        let results = [step(1, 12), step(2, 23), step(4, 38), step(8, 41), step(16, 40)];
+        assert_eq!(scaling_knee(&results), Some(&results[2]));
+        assert_eq!(results[2].speedup(&results[0]), Some(38.0 / 12.0));
+        assert_eq!(results[3].per_thread(), 5_125_000_000);
+        assert_eq!(scaling_knee(&[step(1, 0)]), None);
+
+        let mut out = Vec::new();
+        let rounding = BufferRounding::between(12, 8);
+        write_scaling_table(&mut out, &RunMetadata::default(), &results, 5, None, rounding)
+            .unwrap();
+        let table = String::from_utf8(out).unwrap();
+        for line in [
+            "        4     38.00 GB/s      9.50 GB/s     3.17x",
+            "Knee: 4 threads reach 90% of the best 41.00 GB/s",
+            "Buffer: 8 MiB per thread (rounded down from 12 MiB for mask indexing)",
+        ] {
+            assert!(table.contains(line), "{}", table);
+        }
+    }
+
+    #[test]
+    fn test_parse_interference() {
+        let [a, b] = parse_interference("memory-bandwidth:4, integer:2").unwrap();
+        assert_eq!((a.spec.name, a.threads), ("memory-bandwidth", 4));
+        assert_eq!((b.spec.name, b.threads), ("integer", 2));
+
+        for bad in [
+            "integer:4",
+            "integer:1,float:1,mixed:1",
+            "integer=4,float=4",
+            "integer:0,float:1",
+            "integer:x,float:1",
+            "nope:1,float:1",
+            "integer:1,integer:2",
+        ] {
+            assert!(parse_interference(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_interference_table_marks_skipped_phases() {
+        let results = [
+            InterferenceResult {
+                name:      "memory-bandwidth".to_string(),
+                threads:   4,
+                solo:      2_000_000,
+                contended: Some(1_800_000),
+            },
+            InterferenceResult {
+                name:      "integer".to_string(),
+                threads:   4,
+                solo:      8_000_000,
+                contended: None,
+            },
+        ];
+        let mut out = Vec::new();
+        write_interference_table(&mut out, &RunMetadata::default(), &results, 4, 20, None, None)
+            .unwrap();
+        let table = String::from_utf8(out).unwrap();
+        let row = |name: &str| {
+            table
+                .lines()
+                .find(|line| line.trim_start().starts_with(name))
+                .unwrap()
+        };
+
+        assert!(row("memory-bandwidth").contains("1.80M /s"));
+        assert!(row("memory-bandwidth").ends_with("10.0%"));
+        assert!(row("integer").contains("8.00M /s"));
+        assert!(row("integer").ends_with("n/a"));
+        assert!(table.contains("Solo: 4s each | Contended: 20s side by side"));
+    }
+
+    #[test]
+    fn test_auto_warmup_is_a_capped_fraction() {
+        assert_eq!(auto_warmup(0), Duration::ZERO);
+        assert_eq!(auto_warmup(5), Duration::from_millis(500));
+        assert_eq!(auto_warmup(3600), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_empty_workload_selection_is_an_error() {
+        let err = benchmark_groups(&[], 4).unwrap_err();
+        assert!(matches!(err, LocusError::NoWorkloadsSelected));
+        let message = err.to_string();
+        assert!(message.contains("no workloads left"));
+        assert!(
+            message.contains("memory-bandwidth"),
+            "lists the choices: {}",
+            message
+        );
+
+        let all: Vec<&'static WorkloadSpec> = registry::WORKLOADS.iter().collect();
+        let groups = benchmark_groups(&all, 4).unwrap();
+        assert_eq!(groups.len(), registry::WORKLOADS.len());
+        assert!(groups.iter().all(|g| g.threads == 4));
+    }
+
+    #[test]
+    fn test_benchmark_sets_keep_the_mixed_baseline() {
+        let names = |set: BenchmarkSet| -> Vec<&str> {
+            set.specs().iter().map(|spec| spec.name).collect()
+        };
+        assert_eq!(names(BenchmarkSet::Default), [
+            "integer",
+            "float",
+            "mixed",
+            "memory-latency",
+            "memory-bandwidth",
+        ]);
+        assert_eq!(names(BenchmarkSet::Memory), [
+            "mixed",
+            "memory-latency",
+            "memory-bandwidth",
+            "cache-thrash",
+        ]);
+        assert!(!names(BenchmarkSet::Compute).contains(&"memory-latency"));
+        assert_eq!(BenchmarkSet::All.specs().len(), registry::WORKLOADS.len());
+        for set in BenchmarkSet::value_variants() {
+            assert!(names(*set).contains(&"mixed"), "{}", set.as_str());
+        }
+    }
+
+    #[test]
+    fn test_report_uses_registry_order_and_mixed_baseline() {
+        let results = [
+            result("float", 400),
+            result("mixed", 200),
+            result("integer", 800),
+        ];
+        let report = benchmark_report(&results, 4, 10, Some(0), None, &EventLog::new(), true);
+
+        let names: Vec<&str> = report.results.iter().map(|r| r.workload.as_str()).collect();
+        assert_eq!(names, ["integer", "float", "mixed"]);
+        assert_eq!(report.results[0].relative_to_mixed, 4.0);
+        assert_eq!(report.results[0].per_thread_ops_per_sec, 200);
+    }
+
+    #[test]
+    fn test_report_json_has_every_workload_in_detail() {
+        let results: Vec<WorkloadResult> = registry::WORKLOADS
+            .iter()
+            .map(|spec| result(spec.name, 1_000_000))
+            .collect();
+        let report = benchmark_report(&results, 2, 10, None, None, &EventLog::new(), true);
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: BenchmarkReport = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.results.len(), registry::WORKLOADS.len());
+        assert_eq!(parsed.score, Some(1_000_000));
+        for (entry, spec) in parsed.results.iter().zip(registry::WORKLOADS) {
+            assert_eq!(entry.workload, spec.name);
+            assert_eq!(entry.ops_per_sec, 1_000_000);
+            assert_eq!(entry.relative_to_mixed, 1.0);
+            assert_eq!(entry.per_thread_ops_per_sec, 500_000);
+            let expected = spec.bytes_per_op.map(|bytes| bytes as f64 / 1e3);
+            assert_eq!(entry.gb_per_sec, expected, "{}", spec.name);
+        }
+    }
+
+    #[test]
+    fn test_composite_score_is_a_geometric_mean() {
+        let results = [
+            result("integer", 800),
+            result("float", 200),
+            result("mixed", 0),
+        ];
+        assert_eq!(composite_score(&results), Some(400));
+        assert_eq!(composite_score(&[result("mixed", 0)]), None);
+        assert_eq!(composite_score(&[]), None);
+    }
+
+    #[test]
+    fn test_report_json_carries_run_metadata() {
+        let events = EventLog::new();
+        let report = benchmark_report(&[result("mixed", 1)], 1, 1, None, None, &events, true);
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: BenchmarkReport = serde_json::from_str(&json).unwrap();
+        let metadata = parsed.metadata;
+
+        assert!(!metadata.hostname.is_empty());
+        assert!(!metadata.system.cpu_brand.is_empty());
+        assert_eq!(metadata.system.os, std::env::consts::OS);
+        assert_eq!(metadata.system.arch, std::env::consts::ARCH);
+        // 2024-03-09T14:05:00Z
+        assert_eq!(metadata.timestamp.len(), 20);
+        assert!(metadata.timestamp.ends_with('Z'));
+        assert_eq!(
+            metadata,
+            output::run_metadata(),
+            "captured once per process"
+        );
+    }
+
+    #[test]
+    fn test_table_shows_bandwidth_in_gb_per_sec() {
+        let bytes_per_op = registry::find("memory-bandwidth")
+            .unwrap()
+            .bytes_per_op
+            .unwrap();
+        let results = [
+            result("integer", 1_000_000),
+            result("memory-bandwidth", 2_500_000_000 / bytes_per_op),
+        ];
+        let mut out = Vec::new();
+        write_benchmark_table(
+            &mut out,
+            &RunMetadata::default(),
+            &results,
+            4,
+            None,
+            None,
+            true,
+        )
+        .unwrap();
+        let table = String::from_utf8(out).unwrap();
+
+        assert!(
+            table.contains("Memory BW: 2.50 GB/s memory-bandwidth"),
+            "{}",
+            table
+        );
+        assert_eq!(table.matches("Memory BW:").count(), 1);
+    }
 }
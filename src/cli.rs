@@ -1,190 +1,2621 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+
 use anstyle::{AnsiColor, Color, Style};
-use clap::Parser;
+use clap::builder::PossibleValuesParser;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum, value_parser};
+
+use crate::affinity::{CoreOrder, Pinning, SmtPolicy};
+use crate::benchmark::BenchmarkSet;
+use crate::config::{self, Value};
+use crate::numa::NumaPolicy;
+use crate::output::{self, Format, ProgressFormat};
+use crate::system::CacheLevel;
+use crate::workload::{AccessPattern, FloatMode, InitPattern};
+use crate::{benchmark, notify, registry, reporting, system, workload};
 
 #[derive(Parser, Debug)]
 #[command(name = "locus")]
 #[command(version, about = "CPU stress test with memory subsystem pressure", long_about = None)]
 pub struct Args {
-    #[arg(short, long, default_value_t = 0)]
+    #[arg(env = "LOCUS_DURATION", short, long, default_value_t = 0)]
     pub duration: u64,
 
-    #[arg(short = 'j', long, default_value_t = 0)]
+    /// Stop once the workers have done N ops in total, e.g. 1B
+    #[arg(env = "LOCUS_OPS", long, value_name = "N", value_parser = parse_ops)]
+    pub ops: Option<u64>,
+
+    /// Run one batch per worker, print the totals and exit; a smoke test
+    #[arg(env = "LOCUS_ONCE", long)]
+    pub once: bool,
+
+    #[arg(env = "LOCUS_THREADS", short = 'j', long, default_value_t = 0)]
     pub threads: usize,
 
     #[arg(short, long, default_value = "mixed")]
-    #[arg(value_parser = ["integer", "float", "memory", "memory-latency", "memory-bandwidth", "mixed"])]
+    #[arg(env = "LOCUS_WORKLOAD", value_parser = PossibleValuesParser::new(registry::accepted_names()))]
     pub workload: String,
 
     /// 0 = auto-detect, overrides -x
-    #[arg(short = 'm', long, default_value_t = 0)]
+    #[arg(env = "LOCUS_MEMORY_MB", short = 'm', long, default_value_t = 0)]
     pub memory_mb: usize,
 
-    /// 2=light, 4=balanced, 8=aggressive, 16=extreme
-    #[arg(short = 'x', long, default_value_t = 4)]
+    /// Times the per-complex L3: 2=light, 4=balanced, 8=aggressive, 16=extreme
+    #[arg(
+        env = "LOCUS_MEMORY_MULTIPLIER",
+        short = 'x',
+        long,
+        default_value_t = 4
+    )]
     pub memory_multiplier: usize,
 
-    /// Iterations between stop checks
-    #[arg(short, long, default_value_t = 100_000)]
-    pub batch_size: u64,
+    /// Don't shrink auto-sized buffers to fit in --ram-safety of RAM
+    #[arg(env = "LOCUS_NO_RAM_CAP", long)]
+    pub no_ram_cap: bool,
+
+    /// Keep the buffer at exactly -m/auto size instead of rounding it down
+    /// to a power of two for mask indexing
+    #[arg(env = "LOCUS_EXACT_BUFFER", long)]
+    pub exact_buffer: bool,
+
+    /// Run with a smaller buffer where a worker can't allocate its full one,
+    /// instead of failing the run
+    #[arg(env = "LOCUS_BEST_EFFORT_MEMORY", long)]
+    pub best_effort_memory: bool,
+
+    /// Fraction of RAM auto-sized buffers may use (0.1-1.0); 95% of available
+    /// memory caps them too
+    #[arg(env = "LOCUS_RAM_SAFETY", long, value_name = "FRACTION", default_value_t = system::DEFAULT_RAM_SAFETY,
+          value_parser = parse_ram_safety)]
+    pub ram_safety: f64,
+
+    /// Hard cap on all auto-sized buffers together, e.g. 4G or 512M
+    #[arg(env = "LOCUS_MAX_TOTAL_MEMORY", long, value_name = "SIZE",
+          value_parser = parse_total_memory)]
+    pub max_total_memory: Option<usize>,
+
+    /// Stop the run if the system starts swapping (Linux), instead of warning
+    #[arg(env = "LOCUS_ABORT_ON_SWAP", long)]
+    pub abort_on_swap: bool,
+
+    /// Iterations between stop checks (default: per workload)
+    #[arg(env = "LOCUS_BATCH_SIZE", short, long, value_parser = parse_batch_size)]
+    pub batch_size: Option<u64>,
+
+    /// Independent pointer chains for memory-latency (1 = pure latency)
+    #[arg(env = "LOCUS_CHAINS", long, value_name = "N", default_value_t = 1,
+          value_parser = parse_chains)]
+    pub chains: usize,
+
+    /// Float kernel: simple (add/mul/sqrt) or transcendental (adds libm calls)
+    #[arg(env = "LOCUS_FLOAT_MODE", long, value_name = "MODE", value_enum,
+          default_value_t = FloatMode::Transcendental)]
+    pub float_mode: FloatMode,
+
+    /// Iterations ahead each memory-bandwidth stream prefetches (0 = off)
+    #[arg(env = "LOCUS_PREFETCH", long, value_name = "DISTANCE", default_value_t = 0,
+          value_parser = parse_prefetch)]
+    pub prefetch: usize,
+
+    /// Buffer contents: sequential, random, or cycle (a permutation memory-latency chases)
+    #[arg(env = "LOCUS_INIT_PATTERN", long, value_name = "PATTERN", value_enum,
+          default_value_t = InitPattern::Sequential)]
+    pub init_pattern: InitPattern,
+
+    /// LCG multiplier for memory-latency and memory-bandwidth (odd; decimal or 0x hex)
+    #[arg(env = "LOCUS_LCG_MULT", long, value_name = "N", value_parser = parse_lcg_mult)]
+    pub lcg_mult: Option<u64>,
+
+    /// Added to every value the memory kernels write, on top of the iteration count
+    #[arg(env = "LOCUS_LCG_ADD", long, value_name = "N", default_value_t = 0,
+          value_parser = parse_lcg_constant)]
+    pub lcg_add: u64,
+
+    /// Step the memory kernels this many words at a time instead of jumping
+    #[arg(env = "LOCUS_STRIDE", long, value_name = "WORDS", value_parser = parse_stride)]
+    pub stride: Option<usize>,
+
+    /// Cache level cache-thrash sweeps twice the size of
+    #[arg(env = "LOCUS_TARGET", long, value_name = "LEVEL", value_enum, default_value_t = CacheLevel::L2)]
+    pub target: CacheLevel,
 
-    /// Disable progress reporting
-    #[arg(short, long)]
+    /// Per-thread workload assignment, e.g. integer=8,memory-bandwidth=8
+    #[arg(env = "LOCUS_SPLIT", long, value_name = "SPLIT")]
+    pub split: Option<String>,
+
+    /// Pin worker N to CPU N; the reporter moves to a CPU with no worker
+    #[arg(env = "LOCUS_AFFINITY", long)]
+    pub affinity: bool,
+
+    /// With --affinity, pin worker 0 to the highest CPU and count down
+    #[arg(env = "LOCUS_AFFINITY_REVERSE", long)]
+    pub affinity_reverse: bool,
+
+    /// With --affinity, one worker per physical core first (spread) or
+    /// both SMT siblings of a core first (compact)
+    #[arg(env = "LOCUS_SMT", long, value_name = "POLICY", value_enum)]
+    pub smt: Option<SmtPolicy>,
+
+    /// Sample per-core busy time and warn about any core under 95% busy
+    #[arg(env = "LOCUS_VERIFY_UTILIZATION", long)]
+    pub verify_utilization: bool,
+
+    /// Delay between spawning successive workers, in ms
+    #[arg(env = "LOCUS_STAGGER", long, value_name = "MS", default_value_t = 0)]
+    pub stagger: u64,
+
+    /// Buffer page placement across NUMA nodes (Linux)
+    #[arg(env = "LOCUS_NUMA", long, value_enum, default_value_t = NumaPolicy::Local)]
+    pub numa: NumaPolicy,
+
+    /// Worker thread stack size in KiB (0 = platform default)
+    #[arg(env = "LOCUS_STACK_SIZE", long, value_name = "KB", default_value_t = 0)]
+    pub stack_size: usize,
+
+    /// Disable live progress; the final summary still prints
+    #[arg(env = "LOCUS_QUIET", short, long)]
     pub quiet: bool,
 
-    /// Run all workloads sequentially
-    #[arg(short = 'B', long)]
+    /// Live progress as a redrawn text line, or JSON lines on stderr
+    #[arg(env = "LOCUS_PROGRESS_FORMAT", long, value_enum,
+          default_value_t = ProgressFormat::Text)]
+    pub progress_format: ProgressFormat,
+
+    /// EMA weight of the newest sample in the progress rate (0.01-1.0;
+    /// 1.0 shows the raw rate only)
+    #[arg(env = "LOCUS_RATE_SMOOTHING", long, value_name = "ALPHA",
+          default_value_t = reporting::DEFAULT_RATE_SMOOTHING,
+          value_parser = parse_rate_smoothing)]
+    pub rate_smoothing: f64,
+
+    /// Warn mid-run when the smoothed rate stays this many percent below its
+    /// best (0 = never)
+    #[arg(env = "LOCUS_THROTTLE_ALERT", long, value_name = "PERCENT",
+          default_value_t = reporting::DEFAULT_THROTTLE_ALERT_PCT,
+          value_parser = parse_drop_pct)]
+    pub throttle_alert: u64,
+
+    /// Every SECS, print a multi-line status block for logs (0 = never)
+    #[arg(env = "LOCUS_LOG_STATUS", long, value_name = "SECS", default_value_t = 0)]
+    pub log_status: u64,
+
+    /// Draw the final rate histogram with # instead of block characters
+    #[arg(env = "LOCUS_ASCII", long)]
+    pub ascii: bool,
+
+    /// Print nothing but errors; the exit code carries the outcome
+    #[arg(env = "LOCUS_SILENT", short, long)]
+    pub silent: bool,
+
+    /// Print op counts and rates in full (12,345,678,901) instead of 12.35B
+    #[arg(env = "LOCUS_EXACT_NUMBERS", long)]
+    pub exact_numbers: bool,
+
+    /// Final report format
+    #[arg(env = "LOCUS_FORMAT", long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// Write the final report to PATH instead of stdout
+    #[arg(env = "LOCUS_OUT", long, value_name = "PATH")]
+    pub out: Option<PathBuf>,
+
+    /// POST a JSON summary to this http:// URL when the run ends
+    #[arg(env = "LOCUS_NOTIFY_URL", long, value_name = "URL", value_parser = check_notify_url)]
+    pub notify_url: Option<String>,
+
+    /// Run the --benchmark-set workloads sequentially
+    #[arg(env = "LOCUS_BENCHMARK", short = 'B', long)]
     pub benchmark: bool,
+
+    /// Same as `locus compare`: show saved results side by side
+    #[arg(long, value_name = "FILE", num_args = 1..)]
+    pub compare: Vec<PathBuf>,
+
+    /// Unmeasured warmup before each benchmark workload, in ms
+    /// (default: a tenth of --duration, at most 2 s)
+    #[arg(env = "LOCUS_BENCH_WARMUP", long, value_name = "MS")]
+    pub bench_warmup: Option<u64>,
+
+    /// Run each benchmark workload until its rate settles, with --duration
+    /// (default 120 s) as the cap
+    #[arg(env = "LOCUS_ADAPTIVE", long)]
+    pub adaptive: bool,
+
+    /// Don't sample CPU load before a benchmark or warn when it's busy
+    #[arg(env = "LOCUS_IGNORE_BACKGROUND_LOAD", long)]
+    pub ignore_background_load: bool,
+
+    /// Sweep memory-bandwidth over 1, 2, 4, ... threads up to -j and report
+    /// where bandwidth stops scaling
+    #[arg(env = "LOCUS_BANDWIDTH_SCALING", long)]
+    pub bandwidth_scaling: bool,
+
+    /// Which workloads the benchmark runs
+    #[arg(env = "LOCUS_BENCHMARK_SET", long, value_name = "SET", value_enum, default_value_t = BenchmarkSet::Default)]
+    pub benchmark_set: BenchmarkSet,
+
+    /// Per-workload benchmark seconds, e.g. integer=5,memory-bandwidth=20
+    #[arg(env = "LOCUS_WORKLOAD_DURATIONS", long, value_name = "LIST")]
+    pub workload_durations: Option<String>,
+
+    /// Exit with code 3 if the average rate is below N ops/s, e.g. 50M
+    #[arg(env = "LOCUS_MIN_RATE", long, value_name = "N", value_parser = parse_rate)]
+    pub min_rate: Option<u64>,
+
+    /// Exit with code 3 if the benchmark score is below N, e.g. 20M
+    #[arg(env = "LOCUS_MIN_SCORE", long, value_name = "N", value_parser = parse_rate)]
+    pub min_score: Option<u64>,
+
+    /// Exit with code 3 if a workload falls --fail-threshold below this saved bench JSON
+    #[arg(env = "LOCUS_BASELINE", long, value_name = "PATH")]
+    pub baseline: Option<PathBuf>,
+
+    /// How far under its --baseline rate a workload may fall, in percent
+    #[arg(env = "LOCUS_FAIL_THRESHOLD", long, value_name = "PERCENT",
+          default_value_t = benchmark::DEFAULT_FAIL_THRESHOLD_PCT,
+          value_parser = parse_drop_pct)]
+    pub fail_threshold: u64,
+
+    /// Measure latency idle vs. under bandwidth load from the other threads
+    #[arg(env = "LOCUS_LOADED_LATENCY", long)]
+    pub loaded_latency: bool,
+
+    /// Run two workloads alone, then side by side, and report the slowdown
+    /// of each, e.g. memory-bandwidth:4,integer:4
+    #[arg(env = "LOCUS_INTERFERENCE", long, value_name = "PAIR")]
+    pub interference: Option<String>,
+
+    /// Skip the confirmation prompt for allocations over half of RAM or the
+    /// cgroup limit
+    #[arg(env = "LOCUS_YES", short = 'y', long)]
+    pub yes: bool,
+
+    /// Resolve and print the run plan without starting workers
+    #[arg(env = "LOCUS_DRY_RUN", long)]
+    pub dry_run: bool,
+
+    /// Print the available workloads and exit
+    #[arg(env = "LOCUS_LIST_WORKLOADS", long)]
+    pub list_workloads: bool,
+
+    /// Read defaults from a TOML profile; flags on the command line win
+    #[arg(env = "LOCUS_CONFIG", long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Print the effective configuration as TOML and exit
+    #[arg(env = "LOCUS_DUMP_CONFIG", long)]
+    pub dump_config: bool,
 }
 
-pub fn print_help() {
-    let header = Style::new()
-        .bold()
-        .fg_color(Some(Color::Ansi(AnsiColor::Cyan)));
-    let cmd = Style::new()
-        .bold()
-        .fg_color(Some(Color::Ansi(AnsiColor::Green)));
-    let opt = Style::new().fg_color(Some(Color::Ansi(AnsiColor::Green)));
-    let value = Style::new().fg_color(Some(Color::Ansi(AnsiColor::Yellow)));
-    let desc = Style::new();
-    let example = Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightBlack)));
-    let reset = Style::new();
+/// Args fields a profile may set, in `--dump-config` order. Mode switches
+/// like --dry-run and --list-workloads stay command-line only.
+const CONFIG_KEYS: &[&str] = &[
+    "duration",
+    "ops",
+    "threads",
+    "workload",
+    "split",
+    "memory_mb",
+    "memory_multiplier",
+    "no_ram_cap",
+    "exact_buffer",
+    "best_effort_memory",
+    "ram_safety",
+    "max_total_memory",
+    "abort_on_swap",
+    "numa",
+    "batch_size",
+    "chains",
+    "float_mode",
+    "prefetch",
+    "init_pattern",
+    "lcg_mult",
+    "lcg_add",
+    "stride",
+    "target",
+    "affinity",
+    "affinity_reverse",
+    "smt",
+    "verify_utilization",
+    "stagger",
+    "stack_size",
+    "quiet",
+    "progress_format",
+    "rate_smoothing",
+    "throttle_alert",
+    "log_status",
+    "ascii",
+    "silent",
+    "exact_numbers",
+    "format",
+    "out",
+    "notify_url",
+    "yes",
+    "benchmark",
+    "bench_warmup",
+    "adaptive",
+    "ignore_background_load",
+    "bandwidth_scaling",
+    "benchmark_set",
+    "workload_durations",
+    "min_rate",
+    "min_score",
+    "baseline",
+    "fail_threshold",
+    "loaded_latency",
+    "interference",
+];
 
-    println!("{}locus{} {}", cmd, reset, env!("CARGO_PKG_VERSION"));
-    println!("A configurable CPU stress testing tool with multiple computational workloads.\n");
+/// `locus <command>`. A bare `locus [OPTIONS]` still takes every flag and
+/// means `run`, or `bench` with -B.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subcommand {
+    Run,
+    Bench,
+    Info,
+    Compare,
+}
 
-    println!("{}USAGE:{}", header, reset);
-    println!("    {}locus{} [OPTIONS]\n", cmd, reset);
+/// Options both `run` and `bench` take.
+const SHARED_OPTIONS: &[&str] = &[
+    "duration",
+    "threads",
+    "memory_mb",
+    "memory_multiplier",
+    "no_ram_cap",
+    "exact_buffer",
+    "best_effort_memory",
+    "ram_safety",
+    "max_total_memory",
+    "abort_on_swap",
+    "numa",
+    "batch_size",
+    "chains",
+    "float_mode",
+    "prefetch",
+    "init_pattern",
+    "lcg_mult",
+    "lcg_add",
+    "stride",
+    "target",
+    "affinity",
+    "affinity_reverse",
+    "smt",
+    "verify_utilization",
+    "stagger",
+    "stack_size",
+    "quiet",
+    "silent",
+    "exact_numbers",
+    "format",
+    "out",
+    "notify_url",
+    "yes",
+    "dry_run",
+    "config",
+    "dump_config",
+];
+const RUN_OPTIONS: &[&str] = &[
+    "workload",
+    "split",
+    "loaded_latency",
+    "interference",
+    "min_rate",
+    "ops",
+    "once",
+    "progress_format",
+    "rate_smoothing",
+    "throttle_alert",
+    "log_status",
+    "ascii",
+];
+const BENCH_OPTIONS: &[&str] = &[
+    "bench_warmup",
+    "adaptive",
+    "ignore_background_load",
+    "bandwidth_scaling",
+    "benchmark_set",
+    "workload_durations",
+    "min_score",
+    "baseline",
+    "fail_threshold",
+];
 
-    println!("{}OPTIONS:{}", header, reset);
+impl Subcommand {
+    pub const ALL: [Self; 4] = [Self::Run, Self::Bench, Self::Info, Self::Compare];
 
-    println!(
-        "  {}-d{}, {}--duration{} {}SECS{}",
-        opt, reset, opt, reset, value, reset
-    );
-    println!(
-        "      {}Duration in seconds (0 = run until Ctrl+C) [default: 0]{}",
-        desc, reset
-    );
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Run => "run",
+            Self::Bench => "bench",
+            Self::Info => "info",
+            Self::Compare => "compare",
+        }
+    }
 
-    println!(
-        "\n  {}-j{}, {}--threads{} {}NUM{}",
-        opt, reset, opt, reset, value, reset
-    );
-    println!(
-        "      {}Number of worker threads (0 = auto-detect all cores) [default: 0]{}",
-        desc, reset
-    );
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|sub| sub.name() == name)
+    }
 
-    println!(
-        "\n  {}-w{}, {}--workload{} {}TYPE{}",
-        opt, reset, opt, reset, value, reset
-    );
-    println!("      {}Workload type: [default: mixed]{}", desc, reset);
-    println!(
-        "        {}integer         {}{}- Pure CPU integer arithmetic{}",
-        value, reset, desc, reset
-    );
-    println!(
-        "        {}float           {}{}- Pure CPU floating-point math{}",
-        value, reset, desc, reset
-    );
-    println!(
-        "        {}memory          {}{}- Memory latency test (fallback){}",
-        value, reset, desc, reset
-    );
-    println!(
-        "        {}memory-latency  {}{}- Explicit RAM latency test{}",
-        value, reset, desc, reset
-    );
-    println!(
-        "        {}memory-bandwidth{}{}- RAM bandwidth saturation{}",
-        value, reset, desc, reset
-    );
-    println!(
-        "        {}mixed           {}{}- Integer + float + memory-latency{}",
-        value, reset, desc, reset
-    );
+    fn about(self) -> &'static str {
+        match self {
+            Self::Run => "Stress the CPU with one workload, a --split mix or --loaded-latency",
+            Self::Bench => "Run a set of workloads in turn and print a comparison table",
+            Self::Info => "Print the detected hardware and the available workloads",
+            Self::Compare => "Show saved `bench --format json` results side by side",
+        }
+    }
 
-    println!(
-        "\n  {}-m{}, {}--memory-mb{} {}MB{}",
-        opt, reset, opt, reset, value, reset
-    );
-    println!(
-        "      {}Memory buffer size in MB (0 = auto-detect, overrides -x) [default: 0]{}",
-        desc, reset
-    );
+    /// Whether this command takes the Args field `id`.
+    fn accepts(self, id: &str) -> bool {
+        let own: &[&str] = match self {
+            Self::Run => RUN_OPTIONS,
+            Self::Bench => BENCH_OPTIONS,
+            Self::Info | Self::Compare => return false,
+        };
+        id == "help" || SHARED_OPTIONS.contains(&id) || own.contains(&id)
+    }
+}
 
-    println!(
-        "\n  {}-x{}, {}--memory-multiplier{} {}NUM{}",
-        opt, reset, opt, reset, value, reset
-    );
-    println!(
-        "      {}Memory multiplier for auto-detection{}",
-        desc, reset
-    );
-    println!(
-        "      {}2=light, 4=balanced, 8=aggressive, 16=extreme [default: 4]{}",
-        desc, reset
-    );
+/// What the command line asked for.
+#[derive(Debug)]
+pub enum Invocation {
+    /// `run`, `bench` or the legacy flat form; `args.benchmark` picks the mode
+    Stress(Box<Args>),
+    Info,
+    Compare(Vec<PathBuf>),
+}
 
-    println!(
-        "\n  {}-b{}, {}--batch-size{} {}NUM{}",
-        opt, reset, opt, reset, value, reset
-    );
-    println!(
-        "      {}Work batch size (iterations between stop checks) [default: 100000]{}",
-        desc, reset
-    );
+/// Parses the command line, then fills in anything it didn't set from
+/// `--config`: defaults < LOCUS_* environment < file < command line.
+pub fn parse() -> Invocation {
+    let argv = translate_stress_ng(std::env::args_os().collect()).unwrap_or_else(|e| {
+        Args::command()
+            .error(ErrorKind::UnknownArgument, e)
+            .exit()
+    });
+    let sub = argv
+        .get(1)
+        .and_then(|a| a.to_str())
+        .and_then(Subcommand::from_name);
 
-    println!("\n  {}-q{}, {}--quiet{}", opt, reset, opt, reset);
-    println!("      {}Disable progress reporting{}", desc, reset);
+    match sub {
+        Some(Subcommand::Info) => {
+            info_command().get_matches_from(&argv[1..]);
+            Invocation::Info
+        },
+        Some(Subcommand::Compare) => {
+            let matches = compare_command().get_matches_from(&argv[1..]);
+            let files = matches.get_many::<PathBuf>("files").into_iter().flatten();
+            Invocation::Compare(files.cloned().collect())
+        },
+        Some(Subcommand::Run | Subcommand::Bench) | None => {
+            let argv = if sub.is_some() { &argv[1..] } else { &argv[..] };
+            let (mut args, matches) = parse_stress(sub, argv).unwrap_or_else(|e| e.exit());
+            if !args.compare.is_empty() {
+                return Invocation::Compare(args.compare);
+            }
+            if let Some(path) = args.config.clone() {
+                let text = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+                    eprintln!("Error: cannot read config {}: {}", path.display(), e);
+                    std::process::exit(1);
+                });
+                if let Err(e) = args.merge_config(&text, &matches, sub) {
+                    eprintln!("Error: {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+                output::set_silent(args.silent);
+            }
+            Invocation::Stress(Box::new(args))
+        },
+    }
+}
 
-    println!("\n  {}-B{}, {}--benchmark{}", opt, reset, opt, reset);
-    println!(
-        "      {}Run all workloads sequentially and display comparison table{}",
-        desc, reset
-    );
+/// The flat command for the legacy form, or a copy that only takes what
+/// `sub` accepts. The rest stay defined (Args needs every field) but are
+/// hidden, ignore LOCUS_* variables and are rejected by `parse_stress`.
+fn stress_command(sub: Option<Subcommand>) -> clap::Command {
+    let cmd = Args::command();
+    let Some(sub) = sub else {
+        return cmd;
+    };
+    cmd.name(sub.name())
+        .bin_name(format!("locus {}", sub.name()))
+        .about(sub.about())
+        .disable_version_flag(true)
+        .mut_args(|arg| {
+            if sub.accepts(arg.get_id().as_str()) {
+                arg
+            } else {
+                arg.hide(true).env(None)
+            }
+        })
+}
 
-    println!("\n  {}-h{}, {}--help{}", opt, reset, opt, reset);
-    println!("      {}Print this help message{}", desc, reset);
+fn parse_stress(
+    sub: Option<Subcommand>,
+    argv: &[OsString],
+) -> Result<(Args, ArgMatches), clap::Error> {
+    let mut cmd = stress_command(sub);
+    let matches = cmd.try_get_matches_from_mut(argv)?;
 
-    println!("\n  {}-V{}, {}--version{}", opt, reset, opt, reset);
-    println!("      {}Print version information{}", desc, reset);
+    if let Some(sub) = sub {
+        let foreign = cmd.get_arguments().find(|arg| {
+            let id = arg.get_id().as_str();
+            !sub.accepts(id) && matches.value_source(id) == Some(ValueSource::CommandLine)
+        });
+        if let Some(arg) = foreign {
+            let flag = arg.get_long().unwrap_or(arg.get_id().as_str()).to_string();
+            return Err(cmd.error(
+                ErrorKind::UnknownArgument,
+                format!("'--{}' is not a `locus {}` option", flag, sub.name()),
+            ));
+        }
+    }
 
-    println!("\n{}EXAMPLES:{}", header, reset);
-    println!(
-        "  {}# Default balanced stress test for 10 seconds{}",
-        example, reset
-    );
-    println!("  {}locus{} -d 10\n", cmd, reset);
+    let mut args = Args::from_arg_matches(&matches)?;
+    // Before resolving the workload, which may print a notice
+    output::set_silent(args.silent);
+    // Reports, headers and --dump-config only ever see the canonical name
+    if let Some(spec) = registry::resolve(&args.workload) {
+        args.workload = spec.name.to_string();
+    }
+    if sub == Some(Subcommand::Bench) {
+        args.benchmark = true;
+    }
+    Ok((args, matches))
+}
 
-    println!(
-        "  {}# Run memory latency workload (pointer-chasing pattern){}",
-        example, reset
-    );
-    println!("  {}locus{} -w memory-latency -d 10 -x 8\n", cmd, reset);
+/// stress-ng flags that have no translation, and what to use instead.
+const STRESS_NG_UNSUPPORTED: &[(&str, &str)] = &[
+    ("cpu-method", "-w integer|float|mixed (see `locus info`)"),
+    ("cpu-load", "none; workers always run flat out"),
+    ("cpu-ops", "--ops N (total ops across workers)"),
+    ("vm-ops", "--ops N (total ops across workers)"),
+    ("vm-method", "-w memory-bandwidth or -w memory-latency with --init-pattern"),
+    ("vm-keep", "none; buffers are allocated once and kept"),
+    ("vm-hang", "none; buffers are allocated once and kept"),
+    ("matrix", "-w float"),
+    ("stream", "-w memory-bandwidth"),
+    ("cache", "-w cache-thrash"),
+    ("all", "locus bench --benchmark-set all"),
+    ("class", "locus bench --benchmark-set compute|memory"),
+    ("sequential", "locus bench"),
+    ("taskset", "--affinity"),
+    ("backoff", "--stagger MS"),
+    ("oomable", "--best-effort-memory"),
+    ("tz", "--log-status SECS (includes the CPU temperature)"),
+    ("yaml", "--format json --out FILE"),
+    ("log-file", "--out FILE"),
+    ("io", "none; locus stresses CPU and memory only"),
+    ("hdd", "none; locus stresses CPU and memory only"),
+    ("fork", "none; locus stresses CPU and memory only"),
+    ("sock", "none; locus stresses CPU and memory only"),
+];
 
-    println!(
-        "  {}# Run memory bandwidth workload (parallel streams){}",
-        example, reset
-    );
-    println!("  {}locus{} -w memory-bandwidth -d 10 -x 8\n", cmd, reset);
+/// The stress-ng flags that translate, as found on the command line.
+#[derive(Debug, Default)]
+struct StressNgFlags {
+    cpu:      Option<u64>,
+    vm:       Option<u64>,
+    vm_bytes: Option<u64>,
+    timeout:  Option<u64>,
+}
 
-    println!("  {}# Run full benchmark suite{}", example, reset);
-    println!("  {}locus{} --benchmark -d 10 -x 8\n", cmd, reset);
+/// Rewrites the common stress-ng flags into native ones so runbooks written
+/// for `stress-ng --cpu 0 --timeout 300s --metrics-brief` work unchanged:
+/// --cpu N is -j N, --timeout is -d, --vm N runs N memory-bandwidth workers
+/// (both together become a --split with mixed), --vm-bytes is -m, and
+/// --metrics(-brief) is dropped since the final stats always print. Other
+/// arguments pass through; stress-ng flags without a translation are errors
+/// that name the native equivalent.
+fn translate_stress_ng(argv: Vec<OsString>) -> Result<Vec<OsString>, String> {
+    let mut flags = StressNgFlags::default();
+    let mut native = Vec::with_capacity(argv.len());
+    let mut rest = argv.into_iter();
 
-    println!(
-        "  {}# Manual memory size override (512 MB per thread){}",
-        example, reset
-    );
-    println!("  {}locus{} -w memory-bandwidth -m 512 -d 10", cmd, reset);
+    while let Some(arg) = rest.next() {
+        let Some((name, inline)) = arg
+            .to_str()
+            .and_then(|a| a.strip_prefix("--"))
+            .map(|a| a.split_once('=').map_or((a, None), |(n, v)| (n, Some(v))))
+        else {
+            native.push(arg);
+            continue;
+        };
+        if name.is_empty() {
+            // `--` ends the options; nothing after it is a flag
+            native.push(arg);
+            native.extend(rest);
+            break;
+        }
+        if let Some((_, equivalent)) = STRESS_NG_UNSUPPORTED.iter().find(|(n, _)| *n == name) {
+            return Err(format!(
+                "stress-ng option '--{}' is not supported, native equivalent is {}",
+                name, equivalent
+            ));
+        }
+        match name {
+            "cpu" | "vm" | "vm-bytes" | "timeout" => {},
+            "metrics" | "metrics-brief" => continue,
+            _ => {
+                native.push(arg);
+                continue;
+            },
+        }
+        let value = match inline {
+            Some(value) => value.to_string(),
+            None => rest
+                .next()
+                .and_then(|v| v.into_string().ok())
+                .ok_or_else(|| format!("stress-ng option '--{}' needs a value", name))?,
+        };
+        let invalid =
+            |reason: String| format!("invalid value '{}' for '--{}': {}", value, name, reason);
+        match name {
+            "cpu" => flags.cpu = Some(parse_instances(&value).map_err(invalid)?),
+            "vm" => flags.vm = Some(parse_instances(&value).map_err(invalid)?),
+            "vm-bytes" => flags.vm_bytes = Some(parse_vm_bytes(&value).map_err(invalid)?),
+            _ => flags.timeout = Some(parse_timeout(&value).map_err(invalid)?),
+        }
+    }
+
+    native.extend(flags.to_native()?.into_iter().map(OsString::from));
+    Ok(native)
+}
+
+impl StressNgFlags {
+    fn to_native(&self) -> Result<Vec<String>, String> {
+        let mut native = Vec::new();
+        match (self.cpu, self.vm) {
+            (Some(0), Some(_)) | (Some(_), Some(0)) => {
+                return Err("stress-ng '--cpu 0' or '--vm 0' together with the other is \
+                            not supported, native equivalent is --split \
+                            mixed=N,memory-bandwidth=M"
+                    .to_string());
+            },
+            (Some(cpu), Some(vm)) => {
+                native.push("--split".to_string());
+                native.push(format!("mixed={},memory-bandwidth={}", cpu, vm));
+            },
+            (Some(cpu), None) => native.extend(["-j".to_string(), cpu.to_string()]),
+            (None, Some(vm)) => native.extend([
+                "-w".to_string(),
+                "memory-bandwidth".to_string(),
+                "-j".to_string(),
+                vm.to_string(),
+            ]),
+            (None, None) => {},
+        }
+        if let Some(mb) = self.vm_bytes {
+            native.extend(["-m".to_string(), mb.to_string()]);
+        }
+        if let Some(secs) = self.timeout {
+            native.extend(["-d".to_string(), secs.to_string()]);
+        }
+        Ok(native)
+    }
+}
+
+/// A stress-ng instance count; 0 means one per CPU, like -j 0.
+fn parse_instances(s: &str) -> Result<u64, String> {
+    s.trim()
+        .parse()
+        .map_err(|_| "expected a number of workers (0 = all CPUs)".to_string())
+}
+
+/// A stress-ng timeout: seconds, or a number with an s, m or h suffix.
+fn parse_timeout(s: &str) -> Result<u64, String> {
+    let trimmed = s.trim();
+    let (digits, scale) = match trimmed.char_indices().last() {
+        Some((i, 's')) => (&trimmed[..i], 1),
+        Some((i, 'm')) => (&trimmed[..i], 60),
+        Some((i, 'h')) => (&trimmed[..i], 3600),
+        _ => (trimmed, 1),
+    };
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| "expected a duration like 300, 300s, 5m or 1h".to_string())?;
+    Ok(value.saturating_mul(scale))
+}
+
+/// A stress-ng --vm-bytes size in bytes, with an optional b, k, m or g
+/// suffix, rounded up to whole MB for -m.
+fn parse_vm_bytes(s: &str) -> Result<u64, String> {
+    let trimmed = s.trim();
+    if trimmed.ends_with('%') {
+        return Err("a share of memory is not supported, native equivalent is \
+                    --ram-safety FRACTION"
+            .to_string());
+    }
+    let lower = trimmed.to_ascii_lowercase();
+    let (digits, scale) = match lower.char_indices().last() {
+        Some((i, 'b')) => (&lower[..i], 1),
+        Some((i, 'k')) => (&lower[..i], 1 << 10),
+        Some((i, 'm')) => (&lower[..i], 1 << 20),
+        Some((i, 'g')) => (&lower[..i], 1 << 30),
+        _ => (lower.as_str(), 1),
+    };
+    let bytes = digits
+        .parse::<u64>()
+        .map_err(|_| "expected a size like 256m or 1g".to_string())?
+        .saturating_mul(scale);
+    Ok(bytes.div_ceil(1 << 20).max(1))
+}
+
+fn info_command() -> clap::Command {
+    clap::Command::new("info")
+        .bin_name("locus info")
+        .about(Subcommand::Info.about())
 }
 
-pub fn print_version() {
+fn compare_command() -> clap::Command {
+    clap::Command::new("compare")
+        .bin_name("locus compare")
+        .about(Subcommand::Compare.about())
+        .arg(
+            Arg::new("files")
+                .value_name("FILE")
+                .num_args(1..)
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+}
+
+impl Args {
+    /// The memory kernels' LCG and stride overrides.
+    pub fn access_pattern(&self) -> AccessPattern {
+        AccessPattern {
+            lcg_mult: self.lcg_mult,
+            lcg_add:  self.lcg_add,
+            stride:   self.stride,
+        }
+    }
+
+    pub fn stack_size_bytes(&self) -> Option<usize> {
+        (self.stack_size > 0).then(|| self.stack_size * 1024)
+    }
+
+    /// JSON or XML on stdout must stay parseable, so the banner is only
+    /// shown when the report is text or goes to a file.
+    pub fn show_banner(&self) -> bool {
+        let parsed_from_stdout = self.format != Format::Text && self.out.is_none();
+        !(self.silent || parsed_from_stdout)
+    }
+
+    /// Seconds per benchmark workload: --duration, which --adaptive takes
+    /// as its cap, defaulting it when unset.
+    pub fn bench_duration_secs(&self) -> u64 {
+        match (self.adaptive, self.duration) {
+            (true, 0) => benchmark::ADAPTIVE_CAP_SECS,
+            (_, secs) => secs,
+        }
+    }
+
+    /// Warmup before a benchmark workload that measures for `duration_secs`.
+    pub fn bench_warmup_duration(&self, duration_secs: u64) -> Duration {
+        match self.bench_warmup {
+            Some(ms) => Duration::from_millis(ms),
+            None => benchmark::auto_warmup(duration_secs),
+        }
+    }
+
+    /// How workers are pinned (None = unpinned).
+    pub fn pinning(&self) -> Option<Pinning> {
+        self.affinity.then_some(Pinning {
+            order: if self.affinity_reverse {
+                CoreOrder::Descending
+            } else {
+                CoreOrder::Ascending
+            },
+            smt:   self.smt,
+        })
+    }
+
+    pub fn progress_quiet(&self) -> bool {
+        self.quiet || !self.show_banner()
+    }
+
+    /// Applies every profile entry the command line didn't set explicitly.
+    /// Keys `sub` doesn't take are skipped.
+    fn merge_config(
+        &mut self,
+        text: &str,
+        matches: &ArgMatches,
+        sub: Option<Subcommand>,
+    ) -> Result<(), config::ConfigError> {
+        for entry in config::parse(text)? {
+            let key = entry.key.replace('-', "_");
+            let err = |message: String| config::ConfigError {
+                line:    entry.line,
+                message: format!("{}: {}", entry.key, message),
+            };
+
+            if !CONFIG_KEYS.contains(&key.as_str()) {
+                return Err(err(format!(
+                    "unknown key (expected one of: {})",
+                    CONFIG_KEYS.join(", ")
+                )));
+            }
+            if matches.value_source(&key) == Some(ValueSource::CommandLine)
+                || sub.is_some_and(|sub| !sub.accepts(&key))
+            {
+                continue;
+            }
+            self.set(&key, &entry.value).map_err(err)?;
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: &Value) -> Result<(), String> {
+        match key {
+            "duration" => self.duration = as_u64(value)?,
+            "ops" => self.ops = Some(as_ops(value)?),
+            "threads" => self.threads = as_u64(value)? as usize,
+            "workload" => {
+                let name = as_str(value)?;
+                let spec = registry::resolve(name).ok_or_else(|| {
+                    format!(
+                        "unknown workload '{}' (available: {})",
+                        name,
+                        registry::accepted_names().join(", ")
+                    )
+                })?;
+                self.workload = spec.name.to_string();
+            },
+            "split" => self.split = Some(as_str(value)?.to_string()),
+            "memory_mb" => self.memory_mb = as_u64(value)? as usize,
+            "memory_multiplier" => self.memory_multiplier = as_u64(value)? as usize,
+            "no_ram_cap" => self.no_ram_cap = as_bool(value)?,
+            "exact_buffer" => self.exact_buffer = as_bool(value)?,
+            "best_effort_memory" => self.best_effort_memory = as_bool(value)?,
+            "abort_on_swap" => self.abort_on_swap = as_bool(value)?,
+            "ram_safety" => {
+                self.ram_safety = match value {
+                    Value::Float(f) => check_ram_safety(*f)?,
+                    Value::Int(n) => check_ram_safety(*n as f64)?,
+                    other => return Err(format!("expected number, got {}", other.type_name())),
+                }
+            },
+            "max_total_memory" => {
+                self.max_total_memory = Some(match value {
+                    Value::Str(size) => parse_total_memory(size)?,
+                    other => check_total_memory(as_u64(other)?)?,
+                })
+            },
+            "numa" => self.numa = as_enum(value)?,
+            "batch_size" => self.batch_size = Some(check_batch_size(as_u64(value)?)?),
+            "affinity" => self.affinity = as_bool(value)?,
+            "affinity_reverse" => self.affinity_reverse = as_bool(value)?,
+            "smt" => self.smt = Some(as_enum(value)?),
+            "verify_utilization" => self.verify_utilization = as_bool(value)?,
+            "chains" => self.chains = check_chains(as_u64(value)?)?,
+            "float_mode" => self.float_mode = as_enum(value)?,
+            "prefetch" => self.prefetch = check_prefetch(as_u64(value)?)?,
+            "init_pattern" => self.init_pattern = as_enum(value)?,
+            "lcg_mult" => self.lcg_mult = Some(check_lcg_mult(as_lcg_constant(value)?)?),
+            "lcg_add" => self.lcg_add = as_lcg_constant(value)?,
+            "stride" => self.stride = Some(check_stride(as_u64(value)?)?),
+            "target" => self.target = as_enum(value)?,
+            "stagger" => self.stagger = as_u64(value)?,
+            "stack_size" => self.stack_size = as_u64(value)? as usize,
+            "quiet" => self.quiet = as_bool(value)?,
+            "rate_smoothing" => {
+                self.rate_smoothing = match value {
+                    Value::Float(f) => check_rate_smoothing(*f)?,
+                    Value::Int(n) => check_rate_smoothing(*n as f64)?,
+                    other => return Err(format!("expected number, got {}", other.type_name())),
+                }
+            },
+            "throttle_alert" => self.throttle_alert = check_drop_pct(as_u64(value)?)?,
+            "log_status" => self.log_status = as_u64(value)?,
+            "ascii" => self.ascii = as_bool(value)?,
+            "silent" => self.silent = as_bool(value)?,
+            "exact_numbers" => self.exact_numbers = as_bool(value)?,
+            "format" => self.format = as_enum(value)?,
+            "progress_format" => self.progress_format = as_enum(value)?,
+            "out" => self.out = Some(PathBuf::from(as_str(value)?)),
+            "notify_url" => self.notify_url = Some(check_notify_url(as_str(value)?)?),
+            "yes" => self.yes = as_bool(value)?,
+            "benchmark" => self.benchmark = as_bool(value)?,
+            "bench_warmup" => self.bench_warmup = Some(as_u64(value)?),
+            "adaptive" => self.adaptive = as_bool(value)?,
+            "ignore_background_load" => self.ignore_background_load = as_bool(value)?,
+            "bandwidth_scaling" => self.bandwidth_scaling = as_bool(value)?,
+            "benchmark_set" => self.benchmark_set = as_enum(value)?,
+            "workload_durations" => {
+                let list = as_str(value)?;
+                benchmark::parse_workload_durations(list)?;
+                self.workload_durations = Some(list.to_string());
+            },
+            "min_rate" => self.min_rate = Some(as_rate(value)?),
+            "min_score" => self.min_score = Some(as_rate(value)?),
+            "baseline" => self.baseline = Some(PathBuf::from(as_str(value)?)),
+            "fail_threshold" => self.fail_threshold = check_drop_pct(as_u64(value)?)?,
+            "loaded_latency" => self.loaded_latency = as_bool(value)?,
+            "interference" => {
+                let pair = as_str(value)?;
+                benchmark::parse_interference(pair)?;
+                self.interference = Some(pair.to_string());
+            },
+            _ => unreachable!("{} is listed in CONFIG_KEYS", key),
+        }
+        Ok(())
+    }
+
+    /// The effective configuration as a profile `--config` reads back.
+    /// Unset optional values are written commented out.
+    pub fn to_toml(&self) -> String {
+        let mut out = format!("# locus {} configuration\n", env!("CARGO_PKG_VERSION"));
+        for key in CONFIG_KEYS {
+            let value = match *key {
+                "duration" => Some(self.duration.to_string()),
+                "ops" => self.ops.map(|n| n.to_string()),
+                "threads" => Some(self.threads.to_string()),
+                "workload" => Some(config::quote(&self.workload)),
+                "split" => self.split.as_deref().map(config::quote),
+                "memory_mb" => Some(self.memory_mb.to_string()),
+                "memory_multiplier" => Some(self.memory_multiplier.to_string()),
+                "no_ram_cap" => Some(self.no_ram_cap.to_string()),
+                "exact_buffer" => Some(self.exact_buffer.to_string()),
+                "best_effort_memory" => Some(self.best_effort_memory.to_string()),
+                "abort_on_swap" => Some(self.abort_on_swap.to_string()),
+                "ram_safety" => Some(format!("{:?}", self.ram_safety)),
+                "max_total_memory" => self.max_total_memory.map(|mb| mb.to_string()),
+                "numa" => Some(config::quote(&enum_name(self.numa))),
+                "batch_size" => self.batch_size.map(|b| b.to_string()),
+                "chains" => Some(self.chains.to_string()),
+                "float_mode" => Some(config::quote(&enum_name(self.float_mode))),
+                "prefetch" => Some(self.prefetch.to_string()),
+                "init_pattern" => Some(config::quote(&enum_name(self.init_pattern))),
+                // Hex, and quoted: most multipliers don't fit a TOML integer
+                "lcg_mult" => self.lcg_mult.map(|n| config::quote(&format!("{:#x}", n))),
+                "lcg_add" => Some(match i64::try_from(self.lcg_add) {
+                    Ok(n) => n.to_string(),
+                    Err(_) => config::quote(&format!("{:#x}", self.lcg_add)),
+                }),
+                "stride" => self.stride.map(|words| words.to_string()),
+                "target" => Some(config::quote(&enum_name(self.target))),
+                "affinity" => Some(self.affinity.to_string()),
+                "affinity_reverse" => Some(self.affinity_reverse.to_string()),
+                "smt" => self.smt.map(|policy| config::quote(&enum_name(policy))),
+                "verify_utilization" => Some(self.verify_utilization.to_string()),
+                "stagger" => Some(self.stagger.to_string()),
+                "stack_size" => Some(self.stack_size.to_string()),
+                "quiet" => Some(self.quiet.to_string()),
+                "rate_smoothing" => Some(format!("{:?}", self.rate_smoothing)),
+                "throttle_alert" => Some(self.throttle_alert.to_string()),
+                "log_status" => Some(self.log_status.to_string()),
+                "ascii" => Some(self.ascii.to_string()),
+                "silent" => Some(self.silent.to_string()),
+                "exact_numbers" => Some(self.exact_numbers.to_string()),
+                "format" => Some(config::quote(&enum_name(self.format))),
+                "progress_format" => Some(config::quote(&enum_name(self.progress_format))),
+                "out" => self
+                    .out
+                    .as_ref()
+                    .map(|p| config::quote(&p.display().to_string())),
+                "notify_url" => self.notify_url.as_deref().map(config::quote),
+                "yes" => Some(self.yes.to_string()),
+                "benchmark" => Some(self.benchmark.to_string()),
+                "bench_warmup" => self.bench_warmup.map(|ms| ms.to_string()),
+                "adaptive" => Some(self.adaptive.to_string()),
+                "ignore_background_load" => Some(self.ignore_background_load.to_string()),
+                "bandwidth_scaling" => Some(self.bandwidth_scaling.to_string()),
+                "benchmark_set" => Some(config::quote(&enum_name(self.benchmark_set))),
+                "workload_durations" => self.workload_durations.as_deref().map(config::quote),
+                "min_rate" => self.min_rate.map(|n| n.to_string()),
+                "min_score" => self.min_score.map(|n| n.to_string()),
+                "baseline" => self
+                    .baseline
+                    .as_ref()
+                    .map(|p| config::quote(&p.display().to_string())),
+                "fail_threshold" => Some(self.fail_threshold.to_string()),
+                "loaded_latency" => Some(self.loaded_latency.to_string()),
+                "interference" => self.interference.as_deref().map(config::quote),
+                _ => unreachable!("{} has no serializer", key),
+            };
+            match value {
+                Some(value) => out += &format!("{} = {}\n", key, value),
+                None => out += &format!("# {} =\n", key),
+            }
+        }
+        out
+    }
+}
+
+fn parse_rate_smoothing(s: &str) -> Result<f64, String> {
+    let alpha: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    check_rate_smoothing(alpha)
+}
+
+fn check_rate_smoothing(alpha: f64) -> Result<f64, String> {
+    if reporting::RATE_SMOOTHING_RANGE.contains(&alpha) {
+        Ok(alpha)
+    } else {
+        Err(format!(
+            "{} is outside {:?}-{:?}",
+            alpha,
+            reporting::RATE_SMOOTHING_RANGE.start(),
+            reporting::RATE_SMOOTHING_RANGE.end()
+        ))
+    }
+}
+
+/// A fall in percent, as --throttle-alert and --fail-threshold take it.
+fn parse_drop_pct(s: &str) -> Result<u64, String> {
+    let pct: u64 = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a whole number", s))?;
+    check_drop_pct(pct)
+}
+
+fn check_drop_pct(pct: u64) -> Result<u64, String> {
+    if pct < 100 {
+        Ok(pct)
+    } else {
+        Err(format!("{} is outside 0-99", pct))
+    }
+}
+
+fn parse_ram_safety(s: &str) -> Result<f64, String> {
+    let fraction: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    check_ram_safety(fraction)
+}
+
+fn check_ram_safety(fraction: f64) -> Result<f64, String> {
+    if system::RAM_SAFETY_RANGE.contains(&fraction) {
+        Ok(fraction)
+    } else {
+        Err(format!(
+            "{} is outside {:?}-{:?}",
+            fraction,
+            system::RAM_SAFETY_RANGE.start(),
+            system::RAM_SAFETY_RANGE.end()
+        ))
+    }
+}
+
+/// Megabytes from "4096", "4096M" or "4G" (binary units, as -m uses).
+fn parse_total_memory(s: &str) -> Result<usize, String> {
+    let trimmed = s.trim();
+    let upper = trimmed.to_ascii_uppercase();
+    let (digits, scale) = if let Some(n) = upper.strip_suffix('G') {
+        (n, 1024)
+    } else if let Some(n) = upper.strip_suffix('M') {
+        (n, 1)
+    } else {
+        (upper.as_str(), 1)
+    };
+    let value: u64 = digits
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a size like 4096, 512M or 4G", trimmed))?;
+    check_total_memory(value.saturating_mul(scale))
+}
+
+fn check_total_memory(mb: u64) -> Result<usize, String> {
+    match usize::try_from(mb) {
+        Ok(0) => Err("must be at least 1 MB".to_string()),
+        Ok(mb) => Ok(mb),
+        Err(_) => Err(format!("{} MB is too large", mb)),
+    }
+}
+
+/// Ops/s as a whole number or with the K/M/B suffixes the reports use,
+/// e.g. "250000", "250K" or "1.5M".
+fn parse_rate(s: &str) -> Result<u64, String> {
+    let trimmed = s.trim();
+    let (digits, scale) = match trimmed.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&trimmed[..i], 1e3),
+        Some((i, 'M' | 'm')) => (&trimmed[..i], 1e6),
+        Some((i, 'B' | 'b')) => (&trimmed[..i], 1e9),
+        _ => (trimmed, 1.0),
+    };
+    match digits.parse::<f64>() {
+        Ok(n) if n.is_finite() && n > 0.0 && n * scale <= u64::MAX as f64 => {
+            Ok((n * scale).round() as u64)
+        },
+        _ => Err(format!(
+            "'{}' is not a positive rate like 250000, 250K or 1.5M",
+            trimmed
+        )),
+    }
+}
+
+fn as_rate(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Str(s) => parse_rate(s),
+        Value::Int(n) if *n > 0 => Ok(*n as u64),
+        Value::Int(n) => Err(format!("must be at least 1, got {}", n)),
+        other => Err(format!(
+            "expected integer or string, got {}",
+            other.type_name()
+        )),
+    }
+}
+
+/// An op count takes the same suffixes as a rate.
+fn parse_ops(s: &str) -> Result<u64, String> {
+    parse_rate(s).map_err(|_| {
+        format!(
+            "'{}' is not a positive op count like 1000000, 500M or 1B",
+            s.trim()
+        )
+    })
+}
+
+fn as_ops(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Str(s) => parse_ops(s),
+        other => as_rate(other),
+    }
+}
+
+fn parse_batch_size(s: &str) -> Result<u64, String> {
+    let batch: u64 = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a whole number", s))?;
+    check_batch_size(batch)
+}
+
+/// A batch of 0 would spin on the stop check and count nothing.
+fn check_batch_size(batch: u64) -> Result<u64, String> {
+    match batch {
+        0 => Err("must be at least 1".to_string()),
+        batch => Ok(batch),
+    }
+}
+
+fn parse_chains(s: &str) -> Result<usize, String> {
+    let chains: u64 = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a whole number", s))?;
+    check_chains(chains)
+}
+
+fn check_chains(chains: u64) -> Result<usize, String> {
+    match usize::try_from(chains) {
+        Ok(n) if (1..=workload::MAX_CHAINS).contains(&n) => Ok(n),
+        _ => Err(format!("{} is outside 1-{}", chains, workload::MAX_CHAINS)),
+    }
+}
+
+fn parse_prefetch(s: &str) -> Result<usize, String> {
+    let distance: u64 = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a whole number", s))?;
+    check_prefetch(distance)
+}
+
+fn check_prefetch(distance: u64) -> Result<usize, String> {
+    match usize::try_from(distance) {
+        Ok(n) if n <= workload::MAX_PREFETCH => Ok(n),
+        _ => Err(format!("{} is outside 0-{}", distance, workload::MAX_PREFETCH)),
+    }
+}
+
+/// An LCG constant in decimal or 0x hex, so published multipliers paste in
+/// as written.
+fn parse_lcg_constant(s: &str) -> Result<u64, String> {
+    let trimmed = s.trim();
+    let digits = trimmed.replace('_', "");
+    let parsed = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => digits.parse(),
+    };
+    parsed.map_err(|_| format!("'{}' is not a 64-bit number (decimal or 0x hex)", trimmed))
+}
+
+fn parse_lcg_mult(s: &str) -> Result<u64, String> {
+    check_lcg_mult(parse_lcg_constant(s)?)
+}
+
+/// An even multiplier shifts a zero into the value's low bit every step,
+/// so the indices derived from it collapse onto a shrinking set of words.
+fn check_lcg_mult(mult: u64) -> Result<u64, String> {
+    match mult & 1 {
+        1 => Ok(mult),
+        _ => Err(format!("{:#x} is even; the multiplier must be odd", mult)),
+    }
+}
+
+/// TOML integers stop at i64::MAX, so larger constants come as strings.
+fn as_lcg_constant(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Str(s) => parse_lcg_constant(s),
+        other => as_u64(other),
+    }
+}
+
+fn parse_stride(s: &str) -> Result<usize, String> {
+    let stride: u64 = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a whole number", s))?;
+    check_stride(stride)
+}
+
+fn check_stride(stride: u64) -> Result<usize, String> {
+    match usize::try_from(stride) {
+        Ok(0) => Err("must be at least 1 word".to_string()),
+        Ok(words) => Ok(words),
+        Err(_) => Err(format!("{} words is too large", stride)),
+    }
+}
+
+fn check_notify_url(url: &str) -> Result<String, String> {
+    notify::parse_url(url).map(|_| url.to_string())
+}
+
+fn as_u64(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Int(n) => {
+            u64::try_from(*n).map_err(|_| format!("must not be negative, got {}", n))
+        },
+        other => Err(format!("expected integer, got {}", other.type_name())),
+    }
+}
+
+fn as_bool(value: &Value) -> Result<bool, String> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        other => Err(format!("expected true or false, got {}", other.type_name())),
+    }
+}
+
+fn as_str(value: &Value) -> Result<&str, String> {
+    match value {
+        Value::Str(s) => Ok(s),
+        other => Err(format!("expected string, got {}", other.type_name())),
+    }
+}
+
+fn as_enum<T: ValueEnum>(value: &Value) -> Result<T, String> {
+    let name = as_str(value)?;
+    T::from_str(name, true).map_err(|_| {
+        let choices: Vec<String> = T::value_variants()
+            .iter()
+            .map(|v| enum_name(v.clone()))
+            .collect();
+        format!(
+            "invalid value '{}' (expected one of: {})",
+            name,
+            choices.join(", ")
+        )
+    })
+}
+
+fn enum_name<T: ValueEnum>(value: T) -> String {
+    value
+        .to_possible_value()
+        .expect("no value variants are skipped")
+        .get_name()
+        .to_string()
+}
+
+/// Colors shared by every help page.
+struct HelpStyles {
+    header:  Style,
+    cmd:     Style,
+    opt:     Style,
+    value:   Style,
+    desc:    Style,
+    example: Style,
+    reset:   Style,
+}
+
+impl HelpStyles {
+    fn new() -> Self {
+        Self {
+            header:  Style::new()
+                .bold()
+                .fg_color(Some(Color::Ansi(AnsiColor::Cyan))),
+            cmd:     Style::new()
+                .bold()
+                .fg_color(Some(Color::Ansi(AnsiColor::Green))),
+            opt:     Style::new().fg_color(Some(Color::Ansi(AnsiColor::Green))),
+            value:   Style::new().fg_color(Some(Color::Ansi(AnsiColor::Yellow))),
+            desc:    Style::new(),
+            example: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightBlack))),
+            reset:   Style::new(),
+        }
+    }
+}
+
+/// One entry in the help pages, keyed by its Args field so each subcommand
+/// lists only the options it takes.
+struct OptionHelp {
+    id:    &'static str,
+    short: Option<char>,
+    long:  &'static str,
+    value: Option<&'static str>,
+    lines: &'static [&'static str],
+}
+
+const OPTION_HELP: &[OptionHelp] = &[
+    OptionHelp {
+        id:    "duration",
+        short: Some('d'),
+        long:  "duration",
+        value: Some("SECS"),
+        lines: &[
+            "Duration in seconds, per workload for bench (0 = run until Ctrl+C)",
+            "[default: 0; bench needs a duration]",
+        ],
+    },
+    OptionHelp {
+        id:    "ops",
+        short: None,
+        long:  "ops",
+        value: Some("N"),
+        lines: &[
+            "Stop after N ops across all workers (e.g. 1B) for fixed-work",
+            "comparisons; instead of --duration",
+        ],
+    },
+    OptionHelp {
+        id:    "once",
+        short: None,
+        long:  "once",
+        value: None,
+        lines: &[
+            "Run one batch per worker and exit: a quick check that allocation,",
+            "spawning and the workloads work (not with --duration or --ops);",
+            "exits 1 if a workload measured no ops",
+        ],
+    },
+    OptionHelp {
+        id:    "threads",
+        short: Some('j'),
+        long:  "threads",
+        value: Some("NUM"),
+        lines: &["Number of worker threads (0 = auto-detect all cores) [default: 0]"],
+    },
+    OptionHelp {
+        id:    "workload",
+        short: Some('w'),
+        long:  "workload",
+        value: Some("TYPE"),
+        lines: &["Workload type: [default: mixed]"],
+    },
+    OptionHelp {
+        id:    "split",
+        short: None,
+        long:  "split",
+        value: Some("WORKLOAD=N,..."),
+        lines: &[
+            "Assign workloads per thread (e.g. integer=8,memory-bandwidth=8)",
+            "Counts must fit the thread count; smaller counts act as proportions",
+        ],
+    },
+    OptionHelp {
+        id:    "memory_mb",
+        short: Some('m'),
+        long:  "memory-mb",
+        value: Some("MB"),
+        lines: &["Memory buffer size in MB (0 = auto-detect, overrides -x) [default: 0]"],
+    },
+    OptionHelp {
+        id:    "memory_multiplier",
+        short: Some('x'),
+        long:  "memory-multiplier",
+        value: Some("NUM"),
+        lines: &[
+            "Memory multiplier for auto-detection, in multiples of the L3 one thread",
+            "sees (one CCX/CCD slice on multi-complex chips, not the package total)",
+            "2=light, 4=balanced, 8=aggressive, 16=extreme [default: 4]",
+        ],
+    },
+    OptionHelp {
+        id:    "no_ram_cap",
+        short: None,
+        long:  "no-ram-cap",
+        value: None,
+        lines: &["Keep the auto-sized buffer even past --ram-safety of RAM (swap/OOM risk)"],
+    },
+    OptionHelp {
+        id:    "exact_buffer",
+        short: None,
+        long:  "exact-buffer",
+        value: None,
+        lines: &[
+            "Keep the buffer at exactly its -m or auto size. By default it is",
+            "rounded down to a power of two so the memory kernels index with a",
+            "mask instead of a remainder, which can cost more than an L1 hit",
+        ],
+    },
+    OptionHelp {
+        id:    "best_effort_memory",
+        short: None,
+        long:  "best-effort-memory",
+        value: None,
+        lines: &[
+            "Where a worker can't allocate its buffer, halve it until it can and",
+            "run on, reporting what was allocated, instead of failing the run",
+        ],
+    },
+    OptionHelp {
+        id:    "ram_safety",
+        short: None,
+        long:  "ram-safety",
+        value: Some("FRACTION"),
+        lines: &[
+            "Fraction of RAM auto-sized buffers may use, 0.1-1.0 [default: 0.9]",
+            "95% of currently available memory caps them too",
+        ],
+    },
+    OptionHelp {
+        id:    "max_total_memory",
+        short: None,
+        long:  "max-total-memory",
+        value: Some("SIZE"),
+        lines: &[
+            "Hard cap on all auto-sized buffers together, e.g. 4G or 512M",
+            "Applies even with --no-ram-cap; a larger -m total is an error",
+        ],
+    },
+    OptionHelp {
+        id:    "abort_on_swap",
+        short: None,
+        long:  "abort-on-swap",
+        value: None,
+        lines: &[
+            "Stop (exit 1) if the system starts swapping mid-run instead of",
+            "warning; the final stats say whether it swapped (Linux)",
+        ],
+    },
+    OptionHelp {
+        id:    "batch_size",
+        short: Some('b'),
+        long:  "batch-size",
+        value: Some("NUM"),
+        lines: &[
+            "Work batch size (iterations between stop checks)",
+            "[default: per workload, see `locus info`]",
+        ],
+    },
+    OptionHelp {
+        id:    "chains",
+        short: None,
+        long:  "chains",
+        value: Some("N"),
+        lines: &[
+            "Independent pointer chains per memory-latency worker, 1-64; raise it",
+            "to find where memory-level parallelism saturates [default: 1]",
+        ],
+    },
+    OptionHelp {
+        id:    "float_mode",
+        short: None,
+        long:  "float-mode",
+        value: Some("MODE"),
+        lines: &[
+            "Float kernel for float and mixed: simple (add, mul and sqrt only) or",
+            "transcendental (adds sin, cos and ln_1p, so libm counts) [default:",
+            "transcendental]",
+        ],
+    },
+    OptionHelp {
+        id:    "prefetch",
+        short: None,
+        long:  "prefetch",
+        value: Some("DISTANCE"),
+        lines: &[
+            "Prefetch each memory-bandwidth stream's address DISTANCE iterations",
+            "ahead, 0-64; 0 keeps the plain kernel [default: 0]. Above 0 the addresses",
+            "no longer wait on the loads, so the rate also gains parallelism",
+        ],
+    },
+    OptionHelp {
+        id:    "init_pattern",
+        short: None,
+        long:  "init-pattern",
+        value: Some("PATTERN"),
+        lines: &[
+            "What buffers start out holding: sequential, random, or cycle (one",
+            "random permutation through every word, which memory-latency chases",
+            "instead of deriving indices from values) [default: sequential]",
+        ],
+    },
+    OptionHelp {
+        id:    "lcg_mult",
+        short: None,
+        long:  "lcg-mult",
+        value: Some("N"),
+        lines: &[
+            "Advanced: LCG multiplier memory-latency and memory-bandwidth step their",
+            "values with (every stream's), decimal or 0x hex; must be odd",
+        ],
+    },
+    OptionHelp {
+        id:    "lcg_add",
+        short: None,
+        long:  "lcg-add",
+        value: Some("N"),
+        lines: &[
+            "Advanced: constant those kernels add to each new value along with the",
+            "iteration count [default: 0]",
+        ],
+    },
+    OptionHelp {
+        id:    "stride",
+        short: None,
+        long:  "stride",
+        value: Some("WORDS"),
+        lines: &[
+            "Advanced: step each chain and stream WORDS 8-byte words at a time instead",
+            "of jumping to an index derived from the value. Custom parameters can",
+            "leave parts of the buffer untouched: a stride sharing a factor with the",
+            "buffer length reaches only every gcd-th word",
+        ],
+    },
+    OptionHelp {
+        id:    "target",
+        short: None,
+        long:  "target",
+        value: Some("LEVEL"),
+        lines: &[
+            "Cache level cache-thrash evicts: l1, l2 or l3 (one complex's slice);",
+            "it sweeps twice that level's size per thread [default: l2]",
+        ],
+    },
+    OptionHelp {
+        id:    "affinity",
+        short: None,
+        long:  "affinity",
+        value: None,
+        lines: &[
+            "Pin worker N to CPU N (Linux/Windows). The progress reporter and",
+            "monitor loop go to the highest CPU without a worker, if any",
+        ],
+    },
+    OptionHelp {
+        id:    "affinity_reverse",
+        short: None,
+        long:  "affinity-reverse",
+        value: None,
+        lines: &[
+            "With --affinity, pin worker 0 to the highest CPU and count down,",
+            "to target E-cores or a second CCD at the top of the numbering",
+        ],
+    },
+    OptionHelp {
+        id:    "smt",
+        short: None,
+        long:  "smt",
+        value: Some("POLICY"),
+        lines: &[
+            "With --affinity, spread: one worker per physical core before any SMT",
+            "sibling; compact: both siblings of a core before the next [default:",
+            "CPU numbering order]",
+        ],
+    },
+    OptionHelp {
+        id:    "verify_utilization",
+        short: None,
+        long:  "verify-utilization",
+        value: None,
+        lines: &[
+            "Sample per-core busy time (Linux, Windows) and warn about any worker",
+            "core under 95% busy: interference, descheduling or failed pinning.",
+            "Needs --affinity, or unpinned workers on every CPU",
+        ],
+    },
+    OptionHelp {
+        id:    "stagger",
+        short: None,
+        long:  "stagger",
+        value: Some("MS"),
+        lines: &[
+            "Delay between worker spawns to avoid an allocation stampede",
+            "(timing starts once the last worker is up) [default: 0]",
+            "Without it, workers finish setup and start counting together",
+        ],
+    },
+    OptionHelp {
+        id:    "numa",
+        short: None,
+        long:  "numa",
+        value: Some("POLICY"),
+        lines: &[
+            "Buffer placement: local (first-touch) or interleave (pages spread",
+            "across all memory nodes via mbind; Linux only) [default: local]",
+        ],
+    },
+    OptionHelp {
+        id:    "stack_size",
+        short: None,
+        long:  "stack-size",
+        value: Some("KB"),
+        lines: &["Worker thread stack size in KiB (0 = platform default) [default: 0]"],
+    },
+    OptionHelp {
+        id:    "quiet",
+        short: Some('q'),
+        long:  "quiet",
+        value: None,
+        lines: &["Disable live progress reporting; the final summary still prints"],
+    },
+    OptionHelp {
+        id:    "progress_format",
+        short: None,
+        long:  "progress-format",
+        value: Some("FORMAT"),
+        lines: &[
+            "Live progress: text (one redrawn line) or jsonl [default: text]",
+            "jsonl writes one object per second to stderr: timestamp, elapsed_secs,",
+            "workload, total_ops, interval_ops_per_sec and, for memory workloads,",
+            "interval_gb_per_sec",
+        ],
+    },
+    OptionHelp {
+        id:    "rate_smoothing",
+        short: None,
+        long:  "rate-smoothing",
+        value: Some("ALPHA"),
+        lines: &[
+            "EMA weight of the newest sample in the progress rate, 0.01-1.0",
+            "(1.0 = raw rate only; final stats always use raw totals) [default: 0.3]",
+        ],
+    },
+    OptionHelp {
+        id:    "throttle_alert",
+        short: None,
+        long:  "throttle-alert",
+        value: Some("PERCENT"),
+        lines: &[
+            "Warn mid-run, at most once a minute, when the smoothed rate stays this",
+            "many percent below its post-warmup best (0 = never) [default: 20]",
+        ],
+    },
+    OptionHelp {
+        id:    "log_status",
+        short: None,
+        long:  "log-status",
+        value: Some("SECS"),
+        lines: &[
+            "Every SECS, print elapsed time, total ops, rate, peak and CPU",
+            "temperature as a newline-terminated block for logs; alongside or",
+            "instead of the progress line (0 = never; not with --progress-format",
+            "jsonl) [default: 0]",
+        ],
+    },
+    OptionHelp {
+        id:    "ascii",
+        short: None,
+        long:  "ascii",
+        value: None,
+        lines: &[
+            "Draw the rate histogram in the final stats with # instead of block",
+            "characters, for terminals and logs without them",
+        ],
+    },
+    OptionHelp {
+        id:    "silent",
+        short: Some('s'),
+        long:  "silent",
+        value: None,
+        lines: &[
+            "Print nothing but errors; the exit code carries the outcome",
+            "(--out still gets the report; large allocations need --yes)",
+        ],
+    },
+    OptionHelp {
+        id:    "exact_numbers",
+        short: None,
+        long:  "exact-numbers",
+        value: None,
+        lines: &["Print op counts and rates in full (12,345,678,901) instead of 12.35B"],
+    },
+    OptionHelp {
+        id:    "format",
+        short: None,
+        long:  "format",
+        value: Some("FORMAT"),
+        lines: &[
+            "Final report format: text, json, or junit (bench only: one testcase",
+            "per workload, plus the score with --min-score) [default: text]",
+            "JSON or JUnit on stdout turns off the banner and progress output",
+        ],
+    },
+    OptionHelp {
+        id:    "out",
+        short: None,
+        long:  "out",
+        value: Some("PATH"),
+        lines: &["Write the final report to PATH; progress stays on the terminal"],
+    },
+    OptionHelp {
+        id:    "notify_url",
+        short: None,
+        long:  "notify-url",
+        value: Some("URL"),
+        lines: &[
+            "POST a JSON summary (outcome, elapsed, rates, errors) to URL when the",
+            "run ends; https (Slack, Teams) needs a build with --features tls",
+        ],
+    },
+    OptionHelp {
+        id:    "yes",
+        short: Some('y'),
+        long:  "yes",
+        value: None,
+        lines: &[
+            "Don't ask before allocating over 50% of RAM or past the cgroup limit",
+            "(non-interactive runs never ask, but warn)",
+        ],
+    },
+    OptionHelp {
+        id:    "benchmark",
+        short: Some('B'),
+        long:  "benchmark",
+        value: None,
+        lines: &["Same as `locus bench`: run the benchmark set and display a comparison table"],
+    },
+    OptionHelp {
+        id:    "compare",
+        short: None,
+        long:  "compare",
+        value: Some("FILE..."),
+        lines: &[
+            "Same as `locus compare`: show saved bench JSON results side by side",
+            "(no-command form only; starts no workers)",
+        ],
+    },
+    OptionHelp {
+        id:    "bench_warmup",
+        short: None,
+        long:  "bench-warmup",
+        value: Some("MS"),
+        lines: &[
+            "Unmeasured warmup before each benchmark workload so caches and clocks",
+            "settle [default: a tenth of --duration, at most 2000]",
+        ],
+    },
+    OptionHelp {
+        id:    "adaptive",
+        short: None,
+        long:  "adaptive",
+        value: None,
+        lines: &[
+            "Run each benchmark workload until its last 10 s of rates vary by under",
+            "2% (capped at --duration, default 120 s) and report the settled rate",
+        ],
+    },
+    OptionHelp {
+        id:    "ignore_background_load",
+        short: None,
+        long:  "ignore-background-load",
+        value: None,
+        lines: &[
+            "Skip the 1 s sample of CPU load before the benchmark and its warning",
+            "when the allowed CPUs are over 10% busy",
+        ],
+    },
+    OptionHelp {
+        id:    "bandwidth_scaling",
+        short: None,
+        long:  "bandwidth-scaling",
+        value: None,
+        lines: &[
+            "Instead of the workload set, run memory-bandwidth on 1, 2, 4, ... threads",
+            "up to -j for --duration each and report GB/s per step and the knee, the",
+            "fewest threads within 90% of the best",
+        ],
+    },
+    OptionHelp {
+        id:    "benchmark_set",
+        short: None,
+        long:  "benchmark-set",
+        value: Some("SET"),
+        lines: &[
+            "Workloads to benchmark: default|compute|memory|all; every set keeps",
+            "mixed as the baseline [default: default = integer, float, mixed,",
+            "memory-latency, memory-bandwidth]",
+        ],
+    },
+    OptionHelp {
+        id:    "workload_durations",
+        short: None,
+        long:  "workload-durations",
+        value: Some("LIST"),
+        lines: &[
+            "Seconds for specific benchmark workloads (e.g. integer=5,memory-bandwidth=20);",
+            "the rest run for --duration",
+        ],
+    },
+    OptionHelp {
+        id:    "min_rate",
+        short: None,
+        long:  "min-rate",
+        value: Some("N"),
+        lines: &[
+            "Exit with code 3 if the average rate is below N ops/s (e.g. 50M);",
+            "for CI perf gates",
+        ],
+    },
+    OptionHelp {
+        id:    "min_score",
+        short: None,
+        long:  "min-score",
+        value: Some("N"),
+        lines: &[
+            "Exit with code 3 if the benchmark score (geometric mean of the rates)",
+            "is below N (e.g. 20M)",
+        ],
+    },
+    OptionHelp {
+        id:    "baseline",
+        short: None,
+        long:  "baseline",
+        value: Some("PATH"),
+        lines: &[
+            "A saved `bench --format json` result to hold each workload to: exit",
+            "with code 3 if one falls more than --fail-threshold under its saved",
+            "rate. Workloads the file lacks aren't judged",
+        ],
+    },
+    OptionHelp {
+        id:    "fail_threshold",
+        short: None,
+        long:  "fail-threshold",
+        value: Some("PERCENT"),
+        lines: &["How far under its --baseline rate a workload may fall [default: 10]"],
+    },
+    OptionHelp {
+        id:    "loaded_latency",
+        short: None,
+        long:  "loaded-latency",
+        value: None,
+        lines: &[
+            "Time pointer chasing on one thread, idle and while the rest run",
+            "memory-bandwidth; reports ns/access and the degradation factor",
+        ],
+    },
+    OptionHelp {
+        id:    "interference",
+        short: None,
+        long:  "interference",
+        value: Some("A:N,B:N"),
+        lines: &[
+            "Run each workload alone (a fifth of --duration), then both side by",
+            "side; reports solo rate, contended rate and slowdown for each",
+        ],
+    },
+    OptionHelp {
+        id:    "dry_run",
+        short: None,
+        long:  "dry-run",
+        value: None,
+        lines: &[
+            "Do all detection and validation, print the header and resolved plan",
+            "(threads, buffers, batches, peak allocation), warn about a buffer the",
+            "run couldn't allocate or would confirm first, then exit",
+        ],
+    },
+    OptionHelp {
+        id:    "list_workloads",
+        short: None,
+        long:  "list-workloads",
+        value: None,
+        lines: &["List workloads with their category and bytes per op (see `locus info`)"],
+    },
+];
+
+const CONFIG_HELP: &[OptionHelp] = &[
+    OptionHelp {
+        id:    "config",
+        short: None,
+        long:  "config",
+        value: Some("PATH"),
+        lines: &[
+            "Read options from a flat TOML profile (keys are the long flag",
+            "names, e.g. threads = 8); flags on the command line win. Keys a",
+            "command doesn't take are ignored, so one profile can serve run and bench",
+        ],
+    },
+    OptionHelp {
+        id:    "dump_config",
+        short: None,
+        long:  "dump-config",
+        value: None,
+        lines: &["Print the effective configuration as TOML and exit"],
+    },
+];
+
+/// (comment, arguments after `locus`, commands it appears under)
+const EXAMPLES: &[(&str, &str, &[Subcommand])] = &[
+    (
+        "Default balanced stress test for 10 seconds",
+        "run -d 10",
+        &[Subcommand::Run],
+    ),
+    (
+        "Run memory latency workload (pointer-chasing pattern)",
+        "run -w memory-latency -d 10 -x 8",
+        &[Subcommand::Run],
+    ),
+    (
+        "Run memory bandwidth workload (parallel streams)",
+        "run -w memory-bandwidth -d 10 -x 8",
+        &[Subcommand::Run],
+    ),
+    ("Run full benchmark suite", "bench -d 10 -x 8", &[
+        Subcommand::Bench,
+    ]),
+    (
+        "Half the threads on integer, half saturating memory",
+        "run -j 16 --split integer=8,memory-bandwidth=8 -d 30",
+        &[Subcommand::Run],
+    ),
+    (
+        "Memory latency while the other threads saturate bandwidth",
+        "run --loaded-latency -d 20",
+        &[Subcommand::Run],
+    ),
+    (
+        "How much a bandwidth hog slows a compute job sharing the socket",
+        "run --interference memory-bandwidth:4,integer:4 -d 20",
+        &[Subcommand::Run],
+    ),
+    (
+        "Save benchmark results as JSON",
+        "bench -d 10 --format json --out results.json",
+        &[Subcommand::Bench, Subcommand::Compare],
+    ),
+    (
+        "Compare saved results from two machines",
+        "compare desktop.json laptop.json",
+        &[Subcommand::Compare],
+    ),
+    (
+        "Manual memory size override (512 MB per thread)",
+        "run -w memory-bandwidth -m 512 -d 10",
+        &[Subcommand::Run],
+    ),
+    (
+        "The pre-subcommand form still works and means `run` (or `bench` with -B)",
+        "-w float -d 10",
+        &[],
+    ),
+];
+
+fn print_option(entry: &OptionHelp, s: &HelpStyles) {
+    let mut flags = match entry.short {
+        Some(short) => format!("  {}-{}{}, ", s.opt, short, s.reset),
+        None => "  ".to_string(),
+    };
+    flags += &format!("{}--{}{}", s.opt, entry.long, s.reset);
+    if let Some(value) = entry.value {
+        flags += &format!(" {}{}{}", s.value, value, s.reset);
+    }
+    println!("{}", flags);
+    for line in entry.lines {
+        println!("      {}{}{}", s.desc, line, s.reset);
+    }
+
+    if entry.id == "workload" {
+        for spec in registry::WORKLOADS {
+            println!(
+                "        {}{:<16}{}{}- {}{}",
+                s.value, spec.name, s.reset, s.desc, spec.description, s.reset
+            );
+        }
+        for spec in registry::WORKLOADS {
+            for alias in spec.aliases {
+                println!(
+                    "        {}{:<16}{}{}- Deprecated alias for {}{}",
+                    s.value, alias, s.reset, s.desc, spec.name, s.reset
+                );
+            }
+        }
+    }
+}
+
+fn print_options<'a>(options: impl IntoIterator<Item = &'a OptionHelp>, s: &HelpStyles) {
+    for (i, entry) in options.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print_option(entry, s);
+    }
+}
+
+fn print_examples(topic: Option<Subcommand>, s: &HelpStyles) {
+    println!("\n{}EXAMPLES:{}", s.header, s.reset);
+    for (comment, example_args, commands) in EXAMPLES {
+        if topic.is_some_and(|sub| !commands.contains(&sub)) {
+            continue;
+        }
+        println!("  {}# {}{}", s.example, comment, s.reset);
+        println!("  {}locus{} {}\n", s.cmd, s.reset, example_args);
+    }
+}
+
+/// `locus -h`/`locus help` (None) or `locus <command> -h`. Checked before
+/// clap sees the arguments so the hand-written pages below are used.
+pub fn help_topic(argv: &[String]) -> Option<Option<Subcommand>> {
+    let is_help = |arg: Option<&String>| arg.is_some_and(|a| a == "-h" || a == "--help");
+    let named = |arg: Option<&String>| arg.and_then(|a| Subcommand::from_name(a));
+
+    if is_help(argv.get(1)) {
+        return Some(None);
+    }
+    if argv.get(1).is_some_and(|a| a == "help") {
+        return Some(named(argv.get(2)));
+    }
+    let sub = named(argv.get(1))?;
+    is_help(argv.get(2)).then_some(Some(sub))
+}
+
+pub fn print_help(topic: Option<Subcommand>) {
+    let s = HelpStyles::new();
+
+    println!("{}locus{} {}", s.cmd, s.reset, env!("CARGO_PKG_VERSION"));
+    match topic {
+        None => println!(
+            "A configurable CPU stress testing tool with multiple computational workloads.\n"
+        ),
+        Some(sub) => println!("{}\n", sub.about()),
+    }
+
+    println!("{}USAGE:{}", s.header, s.reset);
+    match topic {
+        None => {
+            println!("    {}locus{} <COMMAND> [OPTIONS]", s.cmd, s.reset);
+            println!(
+                "    {}locus{} [OPTIONS]    (same as `locus run`, or `bench` with -B)\n",
+                s.cmd, s.reset
+            );
+        },
+        Some(Subcommand::Compare) => println!(
+            "    {}locus compare{} {}FILE{}...\n",
+            s.cmd, s.reset, s.value, s.reset
+        ),
+        Some(sub) => println!("    {}locus {}{} [OPTIONS]\n", s.cmd, sub.name(), s.reset),
+    }
+
+    if topic.is_none() {
+        println!("{}COMMANDS:{}", s.header, s.reset);
+        for sub in Subcommand::ALL {
+            println!(
+                "  {}{:<9}{}{}{}{}",
+                s.opt,
+                sub.name(),
+                s.reset,
+                s.desc,
+                sub.about(),
+                s.reset
+            );
+        }
+        println!(
+            "\n{}Run `locus <COMMAND> --help` for the options each command takes.{}\n",
+            s.desc, s.reset
+        );
+    }
+
+    if topic == Some(Subcommand::Compare) {
+        println!("{}ARGUMENTS:{}", s.header, s.reset);
+        println!("  {}FILE{}", s.value, s.reset);
+        println!(
+            "      {}A `locus bench --format json` result; one column per file, named{}",
+            s.desc, s.reset
+        );
+        println!(
+            "      {}after the file. The best rate in each row is marked with *{}",
+            s.desc, s.reset
+        );
+    }
+
+    let takes = |id: &str| topic.is_none_or(|sub| sub.accepts(id));
+    let options: Vec<&OptionHelp> = OPTION_HELP.iter().filter(|o| takes(o.id)).collect();
+    if !options.is_empty() {
+        println!("{}OPTIONS:{}", s.header, s.reset);
+        print_options(options, &s);
+
+        println!("\n{}CONFIG OPTIONS:{}", s.header, s.reset);
+        print_options(CONFIG_HELP, &s);
+
+        println!("\n{}ENVIRONMENT:{}", s.header, s.reset);
+        println!(
+            "  {}Every long option can also be set as LOCUS_<NAME>, e.g. LOCUS_THREADS=4,{}",
+            s.desc, s.reset
+        );
+        println!(
+            "  {}LOCUS_WORKLOAD=memory-bandwidth, LOCUS_NO_RAM_CAP=true. Values are checked{}",
+            s.desc, s.reset
+        );
+        println!(
+            "  {}like the flag. Precedence: defaults < environment < --config < command line{}",
+            s.desc, s.reset
+        );
+    }
+
+    if topic.is_none() || topic == Some(Subcommand::Run) {
+        println!("\n{}STRESS-NG FLAGS:{}", s.header, s.reset);
+        for line in [
+            "--cpu N (-j), --timeout 300s|5m|1h (-d), --vm N (N memory-bandwidth workers),",
+            "--vm-bytes SIZE (-m) and --metrics-brief (the final stats) are translated;",
+            "other stress-ng flags are rejected with their native equivalent",
+        ] {
+            println!("  {}{}{}", s.desc, line, s.reset);
+        }
+    }
+
+    println!("\n  {}-h{}, {}--help{}", s.opt, s.reset, s.opt, s.reset);
+    println!("      {}Print this help message{}", s.desc, s.reset);
+    if topic.is_none() {
+        println!("\n  {}-V{}, {}--version{}", s.opt, s.reset, s.opt, s.reset);
+        println!(
+            "      {}Print version information; add --verbose for the commit, compiler and{}",
+            s.desc, s.reset
+        );
+        println!("      {}build profile{}", s.desc, s.reset);
+    }
+
+    if topic != Some(Subcommand::Info) {
+        print_examples(topic, &s);
+    }
+}
+
+/// `-V`/`--version`, optionally with `--verbose` on either side. Returns
+/// whether the verbose form was asked for.
+pub fn version_request(argv: &[String]) -> Option<bool> {
+    let is_version = |arg: &String| arg == "-V" || arg == "--version";
+    match &argv[1.min(argv.len())..] {
+        [flag] if is_version(flag) => Some(false),
+        [a, b]
+            if (is_version(a) && b == "--verbose") || (a == "--verbose" && is_version(b)) =>
+        {
+            Some(true)
+        },
+        _ => None,
+    }
+}
+
+pub fn print_version(verbose: bool) {
     let cmd = Style::new()
         .bold()
         .fg_color(Some(Color::Ansi(AnsiColor::Green)));
     let reset = Style::new();
 
     println!("{}locus{} {}", cmd, reset, env!("CARGO_PKG_VERSION"));
+    if !verbose {
+        return;
+    }
+
+    let build = output::build_info();
+    let dirty = match build.dirty {
+        Some(true) => " (dirty)",
+        Some(false) => "",
+        None => " (dirty state unknown)",
+    };
+    let features = if build.features.is_empty() {
+        "none".to_string()
+    } else {
+        build.features.join(", ")
+    };
+    println!("commit:   {}{}", build.commit, dirty);
+    println!("rustc:    {}", build.rustc);
+    println!("target:   {}", build.target);
+    println!(
+        "profile:  {} (opt-level {})",
+        build.profile, build.opt_level
+    );
+    println!("features: {}", features);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::assignment;
+
+    /// Every parse reads the process environment, so tests that set LOCUS_*
+    /// variables must not overlap with any other parse.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_env<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        // SAFETY: ENV_LOCK serializes every test that touches the environment
+        unsafe {
+            vars.iter().for_each(|(k, v)| std::env::set_var(k, v));
+        }
+        let result = f();
+        unsafe {
+            vars.iter().for_each(|(k, _)| std::env::remove_var(k));
+        }
+        result
+    }
+
+    fn parse_with_config(argv: &[&str], profile: &str) -> Result<Args, config::ConfigError> {
+        with_env(&[], || {
+            let matches = Args::command().try_get_matches_from(argv).unwrap();
+            let mut args = Args::from_arg_matches(&matches).unwrap();
+            args.merge_config(profile, &matches, None).map(|_| args)
+        })
+    }
+
+    #[test]
+    fn test_env_sits_between_defaults_and_config() {
+        let env = [
+            ("LOCUS_THREADS", "4"),
+            ("LOCUS_DURATION", "300"),
+            ("LOCUS_WORKLOAD", "memory-bandwidth"),
+            ("LOCUS_QUIET", "true"),
+        ];
+        let args = with_env(&env, || {
+            let matches = Args::command()
+                .try_get_matches_from(["locus", "-d", "5"])
+                .unwrap();
+            let mut args = Args::from_arg_matches(&matches).unwrap();
+            args.merge_config("threads = 8", &matches, None).unwrap();
+            args
+        });
+
+        assert_eq!(args.threads, 8, "file beats env");
+        assert_eq!(args.duration, 5, "command line beats env");
+        assert_eq!(args.workload, "memory-bandwidth");
+        assert!(args.quiet);
+        assert_eq!(args.memory_multiplier, 4, "unset env keeps the default");
+    }
+
+    #[test]
+    fn test_invalid_env_fails_like_the_flag() {
+        let from_env = with_env(&[("LOCUS_DURATION", "soon")], || {
+            Args::command().try_get_matches_from(["locus"]).unwrap_err()
+        });
+        let from_flag = parse_error(&["locus", "--duration", "soon"]);
+        assert_eq!(from_env.to_string(), from_flag.to_string());
+
+        let err = with_env(&[("LOCUS_RAM_SAFETY", "2")], || {
+            Args::command().try_get_matches_from(["locus"]).unwrap_err()
+        });
+        assert!(err.to_string().contains("outside 0.1-1.0"));
+    }
+
+    #[test]
+    fn test_target_takes_a_cache_level() {
+        let args = with_env(&[("LOCUS_TARGET", "l3")], || {
+            Args::try_parse_from(["locus", "-w", "cache-thrash"]).unwrap()
+        });
+        assert_eq!(args.target, CacheLevel::L3);
+        assert_eq!(
+            parse_with_config(&["locus"], "target = \"l1\"")
+                .unwrap()
+                .target,
+            CacheLevel::L1
+        );
+        assert!(parse_with_config(&["locus"], "target = \"l4\"").is_err());
+    }
+
+    fn parse_error(argv: &[&str]) -> clap::Error {
+        with_env(&[], || {
+            Args::command().try_get_matches_from(argv).unwrap_err()
+        })
+    }
+
+    #[test]
+    fn test_dump_config_round_trips() {
+        let mut args = parse_with_config(&["locus"], "").unwrap();
+        args.threads = 6;
+        args.workload = "memory-latency".to_string();
+        args.batch_size = Some(4096);
+        args.format = Format::Json;
+        args.out = Some(PathBuf::from("run \"1\".json"));
+
+        let dumped = args.to_toml();
+        let reloaded = parse_with_config(&["locus"], &dumped).unwrap();
+        assert_eq!(reloaded.to_toml(), dumped);
+        assert_eq!(reloaded.threads, 6);
+        assert_eq!(reloaded.batch_size, Some(4096));
+    }
+
+    #[test]
+    fn test_access_pattern_options() {
+        let argv = ["locus", "--lcg-mult", "0x5851_f42d_4c95_7f2d", "--lcg-add", "7"];
+        let args = parse_sub(&[&argv[..], &["--stride", "64"]].concat()).unwrap();
+        assert_eq!(args.access_pattern(), AccessPattern {
+            lcg_mult: Some(0x5851f42d4c957f2d),
+            lcg_add:  7,
+            stride:   Some(64),
+        });
+        assert!(parse_sub(&["locus"]).unwrap().access_pattern().is_default());
+
+        for bad in [["--lcg-mult", "4"], ["--lcg-mult", "0xfg"], ["--stride", "0"]] {
+            assert!(parse_sub(&["locus", bad[0], bad[1]]).is_err(), "{:?}", bad);
+        }
+
+        // Multipliers past i64::MAX go through the profile as hex strings
+        let mut args = parse_with_config(&["locus"], "").unwrap();
+        args.lcg_mult = Some(0xd1342543de82ef95);
+        args.lcg_add = u64::MAX;
+        let dumped = args.to_toml();
+        assert!(dumped.contains("lcg_mult = \"0xd1342543de82ef95\""), "{}", dumped);
+        let reloaded = parse_with_config(&["locus"], &dumped).unwrap();
+        assert_eq!(reloaded.access_pattern(), args.access_pattern());
+        assert!(parse_with_config(&["locus"], "lcg_mult = 6").is_err());
+        assert!(parse_with_config(&["locus"], "stride = 0").is_err());
+    }
+
+    #[test]
+    fn test_command_line_beats_config() {
+        let profile = "threads = 8\nduration = 30\nquiet = true";
+        let args = parse_with_config(&["locus", "-j", "2"], profile).unwrap();
+        assert_eq!(args.threads, 2);
+        assert_eq!(args.duration, 30);
+        assert!(args.quiet);
+
+        // An explicit value equal to the default still wins
+        let args = parse_with_config(&["locus", "-d", "0"], profile).unwrap();
+        assert_eq!(args.duration, 0);
+    }
+
+    #[test]
+    fn test_config_errors_name_key_and_line() {
+        let err = parse_with_config(&["locus"], "threads = 4\nthreds = 8").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.starts_with("threds: unknown key"));
+
+        let err = parse_with_config(&["locus"], "quiet = 1").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1: quiet: expected true or false, got integer"
+        );
+
+        assert!(parse_with_config(&["locus"], "workload = \"nope\"").is_err());
+        assert!(parse_with_config(&["locus"], "chains = 0").is_err());
+        assert_eq!(
+            parse_with_config(&["locus"], "chains = 4").unwrap().chains,
+            4
+        );
+        assert!(parse_with_config(&["locus"], "batch-size = 0").is_err());
+        assert!(parse_with_config(&["locus"], "threads = -1").is_err());
+        assert!(parse_with_config(&["locus"], "numa = \"remote\"").is_err());
+    }
+
+    #[test]
+    fn test_max_total_memory_sizes() {
+        assert_eq!(parse_total_memory("4096"), Ok(4096));
+        assert_eq!(parse_total_memory("512M"), Ok(512));
+        assert_eq!(parse_total_memory("4g"), Ok(4096));
+        assert!(parse_total_memory("0").is_err());
+        assert!(parse_total_memory("4GB").is_err());
+
+        let from =
+            |profile: &str| parse_with_config(&["locus"], profile).map(|a| a.max_total_memory);
+        assert_eq!(from("max-total-memory = 2048").unwrap(), Some(2048));
+        assert_eq!(from("max-total-memory = \"2G\"").unwrap(), Some(2048));
+        assert_eq!(from("").unwrap(), None);
+        assert!(from("max-total-memory = 0").is_err());
+        assert!(parse_with_config(&["locus"], "ram-safety = 1.0").is_ok());
+    }
+
+    #[test]
+    fn test_rate_thresholds() {
+        assert_eq!(parse_rate("250000"), Ok(250_000));
+        assert_eq!(parse_rate("250K"), Ok(250_000));
+        assert_eq!(parse_rate("1.5M"), Ok(1_500_000));
+        assert_eq!(parse_rate("2b"), Ok(2_000_000_000));
+        assert!(parse_rate("0").is_err());
+        assert!(parse_rate("fast").is_err());
+        assert!(parse_rate("-5M").is_err());
+
+        let args =
+            parse_with_config(&["locus"], "min-rate = \"50M\"\nmin-score = 1000").unwrap();
+        assert_eq!(args.min_rate, Some(50_000_000));
+        assert_eq!(args.min_score, Some(1000));
+        assert!(parse_with_config(&["locus"], "min-rate = 0").is_err());
+    }
+
+    #[test]
+    fn test_ops_takes_counts() {
+        assert_eq!(parse_ops("1B"), Ok(1_000_000_000));
+        assert_eq!(parse_ops("2500"), Ok(2500));
+        assert!(parse_ops("0").unwrap_err().contains("op count"));
+
+        let args = parse_with_config(&["locus"], "ops = \"500M\"").unwrap();
+        assert_eq!(args.ops, Some(500_000_000));
+        assert!(args.to_toml().contains("ops = 500000000"));
+        assert!(parse_sub(&["bench", "--ops", "1M"]).is_err());
+    }
+
+    fn parse_sub(argv: &[&str]) -> Result<Args, clap::Error> {
+        let argv: Vec<OsString> = argv.iter().map(OsString::from).collect();
+        let sub = argv
+            .first()
+            .and_then(|a| a.to_str())
+            .and_then(Subcommand::from_name);
+        with_env(&[], || parse_stress(sub, &argv).map(|(args, _)| args))
+    }
+
+    fn parse_stress_ng(argv: &[&str]) -> Result<Args, String> {
+        let argv = translate_stress_ng(argv.iter().map(OsString::from).collect())?;
+        let argv: Vec<&str> = argv.iter().map(|a| a.to_str().unwrap()).collect();
+        parse_sub(&argv).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_stress_ng_cpu_and_timeout() {
+        let runbook = ["locus", "--cpu", "0", "--timeout", "300s", "--metrics-brief"];
+        let args = parse_stress_ng(&runbook).unwrap();
+        assert_eq!((args.threads, args.duration), (0, 300));
+        assert_eq!(args.workload, "mixed");
+
+        let args = parse_stress_ng(&["run", "--cpu=4", "--timeout=5m", "-q"]).unwrap();
+        assert_eq!((args.threads, args.duration), (4, 300));
+        assert!(args.quiet, "native flags pass through");
+        for (timeout, secs) in [("90", 90), ("2h", 7200), ("0", 0)] {
+            let args = parse_stress_ng(&["locus", "--timeout", timeout, "--metrics"]).unwrap();
+            assert_eq!(args.duration, secs, "{}", timeout);
+        }
+        for bad in [["--timeout", "5d"], ["--timeout", "soon"], ["--cpu", "all"]] {
+            let err = parse_stress_ng(&["locus", bad[0], bad[1]]).unwrap_err();
+            assert!(err.contains("invalid value"), "{}", err);
+        }
+        let err = parse_stress_ng(&["locus", "--cpu"]).unwrap_err();
+        assert!(err.contains("needs a value"), "{}", err);
+    }
+
+    #[test]
+    fn test_stress_ng_vm_and_vm_bytes() {
+        let args = parse_stress_ng(&["locus", "--vm", "2", "--vm-bytes", "256m"]).unwrap();
+        assert_eq!(args.workload, "memory-bandwidth");
+        assert_eq!((args.threads, args.memory_mb), (2, 256));
+        for (size, mb) in [("1G", 1024), ("1000k", 1), ("3145728", 3), ("1536m", 1536)] {
+            let args = parse_stress_ng(&["locus", "--vm", "1", "--vm-bytes", size]).unwrap();
+            assert_eq!(args.memory_mb, mb, "{}", size);
+        }
+
+        let args = parse_stress_ng(&["run", "--cpu", "4", "--vm", "2", "--timeout", "1m"]);
+        let args = args.unwrap();
+        assert_eq!(args.split.as_deref(), Some("mixed=4,memory-bandwidth=2"));
+        assert_eq!((args.threads, args.duration), (0, 60));
+
+        let err = parse_stress_ng(&["locus", "--cpu", "0", "--vm", "2"]).unwrap_err();
+        assert!(err.contains("native equivalent is --split"), "{}", err);
+        let err = parse_stress_ng(&["locus", "--vm", "1", "--vm-bytes", "50%"]).unwrap_err();
+        assert!(err.contains("native equivalent is --ram-safety"), "{}", err);
+    }
+
+    #[test]
+    fn test_stress_ng_unsupported_flags_name_the_native_option() {
+        for (flag, native) in [
+            ("--cpu-method=matrixprod", "-w integer|float|mixed"),
+            ("--taskset", "--affinity"),
+            ("--yaml", "--format json"),
+            ("--hdd", "CPU and memory only"),
+        ] {
+            let err = parse_stress_ng(&["locus", flag, "1"]).unwrap_err();
+            let name = flag.split('=').next().unwrap();
+            assert!(err.contains(&format!("'{}' is not supported", name)), "{}", err);
+            assert!(err.contains(native), "{}", err);
+        }
+        // After `--` nothing is a flag, and native options are left alone
+        let argv = ["locus", "-j", "2", "--", "--cpu"].map(OsString::from);
+        assert_eq!(translate_stress_ng(argv.to_vec()).unwrap(), argv);
+    }
+
+    #[test]
+    fn test_subcommands_match_the_legacy_form() {
+        let run = parse_sub(&["run", "-w", "float", "-d", "10", "-j", "2"]).unwrap();
+        let legacy = parse_sub(&["locus", "-w", "float", "-d", "10", "-j", "2"]).unwrap();
+        assert_eq!(run.to_toml(), legacy.to_toml());
+
+        let bench = parse_sub(&["bench", "-d", "5"]).unwrap();
+        let legacy = parse_sub(&["locus", "-B", "-d", "5"]).unwrap();
+        assert!(bench.benchmark);
+        assert_eq!(bench.to_toml(), legacy.to_toml());
+
+        let legacy = parse_sub(&["locus", "--compare", "a.json", "b.json"]).unwrap();
+        assert_eq!(legacy.compare, [
+            PathBuf::from("a.json"),
+            PathBuf::from("b.json")
+        ]);
+        assert!(parse_sub(&["run", "--compare", "a.json"]).is_err());
+    }
+
+    #[test]
+    fn test_subcommands_reject_foreign_options() {
+        let err = parse_sub(&["run", "--benchmark"]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'--benchmark' is not a `locus run` option")
+        );
+        assert!(parse_sub(&["bench", "--split", "integer=1"]).is_err());
+        assert!(parse_sub(&["bench", "-w", "float"]).is_err());
+        assert!(parse_sub(&["run", "--bench-warmup", "100"]).is_err());
+    }
+
+    #[test]
+    fn test_workload_alias_is_canonicalized() {
+        let args = parse_sub(&["run", "-w", "memory"]).unwrap();
+        assert_eq!(args.workload, "memory-latency");
+        assert!(args.to_toml().contains("workload = \"memory-latency\""));
+
+        let args = parse_with_config(&["locus"], "workload = \"memory\"").unwrap();
+        assert_eq!(args.workload, "memory-latency");
+    }
+
+    #[test]
+    fn test_workload_and_split_accept_the_same_names() {
+        for name in registry::accepted_names()
+            .into_iter()
+            .chain(["bogus", "Memory"])
+        {
+            let single = parse_sub(&["run", "-w", name]).map(|args| args.workload);
+            let split = assignment::parse_split(&format!("{}=1", name))
+                .map(|entries| entries[0].0.name.to_string());
+            assert_eq!(
+                single.ok(),
+                split.ok(),
+                "-w and --split disagree on '{}'",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_profile_keys_a_command_doesnt_take_are_skipped() {
+        let profile = "benchmark = true\nsplit = \"integer=1\"\nthreads = 3";
+        let args = with_env(&[], || {
+            let argv = [OsString::from("run")];
+            let (mut args, matches) = parse_stress(Some(Subcommand::Run), &argv).unwrap();
+            args.merge_config(
+                "benchmark = true\nthreads = 3",
+                &matches,
+                Some(Subcommand::Run),
+            )
+            .unwrap();
+            args
+        });
+        assert!(!args.benchmark);
+        assert_eq!(args.threads, 3);
+
+        let args = with_env(&[], || {
+            let argv = [OsString::from("bench")];
+            let (mut args, matches) = parse_stress(Some(Subcommand::Bench), &argv).unwrap();
+            args.merge_config(profile, &matches, Some(Subcommand::Bench))
+                .unwrap();
+            args
+        });
+        assert!(args.benchmark);
+        assert_eq!(args.split, None);
+    }
+
+    #[test]
+    fn test_help_topic() {
+        let argv = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(help_topic(&argv(&["locus", "-h"])), Some(None));
+        assert_eq!(
+            help_topic(&argv(&["locus", "bench", "--help"])),
+            Some(Some(Subcommand::Bench))
+        );
+        assert_eq!(
+            help_topic(&argv(&["locus", "help", "compare"])),
+            Some(Some(Subcommand::Compare))
+        );
+        assert_eq!(help_topic(&argv(&["locus", "run", "-d", "5"])), None);
+        assert_eq!(help_topic(&argv(&["locus", "-d", "5"])), None);
+    }
+
+    #[test]
+    fn test_version_request() {
+        let argv = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(version_request(&argv(&["locus", "-V"])), Some(false));
+        assert_eq!(
+            version_request(&argv(&["locus", "--version", "--verbose"])),
+            Some(true)
+        );
+        assert_eq!(
+            version_request(&argv(&["locus", "--verbose", "-V"])),
+            Some(true)
+        );
+        assert_eq!(version_request(&argv(&["locus", "-V", "-d", "5"])), None);
+        assert_eq!(version_request(&argv(&["locus"])), None);
+    }
 }
@@ -0,0 +1,231 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::output::BenchmarkReport;
+use crate::registry;
+use crate::reporting::format_number;
+
+/// One saved `bench --format json` result and the column it is shown in.
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub label:  String,
+    pub report: BenchmarkReport,
+}
+
+pub fn load(path: &Path) -> Result<Column, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let report: BenchmarkReport = serde_json::from_str(&text).map_err(|e| {
+        format!(
+            "{}: not a `locus bench --format json` result: {}",
+            path.display(),
+            e
+        )
+    })?;
+    let label = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+
+    Ok(Column { label, report })
+}
+
+/// Loads every file, labelling columns by file name, or by full path where
+/// two files share a name.
+pub fn load_all(paths: &[PathBuf]) -> Result<Vec<Column>, String> {
+    let mut columns = paths
+        .iter()
+        .map(|path| load(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let labels: Vec<String> = columns.iter().map(|c| c.label.clone()).collect();
+    for (column, path) in columns.iter_mut().zip(paths) {
+        if labels
+            .iter()
+            .filter(|label| **label == column.label)
+            .count()
+            > 1
+        {
+            column.label = path.display().to_string();
+        }
+    }
+    Ok(columns)
+}
+
+/// Registry order first, then workloads only some files know about.
+fn workload_rows(columns: &[Column]) -> Vec<String> {
+    let mut rows: Vec<String> = registry::WORKLOADS
+        .iter()
+        .map(|spec| spec.name.to_string())
+        .filter(|name| columns.iter().any(|c| rate(c, name).is_some()))
+        .collect();
+
+    for column in columns {
+        for entry in &column.report.results {
+            if !rows.contains(&entry.workload) {
+                rows.push(entry.workload.clone());
+            }
+        }
+    }
+    rows
+}
+
+pub fn rate(column: &Column, workload: &str) -> Option<u64> {
+    column
+        .report
+        .results
+        .iter()
+        .find(|entry| entry.workload == workload)
+        .map(|entry| entry.ops_per_sec)
+}
+
+pub fn write_comparison(out: &mut dyn Write, columns: &[Column]) -> io::Result<()> {
+    let rows = workload_rows(columns);
+    let widths: Vec<usize> = columns.iter().map(|c| c.label.len().max(13)).collect();
+
+    write!(out, "{:<18}", "Workload")?;
+    for (column, width) in columns.iter().zip(&widths) {
+        write!(out, "  {:>width$}", column.label, width = width)?;
+    }
+    writeln!(out)?;
+    write!(out, "{}", "─".repeat(18))?;
+    for width in &widths {
+        write!(out, "  {}", "─".repeat(*width))?;
+    }
+    writeln!(out)?;
+
+    for workload in &rows {
+        let rates: Vec<Option<u64>> = columns.iter().map(|c| rate(c, workload)).collect();
+        let best = rates.iter().flatten().max().copied();
+        let name = registry::find(workload)
+            .map(|spec| spec.display_name())
+            .unwrap_or_else(|| workload.clone());
+
+        write!(out, "{:<18}", name)?;
+        for (rate, width) in rates.iter().zip(&widths) {
+            let cell = match rate {
+                Some(r) if columns.len() > 1 && Some(*r) == best => {
+                    format!("{} /s *", format_number(*r))
+                },
+                Some(r) => format!("{} /s  ", format_number(*r)),
+                None => "n/a  ".to_string(),
+            };
+            write!(out, "  {:>width$}", cell, width = width)?;
+        }
+        writeln!(out)?;
+    }
+
+    writeln!(out)?;
+    for column in columns {
+        writeln!(
+            out,
+            "  {}: locus {}, {} threads, {}s per workload, {}",
+            column.label,
+            column.report.version,
+            column.report.threads,
+            column.report.duration_secs,
+            column.report.virtualization
+        )?;
+    }
+    if columns.len() > 1 {
+        writeln!(out, "  * best rate in the row")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{BenchmarkEntry, BuildInfo, RunMetadata};
+
+    fn column(label: &str, results: &[(&str, u64)]) -> Column {
+        Column {
+            label:  label.to_string(),
+            report: BenchmarkReport {
+                version:        "1.0.0".to_string(),
+                metadata:       RunMetadata::default(),
+                build:          BuildInfo::default(),
+                virtualization: "bare metal".to_string(),
+                threads:        8,
+                duration_secs:  10,
+                results:        results
+                    .iter()
+                    .map(|(workload, ops_per_sec)| BenchmarkEntry {
+                        workload:               workload.to_string(),
+                        ops_per_sec:            *ops_per_sec,
+                        relative_to_mixed:      1.0,
+                        per_thread_ops_per_sec: ops_per_sec / 8,
+                        duration_secs:          10,
+                        setup_secs:             0.0,
+                        measured_secs:          10.0,
+                        converged:              None,
+                        gb_per_sec:             None,
+                        imbalance:              None,
+                        slowest_worker:         None,
+                        code_path:              None,
+                        percent_of_reference:   None,
+                    })
+                    .collect(),
+                score:          None,
+                index:          None,
+                events:         Vec::new(),
+                swap_pages:     None,
+                buffer_rounding: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_comparison_marks_best_and_missing() {
+        let columns = [
+            column("desktop", &[("integer", 4_000_000), ("float", 900_000)]),
+            column("laptop", &[("integer", 2_000_000), ("mixed", 500_000)]),
+        ];
+        let mut out = Vec::new();
+        write_comparison(&mut out, &columns).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert!(lines[0].contains("desktop") && lines[0].contains("laptop"));
+        assert!(lines[2].starts_with("Integer"));
+        assert!(lines[2].contains("4.00M /s *"));
+        assert!(!lines[2].contains("2.00M /s *"));
+        assert!(lines[3].starts_with("Float") && lines[3].contains("n/a"));
+        assert!(lines[4].starts_with("Mixed"));
+    }
+
+    #[test]
+    fn test_load_reads_saved_report() {
+        let path =
+            std::env::temp_dir().join(format!("locus-compare-{}.json", std::process::id()));
+        let saved = column("ignored", &[("float", 123)]).report;
+        std::fs::write(&path, serde_json::to_string(&saved).unwrap()).unwrap();
+
+        let loaded = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            loaded.label,
+            format!("locus-compare-{}", std::process::id())
+        );
+        assert_eq!(loaded.report.results[0].ops_per_sec, 123);
+
+        assert!(load(Path::new("/nonexistent/run.json")).is_err());
+    }
+
+    #[test]
+    fn test_shared_file_names_use_full_paths() {
+        let dir =
+            std::env::temp_dir().join(format!("locus-compare-dup-{}", std::process::id()));
+        let paths = [dir.join("a/run.json"), dir.join("b/run.json")];
+        let saved = serde_json::to_string(&column("x", &[("float", 1)]).report).unwrap();
+        for path in &paths {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, &saved).unwrap();
+        }
+
+        let columns = load_all(&paths).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(columns[0].label, paths[0].display().to_string());
+        assert_eq!(columns[1].label, paths[1].display().to_string());
+    }
+}
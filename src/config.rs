@@ -0,0 +1,229 @@
+use std::fmt;
+
+/// The subset of TOML a flat profile needs: `key = value` lines with
+/// strings, integers, floats and booleans, plus `#` comments.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Str(_) => "string",
+            Self::Int(_) => "integer",
+            Self::Float(_) => "float",
+            Self::Bool(_) => "boolean",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub key:   String,
+    pub value: Value,
+    /// 1-based, for error messages
+    pub line:  usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub line:    usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+pub fn parse(text: &str) -> Result<Vec<Entry>, ConfigError> {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let err = |message: String| ConfigError { line, message };
+
+        let content = strip_comment(raw).trim();
+        if content.is_empty() {
+            continue;
+        }
+        if content.starts_with('[') {
+            return Err(err(format!(
+                "tables are not supported, got '{}'; put every key at the top level",
+                content
+            )));
+        }
+
+        let (key, value) = content
+            .split_once('=')
+            .ok_or_else(|| err(format!("expected 'key = value', got '{}'", content)))?;
+        let key = key.trim();
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(err(format!("invalid key '{}'", key)));
+        }
+        if entries.iter().any(|e| e.key == key) {
+            return Err(err(format!("duplicate key '{}'", key)));
+        }
+
+        let value = parse_value(value.trim()).map_err(|e| err(format!("{}: {}", key, e)))?;
+        entries.push(Entry {
+            key: key.to_string(),
+            value,
+            line,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Drops a trailing `# comment`, leaving `#` inside strings alone.
+fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {},
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..i],
+            None => {},
+        }
+    }
+    line
+}
+
+fn parse_value(s: &str) -> Result<Value, String> {
+    match s {
+        "" => return Err("missing value".to_string()),
+        "true" => return Ok(Value::Bool(true)),
+        "false" => return Ok(Value::Bool(false)),
+        _ => {},
+    }
+
+    if let Some(rest) = s.strip_prefix('"') {
+        let body = rest
+            .strip_suffix('"')
+            .ok_or_else(|| format!("unterminated string {}", s))?;
+        return unescape(body).map(Value::Str);
+    }
+    if let Some(rest) = s.strip_prefix('\'') {
+        let body = rest
+            .strip_suffix('\'')
+            .ok_or_else(|| format!("unterminated string {}", s))?;
+        return Ok(Value::Str(body.to_string()));
+    }
+
+    let digits = s.replace('_', "");
+    if let Ok(n) = digits.parse::<i64>() {
+        return Ok(Value::Int(n));
+    }
+    // Require a '.' so "inf" and "nan" stay errors
+    match digits.parse::<f64>() {
+        Ok(f) if digits.contains('.') && f.is_finite() => Ok(Value::Float(f)),
+        _ => Err(format!("expected a string, number or boolean, got '{}'", s)),
+    }
+}
+
+fn unescape(body: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            if c == '"' {
+                return Err("unescaped '\"' inside string".to_string());
+            }
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => return Err(format!("unsupported escape '\\{}'", other)),
+            None => return Err("string ends with a lone '\\'".to_string()),
+        }
+    }
+    Ok(out)
+}
+
+/// Quotes a string so `parse` reads it back unchanged.
+pub fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_values() {
+        let entries = parse(
+            "# lab profile\n\
+             threads = 16\n\
+             workload = \"memory-bandwidth\"  # trailing comment\n\
+             \n\
+             quiet = true\n\
+             out = 'C:\\results\\run.json'\n\
+             batch-size = 1_000_000\n\
+             ram-safety = 0.75\n",
+        )
+        .unwrap();
+
+        let values: Vec<(&str, &Value)> =
+            entries.iter().map(|e| (e.key.as_str(), &e.value)).collect();
+        assert_eq!(values, [
+            ("threads", &Value::Int(16)),
+            ("workload", &Value::Str("memory-bandwidth".into())),
+            ("quiet", &Value::Bool(true)),
+            ("out", &Value::Str("C:\\results\\run.json".into())),
+            ("batch-size", &Value::Int(1_000_000)),
+            ("ram-safety", &Value::Float(0.75)),
+        ]);
+        assert_eq!(entries[2].line, 5);
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        let err = parse("threads = 4\nworkload = \"mixed\nquiet = true").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("workload"));
+
+        assert_eq!(parse("threads = 4\nthreads = 8").unwrap_err().line, 2);
+        assert_eq!(parse("[run]\nthreads = 4").unwrap_err().line, 1);
+        assert_eq!(parse("threads 4").unwrap_err().line, 1);
+        assert_eq!(parse("threads = four").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn test_quote_round_trips() {
+        let tricky = "say \"hi\" # not a comment \\ done";
+        let entries = parse(&format!("out = {}", quote(tricky))).unwrap();
+        assert_eq!(entries[0].value, Value::Str(tricky.to_string()));
+    }
+}
@@ -0,0 +1,467 @@
+//! What the installed memory is: module sizes, type, speed and how many
+//! channels they populate, so a GB/s figure can be read against what the
+//! platform could do. Best effort throughout: anything that can't be read
+//! leaves the field (or the whole description) out and never fails a run.
+
+use serde::{Deserialize, Serialize};
+
+use crate::reporting::format_mb;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DramInfo {
+    /// One entry per populated module, in MiB
+    pub module_mb: Vec<usize>,
+    /// "DDR4", "LPDDR5", ...
+    pub kind:      Option<String>,
+    /// Configured speed in MT/s; the slowest module sets it for all
+    pub speed_mts: Option<u32>,
+    /// Distinct channels the slot names point at (None = they don't say)
+    pub channels:  Option<usize>,
+}
+
+impl DramInfo {
+    /// "2×16 GiB DDR4-3200 (dual channel)", with whatever is known.
+    pub fn describe(&self) -> String {
+        let sizes = match self.module_mb.as_slice() {
+            [] => String::new(),
+            [first, rest @ ..] if rest.iter().all(|mb| mb == first) => {
+                format!("{}×{}", self.module_mb.len(), format_mb(*first))
+            },
+            modules => modules
+                .iter()
+                .map(|&mb| format_mb(mb))
+                .collect::<Vec<_>>()
+                .join(" + "),
+        };
+        let kind = match (&self.kind, self.speed_mts) {
+            (Some(kind), Some(speed)) => format!("{}-{}", kind, speed),
+            (Some(kind), None) => kind.clone(),
+            (None, Some(speed)) => format!("{} MT/s", speed),
+            (None, None) => String::new(),
+        };
+
+        let mut text = [sizes, kind]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some(channels) = self.channels {
+            text += &format!(" ({})", describe_channels(channels));
+        }
+        text
+    }
+}
+
+fn describe_channels(channels: usize) -> String {
+    match channels {
+        1 => "single channel".to_string(),
+        2 => "dual channel".to_string(),
+        3 => "triple channel".to_string(),
+        4 => "quad channel".to_string(),
+        n => format!("{} channels", n),
+    }
+}
+
+/// None when the platform keeps the module details from an unprivileged
+/// process (Linux without root) or doesn't expose them at all.
+pub fn detect() -> Option<DramInfo> {
+    #[cfg(target_os = "linux")]
+    {
+        parse_smbios(&std::fs::read("/sys/firmware/dmi/tables/DMI").ok()?)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        parse_smbios(&raw_smbios_table()?)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("system_profiler")
+            .args(["-json", "SPMemoryDataType"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        parse_system_profiler(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// The SMBIOS structure table, without the RawSMBIOSData header
+/// (four version bytes and a u32 length) Windows puts in front of it.
+#[cfg(target_os = "windows")]
+fn raw_smbios_table() -> Option<Vec<u8>> {
+    use windows_sys::Win32::System::SystemInformation::GetSystemFirmwareTable;
+
+    const RSMB: u32 = u32::from_be_bytes(*b"RSMB");
+
+    let size = unsafe { GetSystemFirmwareTable(RSMB, 0, std::ptr::null_mut(), 0) };
+    if size == 0 {
+        return None;
+    }
+    let mut buffer = vec![0u8; size as usize];
+    let written = unsafe { GetSystemFirmwareTable(RSMB, 0, buffer.as_mut_ptr().cast(), size) };
+    if written == 0 || written > size {
+        return None;
+    }
+    let length = u32::from_le_bytes(buffer.get(4..8)?.try_into().ok()?) as usize;
+    buffer.get(8..8 + length).map(<[u8]>::to_vec)
+}
+
+/// SMBIOS type 17 (Memory Device) offsets, from DSP0134.
+const MEMORY_DEVICE: u8 = 17;
+const END_OF_TABLE: u8 = 127;
+const SIZE_OFFSET: usize = 0x0c;
+const DEVICE_LOCATOR_OFFSET: usize = 0x10;
+const BANK_LOCATOR_OFFSET: usize = 0x11;
+const TYPE_OFFSET: usize = 0x12;
+const SPEED_OFFSET: usize = 0x15;
+const EXTENDED_SIZE_OFFSET: usize = 0x1c;
+const CONFIGURED_SPEED_OFFSET: usize = 0x20;
+const EXTENDED_SPEED_OFFSET: usize = 0x54;
+const EXTENDED_CONFIGURED_SPEED_OFFSET: usize = 0x58;
+
+struct Module {
+    size_mb:  usize,
+    kind:     Option<&'static str>,
+    speed:    Option<u32>,
+    locators: String,
+}
+
+/// Walks the structure table; None unless it lists at least one populated
+/// module.
+#[cfg_attr(not(any(target_os = "linux", target_os = "windows")), allow(dead_code))]
+fn parse_smbios(table: &[u8]) -> Option<DramInfo> {
+    let mut modules = Vec::new();
+    let mut pos = 0;
+
+    while pos + 4 <= table.len() {
+        let kind = table[pos];
+        let length = table[pos + 1] as usize;
+        if length < 4 || pos + length > table.len() {
+            break;
+        }
+        let formatted = &table[pos..pos + length];
+
+        // Strings follow the formatted area, each NUL-terminated, the set
+        // ending in a second NUL
+        let strings_start = pos + length;
+        let Some(strings_len) = table[strings_start..]
+            .windows(2)
+            .position(|pair| pair == [0, 0])
+        else {
+            break;
+        };
+        let strings: Vec<&[u8]> = table[strings_start..strings_start + strings_len]
+            .split(|&b| b == 0)
+            .collect();
+        pos = strings_start + strings_len + 2;
+
+        match kind {
+            END_OF_TABLE => break,
+            MEMORY_DEVICE => modules.extend(memory_device(formatted, &strings)),
+            _ => {},
+        }
+    }
+
+    summarize(modules)
+}
+
+fn memory_device(formatted: &[u8], strings: &[&[u8]]) -> Option<Module> {
+    let byte = |offset: usize| formatted.get(offset).copied();
+    let word = |offset: usize| {
+        Some(u16::from_le_bytes(
+            formatted.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    };
+    let dword = |offset: usize| {
+        Some(u32::from_le_bytes(
+            formatted.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    // String numbers are 1-based; 0 means none
+    let string = |offset: usize| {
+        let index = byte(offset)? as usize;
+        let text = strings.get(index.checked_sub(1)?)?;
+        Some(String::from_utf8_lossy(text).trim().to_string())
+    };
+
+    let size_mb = match word(SIZE_OFFSET)? {
+        // Empty slot, or a size the firmware doesn't know
+        0 | 0xffff => return None,
+        0x7fff => (dword(EXTENDED_SIZE_OFFSET)? & 0x7fff_ffff) as usize,
+        // Bit 15 set: the rest is in KiB
+        size if size & 0x8000 != 0 => (size & 0x7fff) as usize / 1024,
+        size => size as usize,
+    };
+    let speed = |short: usize, extended: usize| match word(short)? {
+        0 => None,
+        0xffff => dword(extended).filter(|&speed| speed != 0),
+        speed => Some(speed as u32),
+    };
+
+    Some(Module {
+        size_mb,
+        kind: byte(TYPE_OFFSET).and_then(memory_type_name),
+        speed: speed(CONFIGURED_SPEED_OFFSET, EXTENDED_CONFIGURED_SPEED_OFFSET)
+            .or_else(|| speed(SPEED_OFFSET, EXTENDED_SPEED_OFFSET)),
+        locators: [string(BANK_LOCATOR_OFFSET), string(DEVICE_LOCATOR_OFFSET)]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" "),
+    })
+}
+
+fn memory_type_name(code: u8) -> Option<&'static str> {
+    Some(match code {
+        0x12 => "DDR",
+        0x13 => "DDR2",
+        0x18 => "DDR3",
+        0x1a => "DDR4",
+        0x1b => "LPDDR",
+        0x1c => "LPDDR2",
+        0x1d => "LPDDR3",
+        0x1e => "LPDDR4",
+        0x22 => "DDR5",
+        0x23 => "LPDDR5",
+        _ => return None,
+    })
+}
+
+/// `system_profiler -json SPMemoryDataType`: slots under `_items` on Intel
+/// Macs, a single unified-memory entry on Apple Silicon.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_system_profiler(json: &str) -> Option<DramInfo> {
+    let root: serde_json::Value = serde_json::from_str(json).ok()?;
+    let text =
+        |value: &serde_json::Value, key: &str| value.get(key)?.as_str().map(str::to_string);
+
+    let mut modules = Vec::new();
+    for entry in root.get("SPMemoryDataType")?.as_array()? {
+        let slots = match entry.get("_items").and_then(|items| items.as_array()) {
+            Some(items) => items.iter().collect(),
+            None => vec![entry],
+        };
+        for slot in slots {
+            let size = text(slot, "dimm_size").or_else(|| text(slot, "SPMemoryDataType"));
+            let Some(size_mb) = size.as_deref().and_then(parse_profiler_size) else {
+                continue;
+            };
+            modules.push(Module {
+                size_mb,
+                kind: text(slot, "dimm_type").as_deref().and_then(known_type),
+                speed: text(slot, "dimm_speed")
+                    .and_then(|speed| speed.split_whitespace().next()?.parse().ok()),
+                locators: text(slot, "_name").unwrap_or_default(),
+            });
+        }
+    }
+    summarize(modules)
+}
+
+fn known_type(name: &str) -> Option<&'static str> {
+    (0x12..=0x23).find_map(|code| memory_type_name(code).filter(|known| *known == name))
+}
+
+/// "8 GB" or "512 MB"; "empty" slots don't parse.
+fn parse_profiler_size(size: &str) -> Option<usize> {
+    let (number, unit) = size.trim().split_once(' ')?;
+    let number: usize = number.parse().ok()?;
+    match unit {
+        "GB" => Some(number * 1024),
+        "MB" => Some(number),
+        _ => None,
+    }
+}
+
+fn summarize(modules: Vec<Module>) -> Option<DramInfo> {
+    if modules.is_empty() {
+        return None;
+    }
+    let channels: Option<Vec<String>> =
+        modules.iter().map(|m| channel_of(&m.locators)).collect();
+    let channels = channels.map(|mut names| {
+        names.sort();
+        names.dedup();
+        names.len()
+    });
+
+    Some(DramInfo {
+        kind: modules.iter().find_map(|m| m.kind).map(str::to_string),
+        speed_mts: modules.iter().filter_map(|m| m.speed).min(),
+        channels,
+        module_mb: modules.into_iter().map(|m| m.size_mb).collect(),
+    })
+}
+
+/// The channel a slot name points at: "ChannelA-DIMM0", "P0 CHANNEL B",
+/// "DIMM_C1". Board vendors aren't consistent, so None rather than a guess.
+fn channel_of(locators: &str) -> Option<String> {
+    let upper = locators.to_uppercase();
+    let after = |marker: &str| {
+        let start = upper.find(marker)? + marker.len();
+        Some(
+            upper[start..]
+                .trim_start_matches([' ', '_', '-'])
+                .to_string(),
+        )
+    };
+
+    if let Some(rest) = after("CHANNEL") {
+        let id: String = rest
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect();
+        // "ChannelA-DIMM0" names the channel in its first character
+        return id.chars().next().map(String::from);
+    }
+    let rest = after("DIMM")?;
+    let mut chars = rest.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), Some(digit))
+            if letter.is_ascii_uppercase() && digit.is_ascii_digit() =>
+        {
+            Some(letter.to_string())
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A type 17 record as SMBIOS 3.x lays it out: 0x5C bytes of formatted
+    /// area, then its strings.
+    fn memory_device_record(
+        size: u16,
+        kind: u8,
+        speed: u16,
+        bank: &str,
+        device: &str,
+    ) -> Vec<u8> {
+        let mut formatted = vec![0u8; 0x5c];
+        formatted[0] = MEMORY_DEVICE;
+        formatted[1] = 0x5c;
+        formatted[SIZE_OFFSET..SIZE_OFFSET + 2].copy_from_slice(&size.to_le_bytes());
+        formatted[DEVICE_LOCATOR_OFFSET] = 1;
+        formatted[BANK_LOCATOR_OFFSET] = 2;
+        formatted[TYPE_OFFSET] = kind;
+        formatted[SPEED_OFFSET..SPEED_OFFSET + 2].copy_from_slice(&4800u16.to_le_bytes());
+        formatted[CONFIGURED_SPEED_OFFSET..CONFIGURED_SPEED_OFFSET + 2]
+            .copy_from_slice(&speed.to_le_bytes());
+
+        let mut record = formatted;
+        for text in [device, bank] {
+            record.extend_from_slice(text.as_bytes());
+            record.push(0);
+        }
+        record.push(0);
+        record
+    }
+
+    fn end_of_table() -> Vec<u8> {
+        vec![END_OF_TABLE, 4, 0, 0, 0, 0]
+    }
+
+    #[test]
+    fn test_smbios_modules_and_channels() {
+        let mut table = Vec::new();
+        // A BIOS record (type 0) to skip, with one string
+        table.extend_from_slice(&[0, 4, 0, 0]);
+        table.extend_from_slice(b"vendor\0\0");
+        table.extend(memory_device_record(
+            16384,
+            0x1a,
+            3200,
+            "P0 CHANNEL A",
+            "DIMM 0",
+        ));
+        table.extend(memory_device_record(0, 0x1a, 0, "P0 CHANNEL A", "DIMM 1"));
+        table.extend(memory_device_record(
+            16384,
+            0x1a,
+            3200,
+            "P0 CHANNEL B",
+            "DIMM 0",
+        ));
+        table.extend(end_of_table());
+
+        let info = parse_smbios(&table).unwrap();
+        assert_eq!(info.module_mb, [16384, 16384]);
+        assert_eq!(info.channels, Some(2));
+        assert_eq!(info.describe(), "2×16 GiB DDR4-3200 (dual channel)");
+    }
+
+    #[test]
+    fn test_smbios_sizes_and_speed_fallbacks() {
+        // KiB units, and no configured speed: the rated 4800 stands in
+        let mut small = memory_device_record(0x8000 | 4096, 0x22, 0, "BANK 0", "Slot 1");
+        // 0x7FFF defers to the extended size, here 64 GiB
+        let mut large = memory_device_record(0x7fff, 0x22, 5600, "BANK 1", "Slot 2");
+        large[EXTENDED_SIZE_OFFSET..EXTENDED_SIZE_OFFSET + 4]
+            .copy_from_slice(&65536u32.to_le_bytes());
+        small.extend(large);
+
+        let info = parse_smbios(&small).unwrap();
+        assert_eq!(info.module_mb, [4, 65536]);
+        assert_eq!(info.speed_mts, Some(4800));
+        assert_eq!(info.kind.as_deref(), Some("DDR5"));
+        // "BANK 0" says nothing about channels
+        assert_eq!(info.channels, None);
+        assert_eq!(info.describe(), "4 MiB + 64 GiB DDR5-4800");
+    }
+
+    #[test]
+    fn test_unreadable_tables_give_nothing() {
+        assert_eq!(parse_smbios(&[]), None);
+        assert_eq!(parse_smbios(&end_of_table()), None);
+        // Truncated mid-record and missing its string terminator
+        let record = memory_device_record(8192, 0x1a, 2666, "A", "B");
+        assert_eq!(parse_smbios(&record[..40]), None);
+        assert_eq!(parse_smbios(&record[..record.len() - 1]), None);
+        // A length under the header size stops the walk
+        assert_eq!(parse_smbios(&[17, 2, 0, 0, 0, 0]), None);
+
+        assert_eq!(parse_system_profiler("not json"), None);
+        assert_eq!(parse_system_profiler("{}"), None);
+        // Whatever detection finds here, it mustn't panic
+        let _ = detect();
+    }
+
+    #[test]
+    fn test_system_profiler_layouts() {
+        let intel = r#"{"SPMemoryDataType":[{"_name":"Memory Slots","_items":[
+            {"_name":"BANK 0/ChannelA-DIMM0","dimm_size":"8 GB","dimm_speed":"2667 MHz","dimm_type":"DDR4"},
+            {"_name":"BANK 1/ChannelB-DIMM0","dimm_size":"8 GB","dimm_speed":"2667 MHz","dimm_type":"DDR4"},
+            {"_name":"BANK 1/ChannelB-DIMM1","dimm_size":"empty","dimm_type":"empty"}]}]}"#;
+        assert_eq!(
+            parse_system_profiler(intel).unwrap().describe(),
+            "2×8 GiB DDR4-2667 (dual channel)"
+        );
+
+        let apple =
+            r#"{"SPMemoryDataType":[{"SPMemoryDataType":"16 GB","dimm_type":"LPDDR5"}]}"#;
+        assert_eq!(
+            parse_system_profiler(apple).unwrap().describe(),
+            "1×16 GiB LPDDR5"
+        );
+    }
+
+    #[test]
+    fn test_channel_names() {
+        assert_eq!(channel_of("ChannelA-DIMM0").as_deref(), Some("A"));
+        assert_eq!(channel_of("P0 CHANNEL B DIMM 1").as_deref(), Some("B"));
+        assert_eq!(channel_of("P0_Node0_Channel3_Dimm0").as_deref(), Some("3"));
+        assert_eq!(channel_of("CPU1_DIMM_C1").as_deref(), Some("C"));
+        assert_eq!(channel_of("BANK 2 DIMM 0"), None);
+        assert_eq!(channel_of(""), None);
+    }
+}
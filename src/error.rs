@@ -0,0 +1,211 @@
+//! Why `locus` stopped before it had a report to stand behind. The modes
+//! return these; `main` prints them and picks the exit code in one place.
+
+use std::fmt;
+use std::io;
+
+use crate::registry;
+use crate::reporting::BELOW_THRESHOLD_EXIT;
+
+#[derive(Debug)]
+pub enum LocusError {
+    /// Options that can't go together, or one missing what it needs; the
+    /// message says which to drop or add
+    ConflictingOptions(String),
+    InvalidWorkload(String),
+    /// A list option (--split, --workload-durations, --interference) that
+    /// didn't parse
+    InvalidList {
+        option: &'static str,
+        reason: String,
+    },
+    /// The benchmark set left nothing to run
+    NoWorkloadsSelected,
+    /// A --workload-durations entry the --benchmark-set doesn't run
+    WorkloadNotInSet {
+        workload: String,
+        set:      &'static str,
+    },
+    /// Some benchmark workload has no duration; `overrides` when
+    /// --workload-durations was given, so it needs an entry too
+    BenchmarkNeedsDuration {
+        overrides: bool,
+    },
+    /// Auto-sizing came out at 0 MB
+    ZeroMemory,
+    OverMemoryLimit {
+        memory_mb:    usize,
+        threads:      usize,
+        max_total_mb: usize,
+    },
+    /// Too large to allocate unconfirmed, and --silent can't ask
+    AllocationNeedsYes {
+        total_mb: usize,
+        reason:   String,
+    },
+    /// The user answered no at the allocation prompt
+    AllocationDeclined,
+    /// --out couldn't be opened, checked before anything runs
+    OutputUnavailable {
+        target: String,
+        source: io::Error,
+    },
+    /// The finished report couldn't be written or flushed
+    ReportNotWritten {
+        target: String,
+        source: io::Error,
+    },
+    /// `compare` couldn't load a file or write the table
+    Compare(String),
+    /// The --baseline file couldn't be loaded
+    Baseline(String),
+}
+
+impl fmt::Display for LocusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConflictingOptions(message)
+            | Self::Compare(message)
+            | Self::Baseline(message) => f.write_str(message),
+            Self::InvalidWorkload(name) => write!(
+                f,
+                "unknown workload '{}' (available: {})",
+                name,
+                registry::accepted_names().join(", ")
+            ),
+            Self::InvalidList { option, reason } => write!(f, "{}: {}", option, reason),
+            Self::NoWorkloadsSelected => write!(
+                f,
+                "no workloads left to benchmark (available: {})",
+                registry::WORKLOADS
+                    .iter()
+                    .map(|spec| spec.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::WorkloadNotInSet { workload, set } => write!(
+                f,
+                "--workload-durations: {} is not in the {} benchmark set (see --benchmark-set)",
+                workload, set
+            ),
+            Self::BenchmarkNeedsDuration { overrides } => {
+                f.write_str("bench requires --duration to be set (e.g., locus bench -d 60)")?;
+                if *overrides {
+                    f.write_str("\n       or a --workload-durations entry for every workload")?;
+                }
+                Ok(())
+            },
+            Self::ZeroMemory => {
+                f.write_str("memory buffer size resolved to 0 MB; set one with -m")
+            },
+            Self::OverMemoryLimit {
+                memory_mb,
+                threads,
+                max_total_mb,
+            } => write!(
+                f,
+                "-m {} × {} threads = {} MB exceeds --max-total-memory {} MB",
+                memory_mb,
+                threads,
+                // -m takes any usize, so only u128 is sure to hold the product
+                *memory_mb as u128 * *threads as u128,
+                max_total_mb
+            ),
+            Self::AllocationNeedsYes { total_mb, reason } => write!(
+                f,
+                "planned allocation of {} MB {}; pass --yes to allow it with --silent",
+                total_mb, reason
+            ),
+            Self::AllocationDeclined => f.write_str("aborted; nothing was allocated"),
+            Self::OutputUnavailable { target, source } => {
+                write!(f, "cannot write to {}: {}", target, source)
+            },
+            Self::ReportNotWritten { target, source } => {
+                write!(f, "failed to write results to {}: {}", target, source)
+            },
+        }
+    }
+}
+
+impl std::error::Error for LocusError {}
+
+/// How a mode that got as far as its report ended.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Outcome {
+    /// A worker failed or swapping stopped the run; both are said as they
+    /// happen
+    pub faulted:         bool,
+    /// Why the run missed --min-rate/--min-score
+    pub below_threshold: Option<String>,
+    /// Fatal worker events, for --notify-url
+    pub errors:          usize,
+    /// ops/s per workload of a run or benchmark, for --notify-url
+    pub rates:           Vec<(String, u64)>,
+}
+
+impl Outcome {
+    /// A fault (1) outranks a missed threshold (3), but `main` prints the
+    /// threshold message either way.
+    pub fn exit_code(&self) -> i32 {
+        if self.faulted {
+            1
+        } else if self.below_threshold.is_some() {
+            BELOW_THRESHOLD_EXIT
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_outranks_a_missed_threshold() {
+        assert_eq!(Outcome::default().exit_code(), 0);
+        let below = Outcome {
+            below_threshold: Some("below threshold".to_string()),
+            ..Outcome::default()
+        };
+        assert_eq!(below.exit_code(), BELOW_THRESHOLD_EXIT);
+        let both = Outcome {
+            faulted: true,
+            ..below
+        };
+        assert_eq!(both.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_messages_name_the_fix() {
+        let missing = LocusError::BenchmarkNeedsDuration { overrides: true }.to_string();
+        assert!(missing.starts_with("bench requires --duration"));
+        assert!(missing.ends_with("a --workload-durations entry for every workload"));
+        assert!(
+            !LocusError::BenchmarkNeedsDuration { overrides: false }
+                .to_string()
+                .contains('\n')
+        );
+
+        let over = LocusError::OverMemoryLimit {
+            memory_mb:    512,
+            threads:      8,
+            max_total_mb: 2048,
+        };
+        assert_eq!(
+            over.to_string(),
+            "-m 512 × 8 threads = 4096 MB exceeds --max-total-memory 2048 MB"
+        );
+        let wrapping = LocusError::OverMemoryLimit {
+            memory_mb:    usize::MAX,
+            threads:      4,
+            max_total_mb: 4096,
+        };
+        assert!(wrapping.to_string().contains(&(usize::MAX as u128 * 4).to_string()));
+        assert!(
+            LocusError::InvalidWorkload("nope".to_string())
+                .to_string()
+                .contains("memory-bandwidth")
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use crate::output::{self, EventReport};
+use crate::worker;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// The worker carried on, possibly in a degraded way
+    Warning,
+    /// The worker gave up; the whole run stops
+    Fatal,
+}
+
+impl EventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Fatal => "fatal",
+        }
+    }
+}
+
+/// Something a worker wants the main thread to know about. Workers never
+/// print; they send one of these instead.
+#[derive(Debug, Clone)]
+pub struct WorkerEvent {
+    pub thread_id: usize,
+    pub kind:      EventKind,
+    pub message:   String,
+}
+
+/// Worker-side handle for reporting events.
+#[derive(Debug, Clone)]
+pub struct EventSender {
+    thread_id: usize,
+    tx:        Sender<WorkerEvent>,
+}
+
+impl EventSender {
+    pub fn warning(&self, message: impl Into<String>) {
+        self.send(EventKind::Warning, message.into());
+    }
+
+    pub fn fatal(&self, message: impl Into<String>) {
+        self.send(EventKind::Fatal, message.into());
+    }
+
+    fn send(&self, kind: EventKind, message: String) {
+        // The receiver only goes away once the run is over
+        let _ = self.tx.send(WorkerEvent {
+            thread_id: self.thread_id,
+            kind,
+            message,
+        });
+    }
+}
+
+/// Main-thread side: collects events from every worker in a run.
+pub struct EventLog {
+    tx:     Sender<WorkerEvent>,
+    rx:     Receiver<WorkerEvent>,
+    events: Vec<WorkerEvent>,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            tx,
+            rx,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn sender(&self, thread_id: usize) -> EventSender {
+        EventSender {
+            thread_id,
+            tx: self.tx.clone(),
+        }
+    }
+
+    /// Prints and records whatever arrived since the last call. Returns true
+    /// if any of the new events was fatal.
+    pub fn drain(&mut self) -> bool {
+        let mut fatal = false;
+        let mut printed = 0;
+        for event in self.rx.try_iter() {
+            let prefix = match event.kind {
+                EventKind::Warning => "Warning",
+                EventKind::Fatal => "Error",
+            };
+            if event.kind == EventKind::Fatal || !output::silent() {
+                // Start on a fresh line in case the progress line is mid-update
+                eprintln!(
+                    "{}{}: {}: {}",
+                    if printed == 0 { "\n" } else { "" },
+                    prefix,
+                    worker::worker_name(event.thread_id),
+                    event.message
+                );
+                printed += 1;
+            }
+            fatal |= event.kind == EventKind::Fatal;
+            self.events.push(event);
+        }
+        fatal
+    }
+
+    pub fn has_fatal(&self) -> bool {
+        self.events.iter().any(|e| e.kind == EventKind::Fatal)
+    }
+
+    pub fn fatal_count(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|e| e.kind == EventKind::Fatal)
+            .count()
+    }
+
+    pub fn reports(&self) -> Vec<EventReport> {
+        self.events
+            .iter()
+            .map(|event| EventReport {
+                worker:  worker::worker_name(event.thread_id),
+                kind:    event.kind.as_str().to_string(),
+                message: event.message.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_reach_the_log() {
+        let mut log = EventLog::new();
+        let sender = log.sender(3);
+
+        std::thread::spawn(move || sender.warning("could not pin"))
+            .join()
+            .unwrap();
+        assert!(!log.drain());
+        assert!(!log.has_fatal());
+
+        log.sender(4).fatal("allocation failed");
+        assert!(log.drain());
+        assert!(log.has_fatal());
+
+        let reports = log.reports();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].worker, "locus-w3");
+        assert_eq!(reports[1].kind, "fatal");
+    }
+}
@@ -0,0 +1,74 @@
+use crate::reporting::{format_bytes, format_mb};
+use crate::{reference, registry, system};
+
+/// `locus info`: everything detection sees, without starting a run.
+pub fn print_info() {
+    println!("locus {}", env!("CARGO_PKG_VERSION"));
+
+    let info = system::info();
+    println!("  CPU:             {}", info.cpu_brand);
+    println!(
+        "  CPUs:            {} online ({} physical cores), {} available to this process",
+        info.logical_cpus, info.physical_cores, info.available_cpus
+    );
+    let size = |bytes: Option<u64>| bytes.map(format_bytes).unwrap_or_else(|| "unknown".into());
+    println!(
+        "  L1d / L2:        {} / {} per core",
+        size(info.caches.l1d_bytes),
+        size(info.caches.l2_bytes)
+    );
+    match info.l3 {
+        Some(l3) => println!("  L3 cache:        {}", l3.describe()),
+        None => println!("  L3 cache:        unknown"),
+    }
+    println!(
+        "  CPU features:    {}",
+        system::describe_cpu_features(&info.cpu_features)
+    );
+    println!("  Cache line:      {} bytes", info.cache_line_bytes);
+    match info.total_ram_mb {
+        Some(mb) => println!("  System RAM:      {}", format_mb(mb)),
+        None => println!("  System RAM:      unknown"),
+    }
+    match &info.dram {
+        Some(dram) => println!("  DRAM:            {}", dram.describe()),
+        None => println!("  DRAM:            unknown"),
+    }
+    if let Some(mb) = info.available_ram_mb {
+        println!("  Available RAM:   {}", format_mb(mb));
+    }
+    if let Some(mb) = info.cgroup_limit_mb {
+        println!("  cgroup limit:    {}", format_mb(mb));
+    }
+    println!(
+        "  Virtualization:  {}",
+        system::detect_virtualization().describe()
+    );
+
+    match info.numa_nodes {
+        Some(nodes) => println!("  NUMA nodes:      {} with memory", nodes),
+        None => println!("  NUMA nodes:      unknown"),
+    }
+    println!(
+        "  Thread pinning:  {}",
+        if system::affinity_supported() {
+            "supported"
+        } else {
+            "not supported"
+        }
+    );
+    println!(
+        "  Thread CPU time: {}",
+        if system::thread_cpu_time().is_some() {
+            "supported"
+        } else {
+            "not supported"
+        }
+    );
+
+    println!();
+    registry::print_workload_list();
+
+    println!();
+    println!("Bench reference (Index = 100): {}", reference::REFERENCE_MACHINE);
+}
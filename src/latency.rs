@@ -0,0 +1,333 @@
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::events::{EventLog, EventSender};
+use crate::output::{self, BufferRounding, LoadedLatencyReport};
+use crate::worker::WorkerConfig;
+use crate::workload::{
+    AccessPattern,
+    FloatMode,
+    InitPattern,
+    KernelParams,
+    allocate_memory_buffer,
+    stress_memory_latency,
+};
+use crate::{registry, reporting, system, worker};
+
+#[derive(Debug, Clone, Copy)]
+pub struct LoadedLatencyResult {
+    pub idle_ns:           f64,
+    pub loaded_ns:         f64,
+    pub bandwidth_threads: usize,
+    pub bandwidth_gb_s:    f64,
+}
+
+impl LoadedLatencyResult {
+    pub fn degradation(&self) -> f64 {
+        if self.idle_ns > 0.0 {
+            self.loaded_ns / self.idle_ns
+        } else {
+            0.0
+        }
+    }
+}
+
+pub fn ns_per_access(total_ns: u64, accesses: u64) -> f64 {
+    if accesses == 0 {
+        0.0
+    } else {
+        total_ns as f64 / accesses as f64
+    }
+}
+
+/// Pointer-chasing worker that also records time spent inside the kernel,
+/// so allocation and stop checks don't inflate ns/access.
+pub fn timed_latency_worker(
+    stop_flag: Arc<AtomicBool>,
+    access_counter: Arc<AtomicU64>,
+    ns_counter: Arc<AtomicU64>,
+    batch_size: u64,
+    memory_mb: usize,
+    events: EventSender,
+) {
+    let mut buffer = match allocate_memory_buffer(memory_mb, InitPattern::Sequential) {
+        Ok(buffer) => buffer,
+        Err(e) => {
+            events.fatal(e);
+            return;
+        },
+    };
+
+    while !stop_flag.load(Ordering::Relaxed) {
+        let batch_start = Instant::now();
+        stress_memory_latency(batch_size, &mut buffer);
+        let batch_ns = batch_start.elapsed().as_nanos() as u64;
+
+        ns_counter.fetch_add(batch_ns, Ordering::Relaxed);
+        access_counter.fetch_add(batch_size, Ordering::Relaxed);
+    }
+
+    black_box(buffer);
+}
+
+struct Phase {
+    latency_ns:    f64,
+    bandwidth_ops: u64,
+    elapsed:       Duration,
+}
+
+fn run_phase(
+    bandwidth_threads: usize,
+    batch_size: Option<u64>,
+    memory_mb: usize,
+    duration: Duration,
+    stack_size: Option<usize>,
+    events: &mut EventLog,
+) -> Phase {
+    let stop_signal = Arc::new(AtomicBool::new(false));
+    let accesses = Arc::new(AtomicU64::new(0));
+    let latency_ns = Arc::new(AtomicU64::new(0));
+    let bandwidth_counter = Arc::new(AtomicU64::new(0));
+    let latency = registry::find("memory-latency").expect("memory-latency is registered");
+    let bandwidth = registry::find("memory-bandwidth").expect("memory-bandwidth is registered");
+    let latency_batch = latency.batch_size(batch_size);
+    let bandwidth_batch = bandwidth.batch_size(batch_size);
+
+    let mut handles = Vec::with_capacity(bandwidth_threads + 1);
+
+    {
+        let stop = Arc::clone(&stop_signal);
+        let acc = Arc::clone(&accesses);
+        let ns = Arc::clone(&latency_ns);
+        let latency_events = events.sender(0);
+        handles.push(worker::spawn_named(
+            worker::worker_name(0),
+            stack_size,
+            move || {
+                timed_latency_worker(stop, acc, ns, latency_batch, memory_mb, latency_events);
+            },
+        ));
+    }
+
+    for id in 1..=bandwidth_threads {
+        let stop = Arc::clone(&stop_signal);
+        let counter = Arc::clone(&bandwidth_counter);
+        let config = WorkerConfig {
+            workload: bandwidth,
+            kernel: bandwidth.create(&KernelParams {
+                seed:       id as u64,
+                chains:     1,
+                float_mode: FloatMode::default(),
+                prefetch:   0,
+                pattern:    InitPattern::Sequential,
+                access:     AccessPattern::default(),
+            }),
+            batch_size: bandwidth_batch,
+            memory_mb,
+            sweep_bytes: 0,
+            cpu: None,
+            once: false,
+            best_effort: false,
+            pattern: InitPattern::Sequential,
+        };
+        let worker_events = events.sender(id);
+        handles.push(worker::spawn_named(
+            worker::worker_name(id),
+            stack_size,
+            move || {
+                worker::worker_thread(stop, counter, config, None, None, worker_events);
+            },
+        ));
+    }
+
+    let start = Instant::now();
+    while start.elapsed() < duration && !events.drain() {
+        thread::sleep(Duration::from_millis(100));
+    }
+    stop_signal.store(true, Ordering::Release);
+
+    for handle in handles {
+        worker::join_named(handle);
+    }
+    events.drain();
+
+    Phase {
+        latency_ns:    ns_per_access(
+            latency_ns.load(Ordering::Relaxed),
+            accesses.load(Ordering::Relaxed),
+        ),
+        bandwidth_ops: bandwidth_counter.load(Ordering::Relaxed),
+        elapsed:       start.elapsed(),
+    }
+}
+
+/// Measures pointer-chasing latency alone, then again while the remaining
+/// threads saturate memory bandwidth.
+pub fn run_loaded_latency(
+    num_threads: usize,
+    memory_mb: usize,
+    batch_size: Option<u64>,
+    duration_secs: u64,
+    quiet: bool,
+    stack_size: Option<usize>,
+    events: &mut EventLog,
+) -> LoadedLatencyResult {
+    let bandwidth_threads = num_threads.saturating_sub(1);
+    let solo_secs = (duration_secs / 5).max(1);
+
+    if !quiet {
+        eprintln!(
+            "\n[→] Idle phase: latency thread alone for {}s...",
+            solo_secs
+        );
+    }
+    let idle = run_phase(
+        0,
+        batch_size,
+        memory_mb,
+        Duration::from_secs(solo_secs),
+        stack_size,
+        events,
+    );
+
+    // Same sizes would fail the same way under load
+    if events.has_fatal() {
+        return LoadedLatencyResult {
+            idle_ns: idle.latency_ns,
+            loaded_ns: 0.0,
+            bandwidth_threads,
+            bandwidth_gb_s: 0.0,
+        };
+    }
+
+    if !quiet {
+        eprintln!(
+            "[→] Loaded phase: latency thread + {} bandwidth threads for {}s...",
+            bandwidth_threads, duration_secs
+        );
+    }
+    let loaded = run_phase(
+        bandwidth_threads,
+        batch_size,
+        memory_mb,
+        Duration::from_secs(duration_secs),
+        stack_size,
+        events,
+    );
+
+    let bytes_per_op = registry::find("memory-bandwidth")
+        .and_then(|spec| spec.bytes_per_op)
+        .unwrap_or(0);
+    let bandwidth_gb_s =
+        reporting::gb_per_sec(loaded.bandwidth_ops, bytes_per_op, loaded.elapsed);
+
+    LoadedLatencyResult {
+        idle_ns: idle.latency_ns,
+        loaded_ns: loaded.latency_ns,
+        bandwidth_threads,
+        bandwidth_gb_s,
+    }
+}
+
+pub fn loaded_latency_report(
+    result: &LoadedLatencyResult,
+    buffer_rounding: Option<BufferRounding>,
+    events: &EventLog,
+) -> LoadedLatencyReport {
+    LoadedLatencyReport {
+        version:           env!("CARGO_PKG_VERSION").to_string(),
+        metadata:          output::run_metadata(),
+        build:             output::build_info(),
+        virtualization:    system::detect_virtualization().describe(),
+        idle_ns:           result.idle_ns,
+        loaded_ns:         result.loaded_ns,
+        bandwidth_threads: result.bandwidth_threads,
+        bandwidth_gb_s:    result.bandwidth_gb_s,
+        degradation:       result.degradation(),
+        events:            events.reports(),
+        buffer_rounding,
+    }
+}
+
+pub fn write_loaded_latency(
+    out: &mut dyn Write,
+    result: &LoadedLatencyResult,
+    buffer_rounding: Option<BufferRounding>,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "\n════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "  LOADED LATENCY")?;
+    writeln!(
+        out,
+        "════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "  Idle latency:    {:.1} ns/access", result.idle_ns)?;
+    writeln!(
+        out,
+        "  Loaded latency:  {:.1} ns/access ({} bandwidth threads)",
+        result.loaded_ns, result.bandwidth_threads
+    )?;
+    writeln!(
+        out,
+        "  Bandwidth:       {} aggregate",
+        reporting::format_rate_bytes(result.bandwidth_gb_s * 1e9)
+    )?;
+    writeln!(out, "  Degradation:     {:.2}x", result.degradation())?;
+    if let Some(rounding) = buffer_rounding {
+        writeln!(
+            out,
+            "  Buffer:          {}",
+            reporting::describe_buffer_rounding(rounding)
+        )?;
+    }
+    writeln!(
+        out,
+        "════════════════════════════════════════════════════════════"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ns_per_access() {
+        assert_eq!(ns_per_access(1_000, 10), 100.0);
+        assert_eq!(ns_per_access(1_000, 0), 0.0);
+    }
+
+    #[test]
+    fn test_degradation() {
+        let result = LoadedLatencyResult {
+            idle_ns:           80.0,
+            loaded_ns:         120.0,
+            bandwidth_threads: 3,
+            bandwidth_gb_s:    10.0,
+        };
+        assert!((result.degradation() - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_timed_latency_worker_records_time() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let accesses = Arc::new(AtomicU64::new(0));
+        let ns = Arc::new(AtomicU64::new(0));
+
+        let (s, a, n) = (Arc::clone(&stop), Arc::clone(&accesses), Arc::clone(&ns));
+        let events = EventLog::new().sender(0);
+        let handle = thread::spawn(move || timed_latency_worker(s, a, n, 10_000, 1, events));
+
+        thread::sleep(Duration::from_millis(50));
+        stop.store(true, Ordering::Release);
+        handle.join().unwrap();
+
+        assert!(accesses.load(Ordering::Relaxed) > 0);
+        assert!(ns.load(Ordering::Relaxed) > 0);
+    }
+}
@@ -1,244 +1,2235 @@
+mod affinity;
+mod assignment;
 mod benchmark;
 mod cli;
+mod compare;
+mod config;
+mod dram;
+mod error;
+mod events;
+mod info;
+mod latency;
+mod notify;
+mod numa;
+mod output;
+mod plan;
+mod reference;
+mod registry;
 mod reporting;
+mod run;
+mod signal;
+mod swap;
 mod system;
+mod utilization;
 mod worker;
 mod workload;
 
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::thread;
+use std::sync::atomic::AtomicBool;
 use std::time::{Duration, Instant};
 
-use benchmark::{display_benchmark_table, run_single_workload};
-use clap::Parser;
-use cli::{Args, print_help, print_version};
-use reporting::format_number;
+use affinity::SmtPolicy;
+use assignment::WorkloadGroup;
+use benchmark::{BenchmarkConfig, run_single_workload};
+use cli::{Args, Invocation, print_help, print_version};
+use error::{LocusError, Outcome};
+use events::EventLog;
+use numa::NumaPolicy;
+use output::{
+    BufferRounding,
+    Format,
+    GroupReport,
+    ProgressFormat,
+    SingleReport,
+    Sink,
+    WorkerReport,
+    notice,
+};
+use registry::{WorkingSet, WorkloadSpec};
+use reporting::{
+    ProgressOptions,
+    format_bytes,
+    format_duration,
+    format_mb,
+    format_number,
+    format_rate_bytes,
+};
+use run::{RunConfig, RunStats, StopReason};
+use swap::SwapMonitor;
+use worker::WorkerTimes;
+use workload::{FloatMode, InitPattern, KernelParams};
 
 fn main() {
     let args_vec: Vec<String> = std::env::args().collect();
 
-    if args_vec.len() > 1 {
-        match args_vec[1].as_str() {
-            "--help" | "-h" => {
-                print_help();
-                return;
-            },
-            "--version" | "-V" => {
-                print_version();
-                return;
-            },
-            _ => {},
-        }
+    if let Some(topic) = cli::help_topic(&args_vec) {
+        print_help(topic);
+        return;
+    }
+    if let Some(verbose) = cli::version_request(&args_vec) {
+        print_version(verbose);
+        return;
+    }
+
+    let args = match cli::parse() {
+        Invocation::Stress(args) => *args,
+        Invocation::Info => {
+            info::print_info();
+            return;
+        },
+        Invocation::Compare(files) => {
+            exit_with(run_compare(&files).map(|()| Outcome::default()));
+            return;
+        },
+    };
+
+    if args.list_workloads {
+        registry::print_workload_list();
+        return;
     }
 
-    let args = Args::parse();
+    if args.dump_config {
+        print!("{}", args.to_toml());
+        return;
+    }
 
-    let global_stop = Arc::new(AtomicBool::new(false));
+    let started = Instant::now();
+    let result = run_stress(&args);
+    if let Some(url) = &args.notify_url
+        && !args.dry_run
     {
-        let gs = Arc::clone(&global_stop);
-        if let Err(e) = ctrlc::set_handler(move || {
-            gs.store(true, Ordering::Release);
-        }) {
-            eprintln!("Warning: Failed to set global Ctrl+C handler: {}", e);
-        }
+        let notification = notify::notification(
+            &result,
+            started.elapsed(),
+            signal::interrupted(),
+            &output::run_metadata(),
+        );
+        notify::deliver(url, &notification);
+    }
+    exit_with(result);
+}
+
+/// Prints why a run failed, or missed its threshold, and exits with the
+/// matching code; a clean run just returns.
+fn exit_with(result: Result<Outcome, LocusError>) {
+    let code = match result {
+        Ok(outcome) => {
+            if let Some(message) = &outcome.below_threshold {
+                eprintln!("Error: {}", message);
+            }
+            outcome.exit_code()
+        },
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        },
+    };
+    if code != 0 {
+        std::process::exit(code);
+    }
+}
+
+fn run_stress(args: &Args) -> Result<Outcome, LocusError> {
+    reporting::set_exact_numbers(args.exact_numbers);
+    signal::install_handler();
+    check_format(args)?;
+
+    // Opened up front so a bad --out path fails before the run, not after
+    let sink = match &args.out {
+        None if args.silent => Ok(Sink::discard()),
+        out => Sink::open(out.as_deref()),
+    };
+    let sink = sink.map_err(|source| LocusError::OutputUnavailable {
+        target: args.out.as_deref().unwrap_or(Path::new("stdout")).display().to_string(),
+        source,
+    })?;
+
+    numa::configure(args.numa);
+    if args.affinity_reverse && !args.affinity {
+        notice!("[!] --affinity-reverse has no effect without --affinity");
+    }
+    if args.smt.is_some() && !args.affinity {
+        notice!("[!] --smt has no effect without --affinity");
+    }
+    // Pins the JSON timestamp to the start of the run
+    output::capture_run_metadata(args.to_toml());
+
+    // Both sides name their own thread counts
+    if let Some(pair) = &args.interference {
+        return run_interference_mode(args, pair, sink);
     }
 
     let num_threads = if args.threads == 0 {
-        num_cpus::get()
+        detect_thread_count()
     } else {
         args.threads
     };
+    if args.verify_utilization
+        && !(args.affinity || args.loaded_latency)
+        && num_threads < system::available_cpus().len()
+    {
+        notice!("[!] --verify-utilization needs --affinity or a worker on every CPU; skipping");
+    }
+
+    if args.loaded_latency {
+        run_loaded_latency_mode(args, num_threads, sink)
+    } else if args.benchmark {
+        check_bench_options(args)?;
+        let memory_mb = resolve_memory_mb(args, num_threads)?;
+        if args.bandwidth_scaling {
+            run_bandwidth_scaling_mode(args, num_threads, memory_mb, sink)
+        } else {
+            run_benchmark_mode(args, num_threads, memory_mb, sink)
+        }
+    } else {
+        check_run_options(args)?;
+        let groups = resolve_groups(args, num_threads)?;
+        let buffered_threads = groups
+            .iter()
+            .filter(|g| g.spec.needs_buffer)
+            .map(|g| g.threads)
+            .sum();
+
+        let memory_mb = if buffered_threads > 0 {
+            resolve_memory_mb(args, buffered_threads)?
+        } else {
+            0
+        };
+        run_single_mode(args, &groups, memory_mb, sink)
+    }
+}
+
+/// JUnit testcases are benchmark workloads; nothing else has them.
+fn check_format(args: &Args) -> Result<(), LocusError> {
+    if args.format != Format::Junit {
+        return Ok(());
+    }
+    let conflict = if !args.benchmark || args.interference.is_some() || args.loaded_latency {
+        "--format junit reports benchmark workloads; use it with bench"
+    } else if args.bandwidth_scaling {
+        "--bandwidth-scaling reports a knee, not testcases; use --format text or json"
+    } else if args.dry_run {
+        "--dry-run measures nothing to report as testcases; use --format text or json"
+    } else {
+        return Ok(());
+    };
+    Err(LocusError::ConflictingOptions(conflict.to_string()))
+}
 
-    let memory_mb = if args.memory_mb == 0 {
-        system::detect_memory_size(args.memory_multiplier)
+fn check_bench_options(args: &Args) -> Result<(), LocusError> {
+    let conflict = if args.split.is_some() {
+        "--split cannot be combined with --benchmark"
+    } else if args.min_rate.is_some() {
+        "--min-rate gates a run; use --min-score with --benchmark"
+    } else if args.ops.is_some() {
+        "--ops fixes the work of a run; use --duration with --benchmark"
+    } else if args.once {
+        "--once is a run smoke test; drop --benchmark"
     } else {
-        args.memory_mb
+        return Ok(());
     };
+    Err(LocusError::ConflictingOptions(conflict.to_string()))
+}
 
-    if args.benchmark {
-        run_benchmark_mode(&args, num_threads, memory_mb);
+/// `--bandwidth-scaling` replaces the workload set, so the options that
+/// shape the set or score it don't apply.
+fn check_scaling_options(args: &Args) -> Result<(), LocusError> {
+    let conflict = if args.duration == 0 {
+        "--bandwidth-scaling requires --duration to be set (e.g., locus bench -d 5)"
+    } else if args.adaptive {
+        "--bandwidth-scaling runs each step for --duration; drop --adaptive"
+    } else if args.benchmark_set != benchmark::BenchmarkSet::Default
+        || args.workload_durations.is_some()
+    {
+        "--bandwidth-scaling runs memory-bandwidth only; drop --benchmark-set and \
+         --workload-durations"
+    } else if args.min_score.is_some() {
+        "--bandwidth-scaling reports a knee, not a score; drop --min-score"
+    } else if args.baseline.is_some() {
+        "--bandwidth-scaling reports a knee, not workload rates; drop --baseline"
     } else {
-        run_single_mode(&args, num_threads, memory_mb);
+        return Ok(());
+    };
+    Err(LocusError::ConflictingOptions(conflict.to_string()))
+}
+
+fn check_run_options(args: &Args) -> Result<(), LocusError> {
+    let conflict = if args.min_score.is_some() {
+        "--min-score gates a benchmark; use --min-rate for a run"
+    } else if args.ops.is_some() && args.duration > 0 {
+        "--ops and --duration are mutually exclusive; pick one to stop on"
+    } else if args.once && (args.ops.is_some() || args.duration > 0) {
+        "--once stops after one batch; drop --duration/--ops"
+    } else if args.ops.is_some() && args.stagger > 0 {
+        "--ops counts from a synchronized start; drop --stagger"
+    } else if args.log_status > 0 && args.progress_format == ProgressFormat::Jsonl {
+        "--log-status prints text blocks into the JSON lines; drop one of them"
+    } else {
+        return Ok(());
+    };
+    Err(LocusError::ConflictingOptions(conflict.to_string()))
+}
+
+/// One worker per CPU this process is allowed to run on.
+fn detect_thread_count() -> usize {
+    let allowed = system::available_cpus().len();
+    let online = system::online_cpu_count();
+
+    if allowed < online {
+        notice!(
+            "[Auto-detect] Restricted to {} CPUs by affinity mask ({} online)",
+            allowed,
+            online
+        );
+    }
+    allowed
+}
+
+/// Per-thread buffer size for the threads that actually allocate one.
+fn resolve_memory_mb(args: &Args, buffered_threads: usize) -> Result<usize, LocusError> {
+    if args.memory_mb == 0 {
+        let limits = system::MemoryLimits {
+            ram_safety:   args.ram_safety,
+            ram_cap:      !args.no_ram_cap,
+            max_total_mb: args.max_total_memory,
+        };
+        let sizing = system::detect_memory_size(
+            args.memory_multiplier,
+            buffered_threads,
+            &limits,
+            system::info(),
+        );
+        for note in sizing.notes() {
+            notice!("{}", note);
+        }
+        // Every heuristic floors at 1 MB, but a bad detection shouldn't
+        // turn into workers that measure nothing
+        if sizing.size_mb == 0 {
+            return Err(LocusError::ZeroMemory);
+        }
+        return Ok(sizing.size_mb);
+    }
+
+    // In u64 and checked: -m takes any usize, and the product can wrap
+    let total_mb = (args.memory_mb as u64).checked_mul(buffered_threads as u64);
+    if let Some(max_total_mb) = args.max_total_memory
+        && total_mb.is_none_or(|total| total > max_total_mb as u64)
+    {
+        return Err(LocusError::OverMemoryLimit {
+            memory_mb: args.memory_mb,
+            threads: buffered_threads,
+            max_total_mb,
+        });
+    }
+    Ok(args.memory_mb)
+}
+
+/// The per-thread buffer the workers get: `memory_mb` rounded down to a
+/// power of two so the memory kernels can index with a mask, or as given
+/// with --exact-buffer.
+fn mask_buffer_mb(args: &Args, memory_mb: usize) -> usize {
+    if args.exact_buffer {
+        return memory_mb;
+    }
+    let rounded = workload::mask_sized_mb(memory_mb);
+    if rounded != memory_mb {
+        notice!(
+            "[Buffer] {} per thread → {} for mask indexing (--exact-buffer keeps it)",
+            format_mb(memory_mb),
+            format_mb(rounded)
+        );
+    }
+    rounded
+}
+
+fn resolve_groups(args: &Args, num_threads: usize) -> Result<Vec<WorkloadGroup>, LocusError> {
+    match &args.split {
+        Some(split) => assignment::parse_split(split)
+            .and_then(|entries| assignment::resolve_split(&entries, num_threads))
+            .map_err(|reason| LocusError::InvalidList {
+                option: "--split",
+                reason,
+            }),
+        None => {
+            let spec = registry::find(&args.workload)
+                .ok_or_else(|| LocusError::InvalidWorkload(args.workload.clone()))?;
+            Ok(vec![WorkloadGroup {
+                spec,
+                threads: num_threads,
+            }])
+        },
+    }
+}
+
+/// Reads saved results only; no workers, no detection.
+fn run_compare(files: &[PathBuf]) -> Result<(), LocusError> {
+    let columns = compare::load_all(files).map_err(LocusError::Compare)?;
+    compare::write_comparison(&mut io::stdout(), &columns)
+        .map_err(|e| LocusError::Compare(format!("failed to write comparison: {}", e)))
+}
+
+/// The workloads `bench` will run, and the --workload-durations overrides.
+fn bench_plan(
+    args: &Args,
+    num_threads: usize,
+) -> Result<(Vec<WorkloadGroup>, HashMap<&'static str, u64>), LocusError> {
+    let overrides = match &args.workload_durations {
+        Some(list) => benchmark::parse_workload_durations(list).map_err(|reason| {
+            LocusError::InvalidList {
+                option: "--workload-durations",
+                reason,
+            }
+        })?,
+        None => HashMap::new(),
+    };
+
+    let specs = args.benchmark_set.specs();
+    if let Some(name) = overrides.keys().find(|name| !specs.iter().any(|s| s.name == **name)) {
+        return Err(LocusError::WorkloadNotInSet {
+            workload: name.to_string(),
+            set:      args.benchmark_set.as_str(),
+        });
+    }
+    let groups = benchmark::benchmark_groups(&specs, num_threads)?;
+    let duration_secs = args.bench_duration_secs();
+    if groups
+        .iter()
+        .any(|group| overrides.get(group.spec.name).copied().unwrap_or(duration_secs) == 0)
+    {
+        return Err(LocusError::BenchmarkNeedsDuration {
+            overrides: !overrides.is_empty(),
+        });
+    }
+    Ok((groups, overrides))
+}
+
+fn run_benchmark_mode(
+    args: &Args,
+    num_threads: usize,
+    memory_mb: usize,
+    mut sink: Sink,
+) -> Result<Outcome, LocusError> {
+    let (groups, overrides) = bench_plan(args, num_threads)?;
+    // Read before anything runs, so a bad path doesn't cost the whole bench
+    let baseline = match &args.baseline {
+        Some(path) => Some(benchmark::Baseline {
+            saved:    compare::load(path).map_err(LocusError::Baseline)?,
+            fail_pct: args.fail_threshold,
+        }),
+        None => None,
+    };
+    let requested_mb = memory_mb;
+    let memory_mb = mask_buffer_mb(args, requested_mb);
+    let buffer_rounding = BufferRounding::between(requested_mb, memory_mb);
+    let duration_secs = args.bench_duration_secs();
+    let duration_of =
+        |spec: &WorkloadSpec| overrides.get(spec.name).copied().unwrap_or(duration_secs);
+
+    if args.show_banner() {
+        print_benchmark_header(args, &groups, num_threads, memory_mb, &overrides);
+    }
+    if args.dry_run {
+        return finish_dry_run(args, plan::Mode::Benchmark, &groups, memory_mb, sink);
+    }
+    confirm_allocation(args, plan::Mode::Benchmark, &groups, memory_mb)?;
+    if !(args.ignore_background_load || args.silent) {
+        warn_on_background_load();
+    }
+
+    let config = BenchmarkConfig {
+        num_threads,
+        memory_mb,
+        batch_size: args.batch_size,
+        duration_secs,
+        quiet: args.progress_quiet(),
+        pin_threads: args.pinning(),
+        stagger_ms: args.stagger,
+        stack_size: args.stack_size_bytes(),
+        warmup: args.bench_warmup_duration(duration_secs),
+        chains: args.chains,
+        float_mode: args.float_mode,
+        prefetch: args.prefetch,
+        init_pattern: args.init_pattern,
+        access: args.access_pattern(),
+        sweep_bytes: cache_sweep(args).bytes as usize,
+        adaptive: args.adaptive,
+        best_effort: args.best_effort_memory,
+        verify_cores: args.verify_utilization,
+    };
+    let mut results = Vec::new();
+    let mut events = EventLog::new();
+    let mut swap = SwapMonitor::start(args.abort_on_swap);
+
+    let planned: Vec<Duration> = groups
+        .iter()
+        .map(|group| {
+            let secs = duration_of(group.spec);
+            Duration::from_secs(secs) + args.bench_warmup_duration(secs)
+        })
+        .collect();
+    let (mut taken, mut setups) = (Vec::new(), Vec::new());
+
+    for group in &groups {
+        let duration_secs = duration_of(group.spec);
+        let config = BenchmarkConfig {
+            duration_secs,
+            warmup: args.bench_warmup_duration(duration_secs),
+            ..config
+        };
+        let suite = benchmark::suite_progress(&planned, &taken, &setups);
+        let started = Instant::now();
+        let result = run_single_workload(group.spec, &config, suite, &mut events, &mut swap);
+        taken.push(started.elapsed());
+        setups.push(result.setup);
+        results.push(result);
+
+        if signal::interrupted() {
+            if args.show_banner() {
+                eprintln!("\n[!] Interrupted. Skipping remaining workloads.");
+            }
+            break;
+        }
+        if events.has_fatal() {
+            eprintln!("Error: a worker failed. Skipping remaining workloads.");
+            break;
+        }
+        if swap.aborted() {
+            eprintln!("Error: skipping remaining workloads.");
+            break;
+        }
     }
+
+    let swap_pages = swap.finish();
+    let score_miss = match benchmark::composite_score(&results) {
+        Some(score) => {
+            reporting::check_threshold("score", score, "--min-score", args.min_score).err()
+        },
+        None if args.min_score.is_some() => {
+            Some("below threshold: nothing was measured".to_string())
+        },
+        None => None,
+    };
+    let regressions = results
+        .iter()
+        .filter_map(|result| baseline.as_ref()?.check(result).err());
+    let misses: Vec<String> = score_miss.iter().cloned().chain(regressions).collect();
+    let below_threshold = (!misses.is_empty()).then(|| misses.join("\n       "));
+    let reference_kernels = reference::kernels_match(&config.kernel_params());
+    let written = match args.format {
+        Format::Text => benchmark::write_benchmark_table(
+            sink.writer(),
+            &output::run_metadata(),
+            &results,
+            num_threads,
+            swap_pages,
+            buffer_rounding,
+            reference_kernels,
+        ),
+        Format::Json => output::write_json(
+            sink.writer(),
+            &benchmark::benchmark_report(
+                &results,
+                num_threads,
+                duration_secs,
+                swap_pages,
+                buffer_rounding,
+                &events,
+                reference_kernels,
+            ),
+        ),
+        Format::Junit => output::write_junit(
+            sink.writer(),
+            &benchmark::junit_suite(
+                &results,
+                &groups,
+                num_threads,
+                bench_fault(&events, &swap).as_deref(),
+                &benchmark::JunitGates {
+                    min_score:  args.min_score,
+                    score_miss: score_miss.as_deref(),
+                    baseline:   baseline.as_ref(),
+                },
+                reference_kernels,
+            ),
+        ),
+    };
+    sink.finish(written)?;
+    Ok(Outcome {
+        rates: results
+            .iter()
+            .map(|result| (result.name.clone(), result.ops_per_sec))
+            .collect(),
+        ..outcome(&events, &swap, below_threshold)
+    })
 }
 
-fn run_benchmark_mode(args: &Args, num_threads: usize, memory_mb: usize) {
-    if args.duration == 0 {
-        eprintln!("Error: --benchmark requires --duration to be set (e.g., -d 60)");
-        std::process::exit(1);
+/// Why a benchmark stopped short, for the testcase it stopped.
+fn bench_fault(events: &EventLog, swap: &SwapMonitor) -> Option<String> {
+    if let Some(event) = events.reports().into_iter().find(|e| e.kind == "fatal") {
+        Some(format!("{} failed: {}", event.worker, event.message))
+    } else {
+        swap.aborted()
+            .then(|| "stopped for swapping (--abort-on-swap)".to_string())
     }
+}
 
+fn print_benchmark_header(
+    args: &Args,
+    groups: &[WorkloadGroup],
+    num_threads: usize,
+    memory_mb: usize,
+    overrides: &HashMap<&'static str, u64>,
+) {
     println!("════════════════════════════════════════════════════════════");
     println!("    Locus BENCHMARK v{}", env!("CARGO_PKG_VERSION"));
     println!("════════════════════════════════════════════════════════════");
     println!("  Threads:    {}", num_threads);
+    print_virtualization();
+    print_cpu_features();
 
-    if args.memory_mb == 0 {
+    // Workloads run one at a time, so the largest one is the peak
+    let peak_mb = groups
+        .iter()
+        .map(|g| g.total_buffer_mb(memory_mb))
+        .max()
+        .unwrap_or(0);
+    print_memory_buf(args, memory_mb, &format!("{} total", format_mb(peak_mb)));
+    print_working_set(groups, memory_mb);
+    print_numa(args);
+
+    match args.batch_size {
+        Some(batch) => println!("  Batch size: {}", format_number(batch)),
+        None => println!("  Batch size: per-workload default"),
+    }
+    // Every workload runs in turn, memory-latency included
+    print_chains(args, true);
+    print_float_mode(args, true);
+    print_prefetch(args, true);
+    print_init_pattern(args, true);
+    print_access_pattern(args, true);
+    print_sweep(
+        args,
+        groups
+            .iter()
+            .any(|g| g.spec.working_set == WorkingSet::CacheSweep),
+    );
+    let duration_secs = args.bench_duration_secs();
+    let warmup = match args.bench_warmup {
+        None if !overrides.is_empty() => "+ auto warmup each".to_string(),
+        _ => format!(
+            "+{:.1}s unmeasured warmup",
+            args.bench_warmup_duration(duration_secs).as_secs_f64()
+        ),
+    };
+    if args.adaptive {
         println!(
-            "  Memory buf: {} MB per thread ({}x multiplier)",
-            memory_mb, args.memory_multiplier
+            "  Duration:   until the rate settles, at most {}s per workload ({})",
+            duration_secs, warmup
         );
+    } else if duration_secs > 0 {
+        println!("  Duration:   {}s per workload ({})", duration_secs, warmup);
     } else {
-        println!("  Memory buf: {} MB per thread (manual)", memory_mb);
+        println!("  Duration:   set per workload ({})", warmup);
     }
-
-    println!("  Batch size: {}", format_number(args.batch_size));
-    println!("  Duration:   {}s per workload", args.duration);
-    println!("  Total time: ~{}s (5 workloads)", args.duration * 5);
+    if !overrides.is_empty() {
+        // Registry order, not the order they were typed in
+        let listed: Vec<String> = groups
+            .iter()
+            .filter_map(|g| Some(format!("{} {}s", g.spec.name, overrides.get(g.spec.name)?)))
+            .collect();
+        println!("  Overrides:  {}", listed.join(", "));
+    }
+    if let Some(cores) = pinned_worker_cpus(args, num_threads, &system::available_cpus()) {
+        print_affinity(&cores, args.smt);
+    }
+    if args.stagger > 0 {
+        print_stagger(args.stagger, num_threads);
+    }
+    let total_secs: u64 = groups
+        .iter()
+        .map(|g| overrides.get(g.spec.name).copied().unwrap_or(duration_secs))
+        .sum();
+    println!(
+        "  Total time: {}~{}s ({} workloads)",
+        if args.adaptive { "at most " } else { "" },
+        total_secs,
+        groups.len()
+    );
     println!("════════════════════════════════════════════════════════════");
+}
+
+fn check_loaded_latency_options(args: &Args, num_threads: usize) -> Result<(), LocusError> {
+    let conflict = if args.min_rate.is_some() || args.min_score.is_some() {
+        "--loaded-latency reports latency, not a rate; drop --min-rate/--min-score"
+    } else if args.ops.is_some() || args.once {
+        "--loaded-latency runs for --duration; drop --ops/--once"
+    } else if args.duration == 0 {
+        "--loaded-latency requires --duration to be set (e.g., -d 20)"
+    } else if args.benchmark || args.split.is_some() {
+        "--loaded-latency cannot be combined with --benchmark or --split"
+    } else if num_threads < 2 {
+        "--loaded-latency needs at least 2 threads"
+    } else if args.best_effort_memory {
+        "--loaded-latency compares one buffer size idle and loaded; drop --best-effort-memory"
+    } else if args.verify_utilization {
+        "--loaded-latency runs its own workers unsampled; drop --verify-utilization"
+    } else if args.prefetch > 0 {
+        "--loaded-latency loads memory with the plain bandwidth kernel; drop --prefetch"
+    } else if args.init_pattern != InitPattern::default() {
+        "--loaded-latency times the value-derived chase only; drop --init-pattern"
+    } else if !args.access_pattern().is_default() {
+        "--loaded-latency times the built-in access pattern; drop --lcg-mult/--lcg-add/--stride"
+    } else {
+        return Ok(());
+    };
+    Err(LocusError::ConflictingOptions(conflict.to_string()))
+}
+
+fn run_loaded_latency_mode(
+    args: &Args,
+    num_threads: usize,
+    mut sink: Sink,
+) -> Result<Outcome, LocusError> {
+    check_loaded_latency_options(args, num_threads)?;
+    let requested_mb = resolve_memory_mb(args, num_threads)?;
+    let memory_mb = mask_buffer_mb(args, requested_mb);
+    let buffer_rounding = BufferRounding::between(requested_mb, memory_mb);
 
-    let workloads = [
-        "integer",
-        "float",
-        "mixed",
-        "memory-latency",
-        "memory-bandwidth",
+    let [latency, bandwidth] = ["memory-latency", "memory-bandwidth"]
+        .map(|name| registry::find(name).expect("memory workloads are registered"));
+    let groups = [
+        WorkloadGroup {
+            spec:    latency,
+            threads: 1,
+        },
+        WorkloadGroup {
+            spec:    bandwidth,
+            threads: num_threads - 1,
+        },
     ];
-    let mut results = Vec::new();
+    if args.show_banner() {
+        print_loaded_latency_header(args, &groups, memory_mb);
+    }
+    if args.dry_run {
+        return finish_dry_run(args, plan::Mode::LoadedLatency, &groups, memory_mb, sink);
+    }
+    confirm_allocation(args, plan::Mode::LoadedLatency, &groups, memory_mb)?;
+
+    let mut events = EventLog::new();
+    let result = latency::run_loaded_latency(
+        num_threads,
+        memory_mb,
+        args.batch_size,
+        args.duration,
+        args.progress_quiet(),
+        args.stack_size_bytes(),
+        &mut events,
+    );
+
+    let written = match args.format {
+        Format::Text => latency::write_loaded_latency(sink.writer(), &result, buffer_rounding),
+        Format::Json => output::write_json(
+            sink.writer(),
+            &latency::loaded_latency_report(&result, buffer_rounding, &events),
+        ),
+        Format::Junit => unreachable!("--format junit is checked to be bench-only"),
+    };
+    sink.finish(written)?;
+    Ok(Outcome {
+        faulted: events.has_fatal(),
+        errors:  events.fatal_count(),
+        ..Outcome::default()
+    })
+}
+
+fn print_loaded_latency_header(args: &Args, groups: &[WorkloadGroup], memory_mb: usize) {
+    let num_threads: usize = groups.iter().map(|g| g.threads).sum();
+    println!("════════════════════════════════════════════════════════════");
+    println!("    Locus LOADED LATENCY v{}", env!("CARGO_PKG_VERSION"));
+    println!("════════════════════════════════════════════════════════════");
+    println!(
+        "  Threads:    1 latency + {} memory-bandwidth",
+        num_threads - 1
+    );
+    print_virtualization();
+    print_cpu_features();
+    let total_mb = groups
+        .iter()
+        .map(|g| g.total_buffer_mb(memory_mb))
+        .fold(0, usize::saturating_add);
+    print_memory_buf(args, memory_mb, &format!("{} total", format_mb(total_mb)));
+    print_working_set(groups, memory_mb);
+    print_numa(args);
+    match args.batch_size {
+        Some(batch) => println!("  Batch size: {}", format_number(batch)),
+        None => println!("  Batch size: per-workload default"),
+    }
+    println!(
+        "  Duration:   {}s idle + {}s loaded",
+        (args.duration / 5).max(1),
+        args.duration
+    );
+    println!("════════════════════════════════════════════════════════════");
+}
 
-    for workload in &workloads {
-        let result = run_single_workload(
-            workload,
+/// The pair `--interference` names, once the options around it agree.
+fn interference_pair(args: &Args, pair: &str) -> Result<[WorkloadGroup; 2], LocusError> {
+    let conflict = if args.min_rate.is_some() || args.min_score.is_some() {
+        "--interference reports slowdown; drop --min-rate/--min-score"
+    } else if args.ops.is_some() || args.once {
+        "--interference runs for --duration; drop --ops/--once"
+    } else if args.duration == 0 {
+        "--interference requires --duration to be set (e.g., -d 20)"
+    } else if args.benchmark || args.split.is_some() || args.loaded_latency {
+        "--interference runs its own pair; drop --benchmark/--split/--loaded-latency"
+    } else {
+        ""
+    };
+    if !conflict.is_empty() {
+        return Err(LocusError::ConflictingOptions(conflict.to_string()));
+    }
+
+    let pair = benchmark::parse_interference(pair).map_err(|reason| LocusError::InvalidList {
+        option: "--interference",
+        reason,
+    })?;
+    let num_threads: usize = pair.iter().map(|g| g.threads).sum();
+    if args.threads != 0 && args.threads != num_threads {
+        return Err(LocusError::ConflictingOptions(format!(
+            "--interference runs {} threads ({}); drop -j or make it match",
             num_threads,
-            memory_mb,
-            args.batch_size,
+            assignment::describe(&pair)
+        )));
+    }
+    Ok(pair)
+}
+
+fn run_interference_mode(
+    args: &Args,
+    pair: &str,
+    mut sink: Sink,
+) -> Result<Outcome, LocusError> {
+    let pair = interference_pair(args, pair)?;
+    let num_threads: usize = pair.iter().map(|g| g.threads).sum();
+
+    let buffered_threads = pair
+        .iter()
+        .filter(|g| g.spec.needs_buffer)
+        .map(|g| g.threads)
+        .sum();
+    let (memory_mb, buffer_rounding) = if buffered_threads > 0 {
+        let requested_mb = resolve_memory_mb(args, buffered_threads)?;
+        let memory_mb = mask_buffer_mb(args, requested_mb);
+        (memory_mb, BufferRounding::between(requested_mb, memory_mb))
+    } else {
+        (0, None)
+    };
+    let solo_secs = benchmark::interference_solo_secs(args.duration);
+    if args.show_banner() {
+        print_interference_header(args, &pair, memory_mb, solo_secs);
+    }
+    if args.dry_run {
+        return finish_dry_run(args, plan::Mode::Interference, &pair, memory_mb, sink);
+    }
+    confirm_allocation(args, plan::Mode::Interference, &pair, memory_mb)?;
+
+    let config = BenchmarkConfig {
+        num_threads,
+        memory_mb,
+        batch_size: args.batch_size,
+        duration_secs: args.duration,
+        quiet: args.progress_quiet(),
+        pin_threads: args.pinning(),
+        stagger_ms: args.stagger,
+        stack_size: args.stack_size_bytes(),
+        warmup: benchmark::auto_warmup(solo_secs),
+        chains: args.chains,
+        float_mode: args.float_mode,
+        prefetch: args.prefetch,
+        init_pattern: args.init_pattern,
+        access: args.access_pattern(),
+        sweep_bytes: cache_sweep(args).bytes as usize,
+        adaptive: false,
+        best_effort: args.best_effort_memory,
+        verify_cores: args.verify_utilization,
+    };
+    let mut events = EventLog::new();
+    let mut swap = SwapMonitor::start(args.abort_on_swap);
+    let results =
+        benchmark::run_interference(&pair, &config, solo_secs, &mut events, &mut swap);
+
+    if signal::interrupted() {
+        if args.show_banner() {
+            eprintln!("\n[!] Interrupted. Reporting the phases that ran.");
+        }
+    } else if events.has_fatal() {
+        eprintln!("Error: a worker failed. Skipping the phases left.");
+    }
+
+    let swap_pages = swap.finish();
+    let written = match args.format {
+        Format::Text => benchmark::write_interference_table(
+            sink.writer(),
+            &output::run_metadata(),
+            &results,
+            solo_secs,
+            args.duration,
+            swap_pages,
+            buffer_rounding,
+        ),
+        Format::Json => output::write_json(
+            sink.writer(),
+            &benchmark::interference_report(
+                &results,
+                solo_secs,
+                args.duration,
+                swap_pages,
+                buffer_rounding,
+                &events,
+            ),
+        ),
+        Format::Junit => unreachable!("--format junit is checked to be bench-only"),
+    };
+    sink.finish(written)?;
+    Ok(outcome(&events, &swap, None))
+}
+
+fn print_interference_header(
+    args: &Args,
+    pair: &[WorkloadGroup],
+    memory_mb: usize,
+    solo_secs: u64,
+) {
+    let num_threads: usize = pair.iter().map(|g| g.threads).sum();
+    println!("════════════════════════════════════════════════════════════");
+    println!("    Locus INTERFERENCE v{}", env!("CARGO_PKG_VERSION"));
+    println!("════════════════════════════════════════════════════════════");
+    println!("  Threads:    {}", num_threads);
+    print_virtualization();
+    print_cpu_features();
+    println!("  Workloads:  {}", assignment::describe(pair));
+    println!(
+        "  Batch size: {}",
+        describe_batches(pair, args.batch_size)
+    );
+    print_chains(args, pair.iter().any(|g| g.spec.name == "memory-latency"));
+    print_float_mode(args, runs_float(pair));
+    print_prefetch(args, pair.iter().any(|g| g.spec.name == "memory-bandwidth"));
+    print_init_pattern(args, pair.iter().any(|g| g.spec.needs_buffer));
+    print_access_pattern(args, runs_memory_kernels(pair));
+    print_paths(args, pair);
+    print_sweep(
+        args,
+        pair.iter()
+            .any(|g| g.spec.working_set == WorkingSet::CacheSweep),
+    );
+    if memory_mb > 0 {
+        // The contended phase holds both sides' buffers at once
+        let total_mb = pair
+            .iter()
+            .map(|g| g.total_buffer_mb(memory_mb))
+            .fold(0, usize::saturating_add);
+        print_memory_buf(args, memory_mb, &format!("{} total", format_mb(total_mb)));
+        print_numa(args);
+    } else {
+        println!("  Memory buf: none (compute-only workloads)");
+    }
+    print_working_set(pair, memory_mb);
+    println!(
+        "  Duration:   {}s solo each + {}s contended",
+        solo_secs, args.duration
+    );
+    if let Some(cores) = pinned_worker_cpus(args, num_threads, &system::available_cpus()) {
+        print_affinity(&cores, args.smt);
+    }
+    if args.stagger > 0 {
+        print_stagger(args.stagger, num_threads);
+    }
+    println!("════════════════════════════════════════════════════════════");
+}
+
+fn run_bandwidth_scaling_mode(
+    args: &Args,
+    num_threads: usize,
+    memory_mb: usize,
+    mut sink: Sink,
+) -> Result<Outcome, LocusError> {
+    check_scaling_options(args)?;
+    let spec = registry::find("memory-bandwidth").expect("memory-bandwidth is registered");
+    // The last step is the widest, so it's the one the plan and the prompt size
+    let groups = [WorkloadGroup {
+        spec,
+        threads: num_threads,
+    }];
+    let requested_mb = memory_mb;
+    let memory_mb = mask_buffer_mb(args, requested_mb);
+    let buffer_rounding = BufferRounding::between(requested_mb, memory_mb);
+
+    if args.show_banner() {
+        print_scaling_header(args, &groups, num_threads, memory_mb);
+    }
+    if args.dry_run {
+        return finish_dry_run(args, plan::Mode::BandwidthScaling, &groups, memory_mb, sink);
+    }
+    confirm_allocation(args, plan::Mode::BandwidthScaling, &groups, memory_mb)?;
+    if !(args.ignore_background_load || args.silent) {
+        warn_on_background_load();
+    }
+
+    let config = BenchmarkConfig {
+        num_threads,
+        memory_mb,
+        batch_size: args.batch_size,
+        duration_secs: args.duration,
+        quiet: args.progress_quiet(),
+        pin_threads: args.pinning(),
+        stagger_ms: args.stagger,
+        stack_size: args.stack_size_bytes(),
+        warmup: args.bench_warmup_duration(args.duration),
+        chains: args.chains,
+        float_mode: args.float_mode,
+        prefetch: args.prefetch,
+        init_pattern: args.init_pattern,
+        access: args.access_pattern(),
+        sweep_bytes: cache_sweep(args).bytes as usize,
+        adaptive: false,
+        best_effort: args.best_effort_memory,
+        verify_cores: args.verify_utilization,
+    };
+    let mut events = EventLog::new();
+    let mut swap = SwapMonitor::start(args.abort_on_swap);
+    let results = benchmark::run_bandwidth_scaling(&config, &mut events, &mut swap);
+
+    if signal::interrupted() {
+        if args.show_banner() {
+            eprintln!("\n[!] Interrupted. Reporting the steps that ran.");
+        }
+    } else if events.has_fatal() {
+        eprintln!("Error: a worker failed. Skipping the steps left.");
+    } else if swap.aborted() {
+        eprintln!("Error: skipping the steps left.");
+    }
+
+    let swap_pages = swap.finish();
+    let written = match args.format {
+        Format::Text => benchmark::write_scaling_table(
+            sink.writer(),
+            &output::run_metadata(),
+            &results,
             args.duration,
-            args.quiet,
+            swap_pages,
+            buffer_rounding,
+        ),
+        Format::Json => output::write_json(
+            sink.writer(),
+            &benchmark::scaling_report(
+                &results,
+                args.duration,
+                swap_pages,
+                buffer_rounding,
+                &events,
+            ),
+        ),
+        Format::Junit => unreachable!("--format junit is checked to be bench-only"),
+    };
+    sink.finish(written)?;
+    Ok(outcome(&events, &swap, None))
+}
+
+fn print_scaling_header(
+    args: &Args,
+    groups: &[WorkloadGroup],
+    num_threads: usize,
+    memory_mb: usize,
+) {
+    let steps: Vec<String> = benchmark::scaling_thread_counts(num_threads)
+        .iter()
+        .map(|n| n.to_string())
+        .collect();
+    println!("════════════════════════════════════════════════════════════");
+    println!("    Locus BANDWIDTH SCALING v{}", env!("CARGO_PKG_VERSION"));
+    println!("════════════════════════════════════════════════════════════");
+    println!("  Threads:    {}", steps.join(", "));
+    print_virtualization();
+    print_cpu_features();
+    // Steps run one at a time, so the widest one is the peak
+    let peak_mb = groups[0].buffer_mb(memory_mb).saturating_mul(num_threads);
+    print_memory_buf(args, memory_mb, &format!("{} at most", format_mb(peak_mb)));
+    print_numa(args);
+    print_prefetch(args, true);
+    print_init_pattern(args, true);
+    print_access_pattern(args, true);
+    println!(
+        "  Duration:   {}s per step (+{:.1}s unmeasured warmup)",
+        args.duration,
+        args.bench_warmup_duration(args.duration).as_secs_f64()
+    );
+    if let Some(cores) = pinned_worker_cpus(args, num_threads, &system::available_cpus()) {
+        print_affinity(&cores, args.smt);
+    }
+    if args.stagger > 0 {
+        print_stagger(args.stagger, num_threads);
+    }
+    println!("════════════════════════════════════════════════════════════");
+}
+
+fn run_single_mode(
+    args: &Args,
+    groups: &[WorkloadGroup],
+    memory_mb: usize,
+    mut sink: Sink,
+) -> Result<Outcome, LocusError> {
+    let requested_mb = memory_mb;
+    let memory_mb = mask_buffer_mb(args, requested_mb);
+    let num_threads: usize = groups.iter().map(|g| g.threads).sum();
+    let per_thread_mb: Vec<usize> = groups
+        .iter()
+        .flat_map(|g| std::iter::repeat_n(g.buffer_mb(memory_mb), g.threads))
+        .collect();
+
+    let cpus = system::available_cpus();
+    let worker_cpus = pinned_worker_cpus(args, num_threads, &cpus);
+    let reporter_cpu = worker_cpus
+        .as_deref()
+        .and_then(|cores| affinity::reporter_core(cores, &cpus));
+    let watch_cores = args
+        .verify_utilization
+        .then(|| utilization::cores_to_watch(worker_cpus.as_deref(), &cpus, num_threads))
+        .flatten();
+
+    if args.show_banner() {
+        print_single_header(
+            args,
+            groups,
+            memory_mb,
+            &per_thread_mb,
+            worker_cpus.as_deref(),
         );
-        results.push(result);
     }
+    if args.dry_run {
+        return finish_dry_run(args, plan::Mode::Single, groups, memory_mb, sink);
+    }
+    confirm_allocation(args, plan::Mode::Single, groups, memory_mb)?;
 
-    display_benchmark_table(&results, num_threads);
+    let run_config = RunConfig {
+        groups,
+        per_thread_mb: &per_thread_mb,
+        batch_size: args.batch_size,
+        chains: args.chains,
+        float_mode: args.float_mode,
+        prefetch: args.prefetch,
+        init_pattern: args.init_pattern,
+        access: args.access_pattern(),
+        sweep_bytes: cache_sweep(args).bytes as usize,
+        stack_size: args.stack_size_bytes(),
+        stagger: Duration::from_millis(args.stagger),
+        worker_cpus: worker_cpus.as_deref(),
+        reporter_cpu,
+        warmup: Duration::ZERO,
+        duration: (args.duration > 0).then(|| Duration::from_secs(args.duration)),
+        converge: false,
+        ops: args.ops,
+        once: args.once,
+        best_effort: args.best_effort_memory,
+        watch_cores: watch_cores.as_deref(),
+        progress: ProgressOptions {
+            print:              match args.progress_format {
+                // stderr, so JSON on stdout doesn't rule it out
+                ProgressFormat::Jsonl => !(args.quiet || args.silent),
+                ProgressFormat::Text => !args.progress_quiet(),
+            }
+            .then_some(args.progress_format),
+            smoothing:          args.rate_smoothing,
+            throttle_alert_pct: args.throttle_alert,
+            indent:             "",
+            log_status:         (args.log_status > 0)
+                .then(|| Duration::from_secs(args.log_status)),
+        },
+    };
+    let mut events = EventLog::new();
+    let mut swap = SwapMonitor::start(args.abort_on_swap);
+    let stats = run::execute_run(
+        &run_config,
+        Arc::new(AtomicBool::new(false)),
+        &mut events,
+        &mut swap,
+        &mut |stop| announce_stop(args, stop),
+    );
+
+    let mut report = build_single_report(
+        groups,
+        &per_thread_mb,
+        &stats,
+        worker_cpus.as_deref(),
+        &events,
+        swap.finish(),
+    );
+    report.buffer_rounding = BufferRounding::between(requested_mb, memory_mb);
+    for group in &report.groups {
+        if let Some(gb) = group.gb_per_sec
+            && gb > reporting::IMPLAUSIBLE_GB_PER_SEC
+        {
+            notice!(
+                "[!] {:.0} GB/s for {} is implausible; treat its bandwidth as suspect",
+                gb,
+                group.workload
+            );
+        }
+    }
+    let written = match args.format {
+        Format::Text => write_final_stats(sink.writer(), &report, args.ascii),
+        Format::Json => output::write_json(sink.writer(), &report),
+        Format::Junit => unreachable!("--format junit is checked to be bench-only"),
+    };
+    sink.finish(written)?;
+    warn_on_contention(&report);
+    warn_on_imbalance(&report);
+    utilization::warn_on_idle_cores(&stats.core_busy);
+    let below_threshold = reporting::check_threshold(
+        "average rate",
+        report.ops_per_sec,
+        "--min-rate",
+        args.min_rate,
+    )
+    .err();
+    let idle = once_idle_groups(args, &report);
+    if !idle.is_empty() {
+        eprintln!(
+            "Error: --once measured no ops for {}; the smoke test failed",
+            idle.join(", ")
+        );
+    }
+    let outcome = outcome(&events, &swap, below_threshold);
+    Ok(Outcome {
+        faulted: outcome.faulted || !idle.is_empty(),
+        rates:   report
+            .groups
+            .iter()
+            .map(|group| (group.workload.clone(), group.ops_per_sec))
+            .collect(),
+        ..outcome
+    })
+}
+
+/// The workloads a --once smoke test saw do no work, which fails it: a
+/// batch that counts nothing (or finished before the window opened, as
+/// under --stagger) proves nothing about the kernel.
+fn once_idle_groups<'a>(args: &Args, report: &'a SingleReport) -> Vec<&'a str> {
+    if !args.once {
+        return Vec::new();
+    }
+    report
+        .groups
+        .iter()
+        .filter(|group| group.total_ops == 0)
+        .map(|group| group.workload.as_str())
+        .collect()
 }
 
-fn run_single_mode(args: &Args, num_threads: usize, memory_mb: usize) {
-    let workload = match args.workload.as_str() {
-        "integer" | "float" | "memory" | "memory-latency" | "memory-bandwidth" | "mixed" => {
-            &args.workload
+fn announce_stop(args: &Args, stop: StopReason) {
+    let message = match stop {
+        StopReason::InterruptedDuringStagger(up) => {
+            format!("[!] Interrupted during staggered start ({} workers up)", up)
         },
-        _ => {
-            eprintln!("Invalid workload '{}'. Using 'mixed'.", args.workload);
-            "mixed"
+        StopReason::Interrupted => "[!] Interrupted. Stopping...".to_string(),
+        StopReason::OpsReached => "[✓] Op count reached. Stopping...".to_string(),
+        StopReason::TimeLimit => "[✓] Time limit reached. Stopping...".to_string(),
+        StopReason::Converged => "[✓] Rate settled. Stopping...".to_string(),
+        StopReason::WorkerFailed => {
+            eprintln!("Error: a worker failed. Stopping...");
+            return;
         },
+        // The swap monitor has said why; --once and outside stops need nothing
+        StopReason::Swapping | StopReason::Once | StopReason::Stopped => return,
     };
+    if args.show_banner() {
+        eprintln!("\n{}", message);
+    }
+}
+
+/// A run that wrote its report; a failed worker or a stop for swapping
+/// still makes it a fault.
+fn outcome(events: &EventLog, swap: &SwapMonitor, below_threshold: Option<String>) -> Outcome {
+    Outcome {
+        faulted: events.has_fatal() || swap.aborted(),
+        below_threshold,
+        errors: events.fatal_count(),
+        rates: Vec::new(),
+    }
+}
+
+fn print_single_header(
+    args: &Args,
+    groups: &[WorkloadGroup],
+    memory_mb: usize,
+    per_thread_mb: &[usize],
+    worker_cpus: Option<&[usize]>,
+) {
+    let num_threads = per_thread_mb.len();
+    let buffered_threads = per_thread_mb.iter().filter(|&&mb| mb > 0).count();
+    let runs_cache_thrash = groups
+        .iter()
+        .any(|g| g.spec.working_set == WorkingSet::CacheSweep);
 
     println!("════════════════════════════════════════════════════════════");
     println!("          Locus v{}", env!("CARGO_PKG_VERSION"));
     println!("════════════════════════════════════════════════════════════");
     println!("  Threads:    {}", num_threads);
-    println!("  Workload:   {}", workload);
-    println!("  Batch size: {}", format_number(args.batch_size));
+    print_virtualization();
+    print_cpu_features();
+    println!("  Workload:   {}", assignment::describe(groups));
+    println!(
+        "  Batch size: {}",
+        describe_batches(groups, args.batch_size)
+    );
+    print_chains(args, groups.iter().any(|g| g.spec.name == "memory-latency"));
+    print_float_mode(args, runs_float(groups));
+    print_prefetch(args, groups.iter().any(|g| g.spec.name == "memory-bandwidth"));
+    print_init_pattern(args, buffered_threads > 0);
+    print_access_pattern(args, runs_memory_kernels(groups));
+    print_paths(args, groups);
+    print_sweep(args, runs_cache_thrash);
+
+    if buffered_threads == 0 && runs_cache_thrash {
+        println!("  Memory buf: none (cache-thrash sizes its own sweep)");
+    } else if buffered_threads == 0 {
+        println!("  Memory buf: none (compute-only workload)");
+    } else {
+        let total = format_mb(system::total_allocation_mb(per_thread_mb));
+        let total = if buffered_threads < num_threads {
+            format!(
+                "{} total across {} buffered threads",
+                total, buffered_threads
+            )
+        } else {
+            format!("{} total", total)
+        };
+        print_memory_buf(args, memory_mb, &total);
+        print_numa(args);
+    }
+    print_working_set(groups, memory_mb);
+
+    println!("  Duration:   {}", match args.ops {
+        _ if args.once => "one batch per worker (--once)".to_string(),
+        Some(ops) => format!("until {} ops", format_number(ops)),
+        None if args.duration == 0 => "unlimited (Ctrl+C to stop)".to_string(),
+        None => format!("{}s", args.duration),
+    });
+    if let Some(cores) = worker_cpus {
+        print_affinity(cores, args.smt);
+    }
+
+    if args.stagger > 0 {
+        print_stagger(args.stagger, num_threads);
+    }
+    // A warning, not part of the record, so it stays off a redirected stdout
+    eprintln!("  WARNING: This will push CPU to ~99-100%. Monitor temperatures!");
+    println!("════════════════════════════════════════════════════════════\n");
+}
+
+fn describe_batches(groups: &[WorkloadGroup], requested: Option<u64>) -> String {
+    match (requested, groups) {
+        (Some(batch), _) => format_number(batch),
+        (None, [group]) => format_number(group.spec.default_batch),
+        (None, _) => groups
+            .iter()
+            .map(|g| format!("{} {}", g.spec.name, format_number(g.spec.default_batch)))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+fn print_stagger(stagger_ms: u64, num_threads: usize) {
+    let ramp_ms = stagger_ms * num_threads.saturating_sub(1) as u64;
+    println!(
+        "  Stagger:    {} ms between workers (~{:.1}s ramp, not timed)",
+        stagger_ms,
+        ramp_ms as f64 / 1000.0
+    );
+}
+
+fn finish_dry_run(
+    args: &Args,
+    mode: plan::Mode,
+    groups: &[WorkloadGroup],
+    memory_mb: usize,
+    mut sink: Sink,
+) -> Result<Outcome, LocusError> {
+    let mut plan = plan::build_plan(
+        mode,
+        groups,
+        memory_mb,
+        args.batch_size,
+        match mode {
+            plan::Mode::Benchmark => args.bench_duration_secs(),
+            _ => args.duration,
+        },
+        numa::applied().describe(),
+    );
+    plan.warnings = plan::warnings(
+        &plan,
+        system::info().total_ram_mb,
+        system::info().cgroup_limit_mb,
+    );
+    let written = match args.format {
+        Format::Text => plan::write_plan(sink.writer(), &plan),
+        Format::Json => output::write_json(sink.writer(), &plan),
+        Format::Junit => unreachable!("--format junit is checked to be bench-only"),
+    };
+    sink.finish(written)?;
+    Ok(Outcome::default())
+}
 
+/// Asks before a run whose buffers would crowd out the rest of the system.
+/// Non-interactive runs carry on, but say so loudly.
+fn confirm_allocation(
+    args: &Args,
+    mode: plan::Mode,
+    groups: &[WorkloadGroup],
+    memory_mb: usize,
+) -> Result<(), LocusError> {
+    let total_mb =
+        plan::build_plan(mode, groups, memory_mb, None, 0, String::new()).peak_memory_mb;
+    let Some(reason) = system::large_allocation(
+        total_mb,
+        system::info().total_ram_mb,
+        system::info().cgroup_limit_mb,
+    ) else {
+        return Ok(());
+    };
+
+    if args.silent && !args.yes {
+        return Err(LocusError::AllocationNeedsYes { total_mb, reason });
+    }
+    notice!("[Warning] Planned allocation of {} MB {}", total_mb, reason);
+    for group in groups.iter().filter(|g| g.spec.needs_buffer) {
+        notice!(
+            "[Warning]   {} × {}: {} MB each",
+            group.spec.name,
+            group.threads,
+            group.buffer_mb(memory_mb)
+        );
+    }
+    if args.yes {
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        eprintln!(
+            "[Warning] stdin is not a terminal, continuing without confirmation (--yes hides this)"
+        );
+        return Ok(());
+    }
+
+    eprint!("Continue? [y/N] ");
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    let confirmed = io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes");
+    if confirmed {
+        Ok(())
+    } else {
+        Err(LocusError::AllocationDeclined)
+    }
+}
+
+/// Samples the CPUs the benchmark may use for a second before it starts;
+/// whatever else is running there is taken out of the results.
+fn warn_on_background_load() {
+    let cpus = system::available_cpus();
+    let load = utilization::background_load(&cpus, utilization::BACKGROUND_SAMPLE);
+    if let Some(warning) = load.and_then(utilization::background_load_warning) {
+        notice!("{}", warning);
+        notice!("         --ignore-background-load skips this check");
+    }
+}
+
+/// VM results aren't comparable to bare metal, so always say which it is.
+fn print_virtualization() {
+    println!(
+        "  Virtualization: {}",
+        system::detect_virtualization().describe()
+    );
+}
+
+/// Which kernels could have taken a vector or crypto path here; a
+/// hypervisor can hide features the host has.
+fn print_cpu_features() {
+    println!(
+        "  CPU features: {}",
+        system::describe_cpu_features(&system::info().cpu_features)
+    );
+}
+
+/// Only shown when a policy was asked for, so fallbacks are visible.
+fn print_numa(args: &Args) {
+    if args.numa != NumaPolicy::Local {
+        println!("  NUMA:       {}", numa::applied().describe());
+    }
+}
+
+/// "Memory buf: 192 MiB per thread, 3 GiB total (4x multiplier)", then the
+/// L3 it was sized from when auto-detected.
+fn print_memory_buf(args: &Args, memory_mb: usize, total: &str) {
+    let source = if args.memory_mb == 0 {
+        format!("{}x multiplier", args.memory_multiplier)
+    } else {
+        "manual".to_string()
+    };
+    println!(
+        "  Memory buf: {} per thread, {} ({})",
+        format_mb(memory_mb),
+        total,
+        source
+    );
+    if let Some(dram) = &system::info().dram {
+        println!("  Memory:     {}", dram.describe());
+    }
     if args.memory_mb == 0 {
+        print_l3();
+    }
+}
+
+/// Auto-sized buffers are multiples of one complex's slice, so show both.
+fn print_l3() {
+    let info = system::info();
+    if let Some(l3) = info.l3 {
         println!(
-            "  Memory buf: {} MB per thread ({}x multiplier)",
-            memory_mb, args.memory_multiplier
+            "  L3 cache:   {}, {}-byte lines",
+            l3.describe(),
+            info.cache_line_bytes
         );
-    } else {
-        println!("  Memory buf: {} MB per thread (manual)", memory_mb);
     }
+}
+
+/// What the workers keep hot, which is not always what they allocate.
+fn print_working_set(groups: &[WorkloadGroup], memory_mb: usize) {
+    for (i, line) in assignment::describe_working_set(groups, memory_mb)
+        .iter()
+        .enumerate()
+    {
+        let label = if i == 0 { "Working set:" } else { "" };
+        println!("  {:<12} {}", label, line);
+    }
+}
+
+/// What cache-thrash sweeps for --target, sized from the detected caches.
+fn cache_sweep(args: &Args) -> system::CacheSweep {
+    system::CacheSweep::new(system::info(), args.target)
+}
+
+/// Only shown when a cache-thrash worker runs.
+fn print_sweep(args: &Args, runs_cache_thrash: bool) {
+    if runs_cache_thrash {
+        println!("  Sweep:      {}", cache_sweep(args).describe());
+    }
+}
 
+/// Only shown when it changes what a memory-latency worker measures.
+fn print_chains(args: &Args, runs_memory_latency: bool) {
+    if args.chains > 1 && runs_memory_latency {
+        println!("  Chains:     {} per memory-latency worker", args.chains);
+    }
+}
+
+/// Whether any group runs the float kernel, on its own or as a third of mixed.
+fn runs_float(groups: &[WorkloadGroup]) -> bool {
+    groups
+        .iter()
+        .any(|g| matches!(g.spec.name, "float" | "mixed"))
+}
+
+/// Only said when it isn't the default, like --chains.
+fn print_float_mode(args: &Args, runs_float: bool) {
+    if args.float_mode != FloatMode::default() && runs_float {
+        println!("  Float mode: {}", args.float_mode.as_str());
+    }
+}
+
+/// Said whenever memory-bandwidth runs, since --prefetch swaps in a kernel
+/// whose loads don't depend on each other: its rate isn't the plain one's
+/// plus the hint.
+fn print_prefetch(args: &Args, runs_memory_bandwidth: bool) {
+    if !runs_memory_bandwidth {
+        return;
+    }
+    match args.prefetch {
+        0 => println!("  Prefetch:   off"),
+        distance if workload::PREFETCH_SUPPORTED => {
+            println!(
+                "  Prefetch:   {} iterations ahead per bandwidth stream (independent loads)",
+                distance
+            )
+        },
+        distance => println!(
+            "  Prefetch:   {} iterations ahead (independent loads, no prefetch instruction \
+             on this target)",
+            distance
+        ),
+    }
+}
+
+fn runs_memory_kernels(groups: &[WorkloadGroup]) -> bool {
+    groups
+        .iter()
+        .any(|g| matches!(g.spec.name, "memory-latency" | "memory-bandwidth"))
+}
+
+/// Only said when something is overridden, since any override can change
+/// how much of the buffer gets touched.
+fn print_access_pattern(args: &Args, runs_memory: bool) {
+    let access = args.access_pattern();
+    if access.is_default() || !runs_memory {
+        return;
+    }
+    let mut parts = Vec::new();
+    if let Some(mult) = access.lcg_mult {
+        parts.push(format!("LCG multiplier {:#x}", mult));
+    }
+    if access.lcg_add != 0 {
+        parts.push(format!("+{} per value", access.lcg_add));
+    }
+    if let Some(words) = access.stride {
+        parts.push(format!("stride {} words", words));
+    }
     println!(
-        "  Duration:   {}",
-        if args.duration == 0 {
-            "unlimited (Ctrl+C to stop)".to_string()
-        } else {
-            format!("{}s", args.duration)
-        }
+        "  Access:     {} (custom; may not cover the whole buffer)",
+        parts.join(", ")
     );
-    println!("  WARNING: This will push CPU to ~99-100%. Monitor temperatures!");
-    println!("════════════════════════════════════════════════════════════\n");
+}
 
-    let stop_signal = Arc::new(AtomicBool::new(false));
-    let work_counter = Arc::new(AtomicU64::new(0));
+/// Only said when it isn't the default, like --chains.
+fn print_init_pattern(args: &Args, runs_buffered: bool) {
+    if args.init_pattern == InitPattern::default() || !runs_buffered {
+        return;
+    }
+    match args.init_pattern {
+        InitPattern::Cycle => {
+            println!("  Init:       cycle (memory-latency chases one permutation)")
+        },
+        pattern => println!("  Init:       {}", pattern.as_str()),
+    }
+}
 
-    let mut handles = Vec::with_capacity(num_threads);
+/// The code path of each workload that has more than one.
+fn print_paths(args: &Args, groups: &[WorkloadGroup]) {
+    let params = KernelParams {
+        seed:       0,
+        chains:     args.chains,
+        float_mode: args.float_mode,
+        prefetch:   args.prefetch,
+        pattern:    args.init_pattern,
+        access:     args.access_pattern(),
+    };
+    for group in groups {
+        if let Some(path) = group.spec.implementation(&params) {
+            println!("  Path:       {}: {}", group.spec.name, path);
+        }
+    }
+}
 
-    for id in 0..num_threads {
-        let stop = Arc::clone(&stop_signal);
-        let counter = Arc::clone(&work_counter);
-        let batch = args.batch_size;
-        let mem_mb = memory_mb;
-        let wl = workload.to_string();
+/// The CPU per worker under --affinity, None when unpinned.
+fn pinned_worker_cpus(args: &Args, num_threads: usize, cpus: &[usize]) -> Option<Vec<usize>> {
+    let pinning = args.pinning()?;
+    let siblings = pinning.smt.and_then(|_| system::detect_smt_siblings());
+    Some(affinity::pinned_cores(
+        num_threads,
+        cpus,
+        siblings.as_deref(),
+        pinning,
+    ))
+}
 
-        let handle = thread::spawn(move || {
-            worker::worker_thread(id, stop, counter, &wl, batch, mem_mb);
-        });
-        handles.push(handle);
+fn print_affinity(worker_cpus: &[usize], smt: Option<SmtPolicy>) {
+    if !system::affinity_supported() {
+        println!("  Affinity:   not supported on this platform (ignored)");
+        return;
     }
 
-    let start = Instant::now();
-    let duration_limit = if args.duration > 0 {
-        Some(Duration::from_secs(args.duration))
-    } else {
-        None
+    let cpus = system::available_cpus();
+    let mut workers = affinity::describe_cores(worker_cpus);
+    // Ranges read bottom-up, so say where worker 0 went when it's the top
+    if let [first, .., last] = worker_cpus
+        && first > last
+    {
+        workers += &format!(" (worker 0 on CPU {}, counting down)", first);
+    }
+    match affinity::reporter_core(worker_cpus, &cpus) {
+        Some(cpu) => println!(
+            "  Affinity:   workers → CPUs {}, reporter → CPU {}",
+            workers, cpu
+        ),
+        None => println!(
+            "  Affinity:   workers → CPUs {}, reporter unpinned (no free CPU)",
+            workers
+        ),
+    }
+    let Some(policy) = smt else {
+        return;
     };
+    match system::detect_smt_siblings() {
+        Some(siblings) => {
+            let shared = siblings
+                .iter()
+                .filter(|core| core.iter().filter(|cpu| worker_cpus.contains(cpu)).count() > 1)
+                .count();
+            println!(
+                "  SMT:        {} ({} of {} physical cores run sibling workers)",
+                policy.as_str(),
+                shared,
+                siblings.len()
+            );
+        },
+        None => println!(
+            "  SMT:        {} requested, siblings unknown here (CPU numbering order)",
+            policy.as_str()
+        ),
+    }
+}
+
+/// Sustained rates below this share of the peak point at throttling.
+const THROTTLE_THRESHOLD: f64 = 0.90;
 
-    if !args.quiet {
-        let report_stop = Arc::clone(&stop_signal);
-        let report_counter = Arc::clone(&work_counter);
+/// Workers below this share of CPU time vs. wall time were likely
+/// descheduled by other processes or a noisy VM neighbor.
+const CONTENTION_THRESHOLD: f64 = 0.90;
 
-        thread::spawn(move || {
-            reporting::progress_reporter(report_stop, report_counter);
+/// Sum of CPU time over sum of wall time, counting only workers that
+/// reported CPU time.
+fn combined_efficiency<'a>(times: impl Iterator<Item = &'a WorkerTimes>) -> Option<f64> {
+    let (cpu, wall) = times
+        .filter_map(|t| Some((t.cpu?, t.wall)))
+        .fold((Duration::ZERO, Duration::ZERO), |(cpu, wall), (c, w)| {
+            (cpu + c, wall + w)
         });
-    }
+    worker::efficiency(cpu, wall)
+}
 
-    loop {
-        thread::sleep(Duration::from_millis(100));
+fn build_single_report(
+    groups: &[WorkloadGroup],
+    per_thread_mb: &[usize],
+    stats: &RunStats,
+    worker_cpus: Option<&[usize]>,
+    events: &EventLog,
+    swap_pages: Option<u64>,
+) -> SingleReport {
+    let RunStats {
+        elapsed,
+        group_ops,
+        worker_times,
+        rate_samples,
+        process_cpu,
+        ..
+    } = stats;
+    let elapsed = *elapsed;
+    let total_ops = stats.total_ops();
+    let core_classes = system::detect_core_classes();
 
-        if stop_signal.load(Ordering::Relaxed) {
-            break;
+    SingleReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        metadata: output::run_metadata(),
+        build: output::build_info(),
+        virtualization: system::detect_virtualization().describe(),
+        threads: groups.iter().map(|g| g.threads).sum(),
+        elapsed_secs: elapsed.as_secs_f64(),
+        total_ops,
+        ops_per_sec: stats.ops_per_sec(),
+        peak_ops_per_sec: reporting::peak_rate(rate_samples),
+        sustained_ops_per_sec: reporting::sustained_rate(rate_samples),
+        rate_percentiles: reporting::rate_percentiles(rate_samples),
+        rate_histogram: reporting::rate_histogram(rate_samples, reporting::HISTOGRAM_BINS),
+        cpu_efficiency: combined_efficiency(worker_times.iter().map(|(_, t)| t)),
+        groups: groups
+            .iter()
+            .zip(group_ops)
+            .enumerate()
+            .map(|(index, (group, &ops))| GroupReport {
+                workload:       group.spec.name.to_string(),
+                threads:        group.threads,
+                total_ops:      ops,
+                ops_per_sec:    run::rate_per_sec(ops, elapsed),
+                gb_per_sec:     group
+                    .spec
+                    .bytes_per_op
+                    .map(|bytes_per_op| reporting::gb_per_sec(ops, bytes_per_op, elapsed)),
+                cpu_efficiency: combined_efficiency(
+                    worker_times
+                        .iter()
+                        .filter(|(group_index, _)| *group_index == index)
+                        .map(|(_, t)| t),
+                ),
+            })
+            .collect(),
+        workers: worker_times
+            .iter()
+            .enumerate()
+            .map(|(id, (group_index, times))| {
+                let cpu = worker_cpus.and_then(|cpus| cpus.get(id).copied());
+                WorkerReport {
+                    name: worker::worker_name(id),
+                    workload: groups[*group_index].spec.name.to_string(),
+                    wall_secs: times.wall.as_secs_f64(),
+                    cpu_secs: times.cpu.map(|cpu| cpu.as_secs_f64()),
+                    cpu_efficiency: times.efficiency(),
+                    ops_per_sec: (times.ops as f64 / times.wall.as_secs_f64().max(1e-9)) as u64,
+                    cpu,
+                    core_class: cpu
+                        .zip(core_classes.as_ref())
+                        .and_then(|(cpu, classes)| classes.class_of(cpu))
+                        .map(|class| class.label().to_string()),
+                }
+            })
+            .collect(),
+        events: events.reports(),
+        swap_pages,
+        process_cpu_secs: process_cpu.map(|cpu| cpu.as_secs_f64()),
+        allocation_secs: worker_times
+            .iter()
+            .filter_map(|(_, times)| times.allocation)
+            .max()
+            .map(|slowest| slowest.as_secs_f64()),
+        planned_memory_mb: system::total_allocation_mb(per_thread_mb),
+        buffer_rounding: None,
+        allocated_bytes: allocated_short_of_plan(per_thread_mb, worker_times),
+        peak_rss_bytes: system::peak_rss_bytes(),
+    }
+}
+
+/// What the buffers came to, when --best-effort-memory left any worker with
+/// less than it planned; None when every buffer got its full size.
+fn allocated_short_of_plan(
+    per_thread_mb: &[usize],
+    worker_times: &[(usize, WorkerTimes)],
+) -> Option<u64> {
+    let mut short = false;
+    let mut total = 0u64;
+    for (&planned_mb, (_, times)) in per_thread_mb.iter().zip(worker_times) {
+        if planned_mb == 0 {
+            continue;
         }
+        let got = times.buffer_bytes.unwrap_or(0) as u64;
+        short |= got < (planned_mb as u64) << 20;
+        total += got;
+    }
+    short.then_some(total)
+}
 
-        if let Some(limit) = duration_limit
-            && start.elapsed() >= limit
-        {
-            println!("\n[✓] Time limit reached. Stopping...");
-            stop_signal.store(true, Ordering::Release);
-            break;
+fn warn_on_contention(report: &SingleReport) {
+    let contended: Vec<String> = report
+        .workers
+        .iter()
+        .filter_map(|w| {
+            let efficiency = w.cpu_efficiency?;
+            (efficiency < CONTENTION_THRESHOLD)
+                .then(|| format!("{} ({:.1}%)", w.name, efficiency * 100.0))
+        })
+        .collect();
+
+    if !contended.is_empty() {
+        const LISTED: usize = 8;
+        let mut listed = contended[..contended.len().min(LISTED)].join(", ");
+        if contended.len() > LISTED {
+            listed += &format!(", +{} more", contended.len() - LISTED);
         }
+        notice!(
+            "Warning: {} of {} workers got less than {:.0}% CPU time: {}",
+            contended.len(),
+            report.workers.len(),
+            CONTENTION_THRESHOLD * 100.0,
+            listed
+        );
+        notice!(
+            "         Other processes or VM neighbors were likely competing for these cores"
+        );
     }
+}
 
-    for handle in handles {
-        handle.join().expect("Worker thread panicked");
+/// Slow workers only count against peers on the same kind of core; on a
+/// hybrid CPU without --affinity there's no telling which kind that was.
+fn warn_on_imbalance(report: &SingleReport) {
+    let hybrid = system::detect_core_classes().is_some();
+    let slow = reporting::imbalanced_workers(&report.workers, hybrid);
+    if slow.is_empty() {
+        return;
     }
 
-    print_final_stats(
-        start.elapsed(),
-        work_counter.load(Ordering::Relaxed),
-        workload,
+    let listed: Vec<String> = slow
+        .iter()
+        .take(8)
+        .map(|&(index, share)| {
+            let worker = &report.workers[index];
+            match &worker.core_class {
+                Some(class) => format!("{} ({}, {:.0}%)", worker.name, class, share * 100.0),
+                None => format!("{} ({:.0}%)", worker.name, share * 100.0),
+            }
+        })
+        .collect();
+    let more = slow.len().saturating_sub(listed.len());
+    notice!(
+        "Warning: {} of {} workers ran under {:.0}% of their peers' median rate: {}{}",
+        slow.len(),
+        report.workers.len(),
+        reporting::IMBALANCE_THRESHOLD * 100.0,
+        listed.join(", "),
+        if more > 0 {
+            format!(", +{} more", more)
+        } else {
+            String::new()
+        }
     );
+    if hybrid {
+        notice!(
+            "         Peers share a workload and core class (P/E), so this isn't E-core speed"
+        );
+    } else {
+        notice!("         Likely throttling or contention on those cores");
+    }
 }
 
-fn print_final_stats(elapsed: Duration, total_ops: u64, workload: &str) {
-    let ops_per_sec = if elapsed.as_secs() > 0 {
-        total_ops / elapsed.as_secs()
-    } else {
-        total_ops
-    };
+/// "locus-w0 P 1.23M/s" entries, three to a line.
+fn write_worker_rates(out: &mut dyn Write, report: &SingleReport) -> io::Result<()> {
+    writeln!(out, "  Per thread:")?;
+    let entries: Vec<String> = report
+        .workers
+        .iter()
+        .map(|w| {
+            let class = w
+                .core_class
+                .as_deref()
+                .map(|c| format!(" {}", c))
+                .unwrap_or_default();
+            format!("{}{} {}/s", w.name, class, format_number(w.ops_per_sec))
+        })
+        .collect();
+    for row in entries.chunks(3) {
+        let row: Vec<String> = row.iter().map(|e| format!("{:<22}", e)).collect();
+        writeln!(out, "    {}", row.join(" ").trim_end())?;
+    }
+    Ok(())
+}
 
-    println!("\n════════════════════════════════════════════════════════════");
-    println!("      TEST COMPLETE");
-    println!("════════════════════════════════════════════════════════════");
-    println!("  Elapsed:       {:.2}s", elapsed.as_secs_f64());
-    println!("  Total ops:     {}", format_number(total_ops));
-    println!("  Avg rate:      {}/s", format_number(ops_per_sec));
-
-    if workload.starts_with("memory") {
-        let bytes_per_op = if workload == "memory-bandwidth" {
-            // Bandwidth: 8 streams × (1 read + 1 write) × 8 bytes
-            128
-        } else {
-            // Latency: 1 read + 1 write × 8 bytes
-            16
+fn write_final_stats(
+    out: &mut dyn Write,
+    report: &SingleReport,
+    ascii: bool,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "\n════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "      TEST COMPLETE")?;
+    writeln!(
+        out,
+        "════════════════════════════════════════════════════════════"
+    )?;
+    writeln!(out, "  Run:           {}", report.metadata.summary())?;
+    writeln!(
+        out,
+        "  Elapsed:       {}",
+        format_duration(Duration::from_secs_f64(report.elapsed_secs))
+    )?;
+    if let Some(secs) = report.allocation_secs {
+        writeln!(
+            out,
+            "  Allocation:    {} before the start (slowest worker, not timed)",
+            format_duration(Duration::from_secs_f64(secs))
+        )?;
+    }
+    writeln!(out, "  Total ops:     {}", format_number(report.total_ops))?;
+    writeln!(
+        out,
+        "  Avg rate:      {}/s",
+        format_number(report.ops_per_sec)
+    )?;
+    if let (Some(peak), Some(sustained)) =
+        (report.peak_ops_per_sec, report.sustained_ops_per_sec)
+    {
+        writeln!(out, "  Peak rate:     {}/s", format_number(peak))?;
+        writeln!(
+            out,
+            "  Sustained:     {}/s (median of last half)",
+            format_number(sustained)
+        )?;
+        if (sustained as f64) < peak as f64 * THROTTLE_THRESHOLD {
+            writeln!(
+                out,
+                "               {:.0}% below peak: likely thermal or power limits",
+                (1.0 - sustained as f64 / peak as f64) * 100.0
+            )?;
+        }
+    }
+    if let Some(p) = report.rate_percentiles {
+        writeln!(
+            out,
+            "  Percentiles:   p5 {}/s | p50 {}/s | p95 {}/s",
+            format_number(p.p5_ops_per_sec),
+            format_number(p.p50_ops_per_sec),
+            format_number(p.p95_ops_per_sec)
+        )?;
+    }
+    if !report.rate_histogram.is_empty() {
+        let intervals: usize = report.rate_histogram.iter().map(|bin| bin.count).sum();
+        writeln!(out, "  Histogram:     {} one-second rates", intervals)?;
+        for line in reporting::histogram_lines(&report.rate_histogram, ascii) {
+            writeln!(out, "    {}", line)?;
+        }
+    }
+    if let Some(pages) = report.swap_pages {
+        writeln!(
+            out,
+            "  Swapping:      {}",
+            reporting::describe_swapping(pages)
+        )?;
+    }
+    if let Some(cpu_secs) = report.process_cpu_secs {
+        writeln!(
+            out,
+            "  Process CPU:   {}",
+            reporting::describe_process_cpu(cpu_secs, report.elapsed_secs, report.threads)
+        )?;
+    }
+    if let Some(rounding) = report.buffer_rounding {
+        writeln!(
+            out,
+            "  Buffer:        {}",
+            reporting::describe_buffer_rounding(rounding)
+        )?;
+    }
+    if let Some(bytes) = report.allocated_bytes {
+        writeln!(
+            out,
+            "  Allocated:     {} of {} planned (--best-effort-memory)",
+            format_bytes(bytes),
+            format_mb(report.planned_memory_mb)
+        )?;
+    }
+    if let Some(bytes) = report.peak_rss_bytes {
+        match report.planned_memory_mb {
+            0 => writeln!(out, "  Peak RSS:      {}", format_bytes(bytes))?,
+            planned => writeln!(
+                out,
+                "  Peak RSS:      {} (planned buffers {})",
+                format_bytes(bytes),
+                format_mb(planned)
+            )?,
+        }
+    }
+    if let Some(efficiency) = report.cpu_efficiency {
+        let lowest = report
+            .workers
+            .iter()
+            .filter_map(|w| Some((w, w.cpu_efficiency?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        match lowest {
+            Some((worker, lowest)) if report.workers.len() > 1 => writeln!(
+                out,
+                "  CPU time:      {:.1}% of wall time (lowest: {} at {:.1}%)",
+                efficiency * 100.0,
+                worker.name,
+                lowest * 100.0
+            )?,
+            _ => writeln!(
+                out,
+                "  CPU time:      {:.1}% of wall time",
+                efficiency * 100.0
+            )?,
+        }
+    }
+
+    let multi = report.groups.len() > 1;
+    if multi {
+        writeln!(out, "  Per group:")?;
+        for group in &report.groups {
+            let cpu = group
+                .cpu_efficiency
+                .map(|e| format!(", {:.1}% CPU", e * 100.0))
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "    {:<22} {} ops, {}/s{}",
+                format!("{} ×{}", group.workload, group.threads),
+                format_number(group.total_ops),
+                format_number(group.ops_per_sec),
+                cpu
+            )?;
+        }
+    }
+
+    if report.workers.len() > 1 {
+        write_worker_rates(out, report)?;
+    }
+
+    for group in &report.groups {
+        let bytes_per_op = registry::find(&group.workload).and_then(|spec| spec.bytes_per_op);
+        if let (Some(gb_per_sec), Some(bytes_per_op)) = (group.gb_per_sec, bytes_per_op) {
+            if multi {
+                writeln!(
+                    out,
+                    "  Memory BW:     {} ({})",
+                    format_rate_bytes(gb_per_sec * 1e9),
+                    group.workload
+                )?;
+            } else {
+                writeln!(
+                    out,
+                    "  Memory BW:     {}",
+                    format_rate_bytes(gb_per_sec * 1e9)
+                )?;
+            }
+            writeln!(out, "               (estimated, {}B per op)", bytes_per_op)?;
+        }
+    }
+
+    if !report.events.is_empty() {
+        writeln!(out, "  Worker events:")?;
+        for event in &report.events {
+            writeln!(
+                out,
+                "    {} {}: {}",
+                event.worker, event.kind, event.message
+            )?;
+        }
+    }
+
+    writeln!(
+        out,
+        "════════════════════════════════════════════════════════════"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn args(argv: &[&str]) -> Args {
+        Args::try_parse_from(std::iter::once("locus").chain(argv.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn test_mode_conflicts_are_option_errors() {
+        for argv in [
+            &["-B", "--ops", "1M"][..],
+            &["-B", "--split", "integer=1"],
+            &["-B", "--once"],
+        ] {
+            assert!(
+                matches!(
+                    check_bench_options(&args(argv)),
+                    Err(LocusError::ConflictingOptions(_))
+                ),
+                "{:?}",
+                argv
+            );
+        }
+        assert!(check_bench_options(&args(&["-B", "-d", "5"])).is_ok());
+
+        assert!(matches!(
+            check_run_options(&args(&["--ops", "1M", "-d", "5"])),
+            Err(LocusError::ConflictingOptions(_))
+        ));
+        assert!(matches!(
+            check_run_options(&args(&["--log-status", "5", "--progress-format", "jsonl"])),
+            Err(LocusError::ConflictingOptions(_))
+        ));
+        assert!(check_run_options(&args(&["--log-status", "5", "-d", "5"])).is_ok());
+        assert!(matches!(
+            check_loaded_latency_options(&args(&["--loaded-latency", "-d", "5"]), 1),
+            Err(LocusError::ConflictingOptions(_))
+        ));
+        assert!(matches!(
+            interference_pair(&args(&["-d", "5", "-j", "3"]), "integer:1,float:1"),
+            Err(LocusError::ConflictingOptions(_))
+        ));
+        assert!(interference_pair(&args(&["-d", "5", "-j", "2"]), "integer:1,float:1").is_ok());
+        assert!(matches!(
+            check_loaded_latency_options(
+                &args(&["--loaded-latency", "-d", "5", "--best-effort-memory"]),
+                4
+            ),
+            Err(LocusError::ConflictingOptions(_))
+        ));
+    }
+
+    #[test]
+    fn test_shortfall_totals_only_buffered_workers() {
+        let times = |buffer_bytes| WorkerTimes {
+            wall: Duration::from_secs(1),
+            cpu: None,
+            ops: 0,
+            allocation: None,
+            buffer_bytes,
         };
+        let full = [(0, times(Some(8 << 20))), (1, times(Some(64 << 10)))];
+        // The second worker is cache-thrash, whose sweep isn't planned
+        assert_eq!(allocated_short_of_plan(&[8, 0], &full), None);
 
-        let bytes_transferred = total_ops * bytes_per_op;
-        let gb_per_sec = (bytes_transferred as f64) / elapsed.as_secs_f64() / 1_000_000_000.0;
-        println!("  Memory BW:     {:.2} GB/s", gb_per_sec);
-        println!("               (estimated, {}B per op)", bytes_per_op);
+        let short = [(0, times(Some(8 << 20))), (0, times(Some(2 << 20)))];
+        assert_eq!(allocated_short_of_plan(&[8, 8], &short), Some(10 << 20));
     }
 
-    println!("════════════════════════════════════════════════════════════");
+    #[test]
+    fn test_bad_lists_name_their_option() {
+        let split = resolve_groups(&args(&["--split", "integer=9"]), 4).unwrap_err();
+        assert!(matches!(split, LocusError::InvalidList {
+            option: "--split",
+            ..
+        }));
+        let pair = interference_pair(&args(&["-d", "5"]), "integer:1").unwrap_err();
+        assert!(matches!(pair, LocusError::InvalidList {
+            option: "--interference",
+            ..
+        }));
+        let durations =
+            bench_plan(&args(&["-B", "-d", "5", "--workload-durations", "integer=x"]), 2);
+        assert!(matches!(durations, Err(LocusError::InvalidList {
+            option: "--workload-durations",
+            ..
+        })));
+    }
+
+    #[test]
+    fn test_bench_plan_needs_a_duration_for_every_workload() {
+        assert!(matches!(
+            bench_plan(&args(&["-B"]), 2),
+            Err(LocusError::BenchmarkNeedsDuration { overrides: false })
+        ));
+        assert!(matches!(
+            bench_plan(&args(&["-B", "--workload-durations", "integer=5"]), 2),
+            Err(LocusError::BenchmarkNeedsDuration { overrides: true })
+        ));
+        assert!(matches!(
+            bench_plan(
+                &args(&["-B", "-d", "3", "--workload-durations", "cache-thrash=5"]),
+                2
+            ),
+            Err(LocusError::WorkloadNotInSet { .. })
+        ));
+
+        let (groups, overrides) = bench_plan(&args(&["-B", "-d", "3"]), 2).unwrap();
+        assert_eq!(groups.len(), 5);
+        assert!(overrides.is_empty());
+    }
+
+    #[test]
+    fn test_manual_memory_respects_the_total_cap() {
+        let over = resolve_memory_mb(&args(&["-m", "512", "--max-total-memory", "1024"]), 4);
+        assert!(matches!(over, Err(LocusError::OverMemoryLimit {
+            memory_mb:    512,
+            threads:      4,
+            max_total_mb: 1024,
+        })));
+        let within = resolve_memory_mb(&args(&["-m", "256", "--max-total-memory", "1024"]), 4);
+        assert_eq!(within.unwrap(), 256);
+        // A product that wraps usize is over any cap, not a panic
+        let max = usize::MAX.to_string();
+        let wrapping = resolve_memory_mb(&args(&["-m", &max, "--max-total-memory", "4096"]), 4);
+        assert!(matches!(wrapping, Err(LocusError::OverMemoryLimit {
+            memory_mb: usize::MAX,
+            ..
+        })));
+    }
 }
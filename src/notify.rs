@@ -0,0 +1,395 @@
+//! `--notify-url`: one JSON POST when the run ends, so a day-long burn-in
+//! can ping a chat channel. HTTP/1.1 over a `TcpStream`, wrapped in rustls
+//! for https when built with the `tls` feature; the default build links no
+//! TLS stack and turns https URLs away. Delivery is best effort: a failure
+//! is a warning, never the exit code.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::error::{LocusError, Outcome};
+use crate::output::{RunMetadata, notice};
+use crate::reporting::{format_duration, format_number};
+
+/// For the connect, and for each read or write after it, so a dead
+/// endpoint can't hold up the exit for long.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What gets POSTed. Fields are only ever added, like the progress lines.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    /// One line for Slack and Teams webhooks, which show `text`
+    pub text:         String,
+    pub run_id:       String,
+    pub hostname:     String,
+    /// completed, below-threshold, interrupted, failed or error
+    pub outcome:      &'static str,
+    pub elapsed_secs: f64,
+    /// ops/s per workload; empty for the modes that report something else
+    pub rates:        Vec<NotifiedRate>,
+    /// Fatal worker events
+    pub errors:       usize,
+    /// The threshold miss, or why the run never got to a report
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message:      Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NotifiedRate {
+    pub workload:    String,
+    pub ops_per_sec: u64,
+}
+
+/// Builds the payload from how the run ended. A fault outranks an
+/// interrupt, which outranks a missed threshold, as in the exit code.
+pub fn notification(
+    result: &Result<Outcome, LocusError>,
+    elapsed: Duration,
+    interrupted: bool,
+    metadata: &RunMetadata,
+) -> Notification {
+    let (outcome, errors, rates, message) = match result {
+        Err(e) => ("error", 0, &[][..], Some(e.to_string())),
+        Ok(ended) => {
+            let label = if ended.faulted {
+                "failed"
+            } else if interrupted {
+                "interrupted"
+            } else if ended.below_threshold.is_some() {
+                "below-threshold"
+            } else {
+                "completed"
+            };
+            (label, ended.errors, &ended.rates[..], ended.below_threshold.clone())
+        },
+    };
+    let rates: Vec<NotifiedRate> = rates
+        .iter()
+        .map(|(workload, ops_per_sec)| NotifiedRate {
+            workload:    workload.clone(),
+            ops_per_sec: *ops_per_sec,
+        })
+        .collect();
+
+    let mut text = format!(
+        "locus on {}: {} after {}",
+        metadata.hostname,
+        outcome,
+        format_duration(elapsed)
+    );
+    for rate in &rates {
+        text += &format!(", {} {} ops/s", rate.workload, format_number(rate.ops_per_sec));
+    }
+    if errors > 0 {
+        text += &format!(", {} error{}", errors, if errors == 1 { "" } else { "s" });
+    }
+    if let Some(message) = &message {
+        text += &format!(" ({})", message);
+    }
+
+    Notification {
+        text,
+        run_id: metadata.run_id.clone(),
+        hostname: metadata.hostname.clone(),
+        outcome,
+        elapsed_secs: elapsed.as_secs_f64(),
+        rates,
+        errors,
+        message,
+    }
+}
+
+/// Sends `notification` and says whether it arrived.
+pub fn deliver(url: &str, notification: &Notification) {
+    let sent = parse_url(url).map_err(io::Error::other).and_then(|endpoint| {
+        let body = serde_json::to_string(notification).map_err(io::Error::other)?;
+        post_json(&endpoint, &body)
+    });
+    match sent {
+        Ok(()) => notice!("[✓] Notified {}", url),
+        Err(e) => notice!("[!] --notify-url: could not notify {}: {}", url, e),
+    }
+}
+
+/// Where an `http://` or `https://` URL points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// https; only ever set with the `tls` feature
+    tls:       bool,
+    /// Without the brackets of an IPv6 literal
+    host:      String,
+    port:      u16,
+    /// As written, for the Host header
+    authority: String,
+    /// Path and query; "/" when the URL has neither
+    target:    String,
+}
+
+pub fn parse_url(url: &str) -> Result<Endpoint, String> {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return Err(format!("'{}' is not a URL (expected http://host/path)", url));
+    };
+    let tls = match scheme.to_ascii_lowercase().as_str() {
+        "http" => false,
+        "https" if cfg!(feature = "tls") => true,
+        "https" => {
+            return Err("https needs TLS, which this build leaves out; rebuild with \
+                        `--features tls`"
+                .to_string());
+        },
+        other => {
+            return Err(format!("unsupported scheme '{}' (expected http:// or https://)", other));
+        },
+    };
+
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (authority, target) = match rest.find(['/', '?']) {
+        Some(at) if rest[at..].starts_with('?') => (&rest[..at], format!("/{}", &rest[at..])),
+        Some(at) => (&rest[..at], rest[at..].to_string()),
+        None => (rest, "/".to_string()),
+    };
+    if authority.contains('@') {
+        return Err("credentials in the URL aren't supported".to_string());
+    }
+
+    // An IPv6 literal keeps its colons inside the brackets
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(bracketed) => {
+            let (host, after) = bracketed
+                .split_once(']')
+                .ok_or_else(|| format!("unclosed '[' in '{}'", authority))?;
+            (host, after.strip_prefix(':'))
+        },
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    if host.is_empty() {
+        return Err(format!("'{}' has no host", url));
+    }
+    let port = match port {
+        Some(port) => port
+            .parse()
+            .map_err(|_| format!("'{}' is not a port number", port))?,
+        None if tls => 443,
+        None => 80,
+    };
+
+    Ok(Endpoint {
+        tls,
+        host: host.to_string(),
+        port,
+        authority: authority.to_string(),
+        target,
+    })
+}
+
+/// One HTTP/1.1 POST with `Connection: close`; any 2xx status is success.
+fn post_json(endpoint: &Endpoint, body: &str) -> io::Result<()> {
+    let stream = connect(endpoint)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: locus/{}\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        endpoint.target,
+        endpoint.authority,
+        env!("CARGO_PKG_VERSION"),
+        body.len(),
+        body
+    );
+    match endpoint.tls {
+        #[cfg(feature = "tls")]
+        true => exchange(secure(&endpoint.host, stream)?, &request),
+        _ => exchange(stream, &request),
+    }
+}
+
+/// Sends `request` and judges the status line that comes back.
+fn exchange(mut stream: impl Read + Write, request: &str) -> io::Result<()> {
+    stream.write_all(request.as_bytes())?;
+    stream.flush()?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    let status_line = status_line.trim_end();
+    match status_line.split_whitespace().nth(1) {
+        Some(code) if code.len() == 3 && code.starts_with('2') => Ok(()),
+        Some(_) => Err(io::Error::other(format!("server answered '{}'", status_line))),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no HTTP status line in the response",
+        )),
+    }
+}
+
+/// `stream` under TLS, with the server checked against the Mozilla roots
+/// webpki-roots bundles, so no system certificate store is needed.
+#[cfg(feature = "tls")]
+fn secure(
+    host: &str,
+    stream: TcpStream,
+) -> io::Result<rustls::StreamOwned<rustls::ClientConnection, TcpStream>> {
+    use std::sync::Arc;
+
+    let roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let name = rustls::pki_types::ServerName::try_from(host.to_string())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let connection =
+        rustls::ClientConnection::new(Arc::new(config), name).map_err(io::Error::other)?;
+    Ok(rustls::StreamOwned::new(connection, stream))
+}
+
+/// Tries each address the host resolves to, as a browser would.
+fn connect(endpoint: &Endpoint) -> io::Result<TcpStream> {
+    let mut last = io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} resolved to no addresses", endpoint.host),
+    );
+    for addr in (endpoint.host.as_str(), endpoint.port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    use super::*;
+
+    fn metadata() -> RunMetadata {
+        RunMetadata {
+            run_id:   "0b7e3f6a-1c2d-4e5f-8a9b-0c1d2e3f4a5b".to_string(),
+            hostname: "rack-7".to_string(),
+            ..RunMetadata::default()
+        }
+    }
+
+    #[test]
+    fn test_notification_names_the_outcome() {
+        let ended = Outcome {
+            rates: vec![("integer".to_string(), 1_500_000_000)],
+            ..Outcome::default()
+        };
+        let elapsed = Duration::from_secs(30);
+        let done = notification(&Ok(ended), elapsed, false, &metadata());
+        assert_eq!(done.outcome, "completed");
+        assert_eq!(done.run_id, metadata().run_id);
+        assert_eq!(done.elapsed_secs, 30.0);
+        assert_eq!(done.rates[0].ops_per_sec, 1_500_000_000);
+        assert_eq!(done.message, None);
+        assert!(done.text.starts_with("locus on rack-7: completed after "), "{}", done.text);
+        assert!(done.text.contains(", integer "), "{}", done.text);
+
+        let json = serde_json::to_value(&done).unwrap();
+        assert_eq!(json["rates"][0]["workload"], "integer");
+        assert!(json.get("message").is_none());
+
+        let failed = Outcome {
+            faulted: true,
+            errors: 2,
+            below_threshold: Some("below threshold".to_string()),
+            ..Outcome::default()
+        };
+        let failed = notification(&Ok(failed), elapsed, true, &metadata());
+        assert_eq!(failed.outcome, "failed");
+        assert_eq!(failed.errors, 2);
+        assert!(failed.text.contains(", 2 errors (below threshold)"), "{}", failed.text);
+
+        let below = Outcome {
+            below_threshold: Some("below threshold".to_string()),
+            ..Outcome::default()
+        };
+        let interrupted = notification(&Ok(below), elapsed, true, &metadata());
+        assert_eq!(interrupted.outcome, "interrupted");
+
+        let never_ran = notification(&Err(LocusError::ZeroMemory), elapsed, false, &metadata());
+        assert_eq!(never_ran.outcome, "error");
+        assert!(never_ran.rates.is_empty());
+        assert!(never_ran.message.unwrap().contains("set one with -m"));
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let endpoint = parse_url("http://hooks.local:8080/ping?team=ops#x").unwrap();
+        assert_eq!(endpoint.host, "hooks.local");
+        assert_eq!(endpoint.port, 8080);
+        assert_eq!(endpoint.authority, "hooks.local:8080");
+        assert_eq!(endpoint.target, "/ping?team=ops");
+
+        let bare = parse_url("HTTP://relay").unwrap();
+        assert_eq!((bare.port, bare.target.as_str()), (80, "/"));
+        assert_eq!(parse_url("http://relay?a=1").unwrap().target, "/?a=1");
+
+        let v6 = parse_url("http://[::1]:9000/hook").unwrap();
+        assert_eq!((v6.host.as_str(), v6.port), ("::1", 9000));
+        assert_eq!(v6.authority, "[::1]:9000");
+
+        assert!(!bare.tls);
+        let https = parse_url("https://hooks.slack.com/x");
+        if cfg!(feature = "tls") {
+            let https = https.unwrap();
+            assert_eq!((https.tls, https.port), (true, 443));
+        } else {
+            assert!(https.unwrap_err().contains("--features tls"));
+        }
+        assert!(parse_url("ftp://relay").is_err());
+        assert!(parse_url("relay:80").is_err());
+        assert!(parse_url("http://:80/").is_err());
+        assert!(parse_url("http://relay:http/").is_err());
+        assert!(parse_url("http://user:pw@relay/").is_err());
+    }
+
+    #[test]
+    fn test_post_reads_the_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let endpoint = parse_url(&url).unwrap();
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for status in ["204 No Content", "500 Internal Server Error"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut chunk = [0u8; 1024];
+                // The body is the last thing sent, and `{}` has no newlines
+                while !request.ends_with(b"}") {
+                    let n = stream.read(&mut chunk).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&chunk[..n]);
+                }
+                write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).unwrap();
+                requests.push(String::from_utf8(request).unwrap());
+            }
+            requests
+        });
+
+        post_json(&endpoint, "{}").unwrap();
+        let refused = post_json(&endpoint, "{}").unwrap_err();
+        assert!(refused.to_string().contains("500"), "{}", refused);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(requests[0].contains("\r\nContent-Length: 2\r\n"));
+        assert!(requests[0].ends_with("\r\n\r\n{}"));
+    }
+}
@@ -0,0 +1,197 @@
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::ValueEnum;
+
+use crate::affinity;
+use crate::output::notice;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum NumaPolicy {
+    /// Kernel default: pages land on the node of the thread that touches them
+    Local,
+    /// Spread each buffer's pages round-robin across all memory nodes
+    Interleave,
+}
+
+/// The policy actually in effect, after falling back where unsupported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Applied {
+    Local,
+    Interleave(Vec<usize>),
+}
+
+impl Applied {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Local => "local (first-touch)".to_string(),
+            Self::Interleave(nodes) => {
+                format!(
+                    "interleave across nodes {}",
+                    affinity::describe_cores(nodes)
+                )
+            },
+        }
+    }
+}
+
+/// Nodes every new buffer is interleaved across; unset = local.
+static INTERLEAVE_NODES: OnceLock<Vec<usize>> = OnceLock::new();
+static BIND_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Sets the process-wide buffer placement. Call once, before any worker
+/// allocates.
+pub fn configure(requested: NumaPolicy) -> Applied {
+    if requested == NumaPolicy::Local {
+        return Applied::Local;
+    }
+
+    if !cfg!(target_os = "linux") {
+        notice!(
+            "Warning: --numa interleave is only supported on Linux; using default allocation"
+        );
+        return Applied::Local;
+    }
+
+    let nodes = memory_nodes();
+    if nodes.len() < 2 {
+        notice!(
+            "Warning: --numa interleave needs at least 2 memory nodes (found {}); using default allocation",
+            nodes.len()
+        );
+        return Applied::Local;
+    }
+
+    let _ = INTERLEAVE_NODES.set(nodes.clone());
+    Applied::Interleave(nodes)
+}
+
+pub fn applied() -> Applied {
+    match INTERLEAVE_NODES.get() {
+        Some(nodes) => Applied::Interleave(nodes.clone()),
+        None => Applied::Local,
+    }
+}
+
+/// Applies the configured policy to a freshly reserved, not yet touched
+/// buffer. A no-op under the local policy.
+pub fn bind_buffer(addr: *const u8, len: usize) {
+    let Some(nodes) = INTERLEAVE_NODES.get() else {
+        return;
+    };
+    if !interleave(addr, len, nodes) && !BIND_WARNED.swap(true, Ordering::Relaxed) {
+        notice!(
+            "Warning: mbind(MPOL_INTERLEAVE) failed: {}; some buffers use default placement",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// NUMA nodes that have memory attached (empty where unknown).
+#[cfg(target_os = "linux")]
+pub fn memory_nodes() -> Vec<usize> {
+    ["has_memory", "online"]
+        .iter()
+        .find_map(|file| {
+            std::fs::read_to_string(format!("/sys/devices/system/node/{}", file)).ok()
+        })
+        .map(|list| parse_node_list(&list))
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn memory_nodes() -> Vec<usize> {
+    Vec::new()
+}
+
+/// Parses the kernel list format, e.g. "0-1,3", for nodes and CPUs alike.
+pub(crate) fn parse_node_list(s: &str) -> Vec<usize> {
+    let mut nodes = Vec::new();
+    for part in s.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                if let (Ok(first), Ok(last)) = (first.parse::<usize>(), last.parse::<usize>()) {
+                    nodes.extend(first..=last);
+                }
+            },
+            None => nodes.extend(part.parse::<usize>().ok()),
+        }
+    }
+    nodes
+}
+
+/// mbind only accepts whole pages, so the partial pages at either end keep
+/// the default policy.
+#[cfg(target_os = "linux")]
+fn interleave(addr: *const u8, len: usize, nodes: &[usize]) -> bool {
+    const MPOL_INTERLEAVE: libc::c_long = 3;
+    const MAX_NODES: usize = 1024;
+
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = (addr as usize).next_multiple_of(page);
+    let end = (addr as usize + len) / page * page;
+    if end <= start {
+        return true;
+    }
+
+    let mut mask = [0u64; MAX_NODES / 64];
+    for &node in nodes.iter().filter(|&&n| n < MAX_NODES) {
+        mask[node / 64] |= 1 << (node % 64);
+    }
+
+    // The kernel reads maxnode - 1 bits
+    let rc = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            start,
+            end - start,
+            MPOL_INTERLEAVE,
+            mask.as_ptr(),
+            MAX_NODES + 1,
+            0 as libc::c_uint,
+        )
+    };
+    rc == 0
+}
+
+#[cfg(not(target_os = "linux"))]
+fn interleave(_addr: *const u8, _len: usize, _nodes: &[usize]) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_node_list() {
+        assert_eq!(parse_node_list("0\n"), [0]);
+        assert_eq!(parse_node_list("0-1,3"), [0, 1, 3]);
+        assert!(parse_node_list("").is_empty());
+    }
+
+    #[test]
+    fn test_local_policy_needs_no_support() {
+        assert_eq!(configure(NumaPolicy::Local), Applied::Local);
+        assert_eq!(Applied::Local.describe(), "local (first-touch)");
+        assert_eq!(
+            Applied::Interleave(vec![0, 1]).describe(),
+            "interleave across nodes 0-1"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_interleave_on_available_nodes() {
+        let nodes = memory_nodes();
+        if nodes.is_empty() {
+            return;
+        }
+        let buffer: Vec<u64> = Vec::with_capacity(1024 * 1024);
+        assert!(interleave(
+            buffer.as_ptr().cast(),
+            buffer.capacity() * 8,
+            &nodes
+        ));
+    }
+}
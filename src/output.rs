@@ -0,0 +1,851 @@
+use std::fs::File;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::error::LocusError;
+use crate::reporting::format_rfc3339;
+use crate::system::{self, SystemInfo};
+
+/// Set by --silent: only errors reach the terminal.
+static SILENT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_silent(silent: bool) {
+    SILENT.store(silent, Ordering::Relaxed);
+}
+
+pub fn silent() -> bool {
+    SILENT.load(Ordering::Relaxed)
+}
+
+/// `eprintln!` for notices and warnings, dropped under --silent. Errors keep
+/// using `eprintln!` directly.
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        if !$crate::output::silent() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+pub(crate) use notice;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    /// JUnit XML, one testcase per benchmark workload
+    Junit,
+}
+
+/// How live progress is shown on stderr: a redrawn line, or one
+/// [`ProgressSnapshot`] per interval as a JSON line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProgressFormat {
+    Text,
+    Jsonl,
+}
+
+/// One reporter interval, as `--progress-format jsonl` writes it. Fields
+/// are only ever added, never renamed, so dashboards can rely on them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressSnapshot {
+    /// UTC, RFC 3339
+    pub timestamp:            String,
+    /// Since the measured window opened
+    pub elapsed_secs:         f64,
+    /// The workload, or the --split workloads joined with '+'
+    pub workload:             String,
+    pub total_ops:            u64,
+    /// Combined rate over the last interval
+    pub interval_ops_per_sec: u64,
+    /// Bandwidth over the last interval; only when every workload running
+    /// has a bytes-per-op estimate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_gb_per_sec:  Option<f64>,
+}
+
+/// Where the final report goes. Live progress never goes here.
+pub struct Sink {
+    path:   Option<PathBuf>,
+    writer: Box<dyn Write>,
+}
+
+impl Sink {
+    /// Opens `path` for writing, or stdout when `None`. Opened before the run
+    /// starts so a bad path fails fast instead of after a 30-minute test.
+    pub fn open(path: Option<&Path>) -> io::Result<Self> {
+        let writer: Box<dyn Write> = match path {
+            Some(p) => Box::new(BufWriter::new(File::create(p)?)),
+            None => Box::new(io::stdout()),
+        };
+        Ok(Self {
+            path: path.map(Path::to_path_buf),
+            writer,
+        })
+    }
+
+    /// For --silent without --out: the report is built but goes nowhere.
+    pub fn discard() -> Self {
+        Self {
+            path:   None,
+            writer: Box::new(io::sink()),
+        }
+    }
+
+    pub fn writer(&mut self) -> &mut dyn Write {
+        &mut self.writer
+    }
+
+    /// Flushes, and says where the report went when that wasn't stdout.
+    pub fn finish(mut self, written: io::Result<()>) -> Result<(), LocusError> {
+        let result = written.and_then(|()| self.writer.flush());
+        let target = self
+            .path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "stdout".to_string());
+
+        match result {
+            Ok(()) => {
+                if self.path.is_some() {
+                    notice!("[✓] Results written to {}", target);
+                }
+                Ok(())
+            },
+            Err(source) => Err(LocusError::ReportNotWritten { target, source }),
+        }
+    }
+}
+
+pub fn write_json<T: Serialize>(out: &mut dyn Write, value: &T) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, value).map_err(io::Error::other)?;
+    writeln!(out)
+}
+
+/// A `--format junit` report: one suite, the way CI systems that only
+/// read JUnit XML expect a test run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JunitSuite {
+    pub name:       String,
+    /// RFC 3339, when the run started
+    pub timestamp:  String,
+    pub hostname:   String,
+    pub properties: Vec<(String, String)>,
+    pub cases:      Vec<JunitCase>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JunitCase {
+    pub name:       String,
+    pub time_secs:  f64,
+    /// Why it failed; a failure outranks a skip
+    pub failure:    Option<String>,
+    /// Why it never ran
+    pub skipped:    Option<String>,
+    pub system_out: String,
+}
+
+impl JunitSuite {
+    pub fn failures(&self) -> usize {
+        self.cases.iter().filter(|case| case.failure.is_some()).count()
+    }
+
+    fn skipped(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.failure.is_none() && case.skipped.is_some())
+            .count()
+    }
+}
+
+/// Writes `suite` as JUnit XML. Hand-rolled: the format is small and
+/// fixed, and every value goes through [`escape_xml`].
+pub fn write_junit(out: &mut dyn Write, suite: &JunitSuite) -> io::Result<()> {
+    let time: f64 = suite.cases.iter().map(|case| case.time_secs).sum();
+    let counts = format!(
+        "tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\"",
+        suite.cases.len(),
+        suite.failures(),
+        suite.skipped(),
+        time
+    );
+    let name = escape_xml(&suite.name);
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<testsuites name=\"{}\" {}>", name, counts)?;
+    writeln!(
+        out,
+        "  <testsuite name=\"{}\" {} timestamp=\"{}\" hostname=\"{}\">",
+        name,
+        counts,
+        escape_xml(&suite.timestamp),
+        escape_xml(&suite.hostname)
+    )?;
+    if !suite.properties.is_empty() {
+        writeln!(out, "    <properties>")?;
+        for (key, value) in &suite.properties {
+            writeln!(
+                out,
+                "      <property name=\"{}\" value=\"{}\"/>",
+                escape_xml(key),
+                escape_xml(value)
+            )?;
+        }
+        writeln!(out, "    </properties>")?;
+    }
+    for case in &suite.cases {
+        writeln!(
+            out,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\">",
+            escape_xml(&case.name),
+            name,
+            case.time_secs
+        )?;
+        if let Some(message) = &case.failure {
+            let message = escape_xml(message);
+            writeln!(out, "      <failure message=\"{}\">{}</failure>", message, message)?;
+        } else if let Some(message) = &case.skipped {
+            writeln!(out, "      <skipped message=\"{}\"/>", escape_xml(message))?;
+        }
+        if !case.system_out.is_empty() {
+            writeln!(
+                out,
+                "      <system-out>{}</system-out>",
+                escape_xml(&case.system_out)
+            )?;
+        }
+        writeln!(out, "    </testcase>")?;
+    }
+    writeln!(out, "  </testsuite>")?;
+    writeln!(out, "</testsuites>")
+}
+
+/// Text safe in both XML content and attribute values. The characters
+/// XML 1.0 can't carry at all (most C0 controls, U+FFFE, U+FFFF) are
+/// dropped rather than escaped: no reference to them is legal either.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\u{0}'..='\u{8}' | '\u{b}' | '\u{c}' | '\u{e}'..='\u{1f}' => {},
+            '\u{fffe}' | '\u{ffff}' => {},
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Which machine produced a result and when, so files collected from a
+/// fleet describe themselves. Empty when reading files saved before it existed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    /// Random UUID naming this run, so copies of one file can be told
+    /// apart from two runs on the same host in the same second
+    #[serde(default)]
+    pub run_id:    String,
+    pub hostname:  String,
+    /// RFC 3339, UTC, taken when the run started
+    pub timestamp: String,
+    /// The effective configuration as `--dump-config` prints it, which
+    /// `--config` reads back to repeat the run
+    #[serde(default)]
+    pub config:    String,
+    /// Flattened so `os`, `arch` and `cpu_brand` stay where older files
+    /// have them
+    #[serde(flatten)]
+    pub system:    SystemInfo,
+}
+
+impl RunMetadata {
+    /// One line for the top of a text report.
+    pub fn summary(&self) -> String {
+        format!("{} | {} | {}", self.run_id, self.hostname, self.timestamp)
+    }
+}
+
+static RUN_METADATA: OnceLock<RunMetadata> = OnceLock::new();
+
+/// Fixes the metadata for this run; main calls this before starting any
+/// work so the timestamp is the start of the run. Later calls are ignored.
+pub fn capture_run_metadata(config: String) {
+    RUN_METADATA.get_or_init(|| new_run_metadata(config));
+}
+
+/// What [`capture_run_metadata`] recorded, or a config-less capture now
+/// when it was never called.
+pub fn run_metadata() -> RunMetadata {
+    RUN_METADATA
+        .get_or_init(|| new_run_metadata(String::new()))
+        .clone()
+}
+
+fn new_run_metadata(config: String) -> RunMetadata {
+    RunMetadata {
+        run_id: new_run_id(),
+        hostname: system::hostname(),
+        timestamp: format_rfc3339(SystemTime::now()),
+        config,
+        system: system::info().clone(),
+    }
+}
+
+/// A version 4 UUID. The std hasher's keys are randomized per process,
+/// which is all the randomness a run label needs.
+fn new_run_id() -> String {
+    let state = RandomState::new();
+    let salt = (SystemTime::now(), std::process::id());
+    let high = state.hash_one((salt, 0u8));
+    let low = state.hash_one((salt, 1u8));
+    let bits = (u128::from(high) << 64 | u128::from(low)) & !(0xf << 76 | 0x3 << 62)
+        | 0x4 << 76
+        | 0x2 << 62;
+    let hex = format!("{:032x}", bits);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// The binary that produced a result, embedded at build time by build.rs.
+/// Empty when reading files saved before it existed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// Short hash, or "unknown" when built outside a git checkout
+    pub commit:    String,
+    /// Uncommitted changes at build time (None = unknown)
+    pub dirty:     Option<bool>,
+    pub rustc:     String,
+    pub target:    String,
+    pub profile:   String,
+    pub opt_level: String,
+    pub features:  Vec<String>,
+}
+
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        commit:    env!("LOCUS_BUILD_GIT_COMMIT").to_string(),
+        dirty:     match env!("LOCUS_BUILD_GIT_DIRTY") {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        rustc:     env!("LOCUS_BUILD_RUSTC").to_string(),
+        target:    env!("LOCUS_BUILD_TARGET").to_string(),
+        profile:   env!("LOCUS_BUILD_PROFILE").to_string(),
+        opt_level: env!("LOCUS_BUILD_OPT_LEVEL").to_string(),
+        features:  env!("LOCUS_BUILD_FEATURES")
+            .split(',')
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+            .collect(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupReport {
+    pub workload:       String,
+    pub threads:        usize,
+    pub total_ops:      u64,
+    pub ops_per_sec:    u64,
+    /// Estimated from the workload's bytes per op
+    pub gb_per_sec:     Option<f64>,
+    /// CPU time / wall time across the group's workers (None = unsupported)
+    pub cpu_efficiency: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerReport {
+    pub name:           String,
+    pub workload:       String,
+    pub wall_secs:      f64,
+    pub cpu_secs:       Option<f64>,
+    pub cpu_efficiency: Option<f64>,
+    /// This worker's own ops over its own wall time
+    #[serde(default)]
+    pub ops_per_sec:    u64,
+    /// CPU it was pinned to (None = unpinned)
+    #[serde(default)]
+    pub cpu:            Option<usize>,
+    /// "P" or "E" on a hybrid CPU when pinned, else None
+    #[serde(default)]
+    pub core_class:     Option<String>,
+}
+
+/// A warning or fatal error a worker reported during the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventReport {
+    pub worker:  String,
+    pub kind:    String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SingleReport {
+    pub version:               String,
+    #[serde(default)]
+    pub metadata:              RunMetadata,
+    #[serde(default)]
+    pub build:                 BuildInfo,
+    /// "bare-metal", a hypervisor name, or "unknown"
+    pub virtualization:        String,
+    pub threads:               usize,
+    pub elapsed_secs:          f64,
+    pub total_ops:             u64,
+    pub ops_per_sec:           u64,
+    /// Best one-second interval (None = run shorter than one interval)
+    pub peak_ops_per_sec:      Option<u64>,
+    /// Median interval over the second half of the run
+    pub sustained_ops_per_sec: Option<u64>,
+    /// Spread of the interval rates (None = run shorter than one interval)
+    #[serde(default)]
+    pub rate_percentiles:      Option<RatePercentiles>,
+    /// The interval rates in equal-width bins over their range (empty =
+    /// fewer than two intervals)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rate_histogram:        Vec<HistogramBin>,
+    pub cpu_efficiency:        Option<f64>,
+    pub groups:                Vec<GroupReport>,
+    pub workers:               Vec<WorkerReport>,
+    pub events:                Vec<EventReport>,
+    /// Pages swapped in or out during the run (None = not observable here)
+    #[serde(default)]
+    pub swap_pages:            Option<u64>,
+    /// CPU time the whole process used over the measured window, every
+    /// thread included; near `threads × elapsed_secs` when nothing else
+    /// competes for the cores
+    #[serde(default)]
+    pub process_cpu_secs:      Option<f64>,
+    /// The slowest worker's buffer allocation and fill, which the start
+    /// waited on; not part of `elapsed_secs` (None = no buffers)
+    #[serde(default)]
+    pub allocation_secs:       Option<f64>,
+    /// Buffer memory the run planned across all workers
+    #[serde(default)]
+    pub planned_memory_mb:     usize,
+    /// Set when the per-thread buffer was rounded down to a power of two
+    /// (None = used as given, or --exact-buffer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_rounding:       Option<BufferRounding>,
+    /// Buffer bytes the workers actually got, set only when
+    /// --best-effort-memory left some short of `planned_memory_mb`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocated_bytes:       Option<u64>,
+    /// Process peak resident set at the end of the run, buffers, stacks and
+    /// binary included (None = not observable here)
+    #[serde(default)]
+    pub peak_rss_bytes:        Option<u64>,
+}
+
+/// The per-thread buffer asked for (-m or auto-sized) and the power of two
+/// the workers got instead, so the memory kernels could index with a mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BufferRounding {
+    pub requested_mb: usize,
+    pub effective_mb: usize,
+}
+
+impl BufferRounding {
+    /// Only when the workers got something other than what was asked for.
+    pub fn between(requested_mb: usize, effective_mb: usize) -> Option<Self> {
+        (effective_mb != requested_mb).then_some(Self {
+            requested_mb,
+            effective_mb,
+        })
+    }
+}
+
+/// Nearest-rank percentiles of the one-second rates: a single stall moves
+/// the minimum but not p5, while sustained throttling drags p50 down too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatePercentiles {
+    pub p5_ops_per_sec:  u64,
+    pub p50_ops_per_sec: u64,
+    pub p95_ops_per_sec: u64,
+}
+
+/// One slice of the interval rates' range and how many intervals fell in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistogramBin {
+    /// Inclusive bounds
+    pub low_ops_per_sec:  u64,
+    pub high_ops_per_sec: u64,
+    pub count:            usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkEntry {
+    pub workload:               String,
+    pub ops_per_sec:            u64,
+    pub relative_to_mixed:      f64,
+    pub per_thread_ops_per_sec: u64,
+    /// Measured seconds for this workload (0 in reports that predate
+    /// --workload-durations: the report-wide `duration_secs`)
+    #[serde(default)]
+    pub duration_secs:          u64,
+    /// Worker setup before the timer started, allocation included (0 in
+    /// reports that predate it)
+    #[serde(default)]
+    pub setup_secs:             f64,
+    /// Actual length of the measured window (0 in reports that predate it)
+    #[serde(default)]
+    pub measured_secs:          f64,
+    /// With --adaptive, whether the rate settled before the cap (None =
+    /// fixed duration)
+    #[serde(default)]
+    pub converged:              Option<bool>,
+    /// Estimated from the registry's bytes per op (None = compute workload)
+    #[serde(default)]
+    pub gb_per_sec:             Option<f64>,
+    /// (max − min) / mean of the per-thread ops (None = one thread)
+    #[serde(default)]
+    pub imbalance:              Option<f64>,
+    /// Worker with the fewest ops (None = one thread)
+    #[serde(default)]
+    pub slowest_worker:         Option<String>,
+    /// The kernel's code path, for workloads with more than one (None =
+    /// the single portable path)
+    #[serde(default)]
+    pub code_path:              Option<String>,
+    /// Rate as a percentage of the built-in reference (None = the
+    /// workload has no reference rate)
+    #[serde(default)]
+    pub percent_of_reference:   Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub version:        String,
+    #[serde(default)]
+    pub metadata:       RunMetadata,
+    #[serde(default)]
+    pub build:          BuildInfo,
+    /// "bare-metal", a hypervisor name, or "unknown"
+    pub virtualization: String,
+    pub threads:        usize,
+    pub duration_secs:  u64,
+    pub results:        Vec<BenchmarkEntry>,
+    /// Geometric mean of the measured rates (None = nothing measured)
+    #[serde(default)]
+    pub score:          Option<u64>,
+    /// Geometric mean of `percent_of_reference` over the default set (None
+    /// = some of it didn't run)
+    #[serde(default)]
+    pub index:          Option<f64>,
+    pub events:          Vec<EventReport>,
+    /// Pages swapped in or out during the run (None = not observable here)
+    #[serde(default)]
+    pub swap_pages:      Option<u64>,
+    /// Set when the per-thread buffer was rounded down to a power of two
+    /// (None = used as given, or --exact-buffer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_rounding: Option<BufferRounding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedLatencyReport {
+    pub version:           String,
+    #[serde(default)]
+    pub metadata:          RunMetadata,
+    #[serde(default)]
+    pub build:             BuildInfo,
+    /// "bare-metal", a hypervisor name, or "unknown"
+    pub virtualization:    String,
+    pub idle_ns:           f64,
+    pub loaded_ns:         f64,
+    pub bandwidth_threads: usize,
+    pub bandwidth_gb_s:    f64,
+    pub degradation:       f64,
+    pub events:            Vec<EventReport>,
+    /// Set when the per-thread buffer was rounded down to a power of two
+    /// (None = used as given, or --exact-buffer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_rounding:   Option<BufferRounding>,
+}
+
+/// One side of an `--interference` pair.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterferenceEntry {
+    pub workload:              String,
+    pub threads:               usize,
+    pub solo_ops_per_sec:      u64,
+    /// None when the run stopped before the contended phase
+    pub contended_ops_per_sec: Option<u64>,
+    /// Percent slower next to the other workload (negative = faster)
+    pub slowdown_pct:          Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterferenceReport {
+    pub version:        String,
+    #[serde(default)]
+    pub metadata:       RunMetadata,
+    #[serde(default)]
+    pub build:          BuildInfo,
+    /// "bare-metal", a hypervisor name, or "unknown"
+    pub virtualization: String,
+    /// Each workload alone, before the contended phase
+    pub solo_secs:      u64,
+    pub duration_secs:  u64,
+    pub results:         Vec<InterferenceEntry>,
+    pub events:          Vec<EventReport>,
+    /// Pages swapped in or out during the run (None = not observable here)
+    #[serde(default)]
+    pub swap_pages:      Option<u64>,
+    /// Set when the per-thread buffer was rounded down to a power of two
+    /// (None = used as given, or --exact-buffer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_rounding: Option<BufferRounding>,
+}
+
+/// One thread count of a `--bandwidth-scaling` sweep.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingEntry {
+    pub threads:               usize,
+    pub gb_per_sec:            f64,
+    pub gb_per_sec_per_thread: f64,
+    /// Against the one-thread step (None = it measured nothing)
+    pub speedup:               Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingReport {
+    pub version:        String,
+    #[serde(default)]
+    pub metadata:       RunMetadata,
+    #[serde(default)]
+    pub build:          BuildInfo,
+    /// "bare-metal", a hypervisor name, or "unknown"
+    pub virtualization: String,
+    /// Measured seconds per step
+    pub duration_secs:  u64,
+    pub results:        Vec<ScalingEntry>,
+    /// Fewest threads within reach of the best bandwidth
+    pub knee_threads:    Option<usize>,
+    pub events:          Vec<EventReport>,
+    /// Pages swapped in or out during the run (None = not observable here)
+    #[serde(default)]
+    pub swap_pages:      Option<u64>,
+    /// Set when the per-thread buffer was rounded down to a power of two
+    /// (None = used as given, or --exact-buffer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_rounding: Option<BufferRounding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanGroup {
+    pub workload:   String,
+    pub threads:    usize,
+    pub batch_size: u64,
+    /// Per-thread buffer (0 = compute-only)
+    pub buffer_mb:  usize,
+}
+
+/// What `--dry-run` resolved; nothing was started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanReport {
+    pub version:        String,
+    #[serde(default)]
+    pub metadata:       RunMetadata,
+    #[serde(default)]
+    pub build:          BuildInfo,
+    pub virtualization: String,
+    pub mode:           String,
+    pub threads:        usize,
+    /// 0 = until Ctrl+C
+    pub duration_secs:  u64,
+    pub numa:           String,
+    pub groups:         Vec<PlanGroup>,
+    /// Largest amount of buffer memory live at once
+    pub peak_memory_mb: usize,
+    /// What the run would fail on or confirm first (an unaddressable
+    /// buffer, a peak past RAM or the cgroup limit)
+    #[serde(default)]
+    pub warnings:       Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_report_round_trips_through_file() {
+        let report = SingleReport {
+            version:               "test".to_string(),
+            metadata:              RunMetadata::default(),
+            build:                 BuildInfo::default(),
+            virtualization:        "bare-metal".to_string(),
+            threads:               2,
+            elapsed_secs:          1.5,
+            total_ops:             3_000,
+            ops_per_sec:           2_000,
+            peak_ops_per_sec:      Some(2_500),
+            sustained_ops_per_sec: Some(1_900),
+            rate_percentiles:      None,
+            rate_histogram:        Vec::new(),
+            cpu_efficiency:        Some(0.98),
+            groups:                vec![GroupReport {
+                workload:       "memory-bandwidth".to_string(),
+                threads:        2,
+                total_ops:      3_000,
+                ops_per_sec:    2_000,
+                gb_per_sec:     Some(0.25),
+                cpu_efficiency: Some(0.98),
+            }],
+            workers:               vec![WorkerReport {
+                name:           "locus-w0".to_string(),
+                workload:       "memory-bandwidth".to_string(),
+                wall_secs:      1.5,
+                cpu_secs:       None,
+                cpu_efficiency: None,
+                ops_per_sec:    0,
+                cpu:            None,
+                core_class:     None,
+            }],
+            events:                Vec::new(),
+            swap_pages:            Some(0),
+            process_cpu_secs:      Some(5.9),
+            allocation_secs:       Some(0.02),
+            planned_memory_mb:     128,
+            buffer_rounding:       None,
+            allocated_bytes:       None,
+            peak_rss_bytes:        Some(140 << 20),
+        };
+
+        let path = std::env::temp_dir().join(format!("locus-out-{}.json", std::process::id()));
+        let mut sink = Sink::open(Some(&path)).unwrap();
+        let written = write_json(sink.writer(), &report);
+        sink.finish(written).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let parsed: SingleReport = serde_json::from_str(&contents).unwrap();
+        assert_eq!(parsed.total_ops, 3_000);
+        assert_eq!(parsed.groups[0].workload, "memory-bandwidth");
+        assert_eq!(parsed.groups[0].gb_per_sec, Some(0.25));
+        assert_eq!(parsed.workers[0].cpu_secs, None);
+        assert_eq!(parsed.peak_rss_bytes, Some(140 << 20));
+
+        // Files saved before metadata and build info existed still load
+        let mut old: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        let fields = old.as_object_mut().unwrap();
+        fields.remove("metadata");
+        fields.remove("build");
+        let parsed: SingleReport = serde_json::from_value(old).unwrap();
+        assert_eq!(parsed.build, BuildInfo::default());
+    }
+
+    #[test]
+    fn test_run_id_is_a_version_4_uuid() {
+        let id = new_run_id();
+        let groups: Vec<&str> = id.split('-').collect();
+        assert_eq!(groups.iter().map(|g| g.len()).collect::<Vec<_>>(), [
+            8, 4, 4, 4, 12
+        ]);
+        assert!(id.chars().all(|c| c == '-' || c.is_ascii_hexdigit()));
+        assert!(groups[2].starts_with('4'));
+        assert!(matches!(groups[3].as_bytes()[0], b'8' | b'9' | b'a' | b'b'));
+        assert_ne!(new_run_id(), id);
+    }
+
+    #[test]
+    fn test_metadata_without_run_id_or_config_still_parses() {
+        let json = r#"{"hostname": "old", "timestamp": "2025-01-01T00:00:00Z"}"#;
+        let metadata: RunMetadata = serde_json::from_str(json).unwrap();
+        assert_eq!(metadata.hostname, "old");
+        assert!(metadata.run_id.is_empty());
+        assert!(metadata.config.is_empty());
+    }
+
+    #[test]
+    fn test_build_info_is_filled_in_at_build_time() {
+        let build = build_info();
+        assert!(!build.commit.is_empty());
+        assert!(build.rustc.starts_with("rustc"), "{}", build.rustc);
+        assert!(
+            build.target.contains(std::env::consts::ARCH),
+            "{}",
+            build.target
+        );
+        assert!(!build.profile.is_empty());
+        assert!(!build.opt_level.is_empty());
+    }
+
+    #[test]
+    fn test_junit_escapes_and_counts() {
+        let case = |name: &str| JunitCase {
+            name:       name.to_string(),
+            time_secs:  1.25,
+            failure:    None,
+            skipped:    None,
+            system_out: String::new(),
+        };
+        let suite = JunitSuite {
+            name:       "locus.bench".to_string(),
+            timestamp:  "2026-01-01T00:00:00Z".to_string(),
+            hostname:   "a&b".to_string(),
+            properties: vec![("threads".to_string(), "4".to_string())],
+            cases:      vec![
+                JunitCase {
+                    system_out: "rate: 5 ops/s\nper thread: 1 ops/s".to_string(),
+                    ..case("integer")
+                },
+                JunitCase {
+                    failure: Some("w1 failed: <bad> \"value\" it's\u{1}".to_string()),
+                    skipped: Some("outranked".to_string()),
+                    ..case("float")
+                },
+                JunitCase {
+                    skipped: Some("not run".to_string()),
+                    ..case("mixed")
+                },
+            ],
+        };
+        let mut out = Vec::new();
+        write_junit(&mut out, &suite).unwrap();
+        let xml = String::from_utf8(out).unwrap();
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        let counts = "tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\" time=\"3.750\"";
+        assert_eq!(xml.matches(counts).count(), 2, "{}", xml);
+        assert!(xml.contains("hostname=\"a&amp;b\""), "{}", xml);
+        assert!(xml.contains("<property name=\"threads\" value=\"4\"/>"), "{}", xml);
+        let failure = "w1 failed: &lt;bad&gt; &quot;value&quot; it&apos;s";
+        assert!(
+            xml.contains(&format!("<failure message=\"{}\">{}</failure>", failure, failure)),
+            "{}",
+            xml
+        );
+        assert!(!xml.contains("outranked"), "{}", xml);
+        assert!(xml.contains("<skipped message=\"not run\"/>"), "{}", xml);
+        assert!(
+            xml.contains("<system-out>rate: 5 ops/s\nper thread: 1 ops/s</system-out>"),
+            "{}",
+            xml
+        );
+        assert!(xml.ends_with("  </testsuite>\n</testsuites>\n"));
+        assert_eq!(escape_xml("\u{0}\u{1f}\t\u{fffe}é"), "\té");
+    }
+
+    #[test]
+    fn test_open_fails_for_missing_directory() {
+        let path = std::env::temp_dir()
+            .join("locus-definitely-missing-dir")
+            .join("out.json");
+        assert!(Sink::open(Some(&path)).is_err());
+    }
+}
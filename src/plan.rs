@@ -0,0 +1,177 @@
+use std::io::{self, Write};
+
+use crate::assignment::WorkloadGroup;
+use crate::output::{self, PlanGroup, PlanReport};
+use crate::{system, workload};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Single,
+    /// Workloads run one after another, so only one group is live at a time
+    Benchmark,
+    LoadedLatency,
+    /// Each side alone, then both at once, so the peak is the pair
+    Interference,
+    /// One workload on more threads each step; the last step is the peak
+    BandwidthScaling,
+}
+
+impl Mode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Benchmark => "benchmark",
+            Self::LoadedLatency => "loaded-latency",
+            Self::Interference => "interference",
+            Self::BandwidthScaling => "bandwidth-scaling",
+        }
+    }
+}
+
+/// Everything a run would do, resolved but not started.
+pub fn build_plan(
+    mode: Mode,
+    groups: &[WorkloadGroup],
+    memory_mb: usize,
+    batch_size: Option<u64>,
+    duration_secs: u64,
+    numa: String,
+) -> PlanReport {
+    let plan_groups: Vec<PlanGroup> = groups
+        .iter()
+        .map(|group| PlanGroup {
+            workload:   group.spec.name.to_string(),
+            threads:    group.threads,
+            batch_size: group.spec.batch_size(batch_size),
+            buffer_mb:  group.buffer_mb(memory_mb),
+        })
+        .collect();
+
+    // Saturating: -m takes any usize, and the plan still has to print
+    let group_total = |g: &PlanGroup| g.buffer_mb.saturating_mul(g.threads);
+    let peak_memory_mb = match mode {
+        Mode::Benchmark => plan_groups.iter().map(group_total).max().unwrap_or(0),
+        Mode::Single | Mode::LoadedLatency | Mode::Interference | Mode::BandwidthScaling => {
+            plan_groups.iter().map(group_total).fold(0, usize::saturating_add)
+        },
+    };
+    let threads = match mode {
+        Mode::Benchmark => groups.iter().map(|g| g.threads).max().unwrap_or(0),
+        Mode::Single | Mode::LoadedLatency | Mode::Interference | Mode::BandwidthScaling => {
+            groups.iter().map(|g| g.threads).sum()
+        },
+    };
+
+    PlanReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        metadata: output::run_metadata(),
+        build: output::build_info(),
+        virtualization: system::detect_virtualization().describe(),
+        mode: mode.as_str().to_string(),
+        threads,
+        duration_secs,
+        numa,
+        groups: plan_groups,
+        peak_memory_mb,
+        warnings: Vec::new(),
+    }
+}
+
+/// What the real run would stop on: a buffer this target can't address
+/// fails its workers, and a peak past RAM or the cgroup limit asks first.
+pub fn warnings(
+    plan: &PlanReport,
+    total_ram_mb: Option<usize>,
+    cgroup_limit_mb: Option<usize>,
+) -> Vec<String> {
+    let unaddressable = plan
+        .groups
+        .iter()
+        .filter(|g| g.buffer_mb > 0)
+        .filter_map(|g| {
+            let err = workload::buffer_bytes(g.buffer_mb).err()?;
+            Some(format!("{}: {}", g.workload, err))
+        });
+    let too_large = system::large_allocation(plan.peak_memory_mb, total_ram_mb, cgroup_limit_mb)
+        .map(|reason| format!("Peak allocation of {} MB {}", plan.peak_memory_mb, reason));
+    unaddressable.chain(too_large).collect()
+}
+
+pub fn write_plan(out: &mut dyn Write, plan: &PlanReport) -> io::Result<()> {
+    writeln!(out, "[Dry run] Resolved {} plan:", plan.mode)?;
+    for group in &plan.groups {
+        let buffer = if group.buffer_mb > 0 {
+            format!("{} MB buffer each", group.buffer_mb)
+        } else {
+            "no buffer".to_string()
+        };
+        writeln!(
+            out,
+            "  {:<18} ×{:<4} batch {:<8} {}",
+            group.workload, group.threads, group.batch_size, buffer
+        )?;
+    }
+    writeln!(out, "  Peak allocation: {} MB", plan.peak_memory_mb)?;
+    for warning in &plan.warnings {
+        writeln!(out, "  [Warning] {}", warning)?;
+    }
+    writeln!(out, "[Dry run] No workers started.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    fn group(name: &str, threads: usize) -> WorkloadGroup {
+        WorkloadGroup {
+            spec: registry::find(name).unwrap(),
+            threads,
+        }
+    }
+
+    #[test]
+    fn test_single_plan_sums_buffered_groups() {
+        let groups = [group("integer", 4), group("memory-bandwidth", 4)];
+        let plan = build_plan(Mode::Single, &groups, 64, None, 10, "local".into());
+
+        assert_eq!(plan.threads, 8);
+        assert_eq!(plan.peak_memory_mb, 4 * 64);
+        assert_eq!(plan.groups[0].buffer_mb, 0);
+        assert_eq!(plan.groups[1].batch_size, groups[1].spec.default_batch);
+
+        // Any -m parses, so the total saturates instead of wrapping
+        let huge = build_plan(Mode::Single, &groups, usize::MAX, None, 10, "local".into());
+        assert_eq!(huge.peak_memory_mb, usize::MAX);
+    }
+
+    #[test]
+    fn test_benchmark_plan_peaks_at_one_workload() {
+        let groups = [
+            group("integer", 8),
+            group("mixed", 8),
+            group("memory-latency", 8),
+        ];
+        let plan = build_plan(Mode::Benchmark, &groups, 32, Some(500), 5, "local".into());
+
+        assert_eq!(plan.threads, 8);
+        assert_eq!(plan.peak_memory_mb, 8 * 32);
+        assert!(plan.groups.iter().all(|g| g.batch_size == 500));
+    }
+
+    #[test]
+    fn test_warnings_match_what_the_run_would_stop_on() {
+        let groups = [group("integer", 2), group("memory-latency", 2)];
+        let plan = build_plan(Mode::Single, &groups, 64, None, 10, "local".into());
+        assert!(warnings(&plan, Some(16 * 1024), None).is_empty());
+
+        let over = warnings(&plan, Some(16 * 1024), Some(100));
+        assert_eq!(over, ["Peak allocation of 128 MB exceeds the 100 MB cgroup memory limit"]);
+
+        let huge = build_plan(Mode::Single, &groups, usize::MAX, None, 10, "local".into());
+        let huge = warnings(&huge, Some(16 * 1024), None);
+        assert_eq!(huge.len(), 2, "{:?}", huge);
+        assert!(huge[0].starts_with("memory-latency: requested size"), "{}", huge[0]);
+        assert!(huge[1].contains("of system RAM"), "{}", huge[1]);
+    }
+}
@@ -0,0 +1,117 @@
+//! A built-in yardstick for `bench` results when there's no earlier run to
+//! compare against: each workload's rate as a percentage of one thread on a
+//! fixed reference machine, and a geometric-mean index over them.
+
+use crate::workload::{FloatMode, InitPattern, KernelParams};
+
+/// The machine the rates below were measured on. A VM, so treat the index
+/// as a scale rather than a claim about any product.
+pub const REFERENCE_MACHINE: &str = "1 vCPU of a KVM guest reporting \"Intel(R) Xeon(R) \
+                                     Processor\" (AVX-512, 48 KiB L1d, 2 MiB L2)";
+
+/// Short form for the table footnote.
+pub const REFERENCE_LABEL: &str = "1 thread of a KVM Xeon vCPU";
+
+/// ops/s of one worker on [`REFERENCE_MACHINE`]: `locus bench -j 1 -d 8`,
+/// release build of 1.4.3, default set, 1 GiB auto-sized buffer, mean of
+/// two runs. Only the default set is covered, so the index means the same
+/// thing in every run that has it.
+pub const REFERENCE_RATES: &[(&str, u64)] = &[
+    ("integer", 960_000_000),
+    ("float", 38_800_000),
+    ("mixed", 17_300_000),
+    ("memory-latency", 6_800_000),
+    ("memory-bandwidth", 6_200_000),
+];
+
+/// Whether kernels built from `params` are the ones the reference rates
+/// were measured with. Any other kernel option measures something else, so
+/// a percentage of the reference would look precise and mean nothing.
+pub fn kernels_match(params: &KernelParams) -> bool {
+    params.chains == 1
+        && params.float_mode == FloatMode::default()
+        && params.prefetch == 0
+        && params.pattern == InitPattern::default()
+        && params.access.is_default()
+}
+
+/// `ops_per_sec` as a percentage of the reference rate (None = the
+/// workload has none).
+pub fn percent_of_reference(workload: &str, ops_per_sec: u64) -> Option<f64> {
+    let (_, reference) = REFERENCE_RATES.iter().find(|(name, _)| *name == workload)?;
+    Some(ops_per_sec as f64 / *reference as f64 * 100.0)
+}
+
+/// Geometric mean of the percentages over every reference workload, so 100
+/// is the reference machine and 800 is eight of it. None unless `rates`
+/// covers all of them with something measured: an index over a subset
+/// wouldn't compare with one over the full set.
+pub fn reference_index<'a>(rates: impl IntoIterator<Item = (&'a str, u64)>) -> Option<f64> {
+    let rates: Vec<(&str, u64)> = rates.into_iter().collect();
+    let logs: Vec<f64> = REFERENCE_RATES
+        .iter()
+        .map(|(name, _)| {
+            let &(_, ops_per_sec) = rates.iter().find(|(workload, _)| workload == name)?;
+            let percent = percent_of_reference(name, ops_per_sec)?;
+            (percent > 0.0).then(|| percent.ln())
+        })
+        .collect::<Option<_>>()?;
+    Some((logs.iter().sum::<f64>() / logs.len() as f64).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_rates_score_100() {
+        for &(name, ops_per_sec) in REFERENCE_RATES {
+            assert!(crate::registry::find(name).is_some(), "{}", name);
+            assert_eq!(percent_of_reference(name, ops_per_sec), Some(100.0));
+        }
+        let index = reference_index(REFERENCE_RATES.iter().copied()).unwrap();
+        assert!((index - 100.0).abs() < 1e-9, "{}", index);
+
+        // Twice as fast everywhere is twice the index
+        let doubled = REFERENCE_RATES.iter().map(|&(name, ops)| (name, ops * 2));
+        assert!((reference_index(doubled).unwrap() - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_index_needs_every_reference_workload() {
+        assert_eq!(percent_of_reference("collatz", 1000), None);
+        assert_eq!(reference_index(REFERENCE_RATES[1..].iter().copied()), None);
+        let stalled = REFERENCE_RATES.iter().map(|&(name, ops)| {
+            (name, if name == "mixed" { 0 } else { ops })
+        });
+        assert_eq!(reference_index(stalled), None);
+    }
+
+    #[test]
+    fn test_only_default_kernels_match() {
+        let params = KernelParams {
+            seed: 9,
+            ..KernelParams::default()
+        };
+        assert!(kernels_match(&params));
+        assert!(!kernels_match(&KernelParams {
+            float_mode: FloatMode::Simple,
+            ..params
+        }));
+        assert!(!kernels_match(&KernelParams {
+            pattern: InitPattern::Cycle,
+            ..params
+        }));
+        assert!(!kernels_match(&KernelParams {
+            prefetch: 8,
+            ..params
+        }));
+        assert!(!kernels_match(&KernelParams {
+            access: crate::workload::AccessPattern {
+                stride: Some(3),
+                ..params.access
+            },
+            ..params
+        }));
+    }
+}
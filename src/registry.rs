@@ -0,0 +1,443 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::output::notice;
+use crate::workload::{
+    CacheThrash,
+    Collatz,
+    Float,
+    Integer,
+    IntegerLatency,
+    KernelParams,
+    MemoryBandwidth,
+    MemoryLatency,
+    Mixed,
+    Recursion,
+    STREAMS_PER_OP,
+    Workload,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Compute,
+    Memory,
+    Mixed,
+}
+
+impl Category {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compute => "compute",
+            Self::Memory => "memory",
+            Self::Mixed => "mixed",
+        }
+    }
+}
+
+/// What a workload keeps hot, as opposed to what it allocates. Assumes the
+/// kernels as written: every buffer index is derived from a pseudo-random
+/// value modulo the buffer length, so over any run longer than a few batches
+/// every cache line of the buffer is touched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkingSet {
+    /// Accumulators in registers plus a few stack frames; no buffer traffic
+    Registers,
+    /// Random accesses spread over the whole per-thread buffer
+    WholeBuffer,
+    /// Sequential sweeps over twice the `--target` cache level, in a buffer
+    /// of its own rather than the -m/-x one
+    CacheSweep,
+}
+
+impl WorkingSet {
+    /// Hot bytes per thread for a buffer of `buffer_mb`.
+    pub fn bytes(self, buffer_mb: usize) -> u64 {
+        match self {
+            Self::Registers | Self::CacheSweep => 0,
+            Self::WholeBuffer => (buffer_mb as u64).saturating_mul(1024 * 1024),
+        }
+    }
+
+    pub fn pattern(self) -> &'static str {
+        match self {
+            Self::Registers => "registers only",
+            Self::WholeBuffer => "random over the whole buffer",
+            Self::CacheSweep => "sequential sweeps over twice the --target cache",
+        }
+    }
+}
+
+/// Everything the rest of the tool needs to know about a workload.
+/// Adding a workload means adding one entry to [`WORKLOADS`].
+#[derive(Debug)]
+pub struct WorkloadSpec {
+    pub name:          &'static str,
+    pub aliases:       &'static [&'static str],
+    pub description:   &'static str,
+    pub needs_buffer:  bool,
+    /// Bytes moved per op, for the GB/s estimate (None = not a memory workload)
+    pub bytes_per_op:  Option<u64>,
+    /// Rough cost of one op relative to one integer op
+    pub op_weight:     f64,
+    pub category:      Category,
+    pub working_set:   WorkingSet,
+    /// Iterations between stop checks when --batch-size isn't given, sized
+    /// to keep stop checks at most a few milliseconds apart
+    pub default_batch: u64,
+    pub build:         fn(&KernelParams) -> Box<dyn Workload>,
+}
+
+impl WorkloadSpec {
+    /// "memory-latency" → "Memory-Latency"
+    pub fn display_name(&self) -> String {
+        self.name
+            .split('-')
+            .map(|part| {
+                let mut chars = part.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                    None => String::new(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    pub fn batch_size(&self, requested: Option<u64>) -> u64 {
+        requested.unwrap_or(self.default_batch)
+    }
+
+    /// The one place a workload is built from its entry; every mode's
+    /// workers get theirs here.
+    pub fn create(&self, params: &KernelParams) -> Box<dyn Workload> {
+        (self.build)(params)
+    }
+
+    /// The code path workers built from `params` take (see
+    /// [`Workload::implementation`]); building a kernel allocates nothing.
+    pub fn implementation(&self, params: &KernelParams) -> Option<&'static str> {
+        self.create(params).implementation()
+    }
+}
+
+/// Registry order is also the benchmark order.
+pub const WORKLOADS: &[WorkloadSpec] = &[
+    WorkloadSpec {
+        name:          "integer",
+        aliases:       &[],
+        description:   "Pure CPU integer arithmetic",
+        needs_buffer:  false,
+        bytes_per_op:  None,
+        op_weight:     1.0,
+        category:      Category::Compute,
+        working_set:   WorkingSet::Registers,
+        default_batch: 100_000,
+        build:         |params| Box::new(Integer::new(params)),
+    },
+    WorkloadSpec {
+        name:          "integer-latency",
+        aliases:       &[],
+        description:   "Dependent integer multiply chain (latency, not throughput)",
+        needs_buffer:  false,
+        bytes_per_op:  None,
+        op_weight:     1.0,
+        category:      Category::Compute,
+        working_set:   WorkingSet::Registers,
+        default_batch: 100_000,
+        build:         |params| Box::new(IntegerLatency::new(params)),
+    },
+    WorkloadSpec {
+        name:          "float",
+        aliases:       &[],
+        description:   "Pure CPU floating-point math",
+        needs_buffer:  false,
+        bytes_per_op:  None,
+        op_weight:     30.0,
+        category:      Category::Compute,
+        working_set:   WorkingSet::Registers,
+        default_batch: 50_000,
+        build:         |params| Box::new(Float::new(params)),
+    },
+    WorkloadSpec {
+        name:          "recursion",
+        aliases:       &[],
+        description:   "Bounded Ackermann recursion (call/return, stack)",
+        needs_buffer:  false,
+        bytes_per_op:  None,
+        op_weight:     30.0,
+        category:      Category::Compute,
+        working_set:   WorkingSet::Registers,
+        default_batch: 20_000,
+        build:         |params| Box::new(Recursion::new(params)),
+    },
+    WorkloadSpec {
+        name:          "collatz",
+        aliases:       &[],
+        description:   "Collatz sequences (data-dependent branches)",
+        needs_buffer:  false,
+        bytes_per_op:  None,
+        op_weight:     50.0,
+        category:      Category::Compute,
+        working_set:   WorkingSet::Registers,
+        default_batch: 20_000,
+        build:         |params| Box::new(Collatz::new(params)),
+    },
+    WorkloadSpec {
+        name:          "mixed",
+        aliases:       &[],
+        description:   "Integer + float + memory-latency",
+        needs_buffer:  true,
+        bytes_per_op:  None,
+        op_weight:     40.0,
+        category:      Category::Mixed,
+        working_set:   WorkingSet::WholeBuffer,
+        default_batch: 100_000,
+        build:         |params| Box::new(Mixed::new(params)),
+    },
+    WorkloadSpec {
+        name:          "memory-latency",
+        aliases:       &["memory"],
+        description:   "RAM latency test (pointer chasing)",
+        needs_buffer:  true,
+        // 1 read + 1 write × 8 bytes
+        bytes_per_op:  Some(2 * 8),
+        op_weight:     100.0,
+        category:      Category::Memory,
+        working_set:   WorkingSet::WholeBuffer,
+        default_batch: 50_000,
+        build:         |params| Box::new(MemoryLatency::new(params)),
+    },
+    WorkloadSpec {
+        name:          "memory-bandwidth",
+        aliases:       &[],
+        description:   "RAM bandwidth saturation (parallel streams)",
+        needs_buffer:  true,
+        // 8 stream accesses × (1 read + 1 write) × 8 bytes, whatever the
+        // target's stream count
+        bytes_per_op:  Some(STREAMS_PER_OP as u64 * 2 * 8),
+        op_weight:     400.0,
+        category:      Category::Memory,
+        working_set:   WorkingSet::WholeBuffer,
+        default_batch: 20_000,
+        build:         |params| Box::new(MemoryBandwidth::new(params)),
+    },
+    WorkloadSpec {
+        name:          "cache-thrash",
+        aliases:       &[],
+        description:   "Cache refill cost (sweeps 2× the --target level)",
+        // Sized from the cache, not -m/-x
+        needs_buffer:  false,
+        // 1 read + 1 write × 8 bytes
+        bytes_per_op:  Some(2 * 8),
+        op_weight:     1.0,
+        category:      Category::Memory,
+        working_set:   WorkingSet::CacheSweep,
+        default_batch: 1_000_000,
+        build:         |params| Box::new(CacheThrash::new(params)),
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static WorkloadSpec> {
+    WORKLOADS
+        .iter()
+        .find(|spec| spec.name == name || spec.aliases.contains(&name))
+}
+
+/// Set once the deprecation notice has been printed, so mixing `-w`,
+/// `--split` and a config file still warns only once per run
+static ALIAS_NOTICE_SHOWN: AtomicBool = AtomicBool::new(false);
+
+/// Looks up a user-supplied name. Aliases are deprecated: they still resolve,
+/// but the first one seen prints a notice. Record `spec.name`, never `name`.
+pub fn resolve(name: &str) -> Option<&'static WorkloadSpec> {
+    let spec = find(name)?;
+    if let Some(notice) = alias_notice(name, spec, &ALIAS_NOTICE_SHOWN) {
+        notice!("{}", notice);
+    }
+    Some(spec)
+}
+
+fn alias_notice(name: &str, spec: &WorkloadSpec, shown: &AtomicBool) -> Option<String> {
+    if name == spec.name || shown.swap(true, Ordering::Relaxed) {
+        return None;
+    }
+    Some(format!(
+        "Warning: workload '{}' is deprecated, use '{}' instead",
+        name, spec.name
+    ))
+}
+
+/// Canonical names followed by aliases, for CLI validation.
+pub fn accepted_names() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = WORKLOADS.iter().map(|spec| spec.name).collect();
+    names.extend(
+        WORKLOADS
+            .iter()
+            .flat_map(|spec| spec.aliases.iter().copied()),
+    );
+    names
+}
+
+/// BATCH is the default --batch-size for that workload.
+pub fn print_workload_list() {
+    println!(
+        "{:<18} {:<8} {:<6} {:>7} {:>8} {:>8}  DESCRIPTION",
+        "NAME", "CATEGORY", "BUFFER", "B/OP", "WEIGHT", "BATCH"
+    );
+    for spec in WORKLOADS {
+        let bytes = spec
+            .bytes_per_op
+            .map(|b| b.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let mut description = spec.description.to_string();
+        if !spec.aliases.is_empty() {
+            description += &format!(" (deprecated alias: {})", spec.aliases.join(", "));
+        }
+        println!(
+            "{:<18} {:<8} {:<6} {:>7} {:>7.0}x {:>8}  {}",
+            spec.name,
+            spec.category.as_str(),
+            match (spec.working_set, spec.needs_buffer) {
+                (WorkingSet::CacheSweep, _) => "sweep",
+                (_, true) => "yes",
+                (_, false) => "no",
+            },
+            bytes,
+            spec.op_weight,
+            spec.default_batch,
+            description
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workload::{BANDWIDTH_STREAMS, InitPattern, allocate_buffer_bytes};
+
+    #[test]
+    fn test_find_resolves_aliases() {
+        assert_eq!(find("memory").map(|s| s.name), Some("memory-latency"));
+        assert_eq!(find("float").map(|s| s.name), Some("float"));
+        assert!(find("bogus").is_none());
+    }
+
+    #[test]
+    fn test_alias_notice_fires_once() {
+        let shown = AtomicBool::new(false);
+        let spec = find("memory").unwrap();
+
+        assert_eq!(alias_notice("memory-latency", spec, &shown), None);
+        let notice = alias_notice("memory", spec, &shown).unwrap();
+        assert!(notice.contains("'memory' is deprecated"));
+        assert!(notice.contains("'memory-latency'"));
+        assert_eq!(alias_notice("memory", spec, &shown), None);
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        let names = accepted_names();
+        for (i, name) in names.iter().enumerate() {
+            assert!(!names[i + 1..].contains(name), "duplicate name {}", name);
+        }
+    }
+
+    #[test]
+    fn test_bandwidth_ops_count_eight_stream_accesses() {
+        let spec = find("memory-bandwidth").unwrap();
+        assert_eq!(spec.bytes_per_op, Some(128));
+
+        // More streams per iteration means more ops per iteration, not
+        // bigger ones
+        let mut kernel = spec.create(&KernelParams::default());
+        kernel.attach_buffer(allocate_buffer_bytes(64 << 10, InitPattern::Sequential).unwrap());
+        let ops = 1000 * (BANDWIDTH_STREAMS / STREAMS_PER_OP) as u64;
+        assert_eq!(kernel.run(1000), ops);
+    }
+
+    #[test]
+    fn test_memory_workloads_convert_to_bandwidth() {
+        let bytes = |name| find(name).unwrap().bytes_per_op;
+        // One 8-byte read and one write per op
+        assert_eq!(bytes("memory-latency"), Some(16));
+        assert_eq!(bytes("cache-thrash"), Some(16));
+        assert_eq!(bytes("memory-bandwidth"), Some(128));
+        for spec in WORKLOADS {
+            if spec.category == Category::Compute {
+                assert_eq!(spec.bytes_per_op, None, "{}", spec.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_working_set_follows_the_buffer() {
+        for spec in WORKLOADS {
+            let expected = if spec.needs_buffer {
+                64 * 1024 * 1024
+            } else {
+                0
+            };
+            assert_eq!(spec.working_set.bytes(64), expected, "{}", spec.name);
+        }
+        assert_eq!(
+            find("memory-latency").unwrap().working_set,
+            WorkingSet::WholeBuffer
+        );
+        assert_eq!(find("collatz").unwrap().working_set, WorkingSet::Registers);
+    }
+
+    #[test]
+    fn test_every_workload_builds_and_runs() {
+        for pattern in [InitPattern::Sequential, InitPattern::Cycle] {
+            let params = KernelParams {
+                chains: 2,
+                pattern,
+                ..KernelParams::default()
+            };
+            for spec in WORKLOADS {
+                let mut kernel = spec.create(&params);
+                let sweeps = spec.working_set == WorkingSet::CacheSweep;
+                assert_eq!(
+                    kernel.needs_buffer(),
+                    spec.needs_buffer || sweeps,
+                    "{}",
+                    spec.name
+                );
+                if kernel.needs_buffer() {
+                    kernel.attach_buffer(allocate_buffer_bytes(64 << 10, pattern).unwrap());
+                }
+                assert!(kernel.run(300) > 0, "{} did no work", spec.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed_counts_every_op_in_the_batch() {
+        let mut kernel = find("mixed").unwrap().create(&KernelParams::default());
+        kernel.attach_buffer(allocate_buffer_bytes(64 << 10, InitPattern::Sequential).unwrap());
+        assert_eq!(kernel.run(1000), 1000);
+        assert_eq!(kernel.run(900), 900);
+    }
+
+    #[test]
+    fn test_default_batch_sizes_positive() {
+        for spec in WORKLOADS {
+            assert!(spec.default_batch > 0, "{} has no default batch", spec.name);
+        }
+    }
+
+    #[test]
+    fn test_batch_size_override() {
+        let spec = find("float").unwrap();
+        assert_eq!(spec.batch_size(None), spec.default_batch);
+        assert_eq!(spec.batch_size(Some(7)), 7);
+    }
+
+    #[test]
+    fn test_display_name() {
+        assert_eq!(
+            find("memory-latency").unwrap().display_name(),
+            "Memory-Latency"
+        );
+        assert_eq!(find("integer").unwrap().display_name(), "Integer");
+    }
+}
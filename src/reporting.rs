@@ -1,10 +1,85 @@
+use std::collections::HashMap;
 use std::io::Write;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::thread;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
+use crate::output::{
+    BufferRounding,
+    HistogramBin,
+    ProgressFormat,
+    ProgressSnapshot,
+    RatePercentiles,
+    WorkerReport,
+    notice,
+};
+use crate::signal;
+
+/// UTC RFC 3339 with second precision, e.g. "2024-03-09T14:05:00Z".
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, day_secs) = (secs / 86_400, secs % 86_400);
+
+    // Civil-from-days (Howard Hinnant), shifted so years start in March
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        day_secs / 3600,
+        day_secs / 60 % 60,
+        day_secs % 60
+    )
+}
+
+/// Elapsed time for people: "9.87s" under a minute, whole seconds above it,
+/// e.g. "4m 5s" or "6h 0m 12s". Reports keep raw seconds.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    // Compare the rounded value so 59.999s reads "1m 0s", not "60.00s"
+    if (secs * 100.0).round() < 6000.0 {
+        return format!("{:.2}s", secs);
+    }
+    let total = duration.as_secs() + u64::from(duration.subsec_millis() >= 500);
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else {
+        format!("{}m {}s", minutes, seconds)
+    }
+}
+
+/// Set by --exact-numbers: counts print in full rather than abbreviated.
+static EXACT_NUMBERS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_exact_numbers(exact: bool) {
+    EXACT_NUMBERS.store(exact, Ordering::Relaxed);
+}
+
+/// Op counts and rates for the terminal: "12.35B", or "12,345,678,901"
+/// under --exact-numbers.
 pub fn format_number(n: u64) -> String {
+    if EXACT_NUMBERS.load(Ordering::Relaxed) {
+        group_digits(n)
+    } else {
+        abbreviate_number(n)
+    }
+}
+
+fn abbreviate_number(n: u64) -> String {
     if n >= 1_000_000_000 {
         format!("{:.2}B", n as f64 / 1_000_000_000.0)
     } else if n >= 1_000_000 {
@@ -16,39 +91,1099 @@ pub fn format_number(n: u64) -> String {
     }
 }
 
-pub fn progress_reporter(stop_signal: Arc<AtomicBool>, work_counter: Arc<AtomicU64>) {
-    let mut last_ops = 0u64;
+/// Comma every three digits, whatever the locale.
+fn group_digits(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+const BINARY_UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+/// Sizes in binary units, two decimals unless the value is whole:
+/// "192 MiB", "1.50 GiB". Buffer sizes are MiB throughout, whatever the
+/// flags call them.
+pub fn format_bytes(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    // Compare the rounded value so 1 GiB - 1 B prints as "1 GiB", not "1024 MiB"
+    while round_hundredths(value) >= 1024.0 && unit < BINARY_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    with_unit(round_hundredths(value), BINARY_UNITS[unit])
+}
+
+/// Buffer sizes are whole MiB wherever they're configured.
+pub fn format_mb(mb: usize) -> String {
+    format_bytes((mb as u64).saturating_mul(1024 * 1024))
+}
+
+/// No memory system sustains this; a figure above it means the op count
+/// or the bytes per op is wrong.
+pub const IMPLAUSIBLE_GB_PER_SEC: f64 = 100_000.0;
+
+/// Decimal GB/s for `ops` ops of `bytes_per_op` over `elapsed` (0 for an
+/// empty window). The byte total goes through u128: a multi-day bandwidth
+/// soak passes u64::MAX bytes long before its op counter fills.
+pub fn gb_per_sec(ops: u64, bytes_per_op: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        return 0.0;
+    }
+    let bytes = u128::from(ops) * u128::from(bytes_per_op);
+    bytes as f64 / elapsed.as_secs_f64() / 1e9
+}
+
+/// Bandwidth in decimal units, the convention for GB/s: "12.34 GB/s", or
+/// MB/s below 1 GB/s.
+pub fn format_rate_bytes(bytes_per_sec: f64) -> String {
+    let mb = round_hundredths(bytes_per_sec / 1e6);
+    if mb < 1000.0 {
+        format!("{:.2} MB/s", mb)
+    } else {
+        format!("{:.2} GB/s", bytes_per_sec / 1e9)
+    }
+}
+
+fn round_hundredths(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn with_unit(value: f64, unit: &str) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.0} {}", value, unit)
+    } else {
+        format!("{:.2} {}", value, unit)
+    }
+}
+
+pub const DEFAULT_RATE_SMOOTHING: f64 = 0.3;
+pub const RATE_SMOOTHING_RANGE: std::ops::RangeInclusive<f64> = 0.01..=1.0;
+
+/// Exponential moving average of the per-interval rate, so the progress
+/// line shows a trend rather than every uneven batch. `alpha` is the weight
+/// of the newest sample (1.0 = no smoothing); the first sample seeds it.
+#[derive(Debug, Clone, Copy)]
+pub struct RateEma {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl RateEma {
+    pub fn new(alpha: f64) -> Self {
+        Self { alpha, value: None }
+    }
+
+    pub fn update(&mut self, rate: u64) -> u64 {
+        let rate = rate as f64;
+        let next = match self.value {
+            None => rate,
+            Some(prev) => self.alpha * rate + (1.0 - self.alpha) * prev,
+        };
+        self.value = Some(next);
+        next.round() as u64
+    }
+
+    pub fn smooths(&self) -> bool {
+        self.alpha < 1.0
+    }
+}
+
+/// A live rate in the unit people read it in: bandwidth first when the
+/// workload moves a known number of bytes per op, then ops/s.
+pub fn format_rate(ops_per_sec: u64, bytes_per_op: Option<u64>) -> String {
+    format_rate_with_bandwidth(
+        ops_per_sec,
+        bytes_per_op.map(|bytes| ops_per_sec.saturating_mul(bytes)),
+    )
+}
+
+/// "41.20 GB/s, 321.88M ops/s", or "98.09M/s" without a byte count.
+fn format_rate_with_bandwidth(ops_per_sec: u64, bytes_per_sec: Option<u64>) -> String {
+    match bytes_per_sec {
+        Some(bytes) => format!(
+            "{}, {} ops/s",
+            format_rate_bytes(bytes as f64),
+            format_number(ops_per_sec)
+        ),
+        None => format!("{}/s", format_number(ops_per_sec)),
+    }
+}
+
+pub const DEFAULT_THROTTLE_ALERT_PCT: u64 = 20;
+/// Samples before the best rate counts: boost ramp-up and first-touch
+/// faults settle here.
+const THROTTLE_WARMUP_SAMPLES: usize = 5;
+/// Samples in a row the rate has to stay down, so one stall isn't an alert.
+const THROTTLE_SUSTAIN_SAMPLES: usize = 3;
+/// Samples between alerts: a minute at [`REPORT_INTERVAL`].
+const THROTTLE_ALERT_COOLDOWN: usize = 60;
+
+/// Watches the smoothed rate for a sustained fall from the best one seen
+/// after warmup. Alerts once per decline: the rate has to climb back over
+/// the threshold to re-arm, and alerts are at least a cooldown apart.
+#[derive(Debug, Clone)]
+pub struct ThrottleWatch {
+    drop_fraction: f64,
+    seen:          usize,
+    best:          u64,
+    below:         usize,
+    armed:         bool,
+    last_alert:    Option<usize>,
+}
+
+impl ThrottleWatch {
+    /// None when `drop_pct` is 0, which turns the alert off.
+    pub fn new(drop_pct: u64) -> Option<Self> {
+        (drop_pct > 0).then(|| Self {
+            drop_fraction: drop_pct as f64 / 100.0,
+            seen:          0,
+            best:          0,
+            below:         0,
+            armed:         true,
+            last_alert:    None,
+        })
+    }
+
+    /// Feeds one smoothed rate; Some(percent below the best) when it's time
+    /// to alert.
+    pub fn update(&mut self, rate: u64) -> Option<u64> {
+        self.seen += 1;
+        if self.seen <= THROTTLE_WARMUP_SAMPLES {
+            return None;
+        }
+        self.best = self.best.max(rate);
+        if (rate as f64) >= self.best as f64 * (1.0 - self.drop_fraction) {
+            self.below = 0;
+            self.armed = true;
+            return None;
+        }
+
+        self.below += 1;
+        let cooled = self
+            .last_alert
+            .is_none_or(|last| self.seen - last >= THROTTLE_ALERT_COOLDOWN);
+        if !(self.armed && cooled && self.below >= THROTTLE_SUSTAIN_SAMPLES) {
+            return None;
+        }
+        self.armed = false;
+        self.last_alert = Some(self.seen);
+        Some(((1.0 - rate as f64 / self.best as f64) * 100.0).round() as u64)
+    }
+}
+
+/// When `--log-status` blocks are due: every `every` of elapsed time. A
+/// late reporter tick catches up with one block, not a burst of them.
+#[derive(Debug, Clone)]
+pub struct StatusCadence {
+    every: Duration,
+    next:  Duration,
+}
+
+impl StatusCadence {
+    /// None when `every` is zero, which turns the blocks off.
+    pub fn new(every: Duration) -> Option<Self> {
+        (!every.is_zero()).then_some(Self { every, next: every })
+    }
+
+    pub fn due(&mut self, elapsed: Duration) -> bool {
+        if elapsed < self.next {
+            return false;
+        }
+        while self.next <= elapsed {
+            self.next += self.every;
+        }
+        true
+    }
+}
+
+/// The `--log-status` block: one line per figure, newline-separated so it
+/// reads in a log file where the `\r` progress line doesn't. Temperature
+/// is left out where it can't be read.
+fn format_status_block(
+    elapsed: Duration,
+    total_ops: u64,
+    rate: &str,
+    peak: &str,
+    temperature: Option<f64>,
+) -> String {
+    let mut block = format!(
+        "[Status] {}\n  Elapsed:     {}\n  Total ops:   {}\n  Rate:        {}\n  \
+         Peak rate:   {}",
+        format_rfc3339(SystemTime::now()),
+        format_duration(elapsed),
+        format_number(total_ops),
+        rate,
+        peak
+    );
+    if let Some(celsius) = temperature {
+        block += &format!("\n  CPU temp:    {:.1}°C", celsius);
+    }
+    block
+}
+
+/// One workload group as the progress line sees it.
+pub struct ProgressGroup {
+    pub name:         String,
+    pub counter:      Arc<AtomicU64>,
+    /// From the registry; set, the group's rate shows as bandwidth
+    pub bytes_per_op: Option<u64>,
+}
+
+/// Combined ops/sec for each reporter interval, in order.
+pub type RateSamples = Arc<Mutex<Vec<u64>>>;
+
+pub const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How the live progress looks.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressOptions {
+    /// None = sample without printing
+    pub print:              Option<ProgressFormat>,
+    /// EMA alpha for the text line (1.0 = raw rate only)
+    pub smoothing:          f64,
+    /// Warn when the EMA stays this far below its best (0 = never)
+    pub throttle_alert_pct: u64,
+    /// Before the text line; benchmark steps sit under their heading
+    pub indent:             &'static str,
+    /// Print a status block this often (`--log-status`; None = never)
+    pub log_status:         Option<Duration>,
+}
+
+/// Samples the combined rate once per interval into `samples`. With a
+/// `print` format it also shows it: as text, smoothed with `smoothing` as
+/// the EMA alpha, plus a per-group rate when more than one workload group
+/// is running; as JSON lines, one raw [`ProgressSnapshot`]. The total shows
+/// bandwidth next to ops/s only if every group has a bytes-per-op figure,
+/// and the EMA follows bandwidth then. Samples stay raw ops/sec. With the
+/// text line, a sustained fall of `throttle_alert_pct` from the best EMA
+/// gets a warning while it's happening (0 = never). `log_status` adds a
+/// multi-line status block on its own cadence, whatever `print` is.
+pub fn progress_reporter(
+    stop_signal: Arc<AtomicBool>,
+    groups: Vec<ProgressGroup>,
+    samples: RateSamples,
+    options: ProgressOptions,
+) {
+    let ProgressOptions {
+        print,
+        smoothing,
+        throttle_alert_pct,
+        indent,
+        log_status,
+    } = options;
+    let started = Instant::now();
+    let workload = groups
+        .iter()
+        .map(|group| group.name.as_str())
+        .collect::<Vec<_>>()
+        .join("+");
+    // Start from the current counts so ramp-up ops don't land in the first sample
+    let mut last_ops: Vec<u64> = groups
+        .iter()
+        .map(|group| group.counter.load(Ordering::Relaxed))
+        .collect();
+    let mut last_tick = Instant::now();
+    let mut ema = RateEma::new(smoothing);
+    let mut throttle = ThrottleWatch::new(throttle_alert_pct);
+    let mut status = log_status.and_then(StatusCadence::new);
+    // Interval ops/s and bytes/s of the fastest sample so far
+    let mut peak = (0u64, None);
 
     loop {
-        thread::sleep(Duration::from_secs(1));
-        if stop_signal.load(Ordering::Relaxed) {
-            break;
+        // Woken by the stop, so the final join doesn't wait out an interval
+        let next_tick = last_tick + REPORT_INTERVAL;
+        let until_tick = next_tick.saturating_duration_since(Instant::now());
+        if signal::wait_until(until_tick, || stop_signal.load(Ordering::Acquire)) {
+            return;
         }
+        let interval = last_tick.elapsed().as_secs_f64();
+        last_tick = Instant::now();
 
-        let current_ops = work_counter.load(Ordering::Relaxed);
-        let ops_per_sec = current_ops.saturating_sub(last_ops);
-        last_ops = current_ops;
+        let mut current_ops = 0u64;
+        let mut ops_per_sec = 0u64;
+        let mut bytes_per_sec = Some(0u64);
+        let mut breakdown = String::new();
 
-        print!(
-            "\r[Running] Total ops: {} | Rate: {}/s    ",
-            format_number(current_ops),
-            format_number(ops_per_sec)
-        );
-        if let Err(e) = std::io::stdout().flush() {
-            eprintln!("Warning: failed to flush progress output: {}", e);
+        for (group, last) in groups.iter().zip(last_ops.iter_mut()) {
+            let ops = group.counter.load(Ordering::Relaxed);
+            let rate = (ops.saturating_sub(*last) as f64 / interval) as u64;
+            *last = ops;
+
+            current_ops += ops;
+            ops_per_sec += rate;
+            bytes_per_sec = bytes_per_sec
+                .zip(group.bytes_per_op)
+                .map(|(sum, bytes)| sum + rate.saturating_mul(bytes));
+            if groups.len() > 1 {
+                breakdown += &format!(
+                    " | {}: {}",
+                    group.name,
+                    format_rate(rate, group.bytes_per_op)
+                );
+            }
+        }
+
+        samples
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(ops_per_sec);
+
+        // The EMA tracks bytes/s for an all-bandwidth run, ops/s otherwise
+        let shown = |rate: u64| match bytes_per_sec {
+            Some(_) => format_rate_bytes(rate as f64),
+            None => format!("{}/s", format_number(rate)),
+        };
+        let rate = bytes_per_sec.unwrap_or(ops_per_sec);
+        let smoothed = ema.update(rate);
+        let throttled = throttle.as_mut().and_then(|watch| watch.update(smoothed));
+        if ops_per_sec >= peak.0 {
+            peak = (ops_per_sec, bytes_per_sec);
+        }
+        if print == Some(ProgressFormat::Jsonl) {
+            let snapshot = ProgressSnapshot {
+                timestamp:            format_rfc3339(SystemTime::now()),
+                elapsed_secs:         started.elapsed().as_secs_f64(),
+                workload:             workload.clone(),
+                total_ops:            current_ops,
+                interval_ops_per_sec: ops_per_sec,
+                interval_gb_per_sec:  bytes_per_sec.map(|bytes| bytes as f64 / 1e9),
+            };
+            match serde_json::to_string(&snapshot) {
+                Ok(line) => eprintln!("{}", line),
+                Err(e) => notice!("Warning: failed to encode progress: {}", e),
+            }
+        } else if print == Some(ProgressFormat::Text) {
+            let trend = if ema.smooths() {
+                format!(" (EMA {})", shown(smoothed))
+            } else {
+                String::new()
+            };
+            eprint!(
+                "\r{}[Running] Total ops: {} | Rate: {}{}{}    ",
+                indent,
+                format_number(current_ops),
+                format_rate_with_bandwidth(ops_per_sec, bytes_per_sec),
+                trend,
+                breakdown
+            );
+            if let Err(e) = std::io::stderr().flush() {
+                notice!("Warning: failed to flush progress output: {}", e);
+            }
+            if let Some(pct) = throttled {
+                notice!(
+                    "\n[!] Rate down {}% from peak — check for thermal throttling",
+                    pct
+                );
+            }
         }
+
+        let elapsed = started.elapsed();
+        if status.as_mut().is_some_and(|cadence| cadence.due(elapsed)) {
+            let block = format_status_block(
+                elapsed,
+                current_ops,
+                &format_rate_with_bandwidth(ops_per_sec, bytes_per_sec),
+                &format_rate_with_bandwidth(peak.0, peak.1),
+                crate::system::cpu_temperature(),
+            );
+            // Off the end of the text line, which the next tick redraws below
+            let lead = if print == Some(ProgressFormat::Text) { "\n" } else { "" };
+            notice!("{}{}", lead, block);
+        }
+    }
+}
+
+pub fn peak_rate(samples: &[u64]) -> Option<u64> {
+    samples.iter().copied().max()
+}
+
+/// Median of the later half of the run, after any boost clocks have faded.
+pub fn sustained_rate(samples: &[u64]) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut tail = samples[samples.len() / 2..].to_vec();
+    tail.sort_unstable();
+
+    let mid = tail.len() / 2;
+    Some(if tail.len().is_multiple_of(2) {
+        (tail[mid - 1] + tail[mid]) / 2
+    } else {
+        tail[mid]
+    })
+}
+
+/// p5/p50/p95 of the interval rates, by nearest rank.
+pub fn rate_percentiles(samples: &[u64]) -> Option<RatePercentiles> {
+    if samples.is_empty() {
+        return None;
     }
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    Some(RatePercentiles {
+        p5_ops_per_sec:  nearest_rank(&sorted, 5),
+        p50_ops_per_sec: nearest_rank(&sorted, 50),
+        p95_ops_per_sec: nearest_rank(&sorted, 95),
+    })
+}
+
+/// The smallest sample with at least `percent`% of them at or below it.
+fn nearest_rank(sorted: &[u64], percent: usize) -> u64 {
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Bins in the final-stats rate histogram.
+pub const HISTOGRAM_BINS: usize = 10;
+/// Characters in the fullest bin's bar.
+const HISTOGRAM_WIDTH: usize = 30;
+/// Partial blocks for the last eighth-steps of a bar.
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// The interval rates counted into up to `bins` equal slices of their
+/// range, so a run that throttled part way shows two humps instead of one
+/// spread. Fewer bins when the range holds fewer distinct rates; empty
+/// under two samples.
+pub fn rate_histogram(samples: &[u64], bins: usize) -> Vec<HistogramBin> {
+    let (Some(&min), Some(&max)) = (samples.iter().min(), samples.iter().max()) else {
+        return Vec::new();
+    };
+    if samples.len() < 2 || bins == 0 {
+        return Vec::new();
+    }
+    let span = u128::from(max - min) + 1;
+    let bins = (bins as u128).min(span);
+    // Rounded up, so a sample lands where `index` below puts it
+    let edge = |i: u128| u128::from(min) + (span * i).div_ceil(bins);
+    let mut histogram: Vec<HistogramBin> = (0..bins)
+        .map(|i| HistogramBin {
+            low_ops_per_sec:  edge(i) as u64,
+            high_ops_per_sec: (edge(i + 1) - 1) as u64,
+            count:            0,
+        })
+        .collect();
+    for &sample in samples {
+        let index = u128::from(sample - min) * bins / span;
+        histogram[index as usize].count += 1;
+    }
+    histogram
+}
+
+/// One line per bin: its range, a bar scaled to the fullest bin in
+/// eighth-blocks (whole `#`s with `ascii`) and the count.
+pub fn histogram_lines(histogram: &[HistogramBin], ascii: bool) -> Vec<String> {
+    let most = histogram.iter().map(|bin| bin.count).max().unwrap_or(0).max(1);
+    histogram
+        .iter()
+        .map(|bin| {
+            // Anything counted gets at least a sliver
+            let eighths =
+                (bin.count * HISTOGRAM_WIDTH * 8 / most).max(usize::from(bin.count > 0));
+            let bar = if ascii {
+                "#".repeat(eighths.div_ceil(8))
+            } else {
+                let mut bar = "█".repeat(eighths / 8);
+                if eighths % 8 > 0 {
+                    bar.push(EIGHTHS[eighths % 8]);
+                }
+                bar
+            };
+            let range = format!(
+                "{}-{}/s",
+                format_number(bin.low_ops_per_sec),
+                format_number(bin.high_ops_per_sec)
+            );
+            format!("{:>19} {:<width$} {}", range, bar, bin.count, width = HISTOGRAM_WIDTH)
+        })
+        .collect()
+}
+
+/// Samples `--adaptive` judges at a time: ten seconds at [`REPORT_INTERVAL`].
+pub const CONVERGENCE_WINDOW: usize = 10;
+/// Coefficient of variation under which that window counts as settled.
+pub const CONVERGENCE_CV: f64 = 0.02;
+
+/// Mean of the last `window` rates once they've settled: their coefficient
+/// of variation is under `max_cv`, and the later half's mean is within half
+/// that of the earlier half's, so a slow slide doesn't pass for a plateau.
+/// None while the rate still moves or before there are `window` samples.
+pub fn converged_rate(samples: &[u64], window: usize, max_cv: f64) -> Option<u64> {
+    if window < 2 || samples.len() < window {
+        return None;
+    }
+    let recent = &samples[samples.len() - window..];
+    let mean_of =
+        |rates: &[u64]| rates.iter().map(|&r| r as f64).sum::<f64>() / rates.len() as f64;
+    let mean = mean_of(recent);
+    if mean <= 0.0 {
+        return None;
+    }
+    let variance = recent
+        .iter()
+        .map(|&r| (r as f64 - mean).powi(2))
+        .sum::<f64>()
+        / (window - 1) as f64;
+    let (earlier, later) = recent.split_at(window / 2);
+    let drift = (mean_of(later) - mean_of(earlier)).abs() / mean;
+    (variance.sqrt() / mean < max_cv && drift < max_cv / 2.0).then(|| mean.round() as u64)
+}
+
+/// The final stats' note on swapping, so saved results say whether the
+/// memory numbers can be trusted.
+pub fn describe_swapping(pages: u64) -> String {
+    if pages == 0 {
+        "none observed".to_string()
+    } else {
+        format!("{} pages in/out; memory results include disk I/O", pages)
+    }
+}
+
+/// The final stats' note on a rounded-down buffer, so a report says what
+/// size the memory workloads actually ran with.
+pub fn describe_buffer_rounding(rounding: BufferRounding) -> String {
+    format!(
+        "{} per thread (rounded down from {} for mask indexing)",
+        format_mb(rounding.effective_mb),
+        format_mb(rounding.requested_mb)
+    )
+}
+
+/// Process CPU time against wall time. The ratio is how many threads were
+/// busy on average; well under `threads` means the workers spent time
+/// waiting for a core (other load, a cgroup quota, oversubscription).
+pub fn describe_process_cpu(cpu_secs: f64, wall_secs: f64, threads: usize) -> String {
+    let cpu = format_duration(Duration::from_secs_f64(cpu_secs.max(0.0)));
+    if wall_secs <= 0.0 {
+        return cpu;
+    }
+    format!(
+        "{} over {} wall ({:.2} of {} threads busy)",
+        cpu,
+        format_duration(Duration::from_secs_f64(wall_secs)),
+        cpu_secs / wall_secs,
+        threads
+    )
+}
+
+/// Exit code for a run that finished but missed --min-rate or --min-score,
+/// apart from 1 (a worker failed) so a pipeline can tell the two apart.
+pub const BELOW_THRESHOLD_EXIT: i32 = 3;
+
+/// The "below threshold" message when `measured` misses `flag`'s `minimum`.
+pub fn check_threshold(
+    what: &str,
+    measured: u64,
+    flag: &str,
+    minimum: Option<u64>,
+) -> Result<(), String> {
+    match minimum {
+        Some(minimum) if measured < minimum => Err(format!(
+            "below threshold: {} {}/s is under {} {}/s",
+            what,
+            format_number(measured),
+            flag,
+            format_number(minimum)
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Workers below this share of their peers' median rate stand out.
+pub const IMBALANCE_THRESHOLD: f64 = 0.80;
+
+/// Workers well below the median of their peers, with the share of that
+/// median they reached. Peers run the same workload and, on a hybrid CPU,
+/// the same core class, so E-cores aren't flagged for being E-cores.
+/// Unpinned workers there may have run on either kind and aren't compared.
+pub fn imbalanced_workers(workers: &[WorkerReport], hybrid: bool) -> Vec<(usize, f64)> {
+    let comparable = |w: &WorkerReport| !hybrid || w.core_class.is_some();
+    let peers = |w: &WorkerReport| (w.workload.clone(), w.core_class.clone());
+
+    let mut rates: HashMap<(String, Option<String>), Vec<u64>> = HashMap::new();
+    for worker in workers.iter().filter(|w| comparable(w)) {
+        rates
+            .entry(peers(worker))
+            .or_default()
+            .push(worker.ops_per_sec);
+    }
+    let medians: HashMap<_, u64> = rates
+        .into_iter()
+        .filter(|(_, rates)| rates.len() > 1)
+        .map(|(key, mut rates)| {
+            rates.sort_unstable();
+            (key, rates[rates.len() / 2])
+        })
+        .collect();
+
+    workers
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| comparable(w))
+        .filter_map(|(index, worker)| {
+            let median = *medians.get(&peers(worker)).filter(|&&m| m > 0)?;
+            let share = worker.ops_per_sec as f64 / median as f64;
+            (share < IMBALANCE_THRESHOLD).then_some((index, share))
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
+    use std::thread;
+
     use super::*;
 
+    const MIB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_format_bytes_unit_boundaries() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1 KiB");
+        assert_eq!(format_bytes(192 * MIB), "192 MiB");
+        assert_eq!(format_bytes(1023 * MIB), "1023 MiB");
+        assert_eq!(format_bytes(1024 * MIB), "1 GiB");
+        assert_eq!(format_bytes(1536 * MIB), "1.50 GiB");
+        assert_eq!(format_bytes(24 * 192 * MIB), "4.50 GiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * MIB), "3 TiB");
+        assert_eq!(format_bytes(5000 * 1024 * 1024 * MIB), "5000 TiB");
+    }
+
+    #[test]
+    fn test_format_bytes_rounds_to_hundredths() {
+        assert_eq!(format_bytes(1024 * MIB - 1), "1 GiB");
+        assert_eq!(format_bytes(1000 * MIB), "1000 MiB");
+        // 100 MiB / 3 = 33.333 MiB
+        assert_eq!(format_bytes(100 * MIB / 3), "33.33 MiB");
+        assert_eq!(format_bytes(1024 + 5), "1 KiB");
+        assert_eq!(format_bytes(1024 + 6), "1.01 KiB");
+    }
+
+    #[test]
+    fn test_gb_per_sec_survives_a_byte_count_past_u64() {
+        // Thirty days at 10 G ops/s of 1 KiB ops: 2.7e19 bytes, past u64
+        let elapsed = Duration::from_secs(30 * 86_400);
+        let ops = 10_000_000_000 * elapsed.as_secs();
+        assert!(ops.checked_mul(1024).is_none());
+        let gb = gb_per_sec(ops, 1024, elapsed);
+        assert!((gb - 10_240.0).abs() < 1e-6, "{}", gb);
+        assert!(gb < IMPLAUSIBLE_GB_PER_SEC);
+
+        assert!(gb_per_sec(u64::MAX, 1024, Duration::from_secs(1)) > IMPLAUSIBLE_GB_PER_SEC);
+        assert_eq!(gb_per_sec(1000, 64, Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn test_format_rate_bytes_is_decimal() {
+        assert_eq!(format_rate_bytes(12.345e9), "12.35 GB/s");
+        assert_eq!(format_rate_bytes(1e9), "1.00 GB/s");
+        assert_eq!(format_rate_bytes(999.99e6), "999.99 MB/s");
+        // Rounds up to 1000.00 MB/s, so it switches units
+        assert_eq!(format_rate_bytes(999.996e6), "1.00 GB/s");
+        assert_eq!(format_rate_bytes(250e6), "250.00 MB/s");
+        assert_eq!(format_rate_bytes(0.0), "0.00 MB/s");
+    }
+
+    #[test]
+    fn test_describe_process_cpu_gives_busy_threads() {
+        assert_eq!(
+            describe_process_cpu(7.5, 2.5, 4),
+            "7.50s over 2.50s wall (3.00 of 4 threads busy)"
+        );
+        assert_eq!(describe_process_cpu(0.01, 0.0, 4), "0.01s");
+        assert_eq!(
+            describe_process_cpu(28_800.0, 7_200.0, 4),
+            "8h 0m 0s over 2h 0m 0s wall (4.00 of 4 threads busy)"
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        let ms = Duration::from_millis;
+        assert_eq!(format_duration(Duration::ZERO), "0.00s");
+        assert_eq!(format_duration(ms(9_870)), "9.87s");
+        assert_eq!(format_duration(ms(59_990)), "59.99s");
+        assert_eq!(format_duration(ms(59_996)), "1m 0s");
+        assert_eq!(format_duration(ms(245_400)), "4m 5s");
+        assert_eq!(format_duration(ms(245_500)), "4m 6s");
+        assert_eq!(format_duration(ms(3_599_600)), "1h 0m 0s");
+        assert_eq!(format_duration(Duration::from_secs(5_025)), "1h 23m 45s");
+        assert_eq!(
+            format_duration(Duration::from_secs(100 * 3600)),
+            "100h 0m 0s"
+        );
+    }
+
+    fn worker(workload: &str, class: Option<&str>, ops_per_sec: u64) -> WorkerReport {
+        WorkerReport {
+            name: String::new(),
+            workload: workload.to_string(),
+            wall_secs: 10.0,
+            cpu_secs: None,
+            cpu_efficiency: None,
+            ops_per_sec,
+            cpu: None,
+            core_class: class.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_imbalance_compares_within_core_class() {
+        // 2 P-cores and 2 E-cores at their natural speeds: nothing to report
+        let hybrid = [
+            worker("integer", Some("P"), 100),
+            worker("integer", Some("P"), 98),
+            worker("integer", Some("E"), 55),
+            worker("integer", Some("E"), 54),
+        ];
+        assert!(imbalanced_workers(&hybrid, true).is_empty());
+
+        // One P-core throttled to E-core speed does stand out
+        let mut throttled = hybrid.clone();
+        throttled[1].ops_per_sec = 60;
+        let flagged = imbalanced_workers(&throttled, true);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].0, 1);
+        assert!((flagged[0].1 - 0.6).abs() < 1e-9);
+
+        // Without class labels the E-cores would be false positives
+        let unlabeled: Vec<_> = hybrid
+            .iter()
+            .map(|w| WorkerReport {
+                core_class: None,
+                ..w.clone()
+            })
+            .collect();
+        assert_eq!(imbalanced_workers(&unlabeled, false).len(), 2);
+        // ...so unpinned workers on a hybrid CPU aren't compared at all
+        assert!(imbalanced_workers(&unlabeled, true).is_empty());
+    }
+
+    #[test]
+    fn test_threshold() {
+        assert_eq!(
+            check_threshold("average rate", 80_000_000, "--min-rate", None),
+            Ok(())
+        );
+        assert_eq!(
+            check_threshold("average rate", 80_000_000, "--min-rate", Some(1_000)),
+            Ok(())
+        );
+        assert_eq!(
+            check_threshold("average rate", 80_000_000, "--min-rate", Some(80_000_000)),
+            Ok(())
+        );
+        assert_eq!(
+            check_threshold("average rate", 80_000_000, "--min-rate", Some(100_000_000)),
+            Err("below threshold: average rate 80.00M/s is under --min-rate 100.00M/s".into())
+        );
+    }
+
+    #[test]
+    fn test_imbalance_needs_peers() {
+        let mixed = [
+            worker("integer", None, 100),
+            worker("memory-latency", None, 5),
+            worker("memory-latency", None, 0),
+        ];
+        let flagged = imbalanced_workers(&mixed, false);
+        assert_eq!(flagged, [(2, 0.0)]);
+        assert!(imbalanced_workers(&mixed[..1], false).is_empty());
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        let at = |secs| format_rfc3339(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(at(4_107_542_399), "2100-02-28T23:59:59Z");
+    }
+
     #[test]
     fn test_format_number() {
-        assert_eq!(format_number(500), "500");
-        assert_eq!(format_number(1_500), "1.50K");
-        assert_eq!(format_number(2_500_000), "2.50M");
-        assert_eq!(format_number(3_500_000_000), "3.50B");
+        assert_eq!(abbreviate_number(500), "500");
+        assert_eq!(abbreviate_number(1_500), "1.50K");
+        assert_eq!(abbreviate_number(2_500_000), "2.50M");
+        assert_eq!(abbreviate_number(3_500_000_000), "3.50B");
+    }
+
+    #[test]
+    fn test_group_digits() {
+        assert_eq!(group_digits(0), "0");
+        assert_eq!(group_digits(999), "999");
+        assert_eq!(group_digits(1_234), "1,234");
+        assert_eq!(group_digits(1_234_567_890_123), "1,234,567,890,123");
+        assert_eq!(group_digits(u64::MAX), "18,446,744,073,709,551,615");
+    }
+
+    #[test]
+    fn test_sustained_rate_ignores_early_boost() {
+        // Boosts to 200, then throttles to ~100 for the second half
+        let samples = [200, 190, 180, 150, 110, 100, 95, 100];
+        assert_eq!(peak_rate(&samples), Some(200));
+        assert_eq!(sustained_rate(&samples), Some(100));
+
+        assert_eq!(sustained_rate(&[42]), Some(42));
+        assert_eq!(sustained_rate(&[10, 30, 20]), Some(25));
+        assert_eq!(sustained_rate(&[]), None);
+        assert_eq!(peak_rate(&[]), None);
+    }
+
+    fn converged(samples: &[u64]) -> Option<u64> {
+        converged_rate(samples, CONVERGENCE_WINDOW, CONVERGENCE_CV)
+    }
+
+    #[test]
+    fn test_converging_rate_settles_on_its_plateau() {
+        // Boost and first-touch noise, then ±0.5% around 1000
+        let mut samples = vec![1400, 1300, 1150, 1080, 1030];
+        samples.extend([1005, 995, 1000, 1004, 996, 1001, 999, 1003, 997, 1000]);
+        assert_eq!(converged(&samples), Some(1000));
+        // The noisy start is still inside the window one sample earlier
+        assert_eq!(converged(&samples[..samples.len() - 4]), None);
+        assert_eq!(converged(&samples[..9]), None);
+    }
+
+    #[test]
+    fn test_oscillating_rate_never_settles() {
+        let samples: Vec<u64> = (0..60)
+            .map(|i| if i % 2 == 0 { 1100 } else { 900 })
+            .collect();
+        assert_eq!(converged(&samples), None);
+    }
+
+    #[test]
+    fn test_declining_rate_never_settles() {
+        // 0.3% a second: a low spread on its own, but a steady slide
+        let samples: Vec<u64> = (0..60).map(|i| 100_000 - i * 300).collect();
+        assert_eq!(converged(&samples), None);
+        assert_eq!(converged(&[0; 20]), None);
+    }
+
+    #[test]
+    fn test_rate_histogram_bins_span_the_range() {
+        // A run that throttled half way: two humps, nothing in between
+        let mut samples = vec![1000, 1010, 995, 1005, 1000];
+        samples.extend([600, 590, 605, 600, 610, 595]);
+        let histogram = rate_histogram(&samples, 10);
+        assert_eq!(histogram.len(), 10);
+        assert_eq!(histogram[0].low_ops_per_sec, 590);
+        assert_eq!(histogram[9].high_ops_per_sec, 1010);
+        for pair in histogram.windows(2) {
+            assert_eq!(pair[0].high_ops_per_sec + 1, pair[1].low_ops_per_sec);
+        }
+        let counts: Vec<usize> = histogram.iter().map(|bin| bin.count).collect();
+        assert_eq!(counts, [6, 0, 0, 0, 0, 0, 0, 0, 0, 5]);
+
+        // Every sample lands in the bin whose bounds hold it
+        let uneven: Vec<u64> = (0..97).map(|i| i * 7 % 23 + 40).collect();
+        let histogram = rate_histogram(&uneven, 10);
+        for &sample in &uneven {
+            let holding = histogram
+                .iter()
+                .filter(|b| (b.low_ops_per_sec..=b.high_ops_per_sec).contains(&sample));
+            assert_eq!(holding.count(), 1, "{}", sample);
+        }
+        assert_eq!(histogram.iter().map(|b| b.count).sum::<usize>(), uneven.len());
+
+        // Fewer distinct rates than bins, a flat run, and too few samples
+        assert_eq!(rate_histogram(&[5, 7, 6, 5], 10).len(), 3);
+        assert_eq!(rate_histogram(&[9, 9, 9], 10), [HistogramBin {
+            low_ops_per_sec:  9,
+            high_ops_per_sec: 9,
+            count:            3,
+        }]);
+        assert!(rate_histogram(&[9], 10).is_empty());
+        assert!(rate_histogram(&[0, u64::MAX], 10).iter().all(|b| b.count <= 1));
+    }
+
+    #[test]
+    fn test_histogram_bars_scale_to_the_fullest_bin() {
+        let histogram = rate_histogram(&[10, 10, 10, 10, 20, 30, 30], 3);
+        let bars: Vec<String> = histogram_lines(&histogram, false)
+            .iter()
+            .map(|line| line.split_whitespace().nth(1).unwrap_or("").to_string())
+            .collect();
+        assert_eq!(bars[0].chars().count(), HISTOGRAM_WIDTH);
+        assert_eq!(bars[1], "███████▌");
+        assert_eq!(bars[2], "███████████████");
+        let ascii = histogram_lines(&histogram, true);
+        assert!(ascii[1].contains(" ######## ") && ascii[1].ends_with(" 1"), "{:?}", ascii);
+        assert!(ascii.iter().all(|line| line.is_ascii()));
+    }
+
+    #[test]
+    fn test_rate_percentiles_by_nearest_rank() {
+        let spread = |samples: &[u64]| {
+            let p = rate_percentiles(samples).unwrap();
+            (p.p5_ops_per_sec, p.p50_ops_per_sec, p.p95_ops_per_sec)
+        };
+        assert_eq!(rate_percentiles(&[]), None);
+        assert_eq!(spread(&[42]), (42, 42, 42));
+        assert_eq!(spread(&[30, 10, 20]), (10, 20, 30));
+        // One stalled second among steady ones is the minimum, not p5
+        let mut steady = vec![100; 30];
+        steady[7] = 3;
+        assert_eq!(spread(&steady), (100, 100, 100));
+        // Ties at the boundary
+        assert_eq!(spread(&[5, 5, 5, 9, 9, 9, 9, 9, 9, 9]), (5, 9, 9));
+
+        // 1..=1000 in reverse: each percentile lands on its own rank
+        let large: Vec<u64> = (1..=1000).rev().collect();
+        assert_eq!(spread(&large), (50, 500, 950));
+        let large: Vec<u64> = (1..=1001).collect();
+        assert_eq!(spread(&large), (51, 501, 951));
+    }
+
+    #[test]
+    fn test_format_rate_converts_bandwidth_workloads() {
+        assert_eq!(format_rate(98_090_000, None), "98.09M/s");
+        assert_eq!(format_rate(1_000_000, Some(16)), "16.00 MB/s, 1.00M ops/s");
+        assert_eq!(
+            format_rate(321_875_000, Some(128)),
+            "41.20 GB/s, 321.88M ops/s"
+        );
+        // The progress total: bytes summed across groups, ops alongside
+        assert_eq!(
+            format_rate_with_bandwidth(20_000_000, Some(20_000_000 * 16 + 5_000_000 * 128)),
+            "960.00 MB/s, 20.00M ops/s"
+        );
+        assert_eq!(format_rate_with_bandwidth(1_500, None), "1.50K/s");
+    }
+
+    /// Alerts in order, as (sample index, percent down).
+    fn throttle_alerts(rates: &[u64]) -> Vec<(usize, u64)> {
+        let mut watch = ThrottleWatch::new(20).unwrap();
+        rates
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &rate)| watch.update(rate).map(|pct| (i, pct)))
+            .collect()
+    }
+
+    #[test]
+    fn test_throttle_watch_ignores_warmup_and_brief_dips() {
+        assert!(ThrottleWatch::new(0).is_none());
+
+        // A slow start in warmup doesn't set the peak, a fast one isn't it
+        let mut rates = vec![1000, 10, 10, 10, 10];
+        // Two samples down: shorter than the sustain window
+        rates.extend([100, 100, 100, 50, 50, 100, 100, 79, 79, 100]);
+        assert_eq!(throttle_alerts(&rates), []);
+        // Exactly at the threshold doesn't count as down
+        assert_eq!(throttle_alerts(&[0, 0, 0, 0, 0, 100, 80, 80, 80, 80]), []);
+    }
+
+    #[test]
+    fn test_throttle_watch_alerts_once_per_decline() {
+        let mut rates = vec![100; 10];
+        rates.extend([90, 80, 73, 73, 73]);
+        rates.extend([70; 100]);
+        // Index 14 is the third sample under 80
+        assert_eq!(throttle_alerts(&rates), [(14, 27)]);
+
+        // Recovers, then falls again past the cooldown: a second alert
+        rates.extend([100; 5]);
+        rates.extend([60; 5]);
+        assert_eq!(throttle_alerts(&rates), [(14, 27), (122, 40)]);
+
+        // A new decline inside the cooldown waits it out, if it lasts
+        let mut quick = vec![100; 10];
+        quick.extend([50; 3]);
+        quick.extend([100; 3]);
+        quick.extend([50; 60]);
+        assert_eq!(throttle_alerts(&quick), [(12, 50), (72, 50)]);
+    }
+
+    #[test]
+    fn test_status_blocks_follow_their_cadence() {
+        assert!(StatusCadence::new(Duration::ZERO).is_none());
+
+        // Reporter ticks land a little after each second
+        let mut cadence = StatusCadence::new(Duration::from_secs(5)).unwrap();
+        let due: Vec<u64> = (1..=16)
+            .filter(|&tick| cadence.due(Duration::from_millis(tick * 1000 + 3)))
+            .collect();
+        assert_eq!(due, [5, 10, 15]);
+
+        // A stalled tick prints once and the cadence carries on from there
+        let mut cadence = StatusCadence::new(Duration::from_secs(2)).unwrap();
+        assert!(cadence.due(Duration::from_secs(7)));
+        assert!(!cadence.due(Duration::from_millis(7_900)));
+        assert!(cadence.due(Duration::from_secs(8)));
+
+        let block = format_status_block(Duration::from_secs(65), 1_500, "10/s", "12/s", None);
+        let lines: Vec<&str> = block.lines().collect();
+        assert!(lines[0].starts_with("[Status] "), "{}", block);
+        assert_eq!(lines[1..], [
+            "  Elapsed:     1m 5s",
+            "  Total ops:   1.50K",
+            "  Rate:        10/s",
+            "  Peak rate:   12/s",
+        ]);
+        let hot = format_status_block(Duration::ZERO, 0, "0/s", "0/s", Some(71.0));
+        assert!(hot.ends_with("\n  CPU temp:    71.0°C"), "{}", hot);
+    }
+
+    #[test]
+    fn test_rate_ema_seeds_from_the_first_sample() {
+        let mut ema = RateEma::new(0.5);
+        assert_eq!(ema.update(100), 100);
+        assert_eq!(ema.update(200), 150);
+        assert_eq!(ema.update(0), 75);
+        assert_eq!(ema.update(75), 75);
+
+        // 0.3 * 1000 + 0.7 * 0 = 300, then 0.3 * 1000 + 0.7 * 300 = 510
+        let mut ema = RateEma::new(0.3);
+        assert_eq!(ema.update(0), 0);
+        assert_eq!(ema.update(1000), 300);
+        assert_eq!(ema.update(1000), 510);
+
+        let mut raw = RateEma::new(1.0);
+        assert!(!raw.smooths());
+        raw.update(10);
+        assert_eq!(raw.update(90), 90);
+    }
+
+    #[test]
+    fn test_reporter_collects_samples_silently() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let counter = Arc::new(AtomicU64::new(0));
+        let samples: RateSamples = Arc::default();
+
+        let handle = {
+            let (stop, samples) = (Arc::clone(&stop), Arc::clone(&samples));
+            let groups = vec![ProgressGroup {
+                name:         "integer".to_string(),
+                counter:      Arc::clone(&counter),
+                bytes_per_op: None,
+            }];
+            let options = ProgressOptions {
+                print:              None,
+                smoothing:          0.3,
+                throttle_alert_pct: 20,
+                indent:             "",
+                log_status:         None,
+            };
+            thread::spawn(move || progress_reporter(stop, groups, samples, options))
+        };
+
+        thread::sleep(Duration::from_millis(100));
+        counter.fetch_add(1_000, Ordering::Relaxed);
+        thread::sleep(REPORT_INTERVAL);
+        stop.store(true, Ordering::Release);
+        handle.join().unwrap();
+
+        let samples = samples.lock().unwrap();
+        assert_eq!(samples.len(), 1);
+        assert!(samples[0] > 0);
     }
 }
@@ -0,0 +1,627 @@
+//! The spawn / report / stop / join loop behind `run` and every `bench`
+//! step. The modes differ in what they set up beforehand and how they show
+//! the result, not in how workers are started, measured or stopped.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::assignment::WorkloadGroup;
+use crate::events::EventLog;
+use crate::reporting::{self, ProgressGroup, ProgressOptions, RateSamples};
+use crate::swap::SwapMonitor;
+use crate::utilization::{CoreBusy, UtilizationMonitor};
+use crate::worker::{self, OpsBudget, WorkerConfig, WorkerTimes};
+use crate::workload::{AccessPattern, FloatMode, InitPattern, KernelParams};
+use crate::{signal, system};
+
+/// What to start and when to stop it.
+pub struct RunConfig<'a> {
+    pub groups:        &'a [WorkloadGroup],
+    /// Buffer per worker, in group order
+    pub per_thread_mb: &'a [usize],
+    /// None = each workload's default batch
+    pub batch_size:    Option<u64>,
+    /// Pointer chains for memory-latency
+    pub chains:        usize,
+    pub float_mode:    FloatMode,
+    /// Iterations ahead memory-bandwidth prefetches (0 = off)
+    pub prefetch:      usize,
+    /// What worker buffers are filled with
+    pub init_pattern:  InitPattern,
+    /// LCG and stride overrides for the memory kernels
+    pub access:        AccessPattern,
+    /// Bytes per cache-thrash pass
+    pub sweep_bytes:   usize,
+    /// Worker stack size in bytes (None = platform default)
+    pub stack_size:    Option<usize>,
+    pub stagger:       Duration,
+    /// CPU per worker (None = leave to the scheduler)
+    pub worker_cpus:   Option<&'a [usize]>,
+    /// For the reporter and the monitoring thread
+    pub reporter_cpu:  Option<usize>,
+    /// Unmeasured run time before the timer starts
+    pub warmup:        Duration,
+    /// None = until something else stops the run
+    pub duration:      Option<Duration>,
+    /// Stop once the rate settles (--adaptive); `duration` is then the cap
+    pub converge:      bool,
+    /// Total op budget shared by the workers (--ops)
+    pub ops:           Option<u64>,
+    /// Each worker runs one batch and returns (--once)
+    pub once:          bool,
+    /// Shrink buffers that can't be allocated (--best-effort-memory)
+    pub best_effort:   bool,
+    /// CPUs expected to stay busy over the window (--verify-utilization)
+    pub watch_cores:   Option<&'a [usize]>,
+    pub progress:      ProgressOptions,
+}
+
+/// Why the run ended, for the caller to announce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    TimeLimit,
+    OpsReached,
+    /// --once workers returned on their own
+    Once,
+    /// Ctrl-C during a staggered start, with this many workers up; the run
+    /// still stops as [`StopReason::Interrupted`]
+    InterruptedDuringStagger(usize),
+    Interrupted,
+    WorkerFailed,
+    /// The swap monitor asked to stop (--abort-on-swap); it has said why
+    Swapping,
+    /// The stop flag was raised from outside
+    Stopped,
+    /// The rate settled before the time limit (`converge`)
+    Converged,
+}
+
+/// What the run measured. Ops from a stagger ramp or the warmup aren't in
+/// it; a run stopped before its timer started measured nothing.
+#[derive(Debug)]
+pub struct RunStats {
+    pub elapsed:      Duration,
+    /// From the first spawn until every worker was set up and released,
+    /// buffer allocation included; part of neither `elapsed` nor the warmup
+    pub setup:        Duration,
+    /// Per group, in group order
+    pub group_ops:    Vec<u64>,
+    /// With the index of each worker's group
+    pub worker_times: Vec<(usize, WorkerTimes)>,
+    /// Combined ops/sec per reporter interval
+    pub rate_samples: Vec<u64>,
+    /// CPU time the whole process used over the measured window
+    pub process_cpu:  Option<Duration>,
+    /// Mean of the settled window when the run stopped for
+    /// [`StopReason::Converged`]
+    pub converged:    Option<u64>,
+    /// Per watched core; empty unless `watch_cores` was set
+    pub core_busy:    Vec<CoreBusy>,
+}
+
+impl RunStats {
+    pub fn total_ops(&self) -> u64 {
+        self.group_ops.iter().sum()
+    }
+
+    pub fn ops_per_sec(&self) -> u64 {
+        rate_per_sec(self.total_ops(), self.elapsed)
+    }
+}
+
+/// Over fractional seconds: an --ops run rarely ends on a whole one, and a
+/// --once run is over in milliseconds.
+pub fn rate_per_sec(ops: u64, elapsed: Duration) -> u64 {
+    if !elapsed.is_zero() {
+        (ops as f64 / elapsed.as_secs_f64()) as u64
+    } else {
+        ops
+    }
+}
+
+/// Starts every group's workers and the reporter, waits out the warmup,
+/// measures until a limit, a signal, a failed worker or swapping stops the
+/// run, and joins everything. `on_stop` hears each [`StopReason`] as it
+/// happens, before the workers have finished their last batch.
+pub fn execute_run(
+    config: &RunConfig,
+    stop_signal: Arc<AtomicBool>,
+    events: &mut EventLog,
+    swap: &mut SwapMonitor,
+    on_stop: &mut dyn FnMut(StopReason),
+) -> RunStats {
+    let num_threads: usize = config.groups.iter().map(|g| g.threads).sum();
+    let group_counters: Vec<Arc<AtomicU64>> = config
+        .groups
+        .iter()
+        .map(|_| Arc::new(AtomicU64::new(0)))
+        .collect();
+
+    let spawning = Instant::now();
+    let mut handles = Vec::with_capacity(num_threads);
+    let mut id = 0;
+    let gate = worker::start_gate(num_threads, config.stagger);
+    let budget = config.ops.map(|limit| Arc::new(OpsBudget::new(limit)));
+
+    'spawn: for (group_index, (group, group_counter)) in
+        config.groups.iter().zip(&group_counters).enumerate()
+    {
+        for _ in 0..group.threads {
+            if id > 0
+                && !config.stagger.is_zero()
+                && !signal::sleep_unless_interrupted(config.stagger)
+            {
+                on_stop(StopReason::InterruptedDuringStagger(id));
+                break 'spawn;
+            }
+
+            let stop = Arc::clone(&stop_signal);
+            let counter = Arc::clone(group_counter);
+            let worker_config = WorkerConfig {
+                workload:    group.spec,
+                kernel:      group.spec.create(&KernelParams {
+                    seed:       id as u64,
+                    chains:     config.chains,
+                    float_mode: config.float_mode,
+                    prefetch:   config.prefetch,
+                    pattern:    config.init_pattern,
+                    access:     config.access,
+                }),
+                batch_size:  group.spec.batch_size(config.batch_size),
+                memory_mb:   config.per_thread_mb[id],
+                sweep_bytes: config.sweep_bytes,
+                cpu:         config.worker_cpus.map(|cores| cores[id]),
+                once:        config.once,
+                best_effort: config.best_effort,
+                pattern:     config.init_pattern,
+            };
+            let worker_events = events.sender(id);
+            let (start, budget) = (gate.clone(), budget.clone());
+
+            let handle =
+                worker::spawn_named(worker::worker_name(id), config.stack_size, move || {
+                    worker::worker_thread(
+                        stop,
+                        counter,
+                        worker_config,
+                        start,
+                        budget,
+                        worker_events,
+                    )
+                });
+            handles.push((group_index, handle));
+            id += 1;
+        }
+    }
+
+    // Always sampled for the peak/sustained stats; printed per `progress`
+    let rate_samples = RateSamples::default();
+    let reporter = {
+        let report_stop = Arc::clone(&stop_signal);
+        let report_samples = Arc::clone(&rate_samples);
+        let report_groups = config
+            .groups
+            .iter()
+            .zip(&group_counters)
+            .map(|(group, counter)| ProgressGroup {
+                name:         group.spec.name.to_string(),
+                counter:      Arc::clone(counter),
+                bytes_per_op: group.spec.bytes_per_op,
+            })
+            .collect();
+        let (reporter_cpu, progress) = (config.reporter_cpu, config.progress);
+
+        worker::spawn_named("locus-report".to_string(), None, move || {
+            if let Some(cpu) = reporter_cpu {
+                system::pin_current_thread(cpu);
+            }
+            reporting::progress_reporter(report_stop, report_groups, report_samples, progress);
+        })
+    };
+
+    if let Some(cpu) = config.reporter_cpu {
+        system::pin_current_thread(cpu);
+    }
+
+    // Without a stagger every worker is set up and waiting here, and counts
+    // only from the release on; with one, ops done while it was still
+    // ramping up aren't part of the measured window, and neither are the
+    // warmup's
+    if let Some(gate) = &gate {
+        gate.wait();
+    }
+    let setup = spawning.elapsed();
+    let warmup_stop = match config.warmup.is_zero() {
+        true => None,
+        false => warm_up(config.warmup, &stop_signal, events),
+    };
+    let baseline: Vec<u64> = match (&gate, config.warmup.is_zero()) {
+        (Some(_), true) => vec![0; group_counters.len()],
+        _ => group_counters
+            .iter()
+            .map(|counter| counter.load(Ordering::Relaxed))
+            .collect(),
+    };
+    let start = Instant::now();
+    let process_cpu_start = system::process_cpu_time();
+    let mut monitors = Monitors {
+        swap,
+        utilization: UtilizationMonitor::start(config.watch_cores),
+    };
+    // Samples from the ramp and the warmup don't count towards settling
+    let measured_from = rate_samples.lock().unwrap_or_else(|e| e.into_inner()).len();
+    let mut converged = None;
+
+    let stop = match warmup_stop {
+        Some(stop) => stop,
+        // The workers return by themselves after a batch; just join them
+        None if config.once => StopReason::Once,
+        None => watch(
+            config,
+            &stop_signal,
+            budget.as_deref(),
+            start,
+            events,
+            &mut monitors,
+            &mut || {
+                let samples = rate_samples.lock().unwrap_or_else(|e| e.into_inner());
+                converged = reporting::converged_rate(
+                    samples.get(measured_from..).unwrap_or_default(),
+                    reporting::CONVERGENCE_WINDOW,
+                    reporting::CONVERGENCE_CV,
+                );
+                converged.is_some()
+            },
+        ),
+    };
+    // Before the workers wind down, which would read as idle time
+    let core_busy = monitors.utilization.finish();
+    if stop != StopReason::Once {
+        signal::raise_stop(&stop_signal);
+        on_stop(stop);
+    }
+
+    let worker_times: Vec<(usize, WorkerTimes)> = handles
+        .into_iter()
+        .map(|(group_index, handle)| (group_index, worker::join_named(handle)))
+        .collect();
+    let elapsed = start.elapsed();
+    let process_cpu = process_cpu_start
+        .zip(system::process_cpu_time())
+        .map(|(start, end)| end.saturating_sub(start));
+    signal::raise_stop(&stop_signal);
+    worker::join_named(reporter);
+    events.drain();
+
+    let group_ops = group_counters
+        .iter()
+        .zip(&baseline)
+        .map(|(counter, base)| match warmup_stop {
+            None => counter.load(Ordering::Relaxed) - base,
+            Some(_) => 0,
+        })
+        .collect();
+    let rate_samples =
+        std::mem::take(&mut *rate_samples.lock().unwrap_or_else(|e| e.into_inner()));
+
+    RunStats {
+        elapsed,
+        setup,
+        group_ops,
+        worker_times,
+        rate_samples,
+        process_cpu,
+        converged,
+        core_busy,
+    }
+}
+
+/// How often the loops below look at what can't wake them: worker events,
+/// swap, per-core times and convergence. A stop, Ctrl+C or the time limit
+/// ends the wait at once.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sleeps until the next poll or `deadline`, whichever is sooner, waking
+/// early when the run is stopped or interrupted.
+fn wait_for_poll(stop_signal: &AtomicBool, deadline: Option<Instant>) {
+    let until_deadline = deadline.map_or(POLL_INTERVAL, |deadline| {
+        deadline.saturating_duration_since(Instant::now())
+    });
+    signal::wait_until(POLL_INTERVAL.min(until_deadline), || {
+        stop_signal.load(Ordering::Acquire) || signal::interrupted()
+    });
+}
+
+/// Lets the workers run unmeasured for `warmup` so caches and clocks settle.
+/// Some(why) if the run was stopped meanwhile.
+fn warm_up(
+    warmup: Duration,
+    stop_signal: &AtomicBool,
+    events: &mut EventLog,
+) -> Option<StopReason> {
+    let warmup_end = Instant::now() + warmup;
+
+    loop {
+        if Instant::now() >= warmup_end {
+            return None;
+        }
+        wait_for_poll(stop_signal, Some(warmup_end));
+
+        if events.drain() {
+            return Some(StopReason::WorkerFailed);
+        }
+        if signal::interrupted() {
+            return Some(StopReason::Interrupted);
+        }
+        if stop_signal.load(Ordering::Relaxed) {
+            return Some(StopReason::Stopped);
+        }
+    }
+}
+
+/// What [`watch`] samples besides the workers.
+struct Monitors<'a> {
+    swap:        &'a mut SwapMonitor,
+    utilization: UtilizationMonitor,
+}
+
+/// Polls until something ends the measured window. With `converge` set,
+/// `settled` is asked after each poll whether the rate has settled.
+fn watch(
+    config: &RunConfig,
+    stop_signal: &AtomicBool,
+    budget: Option<&OpsBudget>,
+    start: Instant,
+    events: &mut EventLog,
+    monitors: &mut Monitors,
+    settled: &mut dyn FnMut() -> bool,
+) -> StopReason {
+    let deadline = config.duration.map(|limit| start + limit);
+    loop {
+        wait_for_poll(stop_signal, deadline);
+
+        // The worker that spent the budget has already raised the stop flag
+        if budget.is_some_and(|budget| budget.spent()) {
+            return StopReason::OpsReached;
+        }
+        if stop_signal.load(Ordering::Relaxed) {
+            return StopReason::Stopped;
+        }
+        if signal::interrupted() {
+            return StopReason::Interrupted;
+        }
+        if events.drain() {
+            return StopReason::WorkerFailed;
+        }
+        if monitors.swap.check() {
+            return StopReason::Swapping;
+        }
+        monitors.utilization.check();
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return StopReason::TimeLimit;
+        }
+        if config.converge && settled() {
+            return StopReason::Converged;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::registry::{self, Category, WorkingSet, WorkloadSpec};
+    use crate::workload::Workload;
+
+    /// Takes its time over the buffer the way a 512 MB fill would.
+    struct SlowSetup;
+
+    impl Workload for SlowSetup {
+        fn run(&mut self, batch: u64) -> u64 {
+            std::hint::black_box(batch)
+        }
+
+        fn needs_buffer(&self) -> bool {
+            true
+        }
+
+        fn attach_buffer(&mut self, _buffer: Box<[u64]>) {
+            thread::sleep(Duration::from_millis(400));
+        }
+    }
+
+    static SLOW_SETUP: WorkloadSpec = WorkloadSpec {
+        name:          "slow-setup",
+        aliases:       &[],
+        description:   "",
+        needs_buffer:  true,
+        bytes_per_op:  None,
+        op_weight:     1.0,
+        category:      Category::Memory,
+        working_set:   WorkingSet::WholeBuffer,
+        default_batch: 1000,
+        build:         |_| Box::new(SlowSetup),
+    };
+
+    fn config<'a>(groups: &'a [WorkloadGroup], per_thread_mb: &'a [usize]) -> RunConfig<'a> {
+        RunConfig {
+            groups,
+            per_thread_mb,
+            batch_size: Some(1000),
+            chains: 1,
+            float_mode: FloatMode::default(),
+            prefetch: 0,
+            init_pattern: InitPattern::Sequential,
+            access: AccessPattern::default(),
+            sweep_bytes: 1 << 16,
+            stack_size: None,
+            stagger: Duration::ZERO,
+            worker_cpus: None,
+            reporter_cpu: None,
+            warmup: Duration::ZERO,
+            duration: Some(Duration::from_millis(300)),
+            converge: false,
+            ops: None,
+            once: false,
+            best_effort: false,
+            watch_cores: None,
+            progress: ProgressOptions {
+                print:              None,
+                smoothing:          1.0,
+                throttle_alert_pct: 0,
+                indent:             "",
+                log_status:         None,
+            },
+        }
+    }
+
+    fn execute(config: &RunConfig) -> (RunStats, Vec<StopReason>) {
+        let mut heard = Vec::new();
+        let stats = execute_run(
+            config,
+            Arc::new(AtomicBool::new(false)),
+            &mut EventLog::new(),
+            &mut SwapMonitor::start(false),
+            &mut |reason| heard.push(reason),
+        );
+        (stats, heard)
+    }
+
+    fn group(name: &str, threads: usize) -> WorkloadGroup {
+        WorkloadGroup {
+            spec: registry::find(name).unwrap(),
+            threads,
+        }
+    }
+
+    #[test]
+    fn test_time_limit_counts_every_group() {
+        let groups = [group("integer", 1), group("memory-latency", 1)];
+        let (stats, heard) = execute(&config(&groups, &[0, 1]));
+
+        assert_eq!(heard, [StopReason::TimeLimit]);
+        assert!(stats.group_ops.iter().all(|&ops| ops > 0));
+        assert_eq!(stats.worker_times.len(), 2);
+        assert_eq!(stats.worker_times[1].0, 1);
+        assert!(stats.elapsed >= Duration::from_millis(300));
+        assert!(stats.ops_per_sec() > 0);
+    }
+
+    #[test]
+    fn test_ops_budget_and_once_stop_on_their_own() {
+        let groups = [group("integer", 2)];
+        let per_thread_mb = [0, 0];
+        let budget = RunConfig {
+            duration: None,
+            ops: Some(5000),
+            ..config(&groups, &per_thread_mb)
+        };
+        let (stats, heard) = execute(&budget);
+        assert_eq!(heard, [StopReason::OpsReached]);
+        // Overshoots by at most the batch each worker had in flight
+        assert!((5000..=7000).contains(&stats.total_ops()));
+
+        let once = RunConfig {
+            duration: None,
+            once: true,
+            ..config(&groups, &per_thread_mb)
+        };
+        let (stats, heard) = execute(&once);
+        // Nothing to announce: the workers stopped by themselves
+        assert_eq!(heard, []);
+        assert_eq!(stats.total_ops(), 2000);
+    }
+
+    #[test]
+    fn test_stop_ends_the_run_without_waiting_for_a_poll() {
+        let groups = [group("integer", 1)];
+        let open_ended = RunConfig {
+            duration: None,
+            ..config(&groups, &[0])
+        };
+        let stop_signal = Arc::new(AtomicBool::new(false));
+        let stopper = {
+            let stop = Arc::clone(&stop_signal);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(300));
+                signal::raise_stop(&stop);
+                Instant::now()
+            })
+        };
+        let mut heard = Vec::new();
+        execute_run(
+            &open_ended,
+            stop_signal,
+            &mut EventLog::new(),
+            &mut SwapMonitor::start(false),
+            &mut |reason| heard.push(reason),
+        );
+        let tail = Instant::now().saturating_duration_since(stopper.join().unwrap());
+        assert_eq!(heard, [StopReason::Stopped]);
+        assert!(tail < Duration::from_millis(50), "stop to exit took {:?}", tail);
+
+        // The time limit doesn't wait for a poll either
+        let (stats, _) = execute(&config(&groups, &[0]));
+        assert!(stats.elapsed < Duration::from_millis(350), "{:?}", stats.elapsed);
+    }
+
+    #[test]
+    fn test_warmup_ops_are_not_counted() {
+        let groups = [group("integer", 1)];
+        let warm = RunConfig {
+            warmup: Duration::from_millis(300),
+            duration: Some(Duration::from_millis(100)),
+            ..config(&groups, &[0])
+        };
+        let (stats, _) = execute(&warm);
+
+        let ran = stats.worker_times[0].1.ops;
+        assert!(stats.total_ops() > 0);
+        assert!(
+            stats.total_ops() < ran,
+            "{} measured of {} run",
+            stats.total_ops(),
+            ran
+        );
+    }
+
+    #[test]
+    fn test_slow_setup_is_not_measured() {
+        let groups = [WorkloadGroup {
+            spec:    &SLOW_SETUP,
+            threads: 2,
+        }];
+        let (stats, heard) = execute(&config(&groups, &[1, 1]));
+
+        assert_eq!(heard, [StopReason::TimeLimit]);
+        assert!(stats.setup >= Duration::from_millis(400), "{:?}", stats.setup);
+        // The 300 ms window plus a poll and a join, not the setup on top
+        assert!(
+            stats.elapsed >= Duration::from_millis(300)
+                && stats.elapsed < Duration::from_millis(600),
+            "{:?}",
+            stats.elapsed
+        );
+    }
+
+    #[test]
+    fn test_failed_setup_stops_the_run() {
+        // A zero-size buffer fails to allocate
+        let groups = [group("memory-bandwidth", 1)];
+        let failing = RunConfig {
+            duration: None,
+            ..config(&groups, &[0])
+        };
+        let (stats, heard) = execute(&failing);
+        assert_eq!(heard, [StopReason::WorkerFailed]);
+        assert_eq!(stats.total_ops(), 0);
+    }
+
+    #[test]
+    fn test_rate_per_sec_uses_fractional_seconds() {
+        assert_eq!(rate_per_sec(1500, Duration::from_millis(1500)), 1000);
+        assert_eq!(rate_per_sec(42, Duration::ZERO), 42);
+    }
+}
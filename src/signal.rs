@@ -0,0 +1,122 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, Once};
+use std::time::{Duration, Instant};
+
+use crate::output::notice;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static HANDLER: Once = Once::new();
+/// Wakes every [`wait_until`] in the process. One pair for all of them: a
+/// wake for someone else's condition just sends a waiter back to sleep.
+static WAKE_LOCK: Mutex<()> = Mutex::new(());
+static WAKE: Condvar = Condvar::new();
+
+/// Routes Ctrl+C into a process-wide flag the run loops poll. `ctrlc` takes
+/// one handler per process, so later calls are no-ops rather than errors
+/// that would leave Ctrl+C pointing somewhere stale.
+pub fn install_handler() {
+    HANDLER.call_once(|| {
+        let handler = || {
+            INTERRUPTED.store(true, Ordering::Release);
+            wake_waiters();
+        };
+        if let Err(e) = ctrlc::set_handler(handler) {
+            notice!("Warning: Failed to set global Ctrl+C handler: {}", e);
+        }
+    });
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Acquire)
+}
+
+/// Sleeps for `duration`, waking early on Ctrl+C. Returns false if interrupted.
+pub fn sleep_unless_interrupted(duration: Duration) -> bool {
+    !wait_until(duration, interrupted)
+}
+
+/// Raises a run's stop flag and wakes whoever waits on it. Workers only
+/// read the flag between batches; the monitoring loop and the reporter
+/// sleep in [`wait_until`] and see it at once.
+pub fn raise_stop(flag: &AtomicBool) {
+    flag.store(true, Ordering::Release);
+    wake_waiters();
+}
+
+fn wake_waiters() {
+    // Taking the lock orders this after a waiter's last check of its
+    // condition, so the wakeup can't land before it starts waiting
+    drop(WAKE_LOCK.lock().unwrap_or_else(|e| e.into_inner()));
+    WAKE.notify_all();
+}
+
+/// Sleeps until `condition` holds or `timeout` passes, and returns whether
+/// it holds. Only [`raise_stop`] and Ctrl+C wake it early, so `condition`
+/// should be about those; anything else is seen when the timeout runs out.
+pub fn wait_until(timeout: Duration, condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    let mut guard = WAKE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        if condition() {
+            return true;
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+        guard = match WAKE.wait_timeout(guard, remaining) {
+            Ok((guard, _)) => guard,
+            Err(e) => e.into_inner().0,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_handler_is_registered_once() {
+        install_handler();
+        install_handler();
+        assert!(HANDLER.is_completed());
+        // Ours holds the process's only slot
+        assert!(matches!(
+            ctrlc::set_handler(|| {}),
+            Err(ctrlc::Error::MultipleHandlers)
+        ));
+        assert!(!interrupted());
+    }
+
+    #[test]
+    fn test_sleep_unless_interrupted_waits() {
+        let start = Instant::now();
+        assert!(sleep_unless_interrupted(Duration::from_millis(30)));
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_raising_the_stop_flag_wakes_waiters() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let raiser = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                raise_stop(&stop);
+                Instant::now()
+            })
+        };
+        assert!(wait_until(Duration::from_secs(10), || stop.load(Ordering::Acquire)));
+        let late = Instant::now().saturating_duration_since(raiser.join().unwrap());
+        assert!(late < Duration::from_millis(50), "{:?}", late);
+
+        // Already raised: no wait at all; never raised: the whole timeout
+        assert!(wait_until(Duration::ZERO, || stop.load(Ordering::Acquire)));
+        let start = Instant::now();
+        assert!(!wait_until(Duration::from_millis(20), || false));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
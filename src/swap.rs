@@ -0,0 +1,195 @@
+//! Notices when the system starts swapping mid-run. Other processes can push
+//! locus's buffers out to disk after the up-front size check passed, which
+//! quietly turns a memory test into a disk benchmark.
+
+use std::time::Instant;
+
+use crate::output::notice;
+use crate::reporting::REPORT_INTERVAL;
+use crate::system;
+
+/// Cumulative pages swapped since boot (`pswpin`/`pswpout` in /proc/vmstat).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapCounters {
+    pub pages_in:  u64,
+    pub pages_out: u64,
+}
+
+impl SwapCounters {
+    fn pages_since(&self, earlier: &SwapCounters) -> u64 {
+        self.pages_in.saturating_sub(earlier.pages_in)
+            + self.pages_out.saturating_sub(earlier.pages_out)
+    }
+}
+
+/// None where the counters aren't implemented, which makes the monitor a
+/// no-op there.
+pub fn read_counters() -> Option<SwapCounters> {
+    #[cfg(target_os = "linux")]
+    {
+        parse_vmstat(&std::fs::read_to_string("/proc/vmstat").ok()?)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_vmstat(contents: &str) -> Option<SwapCounters> {
+    let field = |name: &str| {
+        contents.lines().find_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            (key == name).then(|| value.trim().parse().ok())?
+        })
+    };
+    Some(SwapCounters {
+        pages_in:  field("pswpin")?,
+        pages_out: field("pswpout")?,
+    })
+}
+
+/// Polled from the monitor loops; rereads the counters at most once per
+/// reporter interval.
+pub struct SwapMonitor {
+    baseline:  Option<SwapCounters>,
+    latest:    Option<SwapCounters>,
+    last_read: Instant,
+    /// --abort-on-swap: stop instead of warning
+    abort:     bool,
+    seen:      bool,
+}
+
+impl SwapMonitor {
+    pub fn start(abort: bool) -> Self {
+        let baseline = read_counters();
+        Self {
+            baseline,
+            latest: baseline,
+            last_read: Instant::now(),
+            abort,
+            seen: false,
+        }
+    }
+
+    /// Warns the first time swapping shows up, or with --abort-on-swap
+    /// reports it as an error. True when the run should stop.
+    pub fn check(&mut self) -> bool {
+        if self.baseline.is_none() || self.last_read.elapsed() < REPORT_INTERVAL {
+            return false;
+        }
+        self.last_read = Instant::now();
+        let Some(pages) = read_counters().and_then(|now| self.observe(now)) else {
+            return false;
+        };
+
+        let available = system::get_available_system_ram_mb()
+            .map(|mb| format!(", {} MB available", mb))
+            .unwrap_or_default();
+        if self.abort {
+            eprintln!(
+                "\nError: the system started swapping ({} pages{}); stopping (--abort-on-swap)",
+                pages, available
+            );
+        } else {
+            notice!(
+                "\n[Warning] The system started swapping ({} pages{}); memory results will include disk I/O",
+                pages,
+                available
+            );
+        }
+        self.abort
+    }
+
+    /// Records a reading; Some(pages since the start) the first time any
+    /// swapping has happened.
+    fn observe(&mut self, now: SwapCounters) -> Option<u64> {
+        self.latest = Some(now);
+        let pages = self.swapped_pages()?;
+        if self.seen || pages == 0 {
+            return None;
+        }
+        self.seen = true;
+        Some(pages)
+    }
+
+    /// Takes a last reading, so swapping since the final check still
+    /// counts, and returns the total.
+    pub fn finish(&mut self) -> Option<u64> {
+        if self.baseline.is_some() {
+            self.latest = read_counters().or(self.latest);
+        }
+        self.swapped_pages()
+    }
+
+    /// Pages swapped in or out since the start, as of the last reading.
+    /// None where swapping can't be observed.
+    pub fn swapped_pages(&self) -> Option<u64> {
+        Some(self.latest?.pages_since(&self.baseline?))
+    }
+
+    /// Whether the run was stopped for swapping.
+    pub fn aborted(&self) -> bool {
+        self.abort && self.seen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counters(pages_in: u64, pages_out: u64) -> SwapCounters {
+        SwapCounters {
+            pages_in,
+            pages_out,
+        }
+    }
+
+    fn monitor(baseline: Option<SwapCounters>, abort: bool) -> SwapMonitor {
+        SwapMonitor {
+            baseline,
+            latest: baseline,
+            last_read: Instant::now(),
+            abort,
+            seen: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_vmstat() {
+        let vmstat = "nr_free_pages 812345\n\
+                      pswpin 120\n\
+                      pswpout 4500\n\
+                      pswpin_extra 9\n";
+        assert_eq!(parse_vmstat(vmstat), Some(counters(120, 4500)));
+        // Kernels without swap support leave the fields out
+        assert_eq!(parse_vmstat("nr_free_pages 1\npswpin 3\n"), None);
+        assert_eq!(parse_vmstat(""), None);
+    }
+
+    #[test]
+    fn test_monitor_reports_the_first_swapping_once() {
+        let mut swap = monitor(Some(counters(100, 200)), false);
+        assert_eq!(swap.swapped_pages(), Some(0));
+
+        assert_eq!(swap.observe(counters(100, 200)), None);
+        assert_eq!(swap.observe(counters(110, 232)), Some(42));
+        // Already reported; the total keeps counting
+        assert_eq!(swap.observe(counters(150, 300)), None);
+        assert_eq!(swap.swapped_pages(), Some(150));
+        assert!(!swap.aborted());
+
+        let mut strict = monitor(Some(counters(0, 0)), true);
+        strict.observe(counters(0, 1));
+        assert!(strict.aborted());
+    }
+
+    #[test]
+    fn test_monitor_is_a_no_op_without_counters() {
+        let mut swap = monitor(None, true);
+        assert!(!swap.check());
+        assert_eq!(swap.swapped_pages(), None);
+        assert!(!swap.aborted());
+    }
+}
@@ -1,175 +1,1135 @@
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::dram::DramInfo;
+use crate::reporting::{format_bytes, format_mb};
+
 const MIN_BUFFER_MB: usize = 32;
-const RAM_SAFETY_FACTOR: f64 = 0.9;
+/// Fraction of system RAM auto-sized buffers may use, unless --ram-safety says
+/// otherwise
+pub const DEFAULT_RAM_SAFETY: f64 = 0.9;
+pub const RAM_SAFETY_RANGE: std::ops::RangeInclusive<f64> = 0.1..=1.0;
+
+/// Most all buffers together may take on a 32-bit target: three quarters of
+/// the 2 GiB of address space Windows gives a 32-bit process (Linux gives
+/// 3 GiB), leaving the rest for stacks, code and the heap. None = 64-bit.
+pub const ADDRESS_SPACE_MB: Option<usize> = if usize::BITS < 64 { Some(1536) } else { None };
+
+/// Sum of the planned per-thread buffers (threads without a buffer count as 0).
+pub fn total_allocation_mb(per_thread_mb: &[usize]) -> usize {
+    saturating_mb(per_thread_mb.iter().fold(0u64, |sum, &mb| sum.saturating_add(mb as u64)))
+}
 
-pub fn detect_memory_size(multiplier: usize) -> usize {
-    let num_cpus = num_cpus::get();
+/// `mb` × `count`: summed in u64, which two 32-bit values can't overflow.
+fn times_mb(mb: usize, count: usize) -> usize {
+    saturating_mb((mb as u64).saturating_mul(count as u64))
+}
 
-    if let Some(l3_mb) = detect_l3_cache() {
-        let recommended = (l3_mb * multiplier).max(MIN_BUFFER_MB);
+/// Back to usize, pinned at usize::MAX where it doesn't fit, which is still
+/// over every limit it gets compared with.
+fn saturating_mb(mb: u64) -> usize {
+    usize::try_from(mb).unwrap_or(usize::MAX)
+}
 
-        if let Some(total_ram_mb) = get_total_system_ram_mb() {
-            let total_allocation_mb = recommended * num_cpus;
-            let max_safe_mb = ((total_ram_mb as f64) * RAM_SAFETY_FACTOR) as usize;
+/// How much of `total_ram_mb` buffers may take at the given safety factor.
+pub fn max_safe_mb(total_ram_mb: usize, ram_safety: f64) -> usize {
+    ((total_ram_mb as f64) * ram_safety) as usize
+}
 
-            if total_allocation_mb > max_safe_mb {
-                let adjusted = (max_safe_mb / num_cpus).max(MIN_BUFFER_MB);
-                eprintln!(
-                    "[Auto-detect] L3 cache: {} MB → Calculated {} MB buffer per thread ({}x multiplier)",
-                    l3_mb, recommended, multiplier
-                );
+/// Share of currently available memory auto-sized buffers may use; page cache
+/// and other processes already hold the rest of the total.
+pub const AVAILABLE_RAM_SAFETY: f64 = 0.95;
 
-                eprintln!(
-                    "[Warning] Total allocation would be {} MB ({} threads × {} MB)",
-                    total_allocation_mb, num_cpus, recommended
-                );
+/// Which figure set the auto-sizing limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RamBound {
+    Total,
+    Available,
+}
 
-                eprintln!(
-                    "[Warning] Exceeds {}% of system RAM ({} MB total, {} MB limit)",
-                    (RAM_SAFETY_FACTOR * 100.0) as usize,
-                    total_ram_mb,
-                    max_safe_mb
-                );
+/// The tighter of `ram_safety` × total and [`AVAILABLE_RAM_SAFETY`] ×
+/// available memory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RamCap {
+    pub limit_mb: usize,
+    pub bound:    RamBound,
+    /// Share of `basis_mb` the limit allows
+    pub fraction: f64,
+    pub basis_mb: usize,
+}
 
-                eprintln!(
-                    "[Auto-detect] Reducing to {} MB per thread (total: {} MB)",
-                    adjusted,
-                    adjusted * num_cpus
-                );
-                return adjusted;
+impl RamBound {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Total => "total",
+            Self::Available => "available",
+        }
+    }
+}
+
+impl RamCap {
+    /// "90% of total RAM (16 GiB)" or "95% of available RAM (8.79 GiB)".
+    pub fn describe(&self) -> String {
+        format!(
+            "{}% of {} RAM ({})",
+            (self.fraction * 100.0).round() as usize,
+            self.bound.as_str(),
+            format_mb(self.basis_mb)
+        )
+    }
+}
+
+/// Falls back to the total-only limit when available memory is unknown.
+pub fn safe_ram_cap(
+    total_ram_mb: usize,
+    available_mb: Option<usize>,
+    ram_safety: f64,
+) -> RamCap {
+    let total = RamCap {
+        limit_mb: max_safe_mb(total_ram_mb, ram_safety),
+        bound:    RamBound::Total,
+        fraction: ram_safety,
+        basis_mb: total_ram_mb,
+    };
+    match available_mb {
+        Some(available) if max_safe_mb(available, AVAILABLE_RAM_SAFETY) < total.limit_mb => {
+            RamCap {
+                limit_mb: max_safe_mb(available, AVAILABLE_RAM_SAFETY),
+                bound:    RamBound::Available,
+                fraction: AVAILABLE_RAM_SAFETY,
+                basis_mb: available,
             }
+        },
+        _ => total,
+    }
+}
+
+/// Per-thread size once `buffered_threads` buffers must fit in `max_safe_mb`.
+fn capped_buffer_mb(recommended: usize, buffered_threads: usize, max_safe_mb: usize) -> usize {
+    if times_mb(recommended, buffered_threads) > max_safe_mb {
+        (max_safe_mb / buffered_threads).max(MIN_BUFFER_MB)
+    } else {
+        recommended
+    }
+}
+
+/// Per-thread size once `buffered_threads` buffers must fit in a hard
+/// `max_total_mb`. Unlike the RAM cap this may go below [`MIN_BUFFER_MB`].
+fn hard_capped_buffer_mb(
+    size_mb: usize,
+    buffered_threads: usize,
+    max_total_mb: usize,
+) -> usize {
+    if times_mb(size_mb, buffered_threads) > max_total_mb {
+        (max_total_mb / buffered_threads).max(1)
+    } else {
+        size_mb
+    }
+}
+
+/// What auto-sizing may allocate, from --ram-safety, --no-ram-cap and
+/// --max-total-memory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryLimits {
+    pub ram_safety:   f64,
+    /// Off means the RAM check only warns
+    pub ram_cap:      bool,
+    /// Applies even with `ram_cap` off
+    pub max_total_mb: Option<usize>,
+}
+
+impl Default for MemoryLimits {
+    fn default() -> Self {
+        Self {
+            ram_safety:   DEFAULT_RAM_SAFETY,
+            ram_cap:      true,
+            max_total_mb: None,
         }
+    }
+}
 
-        eprintln!(
-            "[Auto-detect] L3 cache: {} MB → Using {} MB buffer per thread ({}x multiplier)",
-            l3_mb, recommended, multiplier
-        );
-        return recommended;
-    }
-
-    let base_heuristic = match num_cpus {
-        1..=2 => 32,    // Old single/dual-core (Athlon, Pentium)
-        3..=4 => 64,    // Older quad-core (Ryzen 3 1200, i5-7400)
-        5..=8 => 128,   // Mainstream (Ryzen 5, i7)
-        9..=16 => 192,  // High-end desktop (Ryzen 7, i9)
-        17..=32 => 256, // HEDT (Threadripper, Xeon W)
-        33..=64 => 512,
-        65..=128 => 768,
-        _ => 1024,
+/// What detection sees, collected once by [`SystemInfo::collect`] so buffer
+/// sizing, the headers and every structured report agree. Fields older
+/// report files lack read back as their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemInfo {
+    pub os:               String,
+    pub arch:             String,
+    pub cpu_brand:        String,
+    /// Online logical CPUs
+    pub logical_cpus:     usize,
+    pub physical_cores:   usize,
+    /// Logical CPUs the affinity mask lets this process use
+    pub available_cpus:   usize,
+    pub l3:               Option<L3Cache>,
+    pub caches:           CacheInfo,
+    pub cache_line_bytes: usize,
+    pub total_ram_mb:     Option<usize>,
+    pub available_ram_mb: Option<usize>,
+    pub cgroup_limit_mb:  Option<usize>,
+    /// Nodes with memory; None where NUMA can't be read
+    pub numa_nodes:       Option<usize>,
+    /// Installed modules, where the firmware tables can be read
+    pub dram:             Option<DramInfo>,
+    /// The [`PROBED_CPU_FEATURES`] this CPU and OS support
+    pub cpu_features:     Vec<String>,
+}
+
+impl SystemInfo {
+    pub fn collect() -> Self {
+        let numa_nodes = crate::numa::memory_nodes().len();
+        Self {
+            os:               std::env::consts::OS.to_string(),
+            arch:             std::env::consts::ARCH.to_string(),
+            cpu_brand:        cpu_brand(),
+            logical_cpus:     online_cpu_count(),
+            physical_cores:   num_cpus::get_physical(),
+            available_cpus:   available_cpus().len(),
+            l3:               detect_l3_cache(),
+            caches:           CacheInfo {
+                l1d_bytes: detect_cache_bytes(1),
+                l2_bytes:  detect_cache_bytes(2),
+            },
+            cache_line_bytes: detect_cache_line_bytes().unwrap_or(DEFAULT_CACHE_LINE_BYTES),
+            total_ram_mb:     get_total_system_ram_mb(),
+            available_ram_mb: get_available_system_ram_mb(),
+            cgroup_limit_mb:  cgroup_memory_limit_mb(),
+            numa_nodes:       (numa_nodes > 0).then_some(numa_nodes),
+            dram:             crate::dram::detect(),
+            cpu_features:     detect_cpu_features(),
+        }
+    }
+
+    /// Capacity one core has at `level`: its own L1d or L2, or the L3 slice
+    /// of its complex.
+    pub fn cache_bytes(&self, level: CacheLevel) -> Option<u64> {
+        match level {
+            CacheLevel::L1 => self.caches.l1d_bytes,
+            CacheLevel::L2 => self.caches.l2_bytes,
+            CacheLevel::L3 => self.l3.map(|l3| l3.per_complex_mb as u64 * 1024 * 1024),
+        }
+    }
+}
+
+static SYSTEM_INFO: OnceLock<SystemInfo> = OnceLock::new();
+
+/// This machine, collected on the first call.
+pub fn info() -> &'static SystemInfo {
+    SYSTEM_INFO.get_or_init(SystemInfo::collect)
+}
+
+/// What the RAM check did to the recommendation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RamCheck {
+    /// Within the cap, or RAM size unknown
+    Fits,
+    /// Shrunk to fit the cap
+    Reduced(RamCap),
+    /// Over the cap, kept anyway for --no-ram-cap
+    Ignored(RamCap),
+}
+
+/// How [`detect_memory_size`] arrived at a buffer size, so the caller can
+/// explain it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferSizing {
+    /// Per-thread size to allocate
+    pub size_mb:          usize,
+    /// Multiplier × one L3 slice, or the CPU-count heuristic, before caps
+    pub recommended_mb:   usize,
+    pub multiplier:       usize,
+    pub buffered_threads: usize,
+    /// None when the heuristic stood in for an unknown L3
+    pub l3:               Option<L3Cache>,
+    pub cpus:             usize,
+    pub ram:              RamCheck,
+    /// --max-total-memory, when it cut the size further
+    pub hard_cap_mb:      Option<usize>,
+    /// [`ADDRESS_SPACE_MB`], when a 32-bit target cut it further still
+    pub address_cap_mb:   Option<usize>,
+}
+
+impl BufferSizing {
+    /// The "[Auto-detect]" lines that explain the choice, in order.
+    pub fn notes(&self) -> Vec<String> {
+        let mut notes = Vec::new();
+        let Some(l3) = self.l3 else {
+            notes.push(format!(
+                "[Auto-detect] L3 cache unknown → Using heuristic {} ({}x multiplier, {} CPUs)",
+                format_mb(self.recommended_mb),
+                self.multiplier,
+                self.cpus
+            ));
+            self.hard_cap_notes(&mut notes);
+            return notes;
+        };
+        let l3_desc = l3.describe();
+        let total_mb = times_mb(self.recommended_mb, self.buffered_threads);
+
+        match self.ram {
+            RamCheck::Fits => notes.push(format!(
+                "[Auto-detect] L3 cache: {} → Using {} buffer per thread ({}x multiplier)",
+                l3_desc,
+                format_mb(self.recommended_mb),
+                self.multiplier
+            )),
+            RamCheck::Ignored(cap) => {
+                notes.push(format!(
+                    "[Auto-detect] L3 cache: {} → Using {} buffer per thread ({}x multiplier)",
+                    l3_desc,
+                    format_mb(self.recommended_mb),
+                    self.multiplier
+                ));
+                notes.push(format!(
+                    "[Warning] --no-ram-cap: allocating {} total, over {}",
+                    format_mb(total_mb),
+                    cap.describe()
+                ));
+                notes.push(
+                    "[Warning] Expect heavy swapping; the OOM killer may end this or other processes"
+                        .to_string(),
+                );
+            },
+            RamCheck::Reduced(cap) => {
+                let adjusted =
+                    capped_buffer_mb(self.recommended_mb, self.buffered_threads, cap.limit_mb);
+                notes.push(format!(
+                    "[Auto-detect] L3 cache: {} → Calculated {} buffer per thread ({}x multiplier)",
+                    l3_desc,
+                    format_mb(self.recommended_mb),
+                    self.multiplier
+                ));
+                notes.push(format!(
+                    "[Warning] Total allocation would be {} ({} threads × {})",
+                    format_mb(total_mb),
+                    self.buffered_threads,
+                    format_mb(self.recommended_mb)
+                ));
+                notes.push(format!(
+                    "[Warning] Exceeds {}: {} limit",
+                    cap.describe(),
+                    format_mb(cap.limit_mb)
+                ));
+                notes.push(format!(
+                    "[Auto-detect] Reducing to {} per thread (total: {}, bound by {} RAM)",
+                    format_mb(adjusted),
+                    format_mb(times_mb(adjusted, self.buffered_threads)),
+                    cap.bound.as_str()
+                ));
+            },
+        }
+        self.hard_cap_notes(&mut notes);
+        notes
+    }
+
+    fn hard_cap_notes(&self, notes: &mut Vec<String>) {
+        if let Some(max_total_mb) = self.hard_cap_mb {
+            notes.push(format!(
+                "[Auto-detect] --max-total-memory {}: reducing to {} per thread (total: {})",
+                format_mb(max_total_mb),
+                format_mb(self.size_mb),
+                format_mb(times_mb(self.size_mb, self.buffered_threads))
+            ));
+        }
+        if let Some(limit_mb) = self.address_cap_mb {
+            notes.push(format!(
+                "[Auto-detect] {}-bit target: reducing to {} per thread to stay within {} of \
+                 address space",
+                usize::BITS,
+                format_mb(self.size_mb),
+                format_mb(limit_mb)
+            ));
+        }
+        if (self.hard_cap_mb.is_some() || self.address_cap_mb.is_some())
+            && self.size_mb < MIN_BUFFER_MB
+        {
+            notes.push(format!(
+                "[Warning] Under the {} per-thread minimum; buffers may fit in cache",
+                format_mb(MIN_BUFFER_MB)
+            ));
+        }
+    }
+}
+
+/// Recommended per-thread buffer size on the machine `info` describes, with
+/// the RAM safety check and any hard total cap applied to the
+/// `buffered_threads` that will actually allocate a buffer. Prints nothing; see
+/// [`BufferSizing::notes`].
+pub fn detect_memory_size(
+    multiplier: usize,
+    buffered_threads: usize,
+    limits: &MemoryLimits,
+    info: &SystemInfo,
+) -> BufferSizing {
+    let buffered_threads = buffered_threads.max(1);
+    let mut sizing = ram_sized_buffer(multiplier, buffered_threads, limits, info);
+
+    if let Some(max_total_mb) = limits.max_total_mb {
+        let capped = hard_capped_buffer_mb(sizing.size_mb, buffered_threads, max_total_mb);
+        if capped < sizing.size_mb {
+            sizing.size_mb = capped;
+            sizing.hard_cap_mb = Some(max_total_mb);
+        }
+    }
+    cap_to_address_space(&mut sizing, ADDRESS_SPACE_MB);
+    sizing
+}
+
+/// Shrinks the buffers to fit `limit_mb` of address space in total, which
+/// --no-ram-cap doesn't lift: past it the allocations fail, not swap.
+fn cap_to_address_space(sizing: &mut BufferSizing, limit_mb: Option<usize>) {
+    let Some(limit_mb) = limit_mb else {
+        return;
+    };
+    let capped = hard_capped_buffer_mb(sizing.size_mb, sizing.buffered_threads, limit_mb);
+    if capped < sizing.size_mb {
+        sizing.size_mb = capped;
+        sizing.address_cap_mb = Some(limit_mb);
+    }
+}
+
+fn ram_sized_buffer(
+    multiplier: usize,
+    buffered_threads: usize,
+    limits: &MemoryLimits,
+    info: &SystemInfo,
+) -> BufferSizing {
+    let cpus = info.available_cpus;
+    let Some(l3) = info.l3 else {
+        let base_heuristic = match cpus {
+            0..=2 => 32,    // Old single/dual-core (Athlon, Pentium)
+            3..=4 => 64,    // Older quad-core (Ryzen 3 1200, i5-7400)
+            5..=8 => 128,   // Mainstream (Ryzen 5, i7)
+            9..=16 => 192,  // High-end desktop (Ryzen 7, i9)
+            17..=32 => 256, // HEDT (Threadripper, Xeon W)
+            33..=64 => 512,
+            65..=128 => 768,
+            _ => 1024,
+        };
+        // A float-to-int cast saturates, so a huge multiplier can't wrap
+        let scaled = ((base_heuristic as f64) * (multiplier as f64 / 4.0)) as usize;
+        let heuristic_mb = scaled.max(MIN_BUFFER_MB);
+        return BufferSizing {
+            size_mb: heuristic_mb,
+            recommended_mb: heuristic_mb,
+            multiplier,
+            buffered_threads,
+            l3: None,
+            cpus,
+            ram: RamCheck::Fits,
+            hard_cap_mb: None,
+            address_cap_mb: None,
+        };
+    };
+
+    // One thread only ever sees its own complex's slice
+    let recommended = times_mb(l3.per_complex_mb, multiplier).max(MIN_BUFFER_MB);
+    let mut sizing = BufferSizing {
+        size_mb: recommended,
+        recommended_mb: recommended,
+        multiplier,
+        buffered_threads,
+        l3: Some(l3),
+        cpus,
+        ram: RamCheck::Fits,
+        hard_cap_mb: None,
+        address_cap_mb: None,
+    };
+    let Some(total_ram_mb) = info.total_ram_mb else {
+        return sizing;
     };
 
-    let scaled = ((base_heuristic as f64) * (multiplier as f64 / 4.0)) as usize;
-    let heuristic_mb = scaled.max(MIN_BUFFER_MB);
+    let total_allocation_mb = total_allocation_mb(&vec![recommended; buffered_threads]);
+    let cap = safe_ram_cap(total_ram_mb, info.available_ram_mb, limits.ram_safety);
+    if total_allocation_mb > cap.limit_mb {
+        if limits.ram_cap {
+            sizing.size_mb = capped_buffer_mb(recommended, buffered_threads, cap.limit_mb);
+            sizing.ram = RamCheck::Reduced(cap);
+        } else {
+            sizing.ram = RamCheck::Ignored(cap);
+        }
+    }
+    sizing
+}
+
+/// L3 as the package has it: Zen splits it into one slice per CCX (or CCD),
+/// each shared only by the cores in that complex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L3Cache {
+    /// One slice - all that a single thread can use
+    pub per_complex_mb: usize,
+    /// Distinct L3 instances; 1 when there is only one or it can't be told
+    pub complexes:      usize,
+}
+
+impl L3Cache {
+    #[cfg_attr(
+        not(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "freebsd",
+            target_os = "openbsd"
+        )),
+        allow(dead_code)
+    )]
+    fn single(mb: usize) -> Self {
+        Self {
+            per_complex_mb: mb,
+            complexes:      1,
+        }
+    }
+
+    pub fn total_mb(&self) -> usize {
+        self.per_complex_mb * self.complexes
+    }
+
+    /// "32 MiB" or "32 MiB per complex × 4 (128 MiB total)"
+    pub fn describe(&self) -> String {
+        if self.complexes > 1 {
+            format!(
+                "{} per complex × {} ({} total)",
+                format_mb(self.per_complex_mb),
+                self.complexes,
+                format_mb(self.total_mb())
+            )
+        } else {
+            format_mb(self.per_complex_mb)
+        }
+    }
+}
+
+/// Per-core data cache sizes below L3, which [`L3Cache`] covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheInfo {
+    pub l1d_bytes: Option<u64>,
+    pub l2_bytes:  Option<u64>,
+}
+
+/// A cache level `cache-thrash` can be aimed at (`--target`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CacheLevel {
+    /// Per-core L1 data cache
+    L1,
+    /// Per-core L2
+    L2,
+    /// The L3 slice a core's complex shares
+    L3,
+}
+
+impl CacheLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::L1 => "L1d",
+            Self::L2 => "L2",
+            Self::L3 => "L3",
+        }
+    }
+
+    /// Assumed when detection finds nothing; common sizes on current parts.
+    fn fallback_bytes(self) -> u64 {
+        match self {
+            Self::L1 => 32 * 1024,
+            Self::L2 => 1024 * 1024,
+            Self::L3 => 32 * 1024 * 1024,
+        }
+    }
+}
+
+/// What `cache-thrash` sweeps for a `--target` level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheSweep {
+    pub level:       CacheLevel,
+    pub cache_bytes: u64,
+    /// False when `cache_bytes` is [`CacheLevel`]'s fallback
+    pub detected:    bool,
+    /// Per-thread buffer one pass covers
+    pub bytes:       u64,
+}
+
+impl CacheSweep {
+    /// Twice the level's capacity, in whole cache lines. A sequential pass
+    /// over more than the level holds leaves none of the previous pass
+    /// behind under LRU-like replacement, so every line of every pass is
+    /// refilled from the level below.
+    pub fn new(info: &SystemInfo, level: CacheLevel) -> Self {
+        let detected = info.cache_bytes(level).filter(|&bytes| bytes > 0);
+        let cache_bytes = detected.unwrap_or_else(|| level.fallback_bytes());
+        let line = info.cache_line_bytes.max(1) as u64;
+        Self {
+            level,
+            cache_bytes,
+            detected: detected.is_some(),
+            bytes: (2 * cache_bytes).div_ceil(line) * line,
+        }
+    }
 
-    eprintln!(
-        "[Auto-detect] L3 cache unknown → Using heuristic {} MB ({}x multiplier, {} CPUs)",
-        heuristic_mb, multiplier, num_cpus
-    );
-    heuristic_mb
+    /// "4 MiB per thread, 2× the 2 MiB L2"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} per thread, 2× the {} {}{}",
+            format_bytes(self.bytes),
+            format_bytes(self.cache_bytes),
+            self.level.as_str(),
+            if self.detected {
+                ""
+            } else {
+                " (assumed; not detected)"
+            }
+        )
+    }
 }
 
+/// Size in bytes of the level-`level` data or unified cache one core sees.
 #[cfg(target_os = "linux")]
-fn detect_l3_cache() -> Option<usize> {
-    detect_l3_cache_linux()
+fn detect_cache_bytes(level: u8) -> Option<u64> {
+    cache_bytes_from_sysfs(Path::new("/sys/devices/system/cpu"), level)
+        .or_else(|| cpuid_cache_bytes(level))
 }
 
 #[cfg(target_os = "windows")]
-fn detect_l3_cache() -> Option<usize> {
-    detect_l3_cache_windows()
+fn detect_cache_bytes(level: u8) -> Option<u64> {
+    cache_bytes_from_processor_info(&processor_cache_info()?, level)
 }
 
+/// Apple Silicon reports the performance cores' caches per perflevel; the
+/// plain keys describe the efficiency cores there, and all cores on Intel.
 #[cfg(target_os = "macos")]
-fn detect_l3_cache() -> Option<usize> {
-    detect_l3_cache_macos()
+fn detect_cache_bytes(level: u8) -> Option<u64> {
+    let key = match level {
+        1 => "l1dcachesize",
+        2 => "l2cachesize",
+        _ => return None,
+    };
+    [format!("hw.perflevel0.{}", key), format!("hw.{}", key)]
+        .iter()
+        .find_map(|name| sysctl_u64(name).filter(|&bytes| bytes > 0))
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+fn detect_cache_bytes(level: u8) -> Option<u64> {
+    cpuid_cache_bytes(level)
 }
 
-#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
-fn detect_l3_cache() -> Option<usize> {
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd"
+)))]
+fn detect_cache_bytes(_level: u8) -> Option<u64> {
     None
 }
 
+/// The first data or unified `level` entry under `cpu_root/cpu0/cache/`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn cache_bytes_from_sysfs(cpu_root: &Path, level: u8) -> Option<u64> {
+    let cache = cpu_root.join("cpu0").join("cache");
+    (0..=10).find_map(|index| {
+        let dir = cache.join(format!("index{}", index));
+        let found: u8 = std::fs::read_to_string(dir.join("level"))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()?;
+        let kind = std::fs::read_to_string(dir.join("type")).unwrap_or_default();
+        if found != level || kind.trim() == "Instruction" {
+            return None;
+        }
+        parse_cache_bytes(&std::fs::read_to_string(dir.join("size")).ok()?)
+    })
+}
+
 #[cfg(target_os = "linux")]
-fn detect_l3_cache_linux() -> Option<usize> {
+pub fn detect_l3_cache() -> Option<L3Cache> {
+    // Some containers hide the sysfs cache directories
+    l3_from_sysfs(Path::new("/sys/devices/system/cpu"))
+        .or_else(|| cpuid_l3_cache_mb().map(L3Cache::single))
+}
+
+#[cfg(target_os = "windows")]
+pub fn detect_l3_cache() -> Option<L3Cache> {
+    detect_l3_cache_windows()
+}
+
+#[cfg(target_os = "macos")]
+pub fn detect_l3_cache() -> Option<L3Cache> {
+    detect_l3_cache_macos().map(L3Cache::single)
+}
+
+/// FreeBSD has no portable cache MIB; `hw.l3cachesize` exists on some
+/// platforms and CPUID covers the rest.
+#[cfg(target_os = "freebsd")]
+pub fn detect_l3_cache() -> Option<L3Cache> {
+    sysctl_u64("hw.l3cachesize")
+        .map(|bytes| (bytes / (1024 * 1024)) as usize)
+        .filter(|&mb| mb > 0)
+        .or_else(cpuid_l3_cache_mb)
+        .map(L3Cache::single)
+}
+
+#[cfg(target_os = "openbsd")]
+pub fn detect_l3_cache() -> Option<L3Cache> {
+    cpuid_l3_cache_mb().map(L3Cache::single)
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd"
+)))]
+pub fn detect_l3_cache() -> Option<L3Cache> {
+    None
+}
+
+/// Assumed when detection finds nothing; right for x86 and most Arm cores,
+/// but Apple Silicon uses 128.
+pub const DEFAULT_CACHE_LINE_BYTES: usize =
+    if cfg!(all(target_arch = "aarch64", target_os = "macos")) { 128 } else { 64 };
+
+/// Coherency line size in bytes. Apple Silicon and some POWER and Arm server
+/// parts use 128.
+fn detect_cache_line_bytes() -> Option<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        line_size_from_sysfs(Path::new("/sys/devices/system/cpu"))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        line_size_from_processor_info(&processor_cache_info()?)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        sysctl_u64("hw.cachelinesize").and_then(|bytes| valid_line_size(bytes as usize))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// Some VMs report 0 and some firmware garbage; only plausible lines count.
+fn valid_line_size(bytes: usize) -> Option<usize> {
+    (bytes.is_power_of_two() && (16..=1024).contains(&bytes)).then_some(bytes)
+}
+
+/// The first `coherency_line_size` under `cpu_root/cpu0/cache/index*/`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn line_size_from_sysfs(cpu_root: &Path) -> Option<usize> {
+    let cache = cpu_root.join("cpu0").join("cache");
+    (0..=10).find_map(|index| {
+        let path = cache
+            .join(format!("index{}", index))
+            .join("coherency_line_size");
+        let contents = std::fs::read_to_string(path).ok()?;
+        valid_line_size(contents.trim().parse().ok()?)
+    })
+}
+
+/// Walks `cpu_root/cpuN/cache/index*/` for level-3 entries. The first CPU's
+/// slice gives the size; distinct `shared_cpu_map` values count the slices.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn l3_from_sysfs(cpu_root: &Path) -> Option<L3Cache> {
     use std::fs;
 
-    for index in 0..=10 {
-        let level_path = format!("/sys/devices/system/cpu/cpu0/cache/index{}/level", index);
-        let size_path = format!("/sys/devices/system/cpu/cpu0/cache/index{}/size", index);
+    let mut cpus: Vec<(usize, std::path::PathBuf)> = fs::read_dir(cpu_root)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let id = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("cpu")?
+                .parse()
+                .ok()?;
+            Some((id, entry.path()))
+        })
+        .collect();
+    cpus.sort();
+
+    let mut per_complex_mb = None;
+    let mut shared_maps: Vec<String> = Vec::new();
+    for (_, cpu) in &cpus {
+        for index in 0..=10 {
+            let dir = cpu.join("cache").join(format!("index{}", index));
+            let Ok(level) = fs::read_to_string(dir.join("level")) else {
+                continue;
+            };
+            if level.trim() != "3" {
+                continue;
+            }
 
-        if let Ok(level) = fs::read_to_string(&level_path)
-            && level.trim() == "3"
-            && let Ok(size_str) = fs::read_to_string(&size_path)
-            && let Some(mb) = parse_cache_size(&size_str)
-        {
-            return Some(mb);
+            if per_complex_mb.is_none() {
+                per_complex_mb = fs::read_to_string(dir.join("size"))
+                    .ok()
+                    .and_then(|size| parse_cache_size(&size));
+            }
+            // Without a map every CPU is assumed to share one slice
+            let map = fs::read_to_string(dir.join("shared_cpu_map")).unwrap_or_default();
+            let map = map.trim().to_string();
+            if !shared_maps.contains(&map) {
+                shared_maps.push(map);
+            }
+            break;
         }
     }
 
+    Some(L3Cache {
+        per_complex_mb: per_complex_mb?,
+        complexes:      shared_maps.len().max(1),
+    })
+}
+
+#[cfg_attr(
+    not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")),
+    allow(dead_code)
+)]
+fn cpuid_l3_cache_mb() -> Option<usize> {
+    cpuid_cache_bytes(3).map(|bytes| (bytes / (1024 * 1024)) as usize)
+}
+
+#[cfg_attr(
+    not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")),
+    allow(dead_code)
+)]
+fn cpuid_cache_bytes(level: u8) -> Option<u64> {
+    cache_bytes_from_params(cpuid_cache_params()?, level)
+}
+
+/// The deterministic cache parameters leaf, one [eax, ebx, ecx] per subleaf.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[cfg_attr(
+    not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")),
+    allow(dead_code)
+)]
+fn cpuid_cache_params() -> Option<Vec<[u32; 3]>> {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::{__cpuid, __cpuid_count};
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::{__cpuid, __cpuid_count};
+
+    #[allow(unused_unsafe)]
+    let basic = unsafe { __cpuid(0) };
+    #[allow(unused_unsafe)]
+    let max_extended = unsafe { __cpuid(0x8000_0000) }.eax;
+
+    let mut vendor = [0u8; 12];
+    for (chunk, reg) in vendor.chunks_mut(4).zip([basic.ebx, basic.edx, basic.ecx]) {
+        chunk.copy_from_slice(&reg.to_le_bytes());
+    }
+    let leaf = cache_params_leaf(&vendor, basic.eax, max_extended)?;
+
+    // Bounded in case a hypervisor never reports the terminating null entry
+    Some(
+        (0..16)
+            .map(|subleaf| {
+                #[allow(unused_unsafe)]
+                let regs = unsafe { __cpuid_count(leaf, subleaf) };
+                [regs.eax, regs.ebx, regs.ecx]
+            })
+            .collect(),
+    )
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+#[cfg_attr(
+    not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")),
+    allow(dead_code)
+)]
+fn cpuid_cache_params() -> Option<Vec<[u32; 3]>> {
+    None
+}
+
+/// AMD and Hygon describe their caches in 0x8000_001D; leaf 4 is reserved
+/// there. Everyone else uses leaf 4.
+#[cfg_attr(
+    not(all(
+        any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+        any(target_arch = "x86", target_arch = "x86_64")
+    )),
+    allow(dead_code)
+)]
+fn cache_params_leaf(vendor: &[u8; 12], max_basic: u32, max_extended: u32) -> Option<u32> {
+    match vendor {
+        b"AuthenticAMD" | b"HygonGenuine" => {
+            (max_extended >= 0x8000_001d).then_some(0x8000_001d)
+        },
+        _ => (max_basic >= 4).then_some(4),
+    }
+}
+
+/// Bytes of the first data or unified `level` cache in (eax, ebx, ecx) of
+/// each subleaf, in order. Both leaves share the layout: type in eax[4:0]
+/// (0 ends the list, 2 is instruction), level in eax[7:5], and size = ways ×
+/// partitions × line size × sets, each stored minus one.
+#[cfg_attr(
+    not(all(
+        any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+        any(target_arch = "x86", target_arch = "x86_64")
+    )),
+    allow(dead_code)
+)]
+fn cache_bytes_from_params(
+    subleaves: impl IntoIterator<Item = [u32; 3]>,
+    level: u8,
+) -> Option<u64> {
+    for [eax, ebx, ecx] in subleaves {
+        match eax & 0x1f {
+            0 => break,
+            2 => continue,
+            _ => {},
+        }
+        if (eax >> 5) & 0x7 != level as u32 {
+            continue;
+        }
+        let ways = ((ebx >> 22) & 0x3ff) as u64 + 1;
+        let partitions = ((ebx >> 12) & 0x3ff) as u64 + 1;
+        let line_size = (ebx & 0xfff) as u64 + 1;
+        let sets = ecx as u64 + 1;
+        return Some(ways * partitions * line_size * sets);
+    }
     None
 }
 
 #[cfg(target_os = "windows")]
-fn detect_l3_cache_windows() -> Option<usize> {
+fn detect_l3_cache_windows() -> Option<L3Cache> {
+    l3_from_processor_info(&processor_cache_info()?)
+}
+
+/// The raw `GetLogicalProcessorInformationEx(RelationCache, ..)` records.
+#[cfg(target_os = "windows")]
+fn processor_cache_info() -> Option<Vec<u8>> {
     use std::mem;
 
     use windows_sys::Win32::System::SystemInformation::{
-        GetLogicalProcessorInformationEx,
+        CACHE_RELATIONSHIP,
         RelationCache,
         SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
     };
 
+    // The decoder reads raw bytes; hold it to the SDK's layout
+    const _: () = {
+        assert!(
+            mem::offset_of!(SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX, Anonymous) == SLPI_HEADER
+        );
+        assert!(mem::offset_of!(CACHE_RELATIONSHIP, LineSize) == CACHE_LINE_SIZE_OFFSET);
+        assert!(mem::offset_of!(CACHE_RELATIONSHIP, CacheSize) == CACHE_SIZE_OFFSET);
+        assert!(mem::offset_of!(CACHE_RELATIONSHIP, Type) == CACHE_TYPE_OFFSET);
+        assert!(mem::offset_of!(CACHE_RELATIONSHIP, GroupCount) == CACHE_GROUP_COUNT_OFFSET);
+        assert!(mem::offset_of!(CACHE_RELATIONSHIP, Anonymous) == CACHE_GROUP_MASK_OFFSET);
+    };
+    processor_info(RelationCache)
+}
+
+/// The raw `GetLogicalProcessorInformationEx(RelationProcessorCore, ..)`
+/// records.
+#[cfg(target_os = "windows")]
+fn processor_core_info() -> Option<Vec<u8>> {
+    use std::mem;
+
+    use windows_sys::Win32::System::SystemInformation::{
+        PROCESSOR_RELATIONSHIP,
+        RelationProcessorCore,
+    };
+
+    const _: () = {
+        assert!(mem::offset_of!(PROCESSOR_RELATIONSHIP, GroupCount) == CORE_GROUP_COUNT_OFFSET);
+        assert!(mem::offset_of!(PROCESSOR_RELATIONSHIP, GroupMask) == CORE_GROUP_MASK_OFFSET);
+    };
+    processor_info(RelationProcessorCore)
+}
+
+#[cfg(target_os = "windows")]
+fn processor_info(
+    relation: windows_sys::Win32::System::SystemInformation::LOGICAL_PROCESSOR_RELATIONSHIP,
+) -> Option<Vec<u8>> {
+    use windows_sys::Win32::System::SystemInformation::{
+        GetLogicalProcessorInformationEx,
+        SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
+    };
+
+    let mut buffer_size: u32 = 0;
     unsafe {
-        let mut buffer_size: u32 = 0;
-        GetLogicalProcessorInformationEx(RelationCache, std::ptr::null_mut(), &mut buffer_size);
+        GetLogicalProcessorInformationEx(relation, std::ptr::null_mut(), &mut buffer_size);
+    }
+    if buffer_size == 0 {
+        return None;
+    }
 
-        if buffer_size == 0 {
-            return None;
-        }
+    // u64 elements keep the records 8-byte aligned for the API
+    let mut buffer = vec![0u64; (buffer_size as usize).div_ceil(8)];
+    let ok = unsafe {
+        GetLogicalProcessorInformationEx(
+            relation,
+            buffer.as_mut_ptr() as *mut SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
+            &mut buffer_size,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
 
-        let mut buffer = vec![0u8; buffer_size as usize];
-        let buffer_ptr = buffer.as_mut_ptr() as *mut SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX;
+    let bytes = unsafe {
+        std::slice::from_raw_parts(buffer.as_ptr() as *const u8, buffer_size as usize)
+    };
+    Some(bytes.to_vec())
+}
 
-        if GetLogicalProcessorInformationEx(RelationCache, buffer_ptr, &mut buffer_size) == 0 {
-            return None;
+/// Offsets in SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX: Relationship (u32),
+/// Size (u32), then the union holding a CACHE_RELATIONSHIP.
+const SLPI_HEADER: usize = 8;
+/// In CACHE_RELATIONSHIP: Level (u8), Associativity (u8), LineSize (u16),
+/// CacheSize (u32), Type (u32), Reserved [u8; 18], GroupCount (u16), then
+/// GroupCount GROUP_AFFINITY entries (a pointer-sized Mask, Group u16).
+const CACHE_LINE_SIZE_OFFSET: usize = 2;
+const CACHE_SIZE_OFFSET: usize = 4;
+const CACHE_TYPE_OFFSET: usize = 8;
+/// PROCESSOR_CACHE_TYPE: 0 unified, 1 instruction, 2 data, 3 trace
+const CACHE_INSTRUCTION: u32 = 1;
+#[cfg(target_os = "windows")]
+const CACHE_GROUP_COUNT_OFFSET: usize = 30;
+const CACHE_GROUP_MASK_OFFSET: usize = 32;
+const RELATION_CACHE: u32 = 2;
+/// In PROCESSOR_RELATIONSHIP: Flags (u8), EfficiencyClass (u8),
+/// Reserved [u8; 20], GroupCount (u16), then the GROUP_AFFINITY entries.
+#[cfg(target_os = "windows")]
+const CORE_GROUP_COUNT_OFFSET: usize = 22;
+const CORE_GROUP_MASK_OFFSET: usize = 24;
+const RELATION_PROCESSOR_CORE: u32 = 0;
+
+/// Decodes a `GetLogicalProcessorInformationEx(RelationCache, ..)` buffer.
+/// Every level-3 record is one complex; its first group mask tells apart
+/// records that describe the same cache. Records vary in size, so the walk
+/// follows each one's Size field to the end of the buffer.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn l3_from_processor_info(buffer: &[u8]) -> Option<L3Cache> {
+    let u32_at = |at: usize| Some(u32::from_le_bytes(buffer.get(at..at + 4)?.try_into().ok()?));
+    // KAFFINITY is pointer-sized, so the Group after it moves on 32-bit
+    const MASK_BYTES: usize = std::mem::size_of::<usize>();
+
+    let mut per_complex_bytes = None;
+    let mut masks: Vec<&[u8]> = Vec::new();
+    let mut offset = 0;
+
+    while let (Some(relationship), Some(size)) = (u32_at(offset), u32_at(offset + 4)) {
+        let size = size as usize;
+        if size < SLPI_HEADER || offset + size > buffer.len() {
+            break;
         }
+        let cache = offset + SLPI_HEADER;
+        if relationship == RELATION_CACHE && buffer.get(cache) == Some(&3) {
+            per_complex_bytes.get_or_insert(u32_at(cache + CACHE_SIZE_OFFSET)?);
+            // Mask plus Group; pre-Windows 11 builds always return exactly one
+            let mask_at = cache + CACHE_GROUP_MASK_OFFSET;
+            let mask = buffer.get(mask_at..mask_at + MASK_BYTES + 2)?;
+            if !masks.contains(&mask) {
+                masks.push(mask);
+            }
+        }
+        offset += size;
+    }
+
+    let per_complex_mb = (per_complex_bytes? / (1024 * 1024)) as usize;
+    (per_complex_mb > 0).then(|| L3Cache {
+        per_complex_mb,
+        complexes: masks.len().max(1),
+    })
+}
 
-        let mut offset = 0usize;
-        while offset + mem::size_of::<SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX>()
-            <= buffer_size as usize
+/// Decodes a `GetLogicalProcessorInformationEx(RelationProcessorCore, ..)`
+/// buffer: each record is one physical core, and the set bits of its first
+/// group mask are that core's logical CPUs.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn smt_siblings_from_processor_info(buffer: &[u8]) -> Vec<Vec<usize>> {
+    let u32_at = |at: usize| Some(u32::from_le_bytes(buffer.get(at..at + 4)?.try_into().ok()?));
+    const MASK_BYTES: usize = std::mem::size_of::<usize>();
+
+    let mut groups = Vec::new();
+    let mut offset = 0;
+    while let (Some(relationship), Some(size)) = (u32_at(offset), u32_at(offset + 4)) {
+        let size = size as usize;
+        if size < SLPI_HEADER || offset + size > buffer.len() {
+            break;
+        }
+        let mask_at = offset + SLPI_HEADER + CORE_GROUP_MASK_OFFSET;
+        if relationship == RELATION_PROCESSOR_CORE
+            && let Some(entry) = buffer.get(mask_at..mask_at + MASK_BYTES + 2)
         {
-            let info = &*(buffer.as_ptr().add(offset)
-                as *const SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX);
-
-            if info.Relationship == RelationCache {
-                let cache_info_ptr =
-                    (info as *const SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX as usize
-                        + mem::size_of::<u32>()
-                        + mem::size_of::<u32>()) as *const CacheDescriptor;
-
-                let cache = &*cache_info_ptr;
-
-                if cache.Level == 3 {
-                    let size_mb = cache.CacheSize / (1024 * 1024);
-                    if size_mb > 0 {
-                        return Some(size_mb as usize);
-                    }
-                }
+            let mut mask = [0u8; 8];
+            mask[..MASK_BYTES].copy_from_slice(&entry[..MASK_BYTES]);
+            let mask = u64::from_le_bytes(mask);
+            let group = u16::from_le_bytes([entry[MASK_BYTES], entry[MASK_BYTES + 1]]) as usize;
+            let cpus: Vec<usize> = (0..64)
+                .filter(|bit| mask & (1u64 << bit) != 0)
+                .map(|bit| group * 64 + bit)
+                .collect();
+            if !cpus.is_empty() {
+                groups.push(cpus);
             }
-
-            offset += info.Size as usize;
         }
+        offset += size;
     }
+    groups.sort();
+    groups
+}
 
+/// LineSize of the first cache record in the same buffer; every level
+/// shares one coherency line on the parts Windows runs on.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn line_size_from_processor_info(buffer: &[u8]) -> Option<usize> {
+    let u32_at = |at: usize| Some(u32::from_le_bytes(buffer.get(at..at + 4)?.try_into().ok()?));
+    let mut offset = 0;
+
+    while let (Some(relationship), Some(size)) = (u32_at(offset), u32_at(offset + 4)) {
+        let size = size as usize;
+        if size < SLPI_HEADER || offset + size > buffer.len() {
+            break;
+        }
+        if relationship == RELATION_CACHE {
+            let at = offset + SLPI_HEADER + CACHE_LINE_SIZE_OFFSET;
+            let line = u16::from_le_bytes(buffer.get(at..at + 2)?.try_into().ok()?);
+            return valid_line_size(line as usize);
+        }
+        offset += size;
+    }
     None
 }
 
-#[cfg(target_os = "windows")]
-#[repr(C)]
-struct CacheDescriptor {
-    Level:         u8,
-    Associativity: u8,
-    LineSize:      u16,
-    CacheSize:     u32,
-    Type:          u32,
+/// CacheSize of the first data or unified record at `level` in the same
+/// buffer; per-core levels repeat one record per core.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn cache_bytes_from_processor_info(buffer: &[u8], level: u8) -> Option<u64> {
+    let u32_at = |at: usize| Some(u32::from_le_bytes(buffer.get(at..at + 4)?.try_into().ok()?));
+    let mut offset = 0;
+
+    while let (Some(relationship), Some(size)) = (u32_at(offset), u32_at(offset + 4)) {
+        let size = size as usize;
+        if size < SLPI_HEADER || offset + size > buffer.len() {
+            break;
+        }
+        let cache = offset + SLPI_HEADER;
+        if relationship == RELATION_CACHE
+            && buffer.get(cache) == Some(&level)
+            && u32_at(cache + CACHE_TYPE_OFFSET)? != CACHE_INSTRUCTION
+        {
+            return u32_at(cache + CACHE_SIZE_OFFSET)
+                .map(u64::from)
+                .filter(|&bytes| bytes > 0);
+        }
+        offset += size;
+    }
+    None
 }
 
 #[cfg(target_os = "macos")]
@@ -210,36 +1170,38 @@ fn detect_l3_cache_macos() -> Option<usize> {
 }
 
 fn parse_cache_size(s: &str) -> Option<usize> {
+    parse_cache_bytes(s).map(|bytes| (bytes / (1024 * 1024)) as usize)
+}
+
+/// A sysfs cache `size`: "48K", "2M" or plain bytes.
+fn parse_cache_bytes(s: &str) -> Option<u64> {
     let s = s.trim();
 
-    if s.ends_with('K') || s.ends_with('k') {
-        let kb: usize = s[..s.len() - 1].parse().ok()?;
-        Some(kb / 1024)
-    } else if s.ends_with('M') || s.ends_with('m') {
-        s[..s.len() - 1].parse().ok()
+    if let Some(kb) = s.strip_suffix(['K', 'k']) {
+        Some(kb.parse::<u64>().ok()? * 1024)
+    } else if let Some(mb) = s.strip_suffix(['M', 'm']) {
+        Some(mb.parse::<u64>().ok()? * 1024 * 1024)
     } else {
-        let bytes: usize = s.parse().ok()?;
-        Some(bytes / (1024 * 1024))
+        s.parse().ok()
     }
 }
 
-fn get_total_system_ram_mb() -> Option<usize> {
+/// A `/proc/meminfo` field such as "MemAvailable", in MB.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn meminfo_mb(contents: &str, field: &str) -> Option<usize> {
+    contents.lines().find_map(|line| {
+        let rest = line.strip_prefix(field)?.strip_prefix(':')?;
+        // kB passes u32 at 4 TiB, so it's read as u64 even on 32-bit targets
+        let kb: u64 = rest.split_whitespace().next()?.parse().ok()?;
+        Some(saturating_mb(kb / 1024))
+    })
+}
+
+pub fn get_total_system_ram_mb() -> Option<usize> {
     #[cfg(target_os = "linux")]
     {
-        use std::fs;
-        if let Ok(contents) = fs::read_to_string("/proc/meminfo") {
-            for line in contents.lines() {
-                if line.starts_with("MemTotal:") {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 2
-                        && let Ok(kb) = parts[1].parse::<usize>()
-                    {
-                        return Some(kb / 1024);
-                    }
-                }
-            }
-        }
-        None
+        let contents = std::fs::read_to_string("/proc/meminfo").ok()?;
+        meminfo_mb(&contents, "MemTotal")
     }
 
     #[cfg(target_os = "windows")]
@@ -271,47 +1233,903 @@ fn get_total_system_ram_mb() -> Option<usize> {
         None
     }
 
-    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    // hw.physmem is what the kernel can use; hw.realmem includes what
+    // firmware reserves, so it only stands in when the first is missing
+    #[cfg(target_os = "freebsd")]
+    {
+        sysctl_u64("hw.physmem")
+            .or_else(|| sysctl_u64("hw.realmem"))
+            .map(|bytes| (bytes / (1024 * 1024)) as usize)
+    }
+
+    #[cfg(target_os = "openbsd")]
+    {
+        sysctl_mib_bytes(&[OPENBSD_CTL_HW, OPENBSD_HW_PHYSMEM64])
+            .and_then(|bytes| sysctl_uint(&bytes))
+            .map(|bytes| (bytes / (1024 * 1024)) as usize)
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "freebsd",
+        target_os = "openbsd"
+    )))]
     {
         None
     }
 }
 
-#[cfg(target_os = "macos")]
-fn sysctl_u64(name: &str) -> Option<u64> {
-    use std::ffi::{CString, c_void};
+/// Memory that can be handed out without swapping: MemAvailable on Linux
+/// (absent before 3.14), `ullAvailPhys` on Windows, free + inactive +
+/// speculative pages on macOS, free + inactive pages on FreeBSD. OpenBSD
+/// reports none, so the total alone bounds buffers there.
+pub fn get_available_system_ram_mb() -> Option<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        let contents = std::fs::read_to_string("/proc/meminfo").ok()?;
+        meminfo_mb(&contents, "MemAvailable")
+    }
 
-    unsafe extern "C" {
-        fn sysctlbyname(
-            name: *const std::os::raw::c_char,
-            oldp: *mut c_void,
-            oldlenp: *mut usize,
-            newp: *mut c_void,
-            newlen: usize,
-        ) -> std::os::raw::c_int;
+    #[cfg(target_os = "windows")]
+    {
+        use std::mem;
+
+        use windows_sys::Win32::System::SystemInformation::{
+            GlobalMemoryStatusEx,
+            MEMORYSTATUSEX,
+        };
+
+        unsafe {
+            let mut mem_info: MEMORYSTATUSEX = mem::zeroed();
+            mem_info.dwLength = mem::size_of::<MEMORYSTATUSEX>() as u32;
+
+            if GlobalMemoryStatusEx(&mut mem_info) != 0 {
+                return Some((mem_info.ullAvailPhys / (1024 * 1024)) as usize);
+            }
+        }
+        None
     }
 
-    unsafe {
-        let c_name = CString::new(name).ok()?;
-        let mut value: u64 = 0;
-        let mut size = std::mem::size_of::<u64>();
-        let ret = sysctlbyname(
-            c_name.as_ptr(),
-            &mut value as *mut _ as *mut c_void,
-            &mut size,
-            std::ptr::null_mut(),
-            0,
-        );
-        if ret == 0 && size == std::mem::size_of::<u64>() {
-            Some(value)
+    #[cfg(target_os = "macos")]
+    {
+        macos_vm_available_bytes().map(|bytes| (bytes / (1024 * 1024)) as usize)
+    }
+
+    #[cfg(target_os = "freebsd")]
+    {
+        let page_size = sysctl_u64("hw.pagesize")?;
+        let free = sysctl_u64("vm.stats.vm.v_free_count")?;
+        let inactive = sysctl_u64("vm.stats.vm.v_inactive_count").unwrap_or(0);
+        Some(((free + inactive) * page_size / (1024 * 1024)) as usize)
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "freebsd"
+    )))]
+    {
+        None
+    }
+}
+
+/// `host_statistics64(HOST_VM_INFO64)`, counted the way Activity Monitor
+/// does for memory that's free to reuse.
+#[cfg(target_os = "macos")]
+fn macos_vm_available_bytes() -> Option<u64> {
+    // vm_statistics64 from <mach/vm_statistics.h>
+    #[repr(C, align(8))]
+    #[derive(Default)]
+    #[allow(dead_code)]
+    struct VmStatistics64 {
+        free_count:         u32,
+        active_count:       u32,
+        inactive_count:     u32,
+        wire_count:         u32,
+        zero_fill_count:    u64,
+        reactivations:      u64,
+        pageins:            u64,
+        pageouts:           u64,
+        faults:             u64,
+        cow_faults:         u64,
+        lookups:            u64,
+        hits:               u64,
+        purges:             u64,
+        purgeable_count:    u32,
+        speculative_count:  u32,
+        decompressions:     u64,
+        compressions:       u64,
+        swapins:            u64,
+        swapouts:           u64,
+        compressor_pages:   u32,
+        throttled_count:    u32,
+        external_pages:     u32,
+        internal_pages:     u32,
+        uncompressed_pages: u64,
+    }
+
+    const HOST_VM_INFO64: i32 = 4;
+
+    unsafe extern "C" {
+        fn mach_host_self() -> u32;
+        fn host_statistics64(host: u32, flavor: i32, info: *mut i32, count: *mut u32) -> i32;
+    }
+
+    let mut stats = VmStatistics64::default();
+    let mut count = (std::mem::size_of::<VmStatistics64>() / std::mem::size_of::<i32>()) as u32;
+    let ret = unsafe {
+        host_statistics64(
+            mach_host_self(),
+            HOST_VM_INFO64,
+            &mut stats as *mut _ as *mut i32,
+            &mut count,
+        )
+    };
+    if ret != 0 {
+        return None;
+    }
+    let page_size = sysctl_u64("hw.pagesize")?;
+    let pages =
+        stats.free_count as u64 + stats.inactive_count as u64 + stats.speculative_count as u64;
+    Some(pages * page_size)
+}
+
+/// Planned allocations past this share of RAM ask before starting.
+pub const CONFIRM_RAM_FRACTION: f64 = 0.5;
+
+/// This process's cgroup memory limit (v2 `memory.max`, else v1
+/// `memory.limit_in_bytes`), or None when unlimited or not on Linux.
+pub fn cgroup_memory_limit_mb() -> Option<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        [
+            "/sys/fs/cgroup/memory.max",
+            "/sys/fs/cgroup/memory/memory.limit_in_bytes",
+        ]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| parse_cgroup_limit(&contents))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// "max" (v2) and v1's page-rounded i64::MAX both mean no limit.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cgroup_limit(contents: &str) -> Option<usize> {
+    let bytes: u64 = contents.trim().parse().ok()?;
+    (bytes < 1 << 60).then_some((bytes / (1024 * 1024)) as usize)
+}
+
+/// Why `total_mb` of buffers deserves a confirmation, if it does: any time
+/// it's over the cgroup limit, or past [`CONFIRM_RAM_FRACTION`] of RAM.
+pub fn large_allocation(
+    total_mb: usize,
+    total_ram_mb: Option<usize>,
+    cgroup_limit_mb: Option<usize>,
+) -> Option<String> {
+    if let Some(limit) = cgroup_limit_mb
+        && total_mb > limit
+    {
+        return Some(format!("exceeds the {} MB cgroup memory limit", limit));
+    }
+    let ram = total_ram_mb?;
+    (total_mb > max_safe_mb(ram, CONFIRM_RAM_FRACTION)).then(|| {
+        format!(
+            "is over {}% of system RAM ({} MB)",
+            (CONFIRM_RAM_FRACTION * 100.0) as usize,
+            ram
+        )
+    })
+}
+
+/// Which kind of core a logical CPU belongs to on a hybrid part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoreClass {
+    Performance,
+    Efficiency,
+}
+
+impl CoreClass {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Performance => "P",
+            Self::Efficiency => "E",
+        }
+    }
+}
+
+/// P- and E-core CPU numbers of a hybrid CPU (Alder Lake and later).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreClasses {
+    pub performance: Vec<usize>,
+    pub efficiency:  Vec<usize>,
+}
+
+impl CoreClasses {
+    pub fn class_of(&self, cpu: usize) -> Option<CoreClass> {
+        if self.performance.contains(&cpu) {
+            Some(CoreClass::Performance)
+        } else if self.efficiency.contains(&cpu) {
+            Some(CoreClass::Efficiency)
         } else {
             None
         }
     }
 }
 
-#[cfg(target_os = "macos")]
-fn sysctl_u64_vec(name: &str) -> Option<Vec<u64>> {
+/// P/E split from the hybrid PMU devices Linux registers, or None on
+/// non-hybrid CPUs and other platforms.
+pub fn detect_core_classes() -> Option<CoreClasses> {
+    #[cfg(target_os = "linux")]
+    {
+        core_classes_from_sysfs(Path::new("/sys/devices"))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// `cpu_core/cpus` and `cpu_atom/cpus` only exist when both kinds are present.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn core_classes_from_sysfs(devices: &Path) -> Option<CoreClasses> {
+    let read = |pmu: &str| {
+        let list = std::fs::read_to_string(devices.join(pmu).join("cpus")).ok()?;
+        let cpus = crate::numa::parse_node_list(&list);
+        (!cpus.is_empty()).then_some(cpus)
+    };
+    Some(CoreClasses {
+        performance: read("cpu_core")?,
+        efficiency:  read("cpu_atom")?,
+    })
+}
+
+/// Logical CPUs grouped by physical core, each group ascending and the
+/// groups ordered by their first CPU; a group of one is a core without SMT.
+/// None where the topology can't be read.
+pub fn detect_smt_siblings() -> Option<Vec<Vec<usize>>> {
+    #[cfg(target_os = "linux")]
+    {
+        smt_siblings_from_sysfs(Path::new("/sys/devices/system/cpu"))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let groups = smt_siblings_from_processor_info(&processor_core_info()?);
+        (!groups.is_empty()).then_some(groups)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+/// One group per distinct `thread_siblings_list` ("0,8" or "0-1"); every
+/// sibling of a core lists the same group, so repeats are dropped.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn sibling_groups<'a>(lists: impl IntoIterator<Item = &'a str>) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for list in lists {
+        let mut group = crate::numa::parse_node_list(list);
+        group.sort_unstable();
+        group.dedup();
+        if !group.is_empty() && !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    groups.sort();
+    groups
+}
+
+/// `cpu_root/cpuN/topology/thread_siblings_list` for every CPU listed.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn smt_siblings_from_sysfs(cpu_root: &Path) -> Option<Vec<Vec<usize>>> {
+    let lists: Vec<String> = std::fs::read_dir(cpu_root)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name();
+            let id = name.to_str()?.strip_prefix("cpu")?;
+            if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            std::fs::read_to_string(entry.path().join("topology").join("thread_siblings_list"))
+                .ok()
+        })
+        .collect();
+    let groups = sibling_groups(lists.iter().map(String::as_str));
+    (!groups.is_empty()).then_some(groups)
+}
+
+/// hwmon drivers whose first sensor is the CPU package (Tctl on AMD).
+const CPU_HWMON_DRIVERS: &[&str] = &["coretemp", "k10temp", "zenpower"];
+/// Thermal zone types that are the CPU, for SoCs without such a driver.
+const CPU_THERMAL_ZONES: &[&str] =
+    &["x86_pkg_temp", "cpu-thermal", "cpu_thermal", "soc_thermal"];
+
+/// CPU package temperature in °C, or None where no sensor is exposed (and
+/// off Linux).
+pub fn cpu_temperature() -> Option<f64> {
+    #[cfg(target_os = "linux")]
+    {
+        cpu_temperature_from_sysfs(Path::new("/sys/class"))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// The CPU's hwmon driver if one is loaded, else a CPU thermal zone; both
+/// report millidegrees.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn cpu_temperature_from_sysfs(class: &Path) -> Option<f64> {
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).ok();
+    let millidegrees = |path| read(path)?.trim().parse::<i64>().ok().map(|m| m as f64 / 1000.0);
+    let devices = |kind: &str| {
+        let mut dirs: Vec<_> = std::fs::read_dir(class.join(kind))
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .collect();
+        dirs.sort();
+        dirs
+    };
+    let matches = |dir: &Path, file: &str, names: &[&str]| {
+        read(dir.join(file)).is_some_and(|name| names.contains(&name.trim()))
+    };
+
+    let hwmon = devices("hwmon")
+        .into_iter()
+        .find(|dir| matches(dir, "name", CPU_HWMON_DRIVERS))
+        .and_then(|dir| millidegrees(dir.join("temp1_input")));
+    hwmon.or_else(|| {
+        devices("thermal")
+            .into_iter()
+            .find(|dir| matches(dir, "type", CPU_THERMAL_ZONES))
+            .and_then(|dir| millidegrees(dir.join("temp")))
+    })
+}
+
+/// Logical CPUs this process may run on (honors `taskset`/cpuset), or every
+/// online CPU where the mask can't be read.
+pub fn available_cpus() -> Vec<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(cpus) = linux_affinity_mask().map(|mask| mask_to_cpus(&mask))
+            && !cpus.is_empty()
+        {
+            return cpus;
+        }
+    }
+
+    (0..num_cpus::get()).collect()
+}
+
+/// Online logical CPUs regardless of this process's affinity mask.
+pub fn online_cpu_count() -> usize {
+    #[cfg(target_os = "linux")]
+    {
+        let online = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+        if online > 0 {
+            return online as usize;
+        }
+    }
+
+    num_cpus::get()
+}
+
+#[cfg(target_os = "linux")]
+fn linux_affinity_mask() -> Option<[u64; 16]> {
+    // 16 words = the kernel's default 1024-CPU cpu_set_t
+    let mut mask = [0u64; 16];
+    let ret = unsafe {
+        libc::sched_getaffinity(
+            0,
+            std::mem::size_of_val(&mask),
+            mask.as_mut_ptr() as *mut libc::cpu_set_t,
+        )
+    };
+    (ret == 0).then_some(mask)
+}
+
+fn count_mask_bits(mask: &[u64]) -> usize {
+    mask.iter().map(|word| word.count_ones() as usize).sum()
+}
+
+fn mask_to_cpus(mask: &[u64]) -> Vec<usize> {
+    let mut cpus = Vec::with_capacity(count_mask_bits(mask));
+    for (word_idx, &word) in mask.iter().enumerate() {
+        for bit in 0..64 {
+            if word & (1u64 << bit) != 0 {
+                cpus.push(word_idx * 64 + bit);
+            }
+        }
+    }
+    cpus
+}
+
+/// Pins the calling thread to one logical CPU. Returns false where thread
+/// affinity isn't supported (macOS only offers affinity hints).
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(cpu: usize) -> bool {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(cpu, &mut set);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn pin_current_thread(cpu: usize) -> bool {
+    use windows_sys::Win32::System::Threading::{GetCurrentThread, SetThreadAffinityMask};
+
+    if cpu >= usize::BITS as usize {
+        return false;
+    }
+    unsafe { SetThreadAffinityMask(GetCurrentThread(), 1usize << cpu) != 0 }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn pin_current_thread(_cpu: usize) -> bool {
+    false
+}
+
+pub fn affinity_supported() -> bool {
+    cfg!(any(target_os = "linux", target_os = "windows"))
+}
+
+/// CPU time (user + system) consumed by the calling thread so far, or None
+/// where the platform can't report it.
+#[cfg(unix)]
+pub fn thread_cpu_time() -> Option<Duration> {
+    let mut ts = libc::timespec {
+        tv_sec:  0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } != 0 {
+        return None;
+    }
+    Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+#[cfg(target_os = "windows")]
+pub fn thread_cpu_time() -> Option<Duration> {
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::System::Threading::{GetCurrentThread, GetThreadTimes};
+
+    let zero = || FILETIME {
+        dwLowDateTime:  0,
+        dwHighDateTime: 0,
+    };
+    let (mut creation, mut exit, mut kernel, mut user) = (zero(), zero(), zero(), zero());
+
+    let ok = unsafe {
+        GetThreadTimes(
+            GetCurrentThread(),
+            &mut creation,
+            &mut exit,
+            &mut kernel,
+            &mut user,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+
+    // FILETIME counts 100 ns intervals
+    let ticks = |t: FILETIME| ((t.dwHighDateTime as u64) << 32) | t.dwLowDateTime as u64;
+    Some(Duration::from_nanos((ticks(kernel) + ticks(user)) * 100))
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+pub fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+/// CPU time (user + system) consumed by every thread of this process so far,
+/// or None where the platform can't report it.
+#[cfg(unix)]
+pub fn process_cpu_time() -> Option<Duration> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return None;
+    }
+    let timeval = |tv: libc::timeval| {
+        Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+    };
+    Some(timeval(usage.ru_utime) + timeval(usage.ru_stime))
+}
+
+#[cfg(target_os = "windows")]
+pub fn process_cpu_time() -> Option<Duration> {
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, GetProcessTimes};
+
+    let zero = || FILETIME {
+        dwLowDateTime:  0,
+        dwHighDateTime: 0,
+    };
+    let (mut creation, mut exit, mut kernel, mut user) = (zero(), zero(), zero(), zero());
+
+    let ok = unsafe {
+        GetProcessTimes(
+            GetCurrentProcess(),
+            &mut creation,
+            &mut exit,
+            &mut kernel,
+            &mut user,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+
+    let ticks = |t: FILETIME| ((t.dwHighDateTime as u64) << 32) | t.dwLowDateTime as u64;
+    Some(Duration::from_nanos((ticks(kernel) + ticks(user)) * 100))
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+pub fn process_cpu_time() -> Option<Duration> {
+    None
+}
+
+/// ru_maxrss is bytes on macOS and KiB on Linux and the BSDs.
+#[cfg(unix)]
+const MAXRSS_UNIT: u64 = if cfg!(target_os = "macos") { 1 } else { 1024 };
+
+#[cfg_attr(not(unix), allow(dead_code))]
+fn maxrss_bytes(maxrss: i64, unit: u64) -> Option<u64> {
+    u64::try_from(maxrss)
+        .ok()
+        .filter(|&rss| rss > 0)
+        .map(|rss| rss.saturating_mul(unit))
+}
+
+/// Highest resident set this process has reached so far, in bytes, or None
+/// where the platform can't report it.
+#[cfg(unix)]
+pub fn peak_rss_bytes() -> Option<u64> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    maxrss_bytes(usage.ru_maxrss as i64, MAXRSS_UNIT)
+}
+
+#[cfg(target_os = "windows")]
+pub fn peak_rss_bytes() -> Option<u64> {
+    use windows_sys::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+    };
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    counters.cb = size;
+    let ok = unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) };
+    if ok == 0 {
+        return None;
+    }
+    Some(counters.PeakWorkingSetSize as u64).filter(|&bytes| bytes > 0)
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+pub fn peak_rss_bytes() -> Option<u64> {
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Virtualization {
+    BareMetal,
+    /// Hypervisor name, as best we can tell
+    Hypervisor(String),
+    /// No guest signal, but no reliable way to rule one out either
+    Unknown,
+}
+
+impl Virtualization {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::BareMetal => "bare-metal".to_string(),
+            Self::Hypervisor(name) => name.clone(),
+            Self::Unknown => "unknown".to_string(),
+        }
+    }
+}
+
+/// CPUID first (x86), then DMI vendor strings (Linux). Only x86 can claim
+/// bare metal: anywhere else a missing signal just means "unknown".
+pub fn detect_virtualization() -> Virtualization {
+    let dmi = dmi_hypervisor();
+
+    match hypervisor_bit() {
+        Some(true) => Virtualization::Hypervisor(
+            cpuid_hypervisor_vendor()
+                .or(dmi)
+                .unwrap_or_else(|| "unknown hypervisor".to_string()),
+        ),
+        Some(false) => dmi.map_or(Virtualization::BareMetal, Virtualization::Hypervisor),
+        None => dmi.map_or(Virtualization::Unknown, Virtualization::Hypervisor),
+    }
+}
+
+/// Machine name for result files, or "unknown".
+pub fn hostname() -> String {
+    if let Ok(name) = std::env::var("HOSTNAME")
+        && !name.is_empty()
+    {
+        return name;
+    }
+
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } == 0 {
+            let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+            if len > 0 {
+                return String::from_utf8_lossy(&buf[..len]).into_owned();
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        if let Ok(name) = std::env::var("COMPUTERNAME") {
+            return name;
+        }
+    }
+
+    "unknown".to_string()
+}
+
+/// Marketing name of the CPU, e.g. "AMD Ryzen 9 7950X 16-Core Processor".
+pub fn cpu_brand() -> String {
+    cpuid_brand()
+        .or_else(os_cpu_brand)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Instruction-set extensions a kernel could dispatch on, as the runtime
+/// detection macros spell them.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub const PROBED_CPU_FEATURES: &[&str] = &["avx2", "avx512f", "fma", "aes", "sha"];
+#[cfg(target_arch = "aarch64")]
+pub const PROBED_CPU_FEATURES: &[&str] = &["neon", "sve", "aes"];
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+pub const PROBED_CPU_FEATURES: &[&str] = &[];
+
+/// The probed features this machine supports, checked at runtime so the
+/// answer doesn't depend on the flags the binary was built with.
+pub fn detect_cpu_features() -> Vec<String> {
+    PROBED_CPU_FEATURES
+        .iter()
+        .filter(|feature| cpu_feature_detected(feature))
+        .map(|feature| feature.to_string())
+        .collect()
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn cpu_feature_detected(feature: &str) -> bool {
+    match feature {
+        "avx2" => std::arch::is_x86_feature_detected!("avx2"),
+        "avx512f" => std::arch::is_x86_feature_detected!("avx512f"),
+        "fma" => std::arch::is_x86_feature_detected!("fma"),
+        "aes" => std::arch::is_x86_feature_detected!("aes"),
+        "sha" => std::arch::is_x86_feature_detected!("sha"),
+        _ => false,
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn cpu_feature_detected(feature: &str) -> bool {
+    match feature {
+        "neon" => std::arch::is_aarch64_feature_detected!("neon"),
+        "sve" => std::arch::is_aarch64_feature_detected!("sve"),
+        "aes" => std::arch::is_aarch64_feature_detected!("aes"),
+        _ => false,
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+fn cpu_feature_detected(_feature: &str) -> bool {
+    false
+}
+
+/// "avx2, fma, aes (no avx512f, sha)", naming what was probed and missing
+/// so an empty list can't be mistaken for "not checked".
+pub fn describe_cpu_features(detected: &[String]) -> String {
+    if PROBED_CPU_FEATURES.is_empty() {
+        return format!("not probed on {}", std::env::consts::ARCH);
+    }
+    let missing: Vec<&str> = PROBED_CPU_FEATURES
+        .iter()
+        .copied()
+        .filter(|feature| !detected.iter().any(|d| d == feature))
+        .collect();
+    match (detected.is_empty(), missing.is_empty()) {
+        (true, _) => format!("none of {}", missing.join(", ")),
+        (false, true) => detected.join(", "),
+        (false, false) => format!("{} (no {})", detected.join(", "), missing.join(", ")),
+    }
+}
+
+/// Brand string from extended leaves 0x8000_0002..=0x8000_0004.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn cpuid_brand() -> Option<String> {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::__cpuid;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::__cpuid;
+
+    #[allow(unused_unsafe)]
+    let max_extended = unsafe { __cpuid(0x8000_0000) }.eax;
+    if max_extended < 0x8000_0004 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(48);
+    for leaf in 0x8000_0002..=0x8000_0004 {
+        #[allow(unused_unsafe)]
+        let regs = unsafe { __cpuid(leaf) };
+        for reg in [regs.eax, regs.ebx, regs.ecx, regs.edx] {
+            bytes.extend_from_slice(&reg.to_le_bytes());
+        }
+    }
+    brand_from_bytes(&bytes)
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn cpuid_brand() -> Option<String> {
+    None
+}
+
+/// NUL-padded and often space-padded on both ends.
+#[cfg_attr(
+    not(any(target_arch = "x86", target_arch = "x86_64")),
+    allow(dead_code)
+)]
+fn brand_from_bytes(bytes: &[u8]) -> Option<String> {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let brand = String::from_utf8_lossy(&bytes[..end]).trim().to_string();
+    (!brand.is_empty()).then_some(brand)
+}
+
+fn os_cpu_brand() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/cpuinfo")
+            .ok()
+            .and_then(|contents| cpuinfo_model_name(&contents))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        sysctl_string("machdep.cpu.brand_string")
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn cpuinfo_model_name(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "model name" && !value.trim().is_empty())
+            .then(|| value.trim().to_string())
+    })
+}
+
+/// CPUID.1:ECX bit 31, set by every mainstream hypervisor. None off x86.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn hypervisor_bit() -> Option<bool> {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::__cpuid;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::__cpuid;
+
+    // Safe on newer toolchains, unsafe on the MSRV
+    #[allow(unused_unsafe)]
+    let leaf = unsafe { __cpuid(1) };
+    Some(leaf.ecx & (1 << 31) != 0)
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn hypervisor_bit() -> Option<bool> {
+    None
+}
+
+/// Vendor signature from the hypervisor leaf 0x4000_0000.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn cpuid_hypervisor_vendor() -> Option<String> {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::__cpuid;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::__cpuid;
+
+    #[allow(unused_unsafe)]
+    let leaf = unsafe { __cpuid(0x4000_0000) };
+    let mut signature = [0u8; 12];
+    signature[0..4].copy_from_slice(&leaf.ebx.to_le_bytes());
+    signature[4..8].copy_from_slice(&leaf.ecx.to_le_bytes());
+    signature[8..12].copy_from_slice(&leaf.edx.to_le_bytes());
+    hypervisor_from_signature(&signature)
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn cpuid_hypervisor_vendor() -> Option<String> {
+    None
+}
+
+fn hypervisor_from_signature(signature: &[u8; 12]) -> Option<String> {
+    let name = match signature {
+        b"KVMKVMKVM\0\0\0" => "KVM",
+        b"Microsoft Hv" => "Hyper-V",
+        b"VMwareVMware" => "VMware",
+        b"XenVMMXenVMM" => "Xen",
+        b"VBoxVBoxVBox" => "VirtualBox",
+        b"TCGTCGTCGTCG" => "QEMU (TCG)",
+        b" lrpepyh  vr" => "Parallels",
+        b"bhyve bhyve " => "bhyve",
+        b"ACRNACRNACRN" => "ACRN",
+        b"QNXQVMBSQG\0\0" => "QNX",
+        _ => {
+            let text = String::from_utf8_lossy(signature);
+            let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+            return (!text.is_empty()).then(|| text.to_string());
+        },
+    };
+    Some(name.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn dmi_hypervisor() -> Option<String> {
+    let read = |file: &str| {
+        std::fs::read_to_string(format!("/sys/class/dmi/id/{}", file))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    hypervisor_from_dmi(&read("sys_vendor"), &read("product_name"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn dmi_hypervisor() -> Option<String> {
+    None
+}
+
+fn hypervisor_from_dmi(vendor: &str, product: &str) -> Option<String> {
+    let name = match vendor {
+        "QEMU" => "QEMU",
+        "VMware, Inc." => "VMware",
+        "innotek GmbH" | "Oracle Corporation" if product.contains("VirtualBox") => "VirtualBox",
+        "Microsoft Corporation" if product == "Virtual Machine" => "Hyper-V",
+        "Xen" => "Xen",
+        "Parallels Software International Inc." => "Parallels",
+        "Amazon EC2" => "Amazon EC2",
+        "Google" if product == "Google Compute Engine" => "Google Compute Engine",
+        _ if product.starts_with("KVM") => "KVM",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Raw value of a sysctl by name; `sysctlbyname` exists on macOS and FreeBSD.
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+fn sysctl_bytes(name: &str) -> Option<Vec<u8>> {
     use std::ffi::{CString, c_void};
 
     unsafe extern "C" {
@@ -353,19 +2171,96 @@ fn sysctl_u64_vec(name: &str) -> Option<Vec<u64>> {
         {
             return None;
         }
+        buf.truncate(size);
+        Some(buf)
+    }
+}
+
+/// `CTL_HW` and `HW_PHYSMEM64` from OpenBSD's <sys/sysctl.h>
+#[cfg(target_os = "openbsd")]
+const OPENBSD_CTL_HW: libc::c_int = 6;
+#[cfg(target_os = "openbsd")]
+const OPENBSD_HW_PHYSMEM64: libc::c_int = 19;
+
+/// OpenBSD has no `sysctlbyname`, only numeric MIBs.
+#[cfg(target_os = "openbsd")]
+fn sysctl_mib_bytes(mib: &[libc::c_int]) -> Option<Vec<u8>> {
+    unsafe {
+        let mut size: libc::size_t = 0;
+        if libc::sysctl(
+            mib.as_ptr(),
+            mib.len() as libc::c_uint,
+            std::ptr::null_mut(),
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        ) != 0
+            || size == 0
+        {
+            return None;
+        }
 
-        let count = size / std::mem::size_of::<u64>();
-        let mut out = Vec::with_capacity(count);
-        let ptr = buf.as_ptr() as *const u64;
-        for i in 0..count {
-            out.push(*ptr.add(i));
+        let mut buf = vec![0u8; size];
+        if libc::sysctl(
+            mib.as_ptr(),
+            mib.len() as libc::c_uint,
+            buf.as_mut_ptr().cast(),
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        ) != 0
+        {
+            return None;
         }
-        Some(out)
+        buf.truncate(size);
+        Some(buf)
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+fn sysctl_u64(name: &str) -> Option<u64> {
+    sysctl_bytes(name).and_then(|bytes| sysctl_uint(&bytes))
+}
+
+#[cfg(target_os = "macos")]
+fn sysctl_string(name: &str) -> Option<String> {
+    sysctl_bytes(name).and_then(|bytes| brand_from_bytes(&bytes))
+}
+
+#[cfg(target_os = "macos")]
+fn sysctl_u64_vec(name: &str) -> Option<Vec<u64>> {
+    sysctl_bytes(name).map(|bytes| sysctl_u64s(&bytes))
+}
+
+/// An integer sysctl in native byte order. Widths vary by name and target:
+/// FreeBSD's `hw.physmem` is an unsigned long, 4 bytes on i386 and 8 on
+/// amd64; the `vm.stats` counters are always 4.
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd")),
+    allow(dead_code)
+)]
+fn sysctl_uint(bytes: &[u8]) -> Option<u64> {
+    match bytes.len() {
+        4 => Some(u32::from_ne_bytes(bytes.try_into().ok()?) as u64),
+        8 => Some(u64::from_ne_bytes(bytes.try_into().ok()?)),
+        _ => None,
     }
 }
 
+/// An array of 8-byte integers such as macOS `hw.cachesize`; a trailing
+/// partial element is dropped.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn sysctl_u64s(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks_exact(8)
+        .map(|chunk| u64::from_ne_bytes(chunk.try_into().expect("chunks of 8")))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use super::*;
 
     #[test]
@@ -375,12 +2270,470 @@ mod tests {
         assert_eq!(parse_cache_size("12M"), Some(12));
         assert_eq!(parse_cache_size("256M"), Some(256));
         assert_eq!(parse_cache_size("8388608"), Some(8));
+        assert_eq!(parse_cache_bytes("48K\n"), Some(48 << 10));
+        assert_eq!(parse_cache_bytes("2M"), Some(2 << 20));
+        assert_eq!(parse_cache_bytes("512k"), Some(512 << 10));
+        assert_eq!(parse_cache_bytes("fast"), None);
+    }
+
+    /// Leaf 4 / 0x8000_001D dumps as [eax, ebx, ecx], terminator included.
+    const I7_8700_LEAF4: [[u32; 3]; 5] = [
+        [0x1c00_4121, 0x01c0_003f, 0x0000_003f], // L1d 32 KB
+        [0x1c00_4122, 0x01c0_003f, 0x0000_003f], // L1i 32 KB
+        [0x1c00_4143, 0x00c0_003f, 0x0000_03ff], // L2 256 KB
+        [0x1c03_c163, 0x02c0_003f, 0x0000_3fff], // L3 12 MB, 12-way
+        [0, 0, 0],
+    ];
+    const RYZEN_5950X_8000001D: [[u32; 3]; 5] = [
+        [0x0000_4121, 0x01c0_003f, 0x0000_003f], // L1d 32 KB
+        [0x0000_4122, 0x01c0_003f, 0x0000_003f], // L1i 32 KB
+        [0x0000_4143, 0x01c0_003f, 0x0000_03ff], // L2 512 KB
+        [0x0003_c163, 0x03c0_003f, 0x0000_7fff], // L3 32 MB per CCX
+        [0, 0, 0],
+    ];
+    const ATOM_N270_LEAF4: [[u32; 3]; 4] = [
+        [0x0000_4121, 0x0140_003f, 0x0000_003f], // L1d 24 KB, 6-way
+        [0x0000_4122, 0x01c0_003f, 0x0000_003f], // L1i 32 KB
+        [0x0400_4143, 0x01c0_003f, 0x0000_03ff], // L2 512 KB, no L3
+        [0, 0, 0],
+    ];
+
+    /// Writes a cpuN/cache tree: L1d, L2 per core, then L3 shared by
+    /// `cores_per_complex` consecutive CPUs.
+    fn sysfs_fixture(name: &str, cpus: usize, cores_per_complex: usize, l3: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("locus-sysfs-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for cpu in 0..cpus {
+            let complex = cpu / cores_per_complex;
+            let l3_map: u64 =
+                ((1u64 << cores_per_complex) - 1) << (complex * cores_per_complex);
+            let caches = [
+                ("1", "32K", 1u64 << cpu),
+                ("2", "512K", 1u64 << cpu),
+                ("3", l3, l3_map),
+            ];
+            for (index, (level, size, map)) in caches.iter().enumerate() {
+                let dir = root.join(format!("cpu{}/cache/index{}", cpu, index));
+                std::fs::create_dir_all(&dir).unwrap();
+                std::fs::write(dir.join("level"), format!("{}\n", level)).unwrap();
+                std::fs::write(dir.join("size"), format!("{}\n", size)).unwrap();
+                std::fs::write(dir.join("shared_cpu_map"), format!("{:08x}\n", map)).unwrap();
+            }
+        }
+        // Entries sysfs has next to the CPUs that must be skipped
+        std::fs::create_dir_all(root.join("cpufreq")).unwrap();
+        std::fs::write(root.join("online"), "0-15\n").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_core_classes_from_sysfs() {
+        let root = std::env::temp_dir().join(format!("locus-devices-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        // i7-12700: 8 P-cores with SMT, then 4 E-cores
+        for (pmu, cpus) in [("cpu_core", "0-15\n"), ("cpu_atom", "16-19\n")] {
+            std::fs::create_dir_all(root.join(pmu)).unwrap();
+            std::fs::write(root.join(pmu).join("cpus"), cpus).unwrap();
+        }
+
+        let classes = core_classes_from_sysfs(&root).unwrap();
+        assert_eq!(classes.class_of(3), Some(CoreClass::Performance));
+        assert_eq!(classes.class_of(17), Some(CoreClass::Efficiency));
+        assert_eq!(classes.class_of(20), None);
+
+        // Non-hybrid parts only register the plain `cpu` PMU
+        std::fs::remove_dir_all(root.join("cpu_atom")).unwrap();
+        assert_eq!(core_classes_from_sysfs(&root), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_cpu_temperature_from_sysfs() {
+        let root = std::env::temp_dir().join(format!("locus-thermal-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let write = |dir: &str, file: &str, contents: &str| {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join(file), contents).unwrap();
+        };
+        write("thermal/thermal_zone0", "type", "acpitz\n");
+        write("thermal/thermal_zone0", "temp", "27800\n");
+        assert_eq!(cpu_temperature_from_sysfs(&root), None);
+
+        write("thermal/thermal_zone1", "type", "x86_pkg_temp\n");
+        write("thermal/thermal_zone1", "temp", "61000\n");
+        assert_eq!(cpu_temperature_from_sysfs(&root), Some(61.0));
+
+        // The CPU's hwmon driver wins over a thermal zone
+        write("hwmon/hwmon0", "name", "nvme\n");
+        write("hwmon/hwmon0", "temp1_input", "39850\n");
+        write("hwmon/hwmon1", "name", "k10temp\n");
+        write("hwmon/hwmon1", "temp1_input", "72125\n");
+        assert_eq!(cpu_temperature_from_sysfs(&root), Some(72.125));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_sibling_groups() {
+        // Linux numbers SMT pairs N and N + cores, each listed from both ends
+        let groups = sibling_groups(["0,2\n", "1,3\n", "0,2\n", "1,3\n"]);
+        assert_eq!(groups, [vec![0, 2], vec![1, 3]]);
+        // Ranges, 4-way SMT and cores without SMT
+        let groups = sibling_groups(["4-7\n", "8\n", "0-1,2,3\n", "9\n"]);
+        assert_eq!(groups, [vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8], vec![9]]);
+        assert!(sibling_groups(["", "\n"]).is_empty());
+    }
+
+    #[test]
+    fn test_smt_siblings_from_sysfs() {
+        let root = std::env::temp_dir().join(format!("locus-smt-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for cpu in 0..4 {
+            let topology = root.join(format!("cpu{}", cpu)).join("topology");
+            std::fs::create_dir_all(&topology).unwrap();
+            let list = format!("{},{}\n", cpu % 2, cpu % 2 + 2);
+            std::fs::write(topology.join("thread_siblings_list"), list).unwrap();
+        }
+        // Entries sysfs has next to the CPUs that must be skipped
+        std::fs::create_dir_all(root.join("cpufreq")).unwrap();
+        std::fs::create_dir_all(root.join("cpuidle")).unwrap();
+
+        assert_eq!(
+            smt_siblings_from_sysfs(&root),
+            Some(vec![vec![0, 2], vec![1, 3]])
+        );
+        let _ = std::fs::remove_dir_all(&root);
+        assert_eq!(smt_siblings_from_sysfs(&root), None);
+    }
+
+    #[test]
+    fn test_smt_siblings_from_processor_info() {
+        // RelationProcessorCore records: header, Flags, EfficiencyClass,
+        // Reserved, GroupCount, then one GROUP_AFFINITY
+        let core = |mask: u64| {
+            let mut record = Vec::with_capacity(48);
+            record.extend(0u32.to_le_bytes()); // RelationProcessorCore
+            record.extend(48u32.to_le_bytes());
+            record.extend([1u8, 0]); // LTP_PC_SMT, EfficiencyClass
+            record.extend([0u8; 20]);
+            record.extend(1u16.to_le_bytes());
+            record.extend(mask.to_le_bytes());
+            record.extend([0u8; 8]); // Group 0, Reserved
+            record
+        };
+        let mut buffer = core(0b1100);
+        buffer.extend(core(0b0011));
+        buffer.extend(core(0b1_0000));
+        // A cache record in the same buffer is skipped
+        buffer.extend(cache_record(2, 64, 1 << 20, 0, 0b11));
+        assert_eq!(
+            smt_siblings_from_processor_info(&buffer),
+            [vec![0, 1], vec![2, 3], vec![4]]
+        );
+        assert!(smt_siblings_from_processor_info(&buffer[..40]).is_empty());
+    }
+
+    /// One RelationCache record as GetLogicalProcessorInformationEx lays it
+    /// out on x64: 8-byte header, CACHE_RELATIONSHIP, one GROUP_AFFINITY.
+    fn cache_record(level: u8, line: u16, size: u32, kind: u32, mask: u64) -> Vec<u8> {
+        let mut record = Vec::with_capacity(56);
+        record.extend(2u32.to_le_bytes()); // RelationCache
+        record.extend(56u32.to_le_bytes());
+        record.extend([level, 8]); // Level, Associativity
+        record.extend(line.to_le_bytes());
+        record.extend(size.to_le_bytes());
+        record.extend(kind.to_le_bytes()); // 0 unified, 1 instruction, 2 data
+        record.extend([0u8; 18]);
+        record.extend(1u16.to_le_bytes()); // GroupCount
+        record.extend(mask.to_le_bytes());
+        record.extend([0u8; 8]); // Group 0, Reserved
+        record
+    }
+
+    /// A Ryzen 9 5950X: per core L1d, L1i, L2 (SMT pairs share a mask), then
+    /// each CCD's 32 MB L3 after its eight cores.
+    fn ryzen_5950x_cache_info() -> Vec<u8> {
+        let mut buffer = Vec::new();
+        for ccd in 0..2u64 {
+            for core in 0..8u64 {
+                let mask = 0b11 << (2 * (ccd * 8 + core));
+                buffer.extend(cache_record(1, 64, 32 << 10, 2, mask));
+                buffer.extend(cache_record(1, 64, 32 << 10, 1, mask));
+                buffer.extend(cache_record(2, 64, 512 << 10, 0, mask));
+            }
+            buffer.extend(cache_record(3, 64, 32 << 20, 0, 0xffff << (16 * ccd)));
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_l3_from_processor_info_counts_every_complex() {
+        let buffer = ryzen_5950x_cache_info();
+        assert_eq!(buffer.len(), 50 * 56);
+        assert_eq!(
+            l3_from_processor_info(&buffer),
+            Some(L3Cache {
+                per_complex_mb: 32,
+                complexes:      2,
+            })
+        );
+
+        // The last record ends exactly at the end of the buffer and still counts
+        let first_ccd = &buffer[..25 * 56];
+        assert_eq!(l3_from_processor_info(first_ccd).unwrap().complexes, 1);
+    }
+
+    #[test]
+    fn test_lower_levels_from_processor_info() {
+        let buffer = ryzen_5950x_cache_info();
+        assert_eq!(cache_bytes_from_processor_info(&buffer, 1), Some(32 << 10));
+        assert_eq!(cache_bytes_from_processor_info(&buffer, 2), Some(512 << 10));
+
+        // An instruction record at the front doesn't stand in for L1d
+        let mut l1i_first = cache_record(1, 64, 64 << 10, 1, 1);
+        l1i_first.extend(cache_record(1, 64, 48 << 10, 2, 1));
+        assert_eq!(
+            cache_bytes_from_processor_info(&l1i_first, 1),
+            Some(48 << 10)
+        );
+        assert_eq!(cache_bytes_from_processor_info(&l1i_first, 2), None);
+    }
+
+    #[test]
+    fn test_line_size_from_processor_info() {
+        assert_eq!(
+            line_size_from_processor_info(&ryzen_5950x_cache_info()),
+            Some(64)
+        );
+
+        let apple_like = cache_record(1, 128, 128 << 10, 2, 0xf);
+        assert_eq!(line_size_from_processor_info(&apple_like), Some(128));
+
+        // A zero LineSize is left to the fallback rather than trusted
+        let blank = cache_record(1, 0, 32 << 10, 2, 0x1);
+        assert_eq!(line_size_from_processor_info(&blank), None);
+        assert_eq!(line_size_from_processor_info(&[]), None);
+    }
+
+    #[test]
+    fn test_l3_from_processor_info_rejects_bad_buffers() {
+        // No L3 at all (Atom-class parts)
+        let l2_only = cache_record(2, 64, 1 << 20, 0, 0xf);
+        assert_eq!(l3_from_processor_info(&l2_only), None);
+
+        // A record claiming to run past the end stops the walk instead of reading it
+        let mut truncated = ryzen_5950x_cache_info();
+        truncated.truncate(24 * 56 + 40);
+        assert_eq!(l3_from_processor_info(&truncated), None);
+
+        let mut zero_size = cache_record(3, 64, 16 << 20, 0, 0xff);
+        zero_size[4..8].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(l3_from_processor_info(&zero_size), None);
+        assert_eq!(l3_from_processor_info(&[]), None);
+    }
+
+    #[test]
+    fn test_l3_from_sysfs_single_complex() {
+        let root = sysfs_fixture("1ccx", 8, 8, "32768K");
+        let l3 = l3_from_sysfs(&root);
+        std::fs::remove_dir_all(&root).ok();
+
+        let l3 = l3.unwrap();
+        assert_eq!(l3, L3Cache {
+            per_complex_mb: 32,
+            complexes:      1,
+        });
+        assert_eq!(l3.describe(), "32 MiB");
+    }
+
+    #[test]
+    fn test_l3_from_sysfs_counts_complexes() {
+        let root = sysfs_fixture("4ccx", 16, 4, "16384K");
+        let l3 = l3_from_sysfs(&root);
+        std::fs::remove_dir_all(&root).ok();
+
+        let l3 = l3.unwrap();
+        assert_eq!(l3.per_complex_mb, 16);
+        assert_eq!(l3.complexes, 4);
+        assert_eq!(l3.total_mb(), 64);
+        assert_eq!(l3.describe(), "16 MiB per complex × 4 (64 MiB total)");
+    }
+
+    #[test]
+    fn test_line_size_from_sysfs() {
+        let root = sysfs_fixture("line", 1, 1, "8M");
+        let index0 = root.join("cpu0/cache/index0");
+        let index1 = root.join("cpu0/cache/index1");
+        // No file anywhere: nothing detected
+        assert_eq!(line_size_from_sysfs(&root), None);
+
+        std::fs::write(index1.join("coherency_line_size"), "128\n").unwrap();
+        assert_eq!(line_size_from_sysfs(&root), Some(128));
+
+        // An implausible first entry is skipped for the next one
+        std::fs::write(index0.join("coherency_line_size"), "0\n").unwrap();
+        assert_eq!(line_size_from_sysfs(&root), Some(128));
+        std::fs::write(index0.join("coherency_line_size"), "64\n").unwrap();
+        assert_eq!(line_size_from_sysfs(&root), Some(64));
+        std::fs::remove_dir_all(&root).ok();
+
+        assert_eq!(valid_line_size(96), None);
+        assert_eq!(valid_line_size(4096), None);
+        assert!(info().cache_line_bytes.is_power_of_two());
+    }
+
+    #[test]
+    fn test_lower_levels_from_sysfs() {
+        let root = sysfs_fixture("levels", 1, 1, "8M");
+        assert_eq!(cache_bytes_from_sysfs(&root, 1), Some(32 << 10));
+        assert_eq!(cache_bytes_from_sysfs(&root, 2), Some(512 << 10));
+
+        // index0 turns into the instruction cache; it must be skipped
+        std::fs::write(root.join("cpu0/cache/index0/type"), "Instruction\n").unwrap();
+        let l1d = cache_bytes_from_sysfs(&root, 1);
+        std::fs::remove_dir_all(&root).ok();
+        assert_eq!(l1d, None);
+    }
+
+    #[test]
+    fn test_cache_sweep_is_twice_the_target() {
+        let mut info = SystemInfo {
+            cache_line_bytes: 64,
+            l3: Some(L3Cache {
+                per_complex_mb: 32,
+                complexes:      2,
+            }),
+            caches: CacheInfo {
+                l1d_bytes: Some(48 << 10),
+                l2_bytes:  Some(2 << 20),
+            },
+            ..SystemInfo::default()
+        };
+
+        let l2 = CacheSweep::new(&info, CacheLevel::L2);
+        assert_eq!(l2.bytes, 4 << 20);
+        assert!(l2.detected);
+        assert_eq!(l2.describe(), "4 MiB per thread, 2× the 2 MiB L2");
+        assert_eq!(CacheSweep::new(&info, CacheLevel::L1).bytes, 96 << 10);
+        // One complex's slice, not the whole chip
+        assert_eq!(CacheSweep::new(&info, CacheLevel::L3).bytes, 64 << 20);
+
+        // Odd sizes still sweep whole lines, and unknown ones fall back
+        info.caches.l1d_bytes = Some(1000);
+        assert_eq!(CacheSweep::new(&info, CacheLevel::L1).bytes, 2048);
+        info.caches.l2_bytes = None;
+        let assumed = CacheSweep::new(&info, CacheLevel::L2);
+        assert!(!assumed.detected);
+        assert_eq!(assumed.bytes, 2 << 20);
+        assert!(assumed.describe().ends_with("(assumed; not detected)"));
+    }
+
+    #[test]
+    fn test_l3_from_sysfs_missing_tree() {
+        let root = std::env::temp_dir().join("locus-sysfs-definitely-missing");
+        assert_eq!(l3_from_sysfs(&root), None);
+
+        // CPUs without any level-3 entry
+        let root = sysfs_fixture("no-l3", 2, 1, "1M");
+        for cpu in 0..2 {
+            std::fs::remove_dir_all(root.join(format!("cpu{}/cache/index2", cpu))).unwrap();
+        }
+        let l3 = l3_from_sysfs(&root);
+        std::fs::remove_dir_all(&root).ok();
+        assert_eq!(l3, None);
+    }
+
+    #[test]
+    fn test_l3_from_recorded_cpuid_dumps() {
+        let l3_mb = |subleaves: &[[u32; 3]]| {
+            cache_bytes_from_params(subleaves.iter().copied(), 3).map(|b| b >> 20)
+        };
+        assert_eq!(l3_mb(&I7_8700_LEAF4), Some(12));
+        assert_eq!(l3_mb(&RYZEN_5950X_8000001D), Some(32));
+        assert_eq!(l3_mb(&ATOM_N270_LEAF4), None);
+        // Nothing after the null entry is read
+        let mut truncated = I7_8700_LEAF4;
+        truncated[2] = [0, 0, 0];
+        assert_eq!(l3_mb(&truncated), None);
+    }
+
+    #[test]
+    fn test_lower_levels_from_recorded_cpuid_dumps() {
+        // The L1 instruction cache is skipped, not mistaken for L1d
+        assert_eq!(cache_bytes_from_params(I7_8700_LEAF4, 1), Some(32 << 10));
+        assert_eq!(cache_bytes_from_params(I7_8700_LEAF4, 2), Some(256 << 10));
+        assert_eq!(
+            cache_bytes_from_params(RYZEN_5950X_8000001D, 2),
+            Some(512 << 10)
+        );
+        assert_eq!(cache_bytes_from_params(ATOM_N270_LEAF4, 1), Some(24 << 10));
+    }
+
+    #[test]
+    fn test_cache_params_leaf_follows_vendor() {
+        assert_eq!(
+            cache_params_leaf(b"GenuineIntel", 0x16, 0x8000_0008),
+            Some(4)
+        );
+        assert_eq!(
+            cache_params_leaf(b"AuthenticAMD", 0x10, 0x8000_0020),
+            Some(0x8000_001d)
+        );
+        assert_eq!(cache_params_leaf(b"AuthenticAMD", 0x10, 0x8000_0018), None);
+        assert_eq!(
+            cache_params_leaf(b"HygonGenuine", 0xd, 0x8000_001f),
+            Some(0x8000_001d)
+        );
+        assert_eq!(cache_params_leaf(b"GenuineIntel", 2, 0x8000_0008), None);
+    }
+
+    /// A synthetic machine so sizing doesn't depend on the host.
+    fn machine(
+        l3_mb: Option<usize>,
+        total: Option<usize>,
+        available: Option<usize>,
+    ) -> SystemInfo {
+        SystemInfo {
+            logical_cpus: 16,
+            available_cpus: 16,
+            l3: l3_mb.map(L3Cache::single),
+            total_ram_mb: total,
+            available_ram_mb: available,
+            ..SystemInfo::default()
+        }
+    }
+
+    fn sized(
+        multiplier: usize,
+        threads: usize,
+        limits: &MemoryLimits,
+        info: &SystemInfo,
+    ) -> usize {
+        detect_memory_size(multiplier, threads, limits, info).size_mb
     }
 
     #[test]
     fn test_detect_memory_size_enforces_minimum() {
-        let size = detect_memory_size(4);
-        assert!(size >= MIN_BUFFER_MB);
+        let tiny_l3 = machine(Some(2), Some(16_000), None);
+        assert_eq!(
+            sized(4, 16, &MemoryLimits::default(), &tiny_l3),
+            MIN_BUFFER_MB
+        );
+        // The RAM cap never goes under the minimum either
+        let tiny_ram = machine(Some(32), Some(1_024), None);
+        assert_eq!(
+            sized(4, 64, &MemoryLimits::default(), &tiny_ram),
+            MIN_BUFFER_MB
+        );
+    }
+
+    #[test]
+    fn test_system_info_collects() {
+        let info = SystemInfo::collect();
+        assert_eq!(info.os, std::env::consts::OS);
+        assert!(info.logical_cpus >= 1 && info.available_cpus <= info.logical_cpus);
+        assert!(!info.cpu_brand.is_empty());
+        assert!(info.cache_line_bytes.is_power_of_two());
     }
 
     #[test]
@@ -398,32 +2751,398 @@ mod tests {
 
     #[test]
     fn test_ram_aware_memory_size() {
-        let size = detect_memory_size(4);
-        assert!(size >= MIN_BUFFER_MB);
+        let limits = MemoryLimits::default();
+        // 32 MB × 4 × 16 threads = 2 GB fits easily in 16 GB
+        let roomy = detect_memory_size(4, 16, &limits, &machine(Some(32), Some(16_384), None));
+        assert_eq!(roomy.size_mb, 128);
+        assert_eq!(roomy.ram, RamCheck::Fits);
+        assert!(roomy.notes()[0].contains("Using 128 MiB buffer per thread (4x multiplier)"));
+
+        // 1.8 GB allowed out of 2 GB: 16 threads get 115 MB each
+        let tight = detect_memory_size(4, 16, &limits, &machine(Some(32), Some(2_048), None));
+        assert_eq!(tight.size_mb, 115);
+        assert!(matches!(tight.ram, RamCheck::Reduced(cap) if cap.bound == RamBound::Total));
+        let notes = tight.notes();
+        assert_eq!(notes.len(), 4);
+        assert!(notes[3].contains("Reducing to 115 MiB per thread (total: 1.80 GiB"));
+
+        // Most of the machine already in use: available memory binds
+        let busy = machine(Some(32), Some(16_384), Some(1_024));
+        let busy = detect_memory_size(4, 16, &limits, &busy);
+        assert!(matches!(busy.ram, RamCheck::Reduced(cap) if cap.bound == RamBound::Available));
+        assert_eq!(busy.size_mb, MIN_BUFFER_MB.max(972 / 16));
+
+        // Unknown RAM leaves the recommendation alone
+        assert_eq!(sized(4, 1000, &limits, &machine(Some(32), None, None)), 128);
+    }
 
-        let num_cpus = num_cpus::get();
-        let total = size * num_cpus;
+    #[test]
+    fn test_no_ram_cap_returns_uncapped_recommendation() {
+        let small = machine(Some(32), Some(2_048), None);
+        let uncapped = detect_memory_size(
+            4,
+            64,
+            &MemoryLimits {
+                ram_cap: false,
+                ..MemoryLimits::default()
+            },
+            &small,
+        );
 
-        if let Some(ram_mb) = super::get_total_system_ram_mb() {
-            let max_reasonable = ((ram_mb as f64) * RAM_SAFETY_FACTOR) as usize;
-            assert!(
-                total <= max_reasonable,
-                "Total allocation {} MB should not exceed {}% of RAM ({} MB)",
-                total,
-                (RAM_SAFETY_FACTOR * 100.0) as usize,
-                ram_mb
-            );
+        assert_eq!(uncapped.size_mb, 128);
+        assert!(matches!(uncapped.ram, RamCheck::Ignored(_)));
+        assert!(uncapped.notes()[1].contains("--no-ram-cap: allocating 8 GiB total"));
+        assert!(uncapped.size_mb > sized(4, 64, &MemoryLimits::default(), &small));
+    }
+
+    #[test]
+    fn test_unknown_l3_uses_cpu_heuristic() {
+        let mut info = machine(None, Some(16_384), None);
+        let sizing = detect_memory_size(4, 16, &MemoryLimits::default(), &info);
+        assert_eq!(sizing.size_mb, 192);
+        assert!(sizing.notes()[0].contains("heuristic 192 MiB (4x multiplier, 16 CPUs)"));
+
+        info.available_cpus = 4;
+        assert_eq!(sized(2, 4, &MemoryLimits::default(), &info), MIN_BUFFER_MB);
+        assert_eq!(sized(8, 4, &MemoryLimits::default(), &info), 128);
+    }
+
+    #[test]
+    fn test_total_allocation_sums_per_thread_sizes() {
+        assert_eq!(total_allocation_mb(&[512, 512, 0, 0]), 1024);
+        assert_eq!(total_allocation_mb(&[0, 0]), 0);
+        assert_eq!(total_allocation_mb(&[]), 0);
+    }
+
+    #[test]
+    fn test_fewer_buffered_threads_never_shrinks_buffer() {
+        let info = machine(Some(96), Some(8_192), Some(6_000));
+        let limits = MemoryLimits::default();
+        for threads in [1, 2, 8, 64, 256] {
+            assert!(sized(4, threads, &limits, &info) >= sized(4, threads * 2, &limits, &info));
         }
     }
 
     #[test]
-    fn test_memory_multiplier_scaling() {
-        let size_2x = detect_memory_size(2);
-        let size_4x = detect_memory_size(4);
-        let size_8x = detect_memory_size(8);
+    fn test_cpu_brand_sources() {
+        let mut bytes = b"  Intel(R) Core(TM) i7-9700K CPU @ 3.60GHz".to_vec();
+        bytes.resize(48, 0);
+        assert_eq!(
+            brand_from_bytes(&bytes).as_deref(),
+            Some("Intel(R) Core(TM) i7-9700K CPU @ 3.60GHz")
+        );
+        assert_eq!(brand_from_bytes(&[0; 48]), None);
+
+        let cpuinfo =
+            "processor\t: 0\nvendor_id\t: AuthenticAMD\nmodel name\t: AMD EPYC 7B13\n";
+        assert_eq!(
+            cpuinfo_model_name(cpuinfo).as_deref(),
+            Some("AMD EPYC 7B13")
+        );
+        assert_eq!(cpuinfo_model_name("processor\t: 0\n"), None);
+
+        assert!(!cpu_brand().is_empty());
+        assert!(!hostname().is_empty());
+    }
+
+    #[test]
+    fn test_cpu_features_name_what_is_missing() {
+        let detected = detect_cpu_features();
+        assert!(detected.iter().all(|f| PROBED_CPU_FEATURES.contains(&f.as_str())));
+
+        let all: Vec<String> = PROBED_CPU_FEATURES.iter().map(|f| f.to_string()).collect();
+        let described = describe_cpu_features(&all);
+        assert!(!described.contains("no ") && !described.starts_with("none"));
+        if let [first, ..] = PROBED_CPU_FEATURES {
+            assert_eq!(describe_cpu_features(&all[1..]), format!(
+                "{} (no {})",
+                all[1..].join(", "),
+                first
+            ));
+            assert!(describe_cpu_features(&[]).starts_with("none of "));
+        }
+    }
+
+    #[test]
+    fn test_large_allocation_thresholds() {
+        assert_eq!(large_allocation(4096, Some(8192), None), None);
+        assert!(
+            large_allocation(4097, Some(8192), None)
+                .unwrap()
+                .contains("50% of system RAM")
+        );
+        // The cgroup limit applies even when RAM has room
+        assert!(
+            large_allocation(3000, Some(65536), Some(2048))
+                .unwrap()
+                .contains("2048 MB cgroup")
+        );
+        assert_eq!(large_allocation(1 << 30, None, None), None);
+    }
+
+    #[test]
+    fn test_parse_cgroup_limit() {
+        assert_eq!(parse_cgroup_limit("max\n"), None);
+        assert_eq!(parse_cgroup_limit("9223372036854771712\n"), None);
+        assert_eq!(parse_cgroup_limit("2147483648\n"), Some(2048));
+    }
+
+    const MEMINFO: &str = "MemTotal:       16318480 kB\n\
+                           MemFree:         1203400 kB\n\
+                           MemAvailable:    9437184 kB\n\
+                           Buffers:          402312 kB\n";
+
+    #[test]
+    fn test_meminfo_fields() {
+        assert_eq!(meminfo_mb(MEMINFO, "MemTotal"), Some(15936));
+        assert_eq!(meminfo_mb(MEMINFO, "MemAvailable"), Some(9216));
+        // Only whole field names match
+        assert_eq!(meminfo_mb(MEMINFO, "Mem"), None);
+        assert_eq!(meminfo_mb("MemTotal: 1024 kB\n", "MemAvailable"), None);
+        assert_eq!(meminfo_mb("MemAvailable: lots kB\n", "MemAvailable"), None);
+    }
+
+    #[test]
+    fn test_sysctl_integers_by_width() {
+        // i386 FreeBSD's hw.physmem is an unsigned long
+        assert_eq!(
+            sysctl_uint(&4_000_000_000u32.to_ne_bytes()),
+            Some(4_000_000_000)
+        );
+        assert_eq!(sysctl_uint(&(64u64 << 30).to_ne_bytes()), Some(64 << 30));
+        assert_eq!(sysctl_uint(&[]), None);
+        assert_eq!(sysctl_uint(&[1, 2]), None);
+        assert_eq!(sysctl_uint(&[0; 16]), None);
+    }
+
+    #[test]
+    fn test_sysctl_u64_arrays() {
+        let bytes: Vec<u8> = [0u64, 32 << 10, 4 << 20]
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .chain([0xff; 3])
+            .collect();
+        assert_eq!(sysctl_u64s(&bytes), [0, 32 << 10, 4 << 20]);
+        assert!(sysctl_u64s(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_ram_cap_takes_the_tighter_bound() {
+        // Mostly idle: 90% of total is the tighter one
+        let idle = safe_ram_cap(16_000, Some(15_800), DEFAULT_RAM_SAFETY);
+        assert_eq!(idle.bound, RamBound::Total);
+        assert_eq!(idle.limit_mb, 14_400);
+        assert_eq!(idle.describe(), "90% of total RAM (15.63 GiB)");
+
+        // Half the machine already in use
+        let busy = safe_ram_cap(16_000, Some(8_000), DEFAULT_RAM_SAFETY);
+        assert_eq!(busy.bound, RamBound::Available);
+        assert_eq!(busy.limit_mb, 7_600);
+        assert_eq!(busy.describe(), "95% of available RAM (7.81 GiB)");
+    }
 
-        assert!(size_2x >= MIN_BUFFER_MB);
-        assert!(size_4x >= size_2x);
-        assert!(size_8x >= size_4x);
+    #[test]
+    fn test_ram_cap_without_available_uses_total() {
+        let cap = safe_ram_cap(16_000, None, 0.5);
+        assert_eq!(cap.bound, RamBound::Total);
+        assert_eq!(cap.limit_mb, max_safe_mb(16_000, 0.5));
+    }
+
+    #[test]
+    fn test_hard_cap_goes_below_the_ram_cap_minimum() {
+        let ram_mb = 16 * 1024;
+        // 64 threads at the RAM limit bottom out at the 32 MB minimum...
+        let ram_capped = capped_buffer_mb(1024, 64, max_safe_mb(ram_mb, 0.1));
+        assert_eq!(ram_capped, MIN_BUFFER_MB);
+        // ...which a 1 GB hard total still overrides
+        assert_eq!(hard_capped_buffer_mb(ram_capped, 64, 1024), 16);
+        // A hard cap looser than the RAM limit changes nothing
+        assert_eq!(hard_capped_buffer_mb(ram_capped, 64, 4096), MIN_BUFFER_MB);
+        // Never rounds down to an empty buffer
+        assert_eq!(hard_capped_buffer_mb(64, 4096, 1024), 1);
+    }
+
+    #[test]
+    fn test_max_total_memory_bounds_detected_size() {
+        let limits = MemoryLimits {
+            ram_cap: false,
+            max_total_mb: Some(256),
+            ..MemoryLimits::default()
+        };
+        let info = machine(Some(32), Some(16_384), None);
+        assert_eq!(sized(4, 1, &limits, &info), 128);
+        let capped = detect_memory_size(4, 128, &limits, &info);
+        assert_eq!(capped.size_mb, 2);
+        assert_eq!(capped.hard_cap_mb, Some(256));
+        assert!(capped.notes().last().unwrap().contains("minimum"));
+    }
+
+    #[test]
+    fn test_size_math_stays_in_range_at_32_bit_scale() {
+        let big = u32::MAX as usize;
+        assert_eq!(times_mb(big, 2), saturating_mb(2 * u32::MAX as u64));
+        assert_eq!(times_mb(usize::MAX, 2), usize::MAX);
+        assert_eq!(total_allocation_mb(&[usize::MAX, 1]), usize::MAX);
+        assert_eq!(capped_buffer_mb(big, 2, 1024), 512);
+        assert_eq!(hard_capped_buffer_mb(big, big, 1024), 1);
+        // 5 TiB of RAM is past u32::MAX in kB
+        assert_eq!(meminfo_mb("MemTotal: 5368709120 kB\n", "MemTotal"), Some(5 << 20));
+    }
+
+    #[test]
+    fn test_address_space_caps_buffers_on_32_bit() {
+        let info = machine(Some(32), Some(16_384), None);
+        let limits = MemoryLimits {
+            ram_cap: false,
+            ..MemoryLimits::default()
+        };
+        let mut sizing = detect_memory_size(32, 4, &limits, &info);
+        assert_eq!((sizing.size_mb, sizing.address_cap_mb), (1024, None));
+
+        // What detect_memory_size does on a 32-bit target
+        cap_to_address_space(&mut sizing, Some(1536));
+        assert_eq!(sizing.size_mb, 384);
+        assert_eq!(sizing.address_cap_mb, Some(1536));
+        assert!(sizing.notes().iter().any(|note| note.contains("-bit target")));
+
+        let mut small = detect_memory_size(1, 4, &limits, &info);
+        cap_to_address_space(&mut small, Some(1536));
+        assert_eq!(small.address_cap_mb, None);
+    }
+
+    #[test]
+    fn test_lower_ram_safety_shrinks_buffer_cap() {
+        let ram_mb = 16 * 1024;
+        let conservative = capped_buffer_mb(1024, 16, max_safe_mb(ram_mb, 0.5));
+        let default = capped_buffer_mb(1024, 16, max_safe_mb(ram_mb, DEFAULT_RAM_SAFETY));
+        let aggressive = capped_buffer_mb(1024, 16, max_safe_mb(ram_mb, 0.95));
+
+        assert_eq!(conservative, 512);
+        assert!(conservative < default && default < aggressive);
+        // Under the limit nothing changes
+        assert_eq!(capped_buffer_mb(64, 16, max_safe_mb(ram_mb, 0.5)), 64);
+    }
+
+    #[test]
+    fn test_count_mask_bits() {
+        assert_eq!(count_mask_bits(&[0]), 0);
+        assert_eq!(count_mask_bits(&[0b1011]), 3);
+        assert_eq!(count_mask_bits(&[u64::MAX, 1]), 65);
+    }
+
+    #[test]
+    fn test_mask_to_cpus() {
+        assert_eq!(mask_to_cpus(&[0b1111_0000]), [4, 5, 6, 7]);
+        assert_eq!(mask_to_cpus(&[1, 1]), [0, 64]);
+        assert!(mask_to_cpus(&[0, 0]).is_empty());
+    }
+
+    #[test]
+    fn test_available_cpus_within_online() {
+        let cpus = available_cpus();
+        assert!(!cpus.is_empty());
+        assert!(cpus.len() <= online_cpu_count().max(num_cpus::get()));
+    }
+
+    #[test]
+    fn test_pin_current_thread_doesnt_panic() {
+        let cpu = available_cpus()[0];
+        let pinned = std::thread::spawn(move || pin_current_thread(cpu))
+            .join()
+            .unwrap();
+        if !affinity_supported() {
+            assert!(!pinned);
+        }
+    }
+
+    #[test]
+    fn test_thread_cpu_time_advances() {
+        let Some(before) = thread_cpu_time() else {
+            return;
+        };
+        let spin_until = std::time::Instant::now() + Duration::from_millis(20);
+        let mut x = 0u64;
+        while std::time::Instant::now() < spin_until {
+            x = std::hint::black_box(x.wrapping_add(1));
+        }
+        assert!(thread_cpu_time().unwrap() > before);
+    }
+
+    #[test]
+    fn test_process_cpu_time_covers_other_threads() {
+        let Some(before) = process_cpu_time() else {
+            return;
+        };
+        std::thread::spawn(|| {
+            let started = std::time::Instant::now();
+            while started.elapsed() < Duration::from_millis(30) {
+                std::hint::black_box(0u64);
+            }
+        })
+        .join()
+        .unwrap();
+        let after = process_cpu_time().unwrap();
+        assert!(after > before, "{:?} then {:?}", before, after);
+    }
+
+    #[test]
+    fn test_maxrss_units() {
+        // Linux and the BSDs report KiB, macOS bytes
+        assert_eq!(maxrss_bytes(2_048, 1024), Some(2 << 20));
+        assert_eq!(maxrss_bytes(2 << 20, 1), Some(2 << 20));
+        assert_eq!(maxrss_bytes(0, 1024), None);
+        assert_eq!(maxrss_bytes(-1, 1024), None);
+        assert_eq!(maxrss_bytes(i64::MAX, 1024), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_peak_rss_covers_a_touched_buffer() {
+        let Some(before) = peak_rss_bytes() else {
+            return;
+        };
+        let buffer = vec![1u8; 64 << 20];
+        std::hint::black_box(&buffer);
+        let after = peak_rss_bytes().unwrap();
+        assert!(after >= before.max(64 << 20), "{} then {}", before, after);
+    }
+
+    #[test]
+    fn test_hypervisor_bit_doesnt_panic() {
+        let bit = hypervisor_bit();
+        if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+            assert!(bit.is_some());
+        }
+        let _ = detect_virtualization().describe();
+    }
+
+    #[test]
+    fn test_hypervisor_names() {
+        assert_eq!(
+            hypervisor_from_signature(b"KVMKVMKVM\0\0\0").as_deref(),
+            Some("KVM")
+        );
+        assert_eq!(
+            hypervisor_from_signature(b"NewVisor\0\0\0\0").as_deref(),
+            Some("NewVisor")
+        );
+        assert_eq!(hypervisor_from_signature(&[0; 12]), None);
+        assert_eq!(
+            hypervisor_from_dmi("Microsoft Corporation", "Virtual Machine").as_deref(),
+            Some("Hyper-V")
+        );
+        assert_eq!(
+            hypervisor_from_dmi("Microsoft Corporation", "Surface Pro"),
+            None
+        );
+        assert_eq!(hypervisor_from_dmi("Dell Inc.", "PowerEdge R750"), None);
+    }
+
+    #[test]
+    fn test_memory_multiplier_scaling() {
+        let info = machine(Some(16), Some(65_536), None);
+        let limits = MemoryLimits::default();
+        assert_eq!(sized(2, 8, &limits, &info), 32);
+        assert_eq!(sized(4, 8, &limits, &info), 64);
+        assert_eq!(sized(8, 8, &limits, &info), 128);
     }
 }
@@ -0,0 +1,362 @@
+//! Per-core busy time over the measured window (--verify-utilization). A
+//! core the workers should have saturated that sat partly idle means they
+//! were descheduled, something else ran there, or pinning didn't take, and
+//! the rate undercounts what the CPU can do.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::output::notice;
+use crate::reporting::REPORT_INTERVAL;
+
+/// Below this share of a one-core window spent busy, the core is reported.
+pub const MIN_BUSY: f64 = 0.95;
+
+/// Above this share of the allowed CPUs already busy before a benchmark,
+/// its results are likely off.
+pub const MAX_BACKGROUND_LOAD: f64 = 0.10;
+
+/// How long the pre-benchmark load sample watches for.
+pub const BACKGROUND_SAMPLE: Duration = Duration::from_secs(1);
+
+/// Cumulative time one CPU has spent busy and in total, in the platform's
+/// own ticks. Only the difference between two readings means anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoreTimes {
+    pub busy:  u64,
+    pub total: u64,
+}
+
+impl CoreTimes {
+    /// Share of the time between `earlier` and this reading spent busy
+    /// (None = no ticks passed).
+    fn busy_since(&self, earlier: &CoreTimes) -> Option<f64> {
+        let total = self.total.saturating_sub(earlier.total);
+        (total > 0).then(|| self.busy.saturating_sub(earlier.busy) as f64 / total as f64)
+    }
+}
+
+/// One watched core over the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoreBusy {
+    pub cpu:    usize,
+    /// Over the whole measured window
+    pub busy:   f64,
+    /// The least busy reporter interval
+    pub lowest: f64,
+}
+
+/// CPU id and times for every CPU the platform reports, or None where
+/// per-core times aren't implemented.
+pub fn read_core_times() -> Option<Vec<(usize, CoreTimes)>> {
+    #[cfg(target_os = "linux")]
+    {
+        Some(parse_proc_stat(&std::fs::read_to_string("/proc/stat").ok()?))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        windows_core_times()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+/// The `cpuN` lines of /proc/stat: user nice system idle iowait irq softirq
+/// steal, in USER_HZ ticks. Idle, iowait and steal count as not busy; steal
+/// is time a VM's host ran something else, which is exactly what this
+/// looks for. guest time is already inside user and isn't added again.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_stat(contents: &str) -> Vec<(usize, CoreTimes)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let cpu = fields.next()?.strip_prefix("cpu")?.parse().ok()?;
+            let ticks: Vec<u64> = fields.take(8).map_while(|f| f.parse().ok()).collect();
+            // Kernels before 2.6.11 stop at irq/softirq without steal
+            if ticks.len() < 4 {
+                return None;
+            }
+            let idle: u64 = ticks[3] + ticks.get(4).copied().unwrap_or(0);
+            let steal = ticks.get(7).copied().unwrap_or(0);
+            let total: u64 = ticks.iter().sum();
+            Some((cpu, CoreTimes {
+                busy: total - idle - steal,
+                total,
+            }))
+        })
+        .collect()
+}
+
+/// Per-processor idle, kernel and user time from NtQuerySystemInformation;
+/// kernel time includes idle. Covers the first processor group (64 CPUs),
+/// the same ones --affinity pins to.
+#[cfg(target_os = "windows")]
+fn windows_core_times() -> Option<Vec<(usize, CoreTimes)>> {
+    use windows_sys::Wdk::System::SystemInformation::{
+        NtQuerySystemInformation, SystemProcessorPerformanceInformation,
+    };
+    use windows_sys::Win32::System::WindowsProgramming::{
+        SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION as ProcessorTimes,
+    };
+
+    let mut info = vec![ProcessorTimes::default(); 64];
+    let mut returned = 0u32;
+    let status = unsafe {
+        NtQuerySystemInformation(
+            SystemProcessorPerformanceInformation,
+            info.as_mut_ptr().cast(),
+            std::mem::size_of_val(info.as_slice()) as u32,
+            &mut returned,
+        )
+    };
+    if status < 0 {
+        return None;
+    }
+    let entry = std::mem::size_of::<ProcessorTimes>();
+    let count = returned as usize / entry;
+    Some(
+        info[..count]
+            .iter()
+            .enumerate()
+            .map(|(cpu, p)| {
+                let total = (p.KernelTime + p.UserTime) as u64;
+                (cpu, CoreTimes {
+                    busy: total.saturating_sub(p.IdleTime as u64),
+                    total,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Which CPUs a full-load run should keep busy: the ones workers are pinned
+/// to, or every allowed CPU when unpinned workers outnumber them. None when
+/// unpinned workers leave some CPUs free, since then idle cores are expected.
+pub fn cores_to_watch(
+    worker_cpus: Option<&[usize]>,
+    cpus: &[usize],
+    threads: usize,
+) -> Option<Vec<usize>> {
+    match worker_cpus {
+        Some(pinned) => {
+            let mut cores = pinned.to_vec();
+            cores.sort_unstable();
+            cores.dedup();
+            Some(cores)
+        },
+        None => (threads >= cpus.len()).then(|| cpus.to_vec()),
+    }
+}
+
+/// Polled from the monitor loop like the swap monitor; rereads the times at
+/// most once per reporter interval.
+pub struct UtilizationMonitor {
+    cores:     Vec<usize>,
+    /// Readings for `cores`, in the same order; None = not watching
+    baseline:  Option<Vec<CoreTimes>>,
+    previous:  Vec<CoreTimes>,
+    lowest:    Vec<f64>,
+    last_read: Instant,
+}
+
+impl UtilizationMonitor {
+    /// Inert when `cores` is None or the platform can't read them.
+    pub fn start(cores: Option<&[usize]>) -> Self {
+        let cores = cores.unwrap_or_default().to_vec();
+        let baseline = (!cores.is_empty())
+            .then(read_core_times)
+            .flatten()
+            .and_then(|times| select(&times, &cores));
+        Self {
+            previous: baseline.clone().unwrap_or_default(),
+            lowest: vec![1.0; cores.len()],
+            cores,
+            baseline,
+            last_read: Instant::now(),
+        }
+    }
+
+    pub fn check(&mut self) {
+        if self.baseline.is_none() || self.last_read.elapsed() < REPORT_INTERVAL {
+            return;
+        }
+        self.last_read = Instant::now();
+        if let Some(now) = read_core_times().and_then(|times| select(&times, &self.cores)) {
+            self.observe(now);
+        }
+    }
+
+    fn observe(&mut self, now: Vec<CoreTimes>) {
+        let intervals = self.previous.iter().zip(&now);
+        for (lowest, (previous, current)) in self.lowest.iter_mut().zip(intervals) {
+            if let Some(busy) = current.busy_since(previous) {
+                *lowest = lowest.min(busy);
+            }
+        }
+        self.previous = now;
+    }
+
+    /// Busy share per watched core over the whole window, from a last
+    /// reading; empty when not watching.
+    pub fn finish(&mut self) -> Vec<CoreBusy> {
+        let Some(baseline) = &self.baseline else {
+            return Vec::new();
+        };
+        let end = read_core_times()
+            .and_then(|times| select(&times, &self.cores))
+            .unwrap_or_else(|| self.previous.clone());
+        self.cores
+            .iter()
+            .zip(baseline)
+            .zip(&end)
+            .zip(&self.lowest)
+            .filter_map(|(((&cpu, start), end), &lowest)| {
+                let busy = end.busy_since(start)?;
+                Some(CoreBusy {
+                    cpu,
+                    busy,
+                    lowest: lowest.min(busy),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Share of `cpus` busy over `window`, taken together, with nothing of ours
+/// running; None where the platform can't read per-core times.
+pub fn background_load(cpus: &[usize], window: Duration) -> Option<f64> {
+    let before = select(&read_core_times()?, cpus)?;
+    thread::sleep(window);
+    let after = select(&read_core_times()?, cpus)?;
+    combined_busy(&before, &after)
+}
+
+/// Busy share of the summed ticks of every core between two readings.
+fn combined_busy(before: &[CoreTimes], after: &[CoreTimes]) -> Option<f64> {
+    let sum = |times: &[CoreTimes]| CoreTimes {
+        busy:  times.iter().map(|t| t.busy).sum(),
+        total: times.iter().map(|t| t.total).sum(),
+    };
+    sum(after).busy_since(&sum(before))
+}
+
+/// The warning for a machine `load` busy before a benchmark, if it's over
+/// [`MAX_BACKGROUND_LOAD`].
+pub fn background_load_warning(load: f64) -> Option<String> {
+    (load > MAX_BACKGROUND_LOAD).then(|| {
+        format!(
+            "Warning: CPUs were {:.0}% busy before the benchmark started (over {:.0}%); \
+             results may be affected",
+            load * 100.0,
+            MAX_BACKGROUND_LOAD * 100.0
+        )
+    })
+}
+
+/// `times` for each of `cores`, in that order; None if any is missing.
+fn select(times: &[(usize, CoreTimes)], cores: &[usize]) -> Option<Vec<CoreTimes>> {
+    cores
+        .iter()
+        .map(|cpu| times.iter().find(|(id, _)| id == cpu).map(|(_, t)| *t))
+        .collect()
+}
+
+/// Names the cores that spent less than [`MIN_BUSY`] of the window busy.
+pub fn warn_on_idle_cores(cores: &[CoreBusy]) {
+    let idle: Vec<String> = cores
+        .iter()
+        .filter(|core| core.busy < MIN_BUSY)
+        .map(|core| {
+            format!(
+                "cpu{} {:.1}% (lowest second {:.1}%)",
+                core.cpu,
+                core.busy * 100.0,
+                core.lowest * 100.0
+            )
+        })
+        .collect();
+    if idle.is_empty() {
+        return;
+    }
+    notice!(
+        "Warning: {} of {} cores were under {:.0}% busy at full load: {}",
+        idle.len(),
+        cores.len(),
+        MIN_BUSY * 100.0,
+        idle.join(", ")
+    );
+    notice!("         Something else ran there, workers were descheduled, or pinning failed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn times(busy: u64, total: u64) -> CoreTimes {
+        CoreTimes { busy, total }
+    }
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let stat = "cpu  400 0 100 1500 0 0 0 0 0 0\n\
+                    cpu0 190 0 10 790 10 0 0 0 0 0\n\
+                    cpu1 210 5 90 710 5 20 10 50 30 0\n\
+                    intr 428859 0 0\n\
+                    ctxt 99\n";
+        assert_eq!(parse_proc_stat(stat), [
+            (0, times(200, 1000)),
+            // iowait and steal aren't busy; guest is already in user
+            (1, times(335, 1100)),
+        ]);
+        assert_eq!(parse_proc_stat("cpu7 1 2 3\n"), []);
+        assert_eq!(parse_proc_stat(""), []);
+    }
+
+    #[test]
+    fn test_busy_share_from_deltas() {
+        let before = [(0, times(200, 1000)), (1, times(335, 1100))];
+        let after = [(0, times(295, 1100)), (1, times(375, 1200))];
+        let select_all = |t: &[(usize, CoreTimes)]| select(t, &[0, 1]).unwrap();
+
+        let mut monitor = UtilizationMonitor {
+            cores:     vec![0, 1],
+            baseline:  Some(select_all(&before)),
+            previous:  select_all(&before),
+            lowest:    vec![1.0; 2],
+            last_read: Instant::now(),
+        };
+        monitor.observe(select_all(&after));
+        assert_eq!(monitor.lowest, [0.95, 0.4]);
+        assert_eq!(times(5, 10).busy_since(&times(5, 10)), None);
+        assert_eq!(select(&before, &[0, 2]), None);
+    }
+
+    #[test]
+    fn test_background_load_threshold() {
+        // Two cores, one idle and one half busy over the window
+        let before = [times(100, 1000), times(500, 1000)];
+        let after = [times(100, 1100), times(550, 1100)];
+        let load = combined_busy(&before, &after).unwrap();
+        assert!((load - 0.25).abs() < 1e-9, "{}", load);
+        let warning = background_load_warning(load).unwrap();
+        assert!(warning.contains("25% busy"), "{}", warning);
+
+        assert_eq!(background_load_warning(MAX_BACKGROUND_LOAD), None);
+        assert_eq!(background_load_warning(0.02), None);
+        assert_eq!(combined_busy(&before, &before), None);
+    }
+
+    #[test]
+    fn test_cores_to_watch() {
+        let cpus = [0, 1, 2, 3];
+        assert_eq!(cores_to_watch(Some(&[3, 1, 3]), &cpus, 3), Some(vec![1, 3]));
+        assert_eq!(cores_to_watch(None, &cpus, 4), Some(cpus.to_vec()));
+        assert_eq!(cores_to_watch(None, &cpus, 2), None);
+        assert!(UtilizationMonitor::start(None).finish().is_empty());
+    }
+}
@@ -1,58 +1,293 @@
 use std::hint::black_box;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use crate::workload::{
-    allocate_memory_buffer,
-    stress_float,
-    stress_integer,
-    stress_memory_bandwidth,
-    stress_memory_latency,
-};
+use crate::events::EventSender;
+use crate::registry::{WorkingSet, WorkloadSpec};
+use crate::reporting::format_mb;
+use crate::{signal, system};
+use crate::workload::{InitPattern, Workload, allocate_buffer_bytes, allocate_memory_buffer};
 
+pub fn worker_name(id: usize) -> String {
+    format!("locus-w{}", id)
+}
+
+/// Spawns a named thread so `perf`, debuggers and `/proc/<pid>/task/*/comm`
+/// can tell workers apart. `stack_size` is in bytes (None = platform default).
+pub fn spawn_named<F, T>(name: String, stack_size: Option<usize>, f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let mut builder = thread::Builder::new().name(name.clone());
+    if let Some(bytes) = stack_size {
+        builder = builder.stack_size(bytes);
+    }
+    builder
+        .spawn(f)
+        .unwrap_or_else(|e| panic!("Failed to spawn thread {}: {}", name, e))
+}
+
+/// Joins a thread, naming it in the panic message if it panicked.
+pub fn join_named<T>(handle: JoinHandle<T>) -> T {
+    let name = handle.thread().name().unwrap_or("unnamed").to_string();
+    handle
+        .join()
+        .unwrap_or_else(|_| panic!("Thread {} panicked", name))
+}
+
+/// How long a worker existed versus how long it actually ran on a CPU, and
+/// what it got done in that time.
+#[derive(Debug, Clone, Copy)]
+pub struct WorkerTimes {
+    pub wall:         Duration,
+    /// None where the platform can't report per-thread CPU time
+    pub cpu:          Option<Duration>,
+    pub ops:          u64,
+    /// Allocating and filling the buffer, before the measured window
+    /// (None = the workload has no buffer)
+    pub allocation:   Option<Duration>,
+    /// What the buffer actually got, which --best-effort-memory can leave
+    /// short of the plan (None = no buffer, or allocation failed)
+    pub buffer_bytes: Option<usize>,
+}
+
+impl WorkerTimes {
+    /// CPU time / wall time; well below 1.0 means the worker was descheduled.
+    pub fn efficiency(&self) -> Option<f64> {
+        efficiency(self.cpu?, self.wall)
+    }
+}
+
+pub fn efficiency(cpu: Duration, wall: Duration) -> Option<f64> {
+    (!wall.is_zero()).then(|| cpu.as_secs_f64() / wall.as_secs_f64())
+}
+
+/// What a single worker runs and where.
+pub struct WorkerConfig {
+    pub workload:    &'static WorkloadSpec,
+    /// Built from `workload`; runs on the worker thread
+    pub kernel:      Box<dyn Workload>,
+    pub batch_size:  u64,
+    pub memory_mb:   usize,
+    /// Bytes per cache-thrash pass, from --target; it allocates this instead
+    /// of `memory_mb`
+    pub sweep_bytes: usize,
+    /// CPU to pin to (None = leave to the scheduler)
+    pub cpu:         Option<usize>,
+    /// Return after the first batch (--once)
+    pub once:        bool,
+    /// Halve a buffer that can't be allocated instead of failing the run
+    /// (--best-effort-memory)
+    pub best_effort: bool,
+    /// What the buffer is filled with (--init-pattern)
+    pub pattern:     InitPattern,
+}
+
+/// Holds every worker after setup, plus the spawning thread, so nobody
+/// counts ops while later threads are still allocating. A `--stagger` ramp
+/// is the opposite of a synchronized start, so it gets no gate.
+pub fn start_gate(workers: usize, stagger: Duration) -> Option<Arc<Barrier>> {
+    stagger
+        .is_zero()
+        .then(|| Arc::new(Barrier::new(workers + 1)))
+}
+
+/// A fixed amount of work shared by every worker (`--ops`). Each adds its
+/// batches as it finishes them and raises the stop flag once the total is
+/// spent, so a run overshoots by at most one batch per worker.
+#[derive(Debug)]
+pub struct OpsBudget {
+    limit: u64,
+    done:  AtomicU64,
+}
+
+impl OpsBudget {
+    pub fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            done: AtomicU64::new(0),
+        }
+    }
+
+    /// Records a finished batch; true once the budget is spent.
+    fn spend(&self, ops: u64) -> bool {
+        self.done.fetch_add(ops, Ordering::Relaxed) + ops >= self.limit
+    }
+
+    pub fn spent(&self) -> bool {
+        self.done.load(Ordering::Relaxed) >= self.limit
+    }
+}
+
+/// Runs until `stop_flag` is set. Setup problems are sent to `events`
+/// rather than printed; on a fatal one the worker returns without running,
+/// though it still passes `start` so the others aren't left waiting. Wall
+/// and CPU time are taken from when the worker starts counting.
 pub fn worker_thread(
-    id: usize,
     stop_flag: Arc<AtomicBool>,
     work_counter: Arc<AtomicU64>,
-    workload: &str,
-    batch_size: u64,
-    memory_mb: usize,
-) {
-    let mut int_acc = id as u64;
-    let mut float_acc = id as f64;
-    let mut mem_buffer = allocate_memory_buffer(memory_mb);
+    config: WorkerConfig,
+    start: Option<Arc<Barrier>>,
+    budget: Option<Arc<OpsBudget>>,
+    events: EventSender,
+) -> WorkerTimes {
+    let wait_for_start = || {
+        if let Some(barrier) = &start {
+            barrier.wait();
+        }
+    };
+    let WorkerConfig {
+        workload,
+        mut kernel,
+        batch_size,
+        memory_mb,
+        sweep_bytes,
+        cpu,
+        once,
+        best_effort,
+        pattern,
+    } = config;
+
+    if let Some(cpu) = cpu
+        && system::affinity_supported()
+        && !system::pin_current_thread(cpu)
+    {
+        events.warning(format!("could not pin to CPU {}; running unpinned", cpu));
+    }
 
+    // Allocated (and first touched) here, after pinning, so the pages sit
+    // on the node this worker runs on
+    let mut allocation = None;
+    let mut buffer_bytes = None;
+    if kernel.needs_buffer() {
+        let allocating = Instant::now();
+        let allocated = match workload.working_set {
+            // A shrunken sweep would no longer overflow its cache level
+            WorkingSet::CacheSweep => allocate_buffer_bytes(sweep_bytes, pattern),
+            WorkingSet::Registers | WorkingSet::WholeBuffer if best_effort => {
+                let allocate = |mb| allocate_memory_buffer(mb, pattern);
+                allocate_shrinking(memory_mb, allocate).map(|(buffer, mb)| {
+                    if mb < memory_mb {
+                        events.warning(format!(
+                            "allocated {} of the {} buffer planned (--best-effort-memory)",
+                            format_mb(mb),
+                            format_mb(memory_mb)
+                        ));
+                    }
+                    buffer
+                })
+            },
+            WorkingSet::Registers | WorkingSet::WholeBuffer => {
+                allocate_memory_buffer(memory_mb, pattern)
+                    .map_err(|e| format!("{}; --best-effort-memory runs with less", e))
+            },
+        };
+        allocation = Some(allocating.elapsed());
+        match allocated {
+            Ok(buffer) => {
+                buffer_bytes = Some(std::mem::size_of_val(&*buffer));
+                kernel.attach_buffer(buffer);
+            },
+            Err(e) => {
+                events.fatal(e);
+                wait_for_start();
+                return WorkerTimes {
+                    wall: Duration::ZERO,
+                    cpu: None,
+                    ops: 0,
+                    allocation,
+                    buffer_bytes,
+                };
+            },
+        }
+    }
+
+    wait_for_start();
+    let started = Instant::now();
+    let cpu_at_start = system::thread_cpu_time();
+
+    let mut ops = 0u64;
     loop {
         if stop_flag.load(Ordering::Relaxed) {
             break;
         }
 
-        match workload {
-            "integer" => stress_integer(batch_size, &mut int_acc),
-            "float" => stress_float(batch_size, &mut float_acc),
-            "memory" | "memory-latency" => stress_memory_latency(batch_size, &mut mem_buffer),
-            "memory-bandwidth" => stress_memory_bandwidth(batch_size, &mut mem_buffer),
-            _ => {
-                stress_integer(batch_size / 3, &mut int_acc);
-                stress_float(batch_size / 3, &mut float_acc);
-                stress_memory_latency(batch_size / 3, &mut mem_buffer);
-            },
+        let done = kernel.run(batch_size);
+
+        work_counter.fetch_add(done, Ordering::Relaxed);
+        ops += done;
+
+        if let Some(budget) = &budget
+            && budget.spend(done)
+        {
+            signal::raise_stop(&stop_flag);
+            break;
         }
+        if once {
+            break;
+        }
+    }
+
+    black_box(kernel);
 
-        work_counter.fetch_add(batch_size, Ordering::Relaxed);
+    WorkerTimes {
+        wall: started.elapsed(),
+        cpu: system::thread_cpu_time()
+            .zip(cpu_at_start)
+            .map(|(end, start)| end.saturating_sub(start)),
+        ops,
+        allocation,
+        buffer_bytes,
     }
+}
 
-    black_box(int_acc);
-    black_box(float_acc);
-    black_box(mem_buffer);
+/// `memory_mb` from `allocate`, or failing that the largest halving of it
+/// that succeeds, with the size it got. Only when even 1 MB fails does the
+/// first error come back.
+fn allocate_shrinking(
+    memory_mb: usize,
+    allocate: impl Fn(usize) -> Result<Box<[u64]>, String>,
+) -> Result<(Box<[u64]>, usize), String> {
+    let first_error = match allocate(memory_mb) {
+        Ok(buffer) => return Ok((buffer, memory_mb)),
+        Err(e) => e,
+    };
+    let mut mb = memory_mb / 2;
+    while mb > 0 {
+        if let Ok(buffer) = allocate(mb) {
+            return Ok((buffer, mb));
+        }
+        mb /= 2;
+    }
+    Err(first_error)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::thread;
     use std::time::Duration;
 
     use super::*;
+    use crate::events::{EventKind, EventLog};
+    use crate::registry;
+    use crate::workload::KernelParams;
+
+    fn config(workload: &str, batch_size: u64, memory_mb: usize) -> WorkerConfig {
+        let spec = registry::find(workload).unwrap();
+        WorkerConfig {
+            workload: spec,
+            kernel: spec.create(&KernelParams::default()),
+            batch_size,
+            memory_mb,
+            sweep_bytes: 64 << 10,
+            cpu: None,
+            once: false,
+            best_effort: false,
+            pattern: InitPattern::Sequential,
+        }
+    }
 
     #[test]
     fn test_worker_respects_stop_flag() {
@@ -63,7 +298,14 @@ mod tests {
         let counter_clone = Arc::clone(&counter);
 
         let handle = thread::spawn(move || {
-            worker_thread(0, stop_clone, counter_clone, "integer", 10000, 1);
+            worker_thread(
+                stop_clone,
+                counter_clone,
+                config("integer", 10000, 1),
+                None,
+                None,
+                EventLog::new().sender(0),
+            );
         });
 
         thread::sleep(Duration::from_millis(50));
@@ -83,7 +325,14 @@ mod tests {
             let s = Arc::clone(&stop);
             let c = Arc::clone(&counter);
             handles.push(thread::spawn(move || {
-                worker_thread(id, s, c, "mixed", 5000, 1);
+                worker_thread(
+                    s,
+                    c,
+                    config("mixed", 5000, 1),
+                    None,
+                    None,
+                    EventLog::new().sender(id),
+                );
             }));
         }
 
@@ -98,6 +347,281 @@ mod tests {
         assert!(ops > 10000);
     }
 
+    #[test]
+    fn test_workers_wait_at_the_start_gate() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let counter = Arc::new(AtomicU64::new(0));
+        let gate = start_gate(3, Duration::ZERO).unwrap();
+        let mut log = EventLog::new();
+
+        let mut handles = Vec::new();
+        for id in 0..3 {
+            let (s, c, g) = (Arc::clone(&stop), Arc::clone(&counter), Arc::clone(&gate));
+            // One worker fails setup and must still release the others
+            let memory_mb = if id == 2 { usize::MAX } else { 1 };
+            let events = log.sender(id);
+            handles.push(thread::spawn(move || {
+                worker_thread(
+                    s,
+                    c,
+                    config("memory-latency", 1000, memory_mb),
+                    Some(g),
+                    None,
+                    events,
+                )
+            }));
+        }
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+
+        gate.wait();
+        thread::sleep(Duration::from_millis(50));
+        stop.store(true, Ordering::Release);
+
+        let times: Vec<WorkerTimes> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(counter.load(Ordering::Relaxed) > 0);
+        assert!(times[0].ops > 0 && times[1].ops > 0);
+        assert_eq!(times[2].ops, 0);
+        assert!(log.drain());
+
+        assert!(start_gate(4, Duration::from_millis(10)).is_none());
+    }
+
+    #[test]
+    fn test_ops_budget_stops_within_a_batch_per_worker() {
+        const WORKERS: u64 = 4;
+        const BATCH: u64 = 1000;
+        const LIMIT: u64 = 250_500;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let counter = Arc::new(AtomicU64::new(0));
+        let budget = Arc::new(OpsBudget::new(LIMIT));
+
+        let handles: Vec<_> = (0..WORKERS as usize)
+            .map(|id| {
+                let (s, c, b) = (Arc::clone(&stop), Arc::clone(&counter), Arc::clone(&budget));
+                let events = EventLog::new().sender(id);
+                thread::spawn(move || {
+                    worker_thread(s, c, config("integer", BATCH, 0), None, Some(b), events)
+                })
+            })
+            .collect();
+        // No stop from here: the budget ends the run
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        let ops = counter.load(Ordering::Relaxed);
+        assert!(budget.spent() && stop.load(Ordering::Acquire));
+        assert!(ops >= LIMIT, "{}", ops);
+        assert!(ops <= LIMIT + WORKERS * BATCH, "{}", ops);
+    }
+
+    #[test]
+    fn test_worker_reports_cpu_time() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let counter = Arc::new(AtomicU64::new(0));
+
+        let (s, c) = (Arc::clone(&stop), Arc::clone(&counter));
+        let handle = spawn_named(worker_name(0), None, move || {
+            worker_thread(
+                s,
+                c,
+                config("integer", 10000, 0),
+                None,
+                None,
+                EventLog::new().sender(0),
+            )
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        stop.store(true, Ordering::Release);
+
+        let times = join_named(handle);
+        assert!(times.wall >= Duration::from_millis(50));
+        if let Some(cpu) = times.cpu {
+            assert!(cpu > Duration::ZERO);
+            assert!(cpu <= times.wall + Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_failed_setup_reaches_main_thread() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let counter = Arc::new(AtomicU64::new(0));
+        let mut log = EventLog::new();
+
+        // A buffer size that can't be represented fails allocation
+        let (s, c, events) = (Arc::clone(&stop), Arc::clone(&counter), log.sender(5));
+        let handle = spawn_named(worker_name(5), None, move || {
+            worker_thread(
+                s,
+                c,
+                config("memory-latency", 1000, usize::MAX),
+                None,
+                None,
+                events,
+            )
+        });
+
+        // The worker returns on its own; no stop needed
+        join_named(handle);
+        assert!(log.drain());
+        assert!(log.has_fatal());
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+
+        let reports = log.reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].worker, "locus-w5");
+        assert_eq!(reports[0].kind, EventKind::Fatal.as_str());
+    }
+
+    #[test]
+    fn test_allocation_is_timed_only_for_buffered_workloads() {
+        let run = |workload| {
+            let mut config = config(workload, 100, 1);
+            config.once = true;
+            worker_thread(
+                Arc::new(AtomicBool::new(false)),
+                Arc::new(AtomicU64::new(0)),
+                config,
+                None,
+                None,
+                EventLog::new().sender(0),
+            )
+        };
+        assert!(run("memory-latency").allocation.unwrap() > Duration::ZERO);
+        assert!(run("cache-thrash").allocation.is_some());
+        assert_eq!(run("integer").allocation, None);
+    }
+
+    #[test]
+    fn test_once_runs_a_single_batch() {
+        let counter = Arc::new(AtomicU64::new(0));
+        let mut config = config("collatz", 700, 0);
+        config.once = true;
+
+        let times = worker_thread(
+            Arc::new(AtomicBool::new(false)),
+            Arc::clone(&counter),
+            config,
+            None,
+            None,
+            EventLog::new().sender(0),
+        );
+        assert_eq!(times.ops, 700);
+        assert_eq!(counter.load(Ordering::Relaxed), 700);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_each_worker_first_touches_its_own_buffer() {
+        // Page faults taken by the calling thread: whoever faults a page in
+        // is who first touched it
+        fn thread_faults() -> libc::c_long {
+            let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+            assert_eq!(
+                unsafe { libc::getrusage(libc::RUSAGE_THREAD, &mut usage) },
+                0
+            );
+            usage.ru_minflt + usage.ru_majflt
+        }
+
+        let spawner_before = thread_faults();
+        let worker_faults = thread::spawn(|| {
+            let before = thread_faults();
+            let mut config = config("memory-bandwidth", 1, 64);
+            config.once = true;
+            worker_thread(
+                Arc::new(AtomicBool::new(false)),
+                Arc::new(AtomicU64::new(0)),
+                config,
+                None,
+                None,
+                EventLog::new().sender(0),
+            );
+            thread_faults() - before
+        })
+        .join()
+        .unwrap();
+        let spawner_faults = thread_faults() - spawner_before;
+
+        // 64 MiB is 16384 small pages, or 32 with transparent huge pages
+        assert!(worker_faults >= 32, "worker took {} faults", worker_faults);
+        assert!(
+            spawner_faults < worker_faults,
+            "spawner took {} faults, worker {}",
+            spawner_faults,
+            worker_faults
+        );
+    }
+
+    #[test]
+    fn test_zero_size_buffer_fails_instead_of_counting() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let counter = Arc::new(AtomicU64::new(0));
+        let mut log = EventLog::new();
+
+        let times = worker_thread(
+            Arc::clone(&stop),
+            Arc::clone(&counter),
+            config("memory-bandwidth", 1000, 0),
+            None,
+            None,
+            log.sender(0),
+        );
+
+        assert_eq!(times.ops, 0);
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+        assert!(log.drain());
+        assert_eq!(log.reports()[0].kind, EventKind::Fatal.as_str());
+    }
+
+    #[test]
+    fn test_best_effort_halves_a_too_large_request() {
+        // An allocator under memory pressure: nothing over 8 MB fits
+        let tight = |mb: usize| {
+            if mb > 8 {
+                Err(format!("failed to allocate {} MB", mb))
+            } else {
+                allocate_memory_buffer(mb, InitPattern::Sequential)
+            }
+        };
+        let (buffer, mb) = allocate_shrinking(24, tight).unwrap();
+        assert_eq!(mb, 6);
+        assert_eq!(buffer.len(), 6 << 17);
+        assert_eq!(allocate_shrinking(4, tight).unwrap().1, 4);
+
+        let full = |mb: usize| Err::<Box<[u64]>, _>(format!("no room for {} MB", mb));
+        assert_eq!(allocate_shrinking(24, full).unwrap_err(), "no room for 24 MB");
+    }
+
+    #[test]
+    fn test_efficiency() {
+        let times = WorkerTimes {
+            wall:         Duration::from_secs(10),
+            cpu:          Some(Duration::from_secs(9)),
+            ops:          0,
+            allocation:   None,
+            buffer_bytes: None,
+        };
+        assert!((times.efficiency().unwrap() - 0.9).abs() < 1e-9);
+        assert_eq!(efficiency(Duration::from_secs(1), Duration::ZERO), None);
+
+        let unsupported = WorkerTimes { cpu: None, ..times };
+        assert_eq!(unsupported.efficiency(), None);
+    }
+
+    #[test]
+    fn test_spawn_named_sets_thread_name() {
+        let handle = spawn_named(worker_name(7), Some(256 * 1024), || {
+            assert_eq!(thread::current().name(), Some("locus-w7"));
+        });
+        assert_eq!(handle.thread().name(), Some("locus-w7"));
+        join_named(handle);
+    }
+
     #[test]
     fn test_memory_bandwidth_workload() {
         let stop = Arc::new(AtomicBool::new(false));
@@ -107,7 +631,14 @@ mod tests {
         let counter_clone = Arc::clone(&counter);
 
         let handle = thread::spawn(move || {
-            worker_thread(0, stop_clone, counter_clone, "memory-bandwidth", 10000, 2);
+            worker_thread(
+                stop_clone,
+                counter_clone,
+                config("memory-bandwidth", 10000, 2),
+                None,
+                None,
+                EventLog::new().sender(0),
+            );
         });
 
         thread::sleep(Duration::from_millis(50));
@@ -1,5 +1,468 @@
 use std::hint::black_box;
 
+use clap::ValueEnum;
+
+use crate::numa;
+use crate::reporting::format_bytes;
+
+/// Modern memory controllers can handle 8-16 parallel requests (iirc).
+/// Apple and Graviton cores keep far more misses in flight than 8 streams
+/// generate, so aarch64 runs 16.
+#[cfg(not(target_arch = "aarch64"))]
+pub const BANDWIDTH_STREAMS: usize = 8;
+#[cfg(target_arch = "aarch64")]
+pub const BANDWIDTH_STREAMS: usize = 16;
+
+/// Stream accesses one memory-bandwidth op stands for on every target, so
+/// ops/s (and the reference index, and `compare`) mean the same on aarch64.
+pub const STREAMS_PER_OP: usize = 8;
+
+/// Upper bound for --chains; well past the line fill buffers of current cores
+pub const MAX_CHAINS: usize = 64;
+
+/// Upper bound for --prefetch; sizes the ring of upcoming indices
+pub const MAX_PREFETCH: usize = 64;
+
+/// Whether this target has a prefetch instruction; elsewhere --prefetch
+/// still switches to the precomputed addresses but issues no hint.
+pub const PREFETCH_SUPPORTED: bool = cfg!(any(target_arch = "x86_64", target_arch = "aarch64"));
+
+/// What memory-bandwidth reports as its path, without and with --prefetch
+/// (None = the portable 8-stream kernel). --prefetch runs a different
+/// kernel, one whose addresses don't wait on the loads, so its path says so.
+#[cfg(target_arch = "x86_64")]
+const BANDWIDTH_PATHS: [Option<&str>; 2] =
+    [None, Some("precomputed-address kernel, PREFETCHT0")];
+#[cfg(target_arch = "aarch64")]
+const BANDWIDTH_PATHS: [Option<&str>; 2] = [
+    Some("16-stream aarch64 path"),
+    Some("16-stream aarch64 precomputed-address kernel, PRFM PLDL1KEEP"),
+];
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const BANDWIDTH_PATHS: [Option<&str>; 2] = [
+    None,
+    Some("precomputed-address kernel, no prefetch instruction"),
+];
+
+/// The LCG multiplier memory-latency's chains step with (Knuth's MMIX one)
+pub const LATENCY_LCG_MULT: u64 = 6364136223846793005;
+
+/// Different Linear Congruential Generators (LCG) multipliers for each
+/// bandwidth stream (all coprime)
+#[cfg(not(target_arch = "aarch64"))]
+const LCG_MULTS: [u64; BANDWIDTH_STREAMS] = [
+    6364136223846793005, // Stream 0
+    2862933555777941757, // Stream 1
+    3202034522624059733, // Stream 2
+    7046029254386353087, // Stream 3
+    5495735621104509439, // Stream 4
+    1865811235122147685, // Stream 5
+    8121734705789632447, // Stream 6
+    4976774832059184573, // Stream 7
+];
+/// The same eight, then eight more coprime to all of them
+#[cfg(target_arch = "aarch64")]
+const LCG_MULTS: [u64; BANDWIDTH_STREAMS] = [
+    6364136223846793005, // Stream 0
+    2862933555777941757, // Stream 1
+    3202034522624059733, // Stream 2
+    7046029254386353087, // Stream 3
+    5495735621104509439, // Stream 4
+    1865811235122147685, // Stream 5
+    8121734705789632447, // Stream 6
+    4976774832059184573, // Stream 7
+    5185954308787847947, // Stream 8
+    5585206556094232027, // Stream 9
+    702126728763389183, // Stream 10
+    1184089644772607479, // Stream 11
+    6023653976986616539, // Stream 12
+    5350372741710721963, // Stream 13
+    1949377897788983401, // Stream 14
+    3854534695431866359, // Stream 15
+];
+
+/// One worker's kernel plus the state it carries between batches. Built by
+/// [`crate::registry::WorkloadSpec::create`]; the worker attaches a buffer if
+/// `needs_buffer` asks for one, then calls `run` until told to stop.
+pub trait Workload: Send {
+    /// Runs one batch of about `batch` iterations and returns the ops it
+    /// actually did, which is what the counters record.
+    fn run(&mut self, batch: u64) -> u64;
+
+    /// Whether the worker should allocate a buffer and hand it over with
+    /// [`Workload::attach_buffer`] before the first batch.
+    fn needs_buffer(&self) -> bool;
+
+    fn attach_buffer(&mut self, buffer: Box<[u64]>);
+
+    /// The code path this kernel picked when it was built, for kernels with
+    /// more than one; None = the single portable path.
+    fn implementation(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// Overrides for how memory-latency and memory-bandwidth walk the buffer
+/// (`--lcg-mult`, `--lcg-add`, `--stride`). The default is the built-in
+/// pattern. Other values can leave part of the buffer untouched: an even
+/// multiplier loses a low bit of the value every step, and a stride that
+/// shares a factor with the buffer length only reaches every gcd-th word.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessPattern {
+    /// Replaces the LCG multiplier, every stream's for memory-bandwidth
+    pub lcg_mult: Option<u64>,
+    /// Added to each new value along with the iteration count
+    pub lcg_add:  u64,
+    /// Words from one access to the next instead of a jump derived from the
+    /// value just written (None = the jump)
+    pub stride:   Option<usize>,
+}
+
+impl AccessPattern {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The stride folded into a buffer of `len` words, so stepping by it
+    /// never overflows.
+    fn stride_in(&self, len: usize) -> Option<usize> {
+        self.stride.map(|stride| stride % len)
+    }
+}
+
+/// Next index of a chain or stream at `index`: the value-derived `hash`, or
+/// `stride` words on. `stride` is already below the buffer length.
+#[inline(always)]
+fn next_index(
+    index: usize,
+    hash: usize,
+    stride: Option<usize>,
+    wrap: impl Fn(usize) -> usize,
+) -> usize {
+    match stride {
+        None => wrap(hash),
+        Some(stride) => wrap(index + stride),
+    }
+}
+
+/// What a workload is built from besides its name.
+#[derive(Debug, Clone, Copy)]
+pub struct KernelParams {
+    /// Starting accumulator value, so workers don't compute identical streams
+    pub seed:       u64,
+    /// Independent pointer chains for memory-latency (1 = pure latency)
+    pub chains:     usize,
+    /// Which float kernel float and mixed run
+    pub float_mode: FloatMode,
+    /// Iterations ahead each memory-bandwidth stream prefetches (0 = off)
+    pub prefetch:   usize,
+    /// What the buffer holds; `Cycle` switches memory-latency to chasing it
+    pub pattern:    InitPattern,
+    /// LCG and stride overrides for memory-latency and memory-bandwidth
+    pub access:     AccessPattern,
+}
+
+/// The kernels a run gets with no kernel options: one chain, no prefetch.
+impl Default for KernelParams {
+    fn default() -> Self {
+        Self {
+            seed:       0,
+            chains:     1,
+            float_mode: FloatMode::default(),
+            prefetch:   0,
+            pattern:    InitPattern::default(),
+            access:     AccessPattern::default(),
+        }
+    }
+}
+
+/// What the float kernel spends its time on (`--float-mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum FloatMode {
+    /// Add, multiply and sqrt only: the FPU's own pipelines, no libm
+    Simple,
+    /// sin, cos and ln_1p on top, so libm's quality shows in the rate
+    #[default]
+    Transcendental,
+}
+
+impl FloatMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Simple => "simple",
+            Self::Transcendental => "transcendental",
+        }
+    }
+
+    /// The kernel for this mode and, when it isn't the portable one, the
+    /// path taken. Only simple vectorizes: transcendental is bound by scalar
+    /// libm calls either way.
+    fn kernel(self) -> (fn(u64, &mut f64), Option<&'static str>) {
+        match self {
+            #[cfg(target_arch = "aarch64")]
+            Self::Simple if std::arch::is_aarch64_feature_detected!("neon") => {
+                (stress_float_simple_neon, Some("NEON path"))
+            },
+            Self::Simple => (stress_float_simple, None),
+            Self::Transcendental => (stress_float, None),
+        }
+    }
+}
+
+/// What a fresh buffer is filled with (`--init-pattern`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum InitPattern {
+    /// Each word's own index, scrambled by a constant
+    #[default]
+    Sequential,
+    /// Pseudorandom words, the same for every run
+    Random,
+    /// One random cycle through every word (Sattolo); memory-latency walks
+    /// it instead of deriving the next index from the value
+    Cycle,
+}
+
+impl InitPattern {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sequential => "sequential",
+            Self::Random => "random",
+            Self::Cycle => "cycle",
+        }
+    }
+}
+
+/// Compute workloads keep one accumulator and no buffer.
+macro_rules! accumulator_workload {
+    ($name:ident, $acc:ty, $kernel:ident) => {
+        pub struct $name {
+            acc: $acc,
+        }
+
+        impl $name {
+            pub fn new(params: &KernelParams) -> Self {
+                Self {
+                    acc: params.seed as $acc,
+                }
+            }
+        }
+
+        impl Workload for $name {
+            fn run(&mut self, batch: u64) -> u64 {
+                $kernel(batch, &mut self.acc);
+                batch
+            }
+
+            fn needs_buffer(&self) -> bool {
+                false
+            }
+
+            fn attach_buffer(&mut self, _buffer: Box<[u64]>) {}
+        }
+    };
+}
+
+accumulator_workload!(Integer, u64, stress_integer);
+accumulator_workload!(IntegerLatency, u64, stress_integer_latency);
+accumulator_workload!(Recursion, u64, stress_recursion);
+accumulator_workload!(Collatz, u64, stress_collatz);
+
+/// Like the accumulator workloads, but the kernel is picked by --float-mode.
+pub struct Float {
+    acc:    f64,
+    kernel: fn(u64, &mut f64),
+    path:   Option<&'static str>,
+}
+
+impl Float {
+    pub fn new(params: &KernelParams) -> Self {
+        let (float_kernel, float_path) = params.float_mode.kernel();
+        Self {
+            acc:    params.seed as f64,
+            kernel: float_kernel,
+            path:   float_path,
+        }
+    }
+}
+
+impl Workload for Float {
+    fn run(&mut self, batch: u64) -> u64 {
+        (self.kernel)(batch, &mut self.acc);
+        batch
+    }
+
+    fn needs_buffer(&self) -> bool {
+        false
+    }
+
+    fn attach_buffer(&mut self, _buffer: Box<[u64]>) {}
+
+    fn implementation(&self) -> Option<&'static str> {
+        self.path
+    }
+}
+
+/// A third each of integer, float and memory-latency; integer takes the
+/// remainder so a batch under 3 still counts.
+pub struct Mixed {
+    int_acc:      u64,
+    float_acc:    f64,
+    float_kernel: fn(u64, &mut f64),
+    float_path:   Option<&'static str>,
+    buffer:       Box<[u64]>,
+}
+
+impl Mixed {
+    pub fn new(params: &KernelParams) -> Self {
+        let (kernel, path) = params.float_mode.kernel();
+        Self {
+            int_acc:      params.seed,
+            float_acc:    params.seed as f64,
+            float_kernel: kernel,
+            float_path:   path,
+            buffer:       Box::default(),
+        }
+    }
+}
+
+impl Workload for Mixed {
+    fn run(&mut self, batch: u64) -> u64 {
+        let third = batch / 3;
+        stress_integer(third + batch % 3, &mut self.int_acc);
+        (self.float_kernel)(third, &mut self.float_acc);
+        stress_memory_latency(third, &mut self.buffer);
+        batch
+    }
+
+    fn needs_buffer(&self) -> bool {
+        true
+    }
+
+    fn attach_buffer(&mut self, buffer: Box<[u64]>) {
+        self.buffer = buffer;
+    }
+
+    fn implementation(&self) -> Option<&'static str> {
+        self.float_path
+    }
+}
+
+pub struct MemoryLatency {
+    buffer:  Box<[u64]>,
+    chains:  usize,
+    access:  AccessPattern,
+    /// Where each chain resumes on an `InitPattern::Cycle` buffer, so
+    /// successive batches keep walking the cycle instead of retracing its
+    /// start; None = the value-derived chase
+    cursors: Option<Vec<usize>>,
+}
+
+impl MemoryLatency {
+    pub fn new(params: &KernelParams) -> Self {
+        Self {
+            buffer:  Box::default(),
+            chains:  params.chains,
+            access:  params.access,
+            cursors: (params.pattern == InitPattern::Cycle).then(Vec::new),
+        }
+    }
+}
+
+impl Workload for MemoryLatency {
+    fn run(&mut self, batch: u64) -> u64 {
+        match &mut self.cursors {
+            Some(cursors) => stress_memory_chase(batch, &self.buffer, cursors),
+            None => {
+                stress_memory_latency_mlp(batch, &mut self.buffer, self.chains, &self.access)
+            },
+        }
+        batch
+    }
+
+    fn needs_buffer(&self) -> bool {
+        true
+    }
+
+    fn attach_buffer(&mut self, buffer: Box<[u64]>) {
+        if let Some(cursors) = &mut self.cursors {
+            let chains = self.chains.clamp(1, MAX_CHAINS.min(buffer.len()));
+            *cursors = cycle_cursors(&buffer, chains);
+        }
+        self.buffer = buffer;
+    }
+}
+
+pub struct MemoryBandwidth {
+    buffer:   Box<[u64]>,
+    prefetch: usize,
+    access:   AccessPattern,
+}
+
+impl MemoryBandwidth {
+    pub fn new(params: &KernelParams) -> Self {
+        Self {
+            buffer:   Box::default(),
+            prefetch: params.prefetch,
+            access:   params.access,
+        }
+    }
+}
+
+impl Workload for MemoryBandwidth {
+    fn run(&mut self, batch: u64) -> u64 {
+        if self.prefetch == 0 {
+            stress_memory_bandwidth(batch, &mut self.buffer, &self.access);
+        } else {
+            let (buffer, access) = (&mut self.buffer, &self.access);
+            stress_memory_bandwidth_prefetch(batch, buffer, self.prefetch, access);
+        }
+        batch * BANDWIDTH_STREAMS as u64 / STREAMS_PER_OP as u64
+    }
+
+    fn needs_buffer(&self) -> bool {
+        true
+    }
+
+    fn attach_buffer(&mut self, buffer: Box<[u64]>) {
+        self.buffer = buffer;
+    }
+
+    fn implementation(&self) -> Option<&'static str> {
+        BANDWIDTH_PATHS[usize::from(self.prefetch > 0)]
+    }
+}
+
+/// Takes the sweep buffer rather than the -m/-x one; the worker sizes it.
+pub struct CacheThrash {
+    buffer: Box<[u64]>,
+    /// Where the sweep resumes in the next batch
+    cursor: usize,
+}
+
+impl CacheThrash {
+    pub fn new(_params: &KernelParams) -> Self {
+        Self {
+            buffer: Box::default(),
+            cursor: 0,
+        }
+    }
+}
+
+impl Workload for CacheThrash {
+    fn run(&mut self, batch: u64) -> u64 {
+        stress_cache_thrash(batch, &mut self.buffer, &mut self.cursor);
+        batch
+    }
+
+    fn needs_buffer(&self) -> bool {
+        true
+    }
+
+    fn attach_buffer(&mut self, buffer: Box<[u64]>) {
+        self.buffer = buffer;
+    }
+}
+
 #[inline(always)]
 pub fn stress_integer(iterations: u64, accumulator: &mut u64) {
     for i in 0..iterations {
@@ -11,6 +474,24 @@ pub fn stress_integer(iterations: u64, accumulator: &mut u64) {
     }
 }
 
+/// The latency half of the integer pair: every multiply consumes the
+/// previous product, so the core can't overlap them and the rate is one
+/// multiply per multiply latency rather than per issue slot.
+///
+/// The multiplier goes through `black_box` so the chain can't be folded
+/// into a power; the chain itself stays in a register, since a `black_box`
+/// per step would add a store-forward round trip to every link.
+#[inline(always)]
+pub fn stress_integer_latency(iterations: u64, accumulator: &mut u64) {
+    let multiplier = black_box(0x9e3779b97f4a7c15_u64);
+    // Odd times odd stays odd, so the chain never collapses to zero.
+    let mut x = black_box(*accumulator | 1);
+    for _ in 0..iterations {
+        x = x.wrapping_mul(multiplier);
+    }
+    *accumulator = black_box(x);
+}
+
 #[inline(always)]
 pub fn stress_float(iterations: u64, accumulator: &mut f64) {
     for i in 0..iterations {
@@ -22,6 +503,123 @@ pub fn stress_float(iterations: u64, accumulator: &mut f64) {
     }
 }
 
+/// The same chain without the libm calls: only add, mul and sqrt, which
+/// every FPU does in hardware.
+#[inline(always)]
+pub fn stress_float_simple(iterations: u64, accumulator: &mut f64) {
+    for i in 0..iterations {
+        let x = black_box(i as f64 + 1.0);
+        let y = x.sqrt() * 1.618033988749895;
+        let w = y * 0.5 + 0.25;
+        *accumulator = black_box(*accumulator + w);
+    }
+}
+
+/// [`stress_float_simple`] two iterations at a time, one per NEON lane, on
+/// aarch64. Same math and the same op count; an odd iteration left over
+/// runs scalar.
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub fn stress_float_simple_neon(iterations: u64, accumulator: &mut f64) {
+    use std::arch::aarch64::{
+        vaddq_f64,
+        vaddvq_f64,
+        vdupq_n_f64,
+        vfmaq_f64,
+        vld1q_f64,
+        vmulq_f64,
+        vsqrtq_f64,
+    };
+
+    #[allow(unused_unsafe)]
+    let lanes = unsafe {
+        let phi = vdupq_n_f64(1.618033988749895);
+        let half = vdupq_n_f64(0.5);
+        let quarter = vdupq_n_f64(0.25);
+        let two = vdupq_n_f64(2.0);
+        let mut x = vld1q_f64([1.0, 2.0].as_ptr());
+        let mut acc = vdupq_n_f64(0.0);
+        for _ in 0..iterations / 2 {
+            let y = vmulq_f64(vsqrtq_f64(black_box(x)), phi);
+            // quarter + y * half
+            acc = black_box(vaddq_f64(acc, vfmaq_f64(quarter, y, half)));
+            x = vaddq_f64(x, two);
+        }
+        vaddvq_f64(acc)
+    };
+    *accumulator += lanes;
+
+    if iterations % 2 == 1 {
+        let x = black_box(iterations as f64);
+        *accumulator = black_box(*accumulator + (x.sqrt() * 1.618033988749895 * 0.5 + 0.25));
+    }
+}
+
+/// Deepest `n` fed to Ackermann(2, n); the call stack stays under ~20 frames
+pub const RECURSION_MAX_N: u64 = 7;
+
+/// Plain recursive Ackermann. Only ever called with m <= 2, where the result
+/// is 2n + 3 and the depth is bounded by roughly 2n + 5.
+#[inline(never)]
+pub fn ackermann(m: u64, n: u64) -> u64 {
+    match (m, n) {
+        (0, n) => n + 1,
+        (m, 0) => ackermann(m - 1, 1),
+        (m, n) => ackermann(m - 1, ackermann(m, n - 1)),
+    }
+}
+
+/// Call/return stress - one Ackermann(2, n) evaluation per iteration,
+/// hundreds of calls deep-and-back each
+#[inline(always)]
+pub fn stress_recursion(iterations: u64, accumulator: &mut u64) {
+    for i in 0..iterations {
+        let n = black_box(i % (RECURSION_MAX_N + 1));
+        let result = ackermann(black_box(2), n);
+        *accumulator = black_box(accumulator.wrapping_add(result));
+    }
+}
+
+/// Steps for `start` to reach 1. Wrapping keeps huge starts from panicking;
+/// the starting values `stress_collatz` feeds in never get near overflow.
+#[inline(always)]
+pub fn collatz_steps(start: u64) -> u64 {
+    let mut n = start.max(1);
+    let mut steps = 0u64;
+    while n != 1 {
+        n = if n.is_multiple_of(2) {
+            n / 2
+        } else {
+            n.wrapping_mul(3).wrapping_add(1)
+        };
+        steps += 1;
+    }
+    steps
+}
+
+/// Branch-heavy integer test - one Collatz sequence per iteration, with
+/// data-dependent lengths the branch predictor can't learn
+#[inline(always)]
+pub fn stress_collatz(iterations: u64, accumulator: &mut u64) {
+    // Restart the window so lengths stay bounded on long runs
+    const WINDOW: u64 = 1 << 20;
+    for i in 0..iterations {
+        let start = black_box(i % WINDOW + 1);
+        *accumulator = black_box(accumulator.wrapping_add(collatz_steps(start)));
+    }
+}
+
+/// `mb` rounded down to a power of two (0 stays 0). A MiB is 2^17 words,
+/// so the buffer's element count is then a power of two too and the memory
+/// kernels index it with a mask instead of a 64-bit remainder, which on many
+/// cores costs more than the L1 hit it guards.
+pub fn mask_sized_mb(mb: usize) -> usize {
+    match mb {
+        0 => 0,
+        mb => 1 << mb.ilog2(),
+    }
+}
+
 /// Memory latency test - single pointer-chasing chain
 /// (~70-100ns)
 #[inline(always)]
@@ -31,42 +629,160 @@ pub fn stress_memory_latency(iterations: u64, buffer: &mut [u64]) {
     }
 
     let len = buffer.len();
+    if len.is_power_of_two() {
+        let mask = len - 1;
+        latency_chain(iterations, buffer, |hash| hash & mask);
+    } else {
+        latency_chain(iterations, buffer, |hash| hash % len);
+    }
+}
+
+/// The chain itself; `wrap` folds the next index into the buffer.
+#[inline(always)]
+fn latency_chain(iterations: u64, buffer: &mut [u64], wrap: impl Fn(usize) -> usize) {
     let mut index = 0usize;
 
     for i in 0..iterations {
         let value = black_box(buffer[index]);
-        let new_value = value.wrapping_mul(6364136223846793005_u64).wrapping_add(i);
+        let new_value = value.wrapping_mul(LATENCY_LCG_MULT).wrapping_add(i);
         buffer[index] = black_box(new_value);
         // Next index depends on current value - defeats prefetch
-        index = black_box(((new_value >> 17) ^ i) as usize % len);
+        index = black_box(wrap(((new_value >> 17) ^ i) as usize));
+    }
+}
+
+/// Memory-level parallelism test - `chains` independent pointer-chasing
+/// chains, stepped round-robin. Each iteration is still one dependent access,
+/// so rates stay comparable across chain counts; chains = 1 with the default
+/// `access` is exactly `stress_memory_latency`.
+#[inline(always)]
+pub fn stress_memory_latency_mlp(
+    iterations: u64,
+    buffer: &mut [u64],
+    chains: usize,
+    access: &AccessPattern,
+) {
+    if buffer.is_empty() {
+        return;
+    }
+
+    let len = buffer.len();
+    if len.is_power_of_two() {
+        let mask = len - 1;
+        latency_chains(iterations, buffer, chains, access, |hash| hash & mask);
+    } else {
+        latency_chains(iterations, buffer, chains, access, |hash| hash % len);
+    }
+}
+
+#[inline(always)]
+fn latency_chains(
+    iterations: u64,
+    buffer: &mut [u64],
+    chains: usize,
+    access: &AccessPattern,
+    wrap: impl Fn(usize) -> usize,
+) {
+    let len = buffer.len();
+    let mult = access.lcg_mult.unwrap_or(LATENCY_LCG_MULT);
+    let stride = access.stride_in(len);
+    let chains = chains.clamp(1, MAX_CHAINS.min(len));
+    let mut indices = [0usize; MAX_CHAINS];
+    // Spread the starting points so the chains begin in separate regions
+    for (chain, index) in indices[..chains].iter_mut().enumerate() {
+        *index = (len / chains) * chain;
+    }
+
+    let mut i = 0u64;
+    while i < iterations {
+        for index in indices[..chains].iter_mut() {
+            if i == iterations {
+                break;
+            }
+            let value = black_box(buffer[*index]);
+            let new_value = value
+                .wrapping_mul(mult)
+                .wrapping_add(i.wrapping_add(access.lcg_add));
+            buffer[*index] = black_box(new_value);
+            let hash = ((new_value >> 17) ^ i) as usize;
+            *index = black_box(next_index(*index, hash, stride, &wrap));
+            i += 1;
+        }
+    }
+}
+
+/// Starting points for `chains` cursors spaced evenly along the cycle in
+/// `buffer`, not across its indices: cursor k is `k * len / chains` steps
+/// on from cursor 0, so no chain trails another by a few hops and reads
+/// lines it just pulled in. Walks the cycle once, during setup.
+fn cycle_cursors(buffer: &[u64], chains: usize) -> Vec<usize> {
+    let mut cursors = vec![0];
+    let mut index = 0;
+    let mut steps = 0;
+    for chain in 1..chains {
+        let target = chain * buffer.len() / chains;
+        while steps < target {
+            index = buffer[index] as usize;
+            steps += 1;
+        }
+        cursors.push(index);
+    }
+    cursors
+}
+
+/// Pure pointer chase over an `InitPattern::Cycle` buffer: each word is the
+/// index of the next, so every load waits on the one before and the walk
+/// covers the whole buffer before it repeats. One chain per cursor, stepped
+/// round-robin like [`stress_memory_latency_mlp`]; the cursors start at
+/// different points of the same cycle and never meet. Read-only, so the
+/// cycle survives for the next batch.
+#[inline(always)]
+pub fn stress_memory_chase(iterations: u64, buffer: &[u64], cursors: &mut [usize]) {
+    if buffer.is_empty() || cursors.is_empty() {
+        return;
+    }
+
+    let mut i = 0u64;
+    while i < iterations {
+        for cursor in cursors.iter_mut() {
+            if i == iterations {
+                break;
+            }
+            *cursor = black_box(buffer[*cursor] as usize);
+            i += 1;
+        }
     }
 }
 
 /// Memory bandwidth test - parallel independent streams
 #[inline(always)]
-pub fn stress_memory_bandwidth(iterations: u64, buffer: &mut [u64]) {
+pub fn stress_memory_bandwidth(iterations: u64, buffer: &mut [u64], access: &AccessPattern) {
     if buffer.is_empty() {
         return;
     }
 
     let len = buffer.len();
+    if len.is_power_of_two() {
+        let mask = len - 1;
+        bandwidth_streams(iterations, buffer, access, |hash| hash & mask);
+    } else {
+        bandwidth_streams(iterations, buffer, access, |hash| hash % len);
+    }
+}
 
-    // Modern memory controllers can handle 8-16 parallel requests (iirc)
-    const STREAMS: usize = 8;
-    let mut indices = [0usize; STREAMS];
+#[inline(always)]
+fn bandwidth_streams(
+    iterations: u64,
+    buffer: &mut [u64],
+    access: &AccessPattern,
+    wrap: impl Fn(usize) -> usize,
+) {
+    let len = buffer.len();
 
-    // Different Linear Congruential Generators (LCG) multipliers for each stream
-    // (all coprime)
-    const LCG_MULTS: [u64; STREAMS] = [
-        6364136223846793005, // Stream 0
-        2862933555777941757, // Stream 1
-        3202034522624059733, // Stream 2
-        7046029254386353087, // Stream 3
-        5495735621104509439, // Stream 4
-        1865811235122147685, // Stream 5
-        8121734705789632447, // Stream 6
-        4976774832059184573, // Stream 7
-    ];
+    const STREAMS: usize = BANDWIDTH_STREAMS;
+    let mults = access.lcg_mult.map_or(LCG_MULTS, |mult| [mult; STREAMS]);
+    let stride = access.stride_in(len);
+    let mut indices = [0usize; STREAMS];
 
     // Initialize streams at different buffer offsets
     for (i, idx) in indices.iter_mut().enumerate() {
@@ -82,8 +798,8 @@ pub fn stress_memory_bandwidth(iterations: u64, buffer: &mut [u64]) {
         let mut new_values = [0u64; STREAMS];
         for stream_id in 0..STREAMS {
             new_values[stream_id] = values[stream_id]
-                .wrapping_mul(LCG_MULTS[stream_id])
-                .wrapping_add(iter);
+                .wrapping_mul(mults[stream_id])
+                .wrapping_add(iter.wrapping_add(access.lcg_add));
         }
 
         for stream_id in 0..STREAMS {
@@ -91,25 +807,264 @@ pub fn stress_memory_bandwidth(iterations: u64, buffer: &mut [u64]) {
         }
 
         for stream_id in 0..STREAMS {
-            indices[stream_id] = black_box(((new_values[stream_id] >> 17) as usize) % len);
+            let hash = (new_values[stream_id] >> 17) as usize;
+            indices[stream_id] = black_box(next_index(indices[stream_id], hash, stride, &wrap));
         }
     }
 }
 
-pub fn allocate_memory_buffer(size_mb: usize) -> Box<[u64]> {
-    let bytes = size_mb
-        .checked_mul(1024)
-        .and_then(|b| b.checked_mul(1024))
-        .expect("Requested memory size (MB) too large, multiplication overflow");
+/// Memory bandwidth test with software prefetch (--prefetch). Each stream's
+/// addresses come from its own LCG state rather than the value it loaded, so
+/// the index `distance` iterations ahead is known early and prefetched while
+/// the current one is still in flight. Same read-modify-write per access as
+/// [`stress_memory_bandwidth`], but no load waits on the one before, so it
+/// also gains memory-level parallelism the plain kernel doesn't have and is
+/// reported as a kernel of its own. With a stride the state is the index
+/// itself.
+#[inline(always)]
+pub fn stress_memory_bandwidth_prefetch(
+    iterations: u64,
+    buffer: &mut [u64],
+    distance: usize,
+    access: &AccessPattern,
+) {
+    if buffer.is_empty() {
+        return;
+    }
+
+    let len = buffer.len();
+    if len.is_power_of_two() {
+        let mask = len - 1;
+        prefetched_streams(iterations, buffer, distance, access, |hash| hash & mask);
+    } else {
+        prefetched_streams(iterations, buffer, distance, access, |hash| hash % len);
+    }
+}
+
+#[inline(always)]
+fn prefetched_streams(
+    iterations: u64,
+    buffer: &mut [u64],
+    distance: usize,
+    access: &AccessPattern,
+    wrap: impl Fn(usize) -> usize,
+) {
+    let len = buffer.len();
+    let distance = distance.clamp(1, MAX_PREFETCH);
 
+    const STREAMS: usize = BANDWIDTH_STREAMS;
+    let mults = access.lcg_mult.map_or(LCG_MULTS, |mult| [mult; STREAMS]);
+    let stride = access.stride_in(len);
+    let mut states = [0u64; STREAMS];
+    for (i, state) in states.iter_mut().enumerate() {
+        *state = ((len / STREAMS) * i) as u64;
+    }
+
+    let mut step = 0u64;
+    let mut advance = |states: &mut [u64; STREAMS]| {
+        let mut next = [0usize; STREAMS];
+        for stream_id in 0..STREAMS {
+            next[stream_id] = match stride {
+                None => {
+                    states[stream_id] = states[stream_id]
+                        .wrapping_mul(mults[stream_id])
+                        .wrapping_add(step.wrapping_add(access.lcg_add));
+                    wrap((states[stream_id] >> 17) as usize)
+                },
+                Some(stride) => {
+                    let index = wrap(states[stream_id] as usize + stride);
+                    states[stream_id] = index as u64;
+                    index
+                },
+            };
+        }
+        step += 1;
+        next
+    };
+
+    // The next `distance` indices of every stream, consumed in order; each
+    // slot is refilled with the index `distance` iterations on
+    let mut ahead = [[0usize; STREAMS]; MAX_PREFETCH];
+    for slot in ahead[..distance].iter_mut() {
+        *slot = advance(&mut states);
+        for &index in slot.iter() {
+            prefetch_line(&buffer[index]);
+        }
+    }
+
+    let mut slot = 0;
+    for iter in 0..iterations {
+        let current = ahead[slot];
+        ahead[slot] = advance(&mut states);
+        for &index in ahead[slot].iter() {
+            prefetch_line(&buffer[index]);
+        }
+
+        for (stream_id, &index) in current.iter().enumerate() {
+            let value = black_box(buffer[index]);
+            let new_value = value
+                .wrapping_mul(mults[stream_id])
+                .wrapping_add(iter.wrapping_add(access.lcg_add));
+            buffer[index] = black_box(new_value);
+        }
+
+        slot += 1;
+        if slot == distance {
+            slot = 0;
+        }
+    }
+}
+
+/// Hints that the line holding `word` will be read soon, into every cache
+/// level. A no-op on targets without a prefetch instruction.
+#[inline(always)]
+fn prefetch_line(word: &u64) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use std::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+        #[allow(unused_unsafe)]
+        unsafe {
+            _mm_prefetch::<_MM_HINT_T0>((word as *const u64).cast())
+        };
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        std::arch::asm!(
+            "prfm pldl1keep, [{addr}]",
+            addr = in(reg) word as *const u64,
+            options(nostack, readonly, preserves_flags)
+        );
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    let _ = word;
+}
+
+/// Cache refill test - sequential read-modify-write sweeps, each touching
+/// every word of the buffer once before starting over. Sized to overflow a
+/// cache level (see `system::CacheSweep`), so each element is a miss in that
+/// level; the position carries over between batches in `cursor`.
+#[inline(always)]
+pub fn stress_cache_thrash(iterations: u64, buffer: &mut [u64], cursor: &mut usize) {
+    if buffer.is_empty() {
+        return;
+    }
+
+    let len = buffer.len();
+    let mut index = *cursor % len;
+    for _ in 0..iterations {
+        buffer[index] = black_box(buffer[index].wrapping_add(1));
+        index += 1;
+        if index == len {
+            index = 0;
+        }
+    }
+    *cursor = index;
+}
+
+pub fn allocate_memory_buffer(
+    size_mb: usize,
+    pattern: InitPattern,
+) -> Result<Box<[u64]>, String> {
+    allocate_buffer_bytes(buffer_bytes(size_mb)?, pattern)
+}
+
+/// A `size_mb` buffer in bytes, or why this target can't hold one; the
+/// check `allocate_memory_buffer` makes, for a plan that allocates nothing.
+pub fn buffer_bytes(size_mb: usize) -> Result<usize, String> {
+    let bytes = (size_mb as u64).saturating_mul(1024 * 1024);
+    addressable_bytes(bytes, MAX_ALLOCATION_BYTES)
+}
+
+/// Largest single allocation Rust allows: isize::MAX bytes, just under
+/// 2 GiB on 32-bit targets.
+pub const MAX_ALLOCATION_BYTES: u64 = isize::MAX as u64;
+
+/// `bytes` as a usize, or an error naming the target's limit when it's over
+/// `limit` (which fits in usize) rather than a failed or wrapped allocation.
+fn addressable_bytes(bytes: u64, limit: u64) -> Result<usize, String> {
+    if bytes > limit {
+        return Err(format!(
+            "requested size {} exceeds addressable memory on this target ({}-bit, at most {} \
+             per buffer)",
+            format_bytes(bytes),
+            usize::BITS,
+            format_bytes(limit)
+        ));
+    }
+    Ok(bytes as usize)
+}
+
+/// `bytes` rounded down to whole words, filled with `pattern` so no page
+/// stays untouched.
+/// An empty buffer is an error: the kernels would return without touching
+/// memory and the run would count ops it never did.
+///
+/// The fill runs on the calling thread and nowhere else. Under the default
+/// local policy a page lands on the node of the thread that first writes
+/// it, which is why workers allocate their own buffers; splitting the fill
+/// across helper threads would be faster to start but would scatter each
+/// buffer over whichever nodes the helpers ran on.
+pub fn allocate_buffer_bytes(
+    bytes: usize,
+    pattern: InitPattern,
+) -> Result<Box<[u64]>, String> {
     let elem_size = std::mem::size_of::<u64>();
     let num_elements = bytes / elem_size;
+    if num_elements == 0 {
+        return Err(format!(
+            "buffer size resolved to {}, too small for a single element",
+            format_bytes(bytes as u64)
+        ));
+    }
+
+    let mut buffer: Vec<u64> = Vec::new();
+    buffer.try_reserve_exact(num_elements).map_err(|e| {
+        format!(
+            "failed to allocate {} buffer: {}",
+            format_bytes(bytes as u64),
+            e
+        )
+    })?;
+    // Placement is decided on first touch, so bind before filling
+    numa::bind_buffer(buffer.as_ptr().cast(), num_elements * elem_size);
+    let words = num_elements as u64;
+    let mut rng = SplitMix64(0x9e3779b97f4a7c15);
+    match pattern {
+        InitPattern::Sequential => buffer.extend((0..words).map(|i| i ^ 0xdeadbeef)),
+        InitPattern::Random => buffer.extend((0..num_elements).map(|_| rng.next_word())),
+        InitPattern::Cycle => {
+            buffer.extend(0..words);
+            sattolo_cycle(&mut buffer, &mut rng);
+        },
+    }
+    Ok(buffer.into_boxed_slice())
+}
 
-    let mut buffer = Vec::with_capacity(num_elements);
-    for i in 0..num_elements {
-        buffer.push((i as u64) ^ 0xdeadbeef);
+/// Shuffles the identity permutation in `slots` into a single cycle through
+/// all of them (Sattolo's algorithm: Fisher-Yates, but never swapping a slot
+/// with itself). Each swap is a random access, so on a buffer far past the
+/// last-level cache this takes a few seconds per GiB.
+fn sattolo_cycle(slots: &mut [u64], rng: &mut SplitMix64) {
+    for i in (1..slots.len()).rev() {
+        let j = (rng.next_word() % i as u64) as usize;
+        slots.swap(i, j);
+    }
+}
+
+/// Fixed-seed generator for buffer contents; quality only needs to beat
+/// the hardware prefetchers.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_word(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
     }
-    buffer.into_boxed_slice()
 }
 
 #[cfg(test)]
@@ -123,6 +1078,48 @@ mod tests {
         assert_ne!(acc, 0);
     }
 
+    #[test]
+    fn test_stress_integer_latency_prevents_optimization() {
+        let mut acc = 0u64;
+        stress_integer_latency(1000, &mut acc);
+        assert_ne!(acc, 0);
+
+        // Each step depends on the last, so a longer chain lands elsewhere.
+        let mut longer = 0u64;
+        stress_integer_latency(1001, &mut longer);
+        assert_ne!(longer, acc);
+    }
+
+    #[test]
+    fn test_integer_pair_both_run() {
+        let params = KernelParams {
+            seed: 7,
+            ..KernelParams::default()
+        };
+        let mut throughput = Integer::new(&params);
+        let mut latency = IntegerLatency::new(&params);
+        assert_eq!(throughput.run(500), 500);
+        assert_eq!(latency.run(500), 500);
+        assert_ne!(throughput.acc, 0);
+        assert_ne!(latency.acc, 0);
+    }
+
+    #[test]
+    fn test_mixed_counts_batches_under_three() {
+        let params = KernelParams {
+            seed: 7,
+            ..KernelParams::default()
+        };
+        let mut mixed = Mixed::new(&params);
+        mixed.attach_buffer((0..64u64).collect());
+        // Integer's first step adds 0, so only a batch of 2 moves it
+        assert_eq!(mixed.run(1), 1);
+        let start = mixed.int_acc;
+        assert_eq!(mixed.run(2), 2);
+        assert_ne!(mixed.int_acc, start);
+        assert_eq!(mixed.run(7), 7);
+    }
+
     #[test]
     fn test_stress_float_prevents_optimization() {
         let mut acc = 0.0f64;
@@ -131,6 +1128,139 @@ mod tests {
         assert_ne!(acc, 0.0);
     }
 
+    #[test]
+    fn test_both_float_modes_accumulate() {
+        for mode in [FloatMode::Simple, FloatMode::Transcendental] {
+            let mut float = Float::new(&KernelParams {
+                float_mode: mode,
+                ..KernelParams::default()
+            });
+            assert_eq!(float.run(1000), 1000);
+            assert!(float.acc.is_finite(), "{}", mode.as_str());
+            assert_ne!(float.acc, 0.0, "{}", mode.as_str());
+        }
+    }
+
+    #[test]
+    fn test_float_dispatch_falls_back_off_aarch64() {
+        let (_, simple) = FloatMode::Simple.kernel();
+        assert_eq!(simple.is_some(), cfg!(target_arch = "aarch64"));
+        assert_eq!(FloatMode::Transcendental.kernel().1, None);
+        assert_eq!(LCG_MULTS.len(), BANDWIDTH_STREAMS);
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    #[test]
+    fn test_neon_float_matches_scalar() {
+        for iterations in [0, 1, 1000, 1001] {
+            let (mut scalar, mut neon) = (3.0, 3.0);
+            stress_float_simple(iterations, &mut scalar);
+            stress_float_simple_neon(iterations, &mut neon);
+            // Summed in a different order, so only close
+            assert!((scalar - neon).abs() <= scalar.abs() * 1e-12, "{} vs {}", scalar, neon);
+        }
+    }
+
+    #[test]
+    fn test_mask_sized_mb_rounds_down_to_a_power_of_two() {
+        assert_eq!(mask_sized_mb(0), 0);
+        assert_eq!(mask_sized_mb(1), 1);
+        assert_eq!(mask_sized_mb(384), 256);
+        assert_eq!(mask_sized_mb(512), 512);
+        // A MiB is 2^17 words, so the element count is a power of two too
+        let elements = allocate_memory_buffer(mask_sized_mb(3), InitPattern::Sequential)
+            .unwrap()
+            .len();
+        assert!(elements.is_power_of_two());
+    }
+
+    #[test]
+    fn test_memory_kernels_stay_in_bounds_at_exact_sizes() {
+        // Not a power of two, so these take the remainder path
+        let mut buffer = allocate_buffer_bytes(8 * 1000, InitPattern::Sequential).unwrap();
+        stress_memory_latency(5000, &mut buffer);
+        stress_memory_latency_mlp(5000, &mut buffer, 4, &AccessPattern::default());
+        stress_memory_bandwidth(5000, &mut buffer, &AccessPattern::default());
+        let mut masked = allocate_buffer_bytes(8 * 1024, InitPattern::Sequential).unwrap();
+        stress_memory_latency(5000, &mut masked);
+        stress_memory_bandwidth(5000, &mut masked, &AccessPattern::default());
+    }
+
+    #[test]
+    fn test_cycle_pattern_visits_every_index_once() {
+        // Odd length, so the mask path can't hide a bias
+        let buffer = allocate_buffer_bytes(8 * 1001, InitPattern::Cycle).unwrap();
+        let mut seen = vec![false; buffer.len()];
+        let mut index = 0;
+        for _ in 0..buffer.len() {
+            assert!(!seen[index], "{} visited twice", index);
+            seen[index] = true;
+            index = buffer[index] as usize;
+        }
+        assert_eq!(index, 0, "the walk should close after every index");
+        assert!(seen.iter().all(|&s| s));
+
+        // Two chains share the iterations, 500 steps each along the cycle
+        let walk = |from: usize| (0..500).fold(from, |index, _| buffer[index] as usize);
+        let mut cursors = vec![0, 500];
+        stress_memory_chase(1000, &buffer, &mut cursors);
+        assert_eq!(cursors, [walk(0), walk(500)]);
+
+        // Cursors split the cycle into equal arcs, whatever indices they land on
+        let walk_from = |from: usize, steps: usize| {
+            (0..steps).fold(from, |index, _| buffer[index] as usize)
+        };
+        let cursors = cycle_cursors(&buffer, 4);
+        assert_eq!(cursors[0], 0);
+        for (k, pair) in cursors.windows(2).enumerate() {
+            let arc = (k + 1) * 1001 / 4 - k * 1001 / 4;
+            assert_eq!(walk_from(pair[0], arc), pair[1]);
+        }
+        assert_eq!(cycle_cursors(&buffer, 1), [0]);
+    }
+
+    #[test]
+    fn test_prefetch_distances_touch_the_buffer_in_bounds() {
+        for (bytes, distance) in [(8 * 1000, 1), (8 * 1024, 16), (8 * 1024, MAX_PREFETCH * 2)] {
+            let mut buffer = allocate_buffer_bytes(bytes, InitPattern::Sequential).unwrap();
+            let before = buffer.clone();
+            let access = AccessPattern::default();
+            stress_memory_bandwidth_prefetch(5000, &mut buffer, distance, &access);
+            assert_ne!(buffer, before, "distance {}", distance);
+        }
+    }
+
+    #[test]
+    fn test_ackermann_known_values() {
+        assert_eq!(ackermann(0, 4), 5);
+        assert_eq!(ackermann(1, 3), 5);
+        assert_eq!(ackermann(2, 3), 9);
+        assert_eq!(ackermann(2, RECURSION_MAX_N), 2 * RECURSION_MAX_N + 3);
+    }
+
+    #[test]
+    fn test_stress_recursion_accumulates_every_call() {
+        let mut acc = 0u64;
+        stress_recursion(RECURSION_MAX_N + 1, &mut acc);
+        // Sum of 2n + 3 for n in 0..=7
+        assert_eq!(acc, (0..=RECURSION_MAX_N).map(|n| 2 * n + 3).sum::<u64>());
+    }
+
+    #[test]
+    fn test_collatz_known_step_counts() {
+        assert_eq!(collatz_steps(1), 0);
+        assert_eq!(collatz_steps(6), 8);
+        assert_eq!(collatz_steps(27), 111);
+        assert_eq!(collatz_steps(0), 0);
+    }
+
+    #[test]
+    fn test_stress_collatz_accumulates_steps() {
+        let mut acc = 0u64;
+        stress_collatz(27, &mut acc);
+        assert_eq!(acc, (1..=27).map(collatz_steps).sum::<u64>());
+    }
+
     #[test]
     fn test_stress_memory_latency_modifies_buffer() {
         let mut buffer = vec![0u64; 16384].into_boxed_slice();
@@ -139,10 +1269,105 @@ mod tests {
         assert!(non_zero_count > 0);
     }
 
+    #[test]
+    fn test_single_chain_matches_memory_latency() {
+        let mut single = vec![0u64; 4096].into_boxed_slice();
+        let mut mlp = single.clone();
+        stress_memory_latency(5000, &mut single);
+        stress_memory_latency_mlp(5000, &mut mlp, 1, &AccessPattern::default());
+        assert_eq!(single, mlp);
+    }
+
+    #[test]
+    fn test_every_chain_advances_from_its_own_region() {
+        const CHAINS: usize = 8;
+        const LEN: usize = 8192;
+        let initial: Vec<u64> = (0..LEN as u64).map(|i| i ^ 0xdeadbeef).collect();
+        let mut buffer = initial.clone().into_boxed_slice();
+
+        let access = AccessPattern::default();
+        stress_memory_latency_mlp(CHAINS as u64 * 100, &mut buffer, CHAINS, &access);
+
+        // Every chain's first access lands on its own starting offset
+        for chain in 0..CHAINS {
+            let start = (LEN / CHAINS) * chain;
+            assert_ne!(buffer[start], initial[start], "chain {} never ran", chain);
+        }
+        let modified = buffer.iter().zip(&initial).filter(|(a, b)| a != b).count();
+        assert!(modified > CHAINS * 50, "chains should wander: {}", modified);
+    }
+
+    #[test]
+    fn test_chains_are_clamped_to_the_buffer() {
+        let mut buffer = vec![1u64; 4].into_boxed_slice();
+        stress_memory_latency_mlp(100, &mut buffer, MAX_CHAINS * 2, &AccessPattern::default());
+        stress_memory_latency_mlp(100, &mut [], 4, &AccessPattern::default());
+        stress_memory_latency_mlp(100, &mut buffer, 0, &AccessPattern::default());
+    }
+
+    #[test]
+    fn test_custom_access_patterns_still_modify_the_buffer() {
+        let custom = [
+            AccessPattern {
+                lcg_mult: Some(0x5851f42d4c957f2d),
+                ..AccessPattern::default()
+            },
+            AccessPattern {
+                lcg_add: 1442695040888963407,
+                ..AccessPattern::default()
+            },
+            AccessPattern {
+                stride: Some(8),
+                ..AccessPattern::default()
+            },
+            AccessPattern {
+                lcg_mult: Some(3),
+                lcg_add:  7,
+                stride:   Some(1 << 40),
+            },
+        ];
+        // A power-of-two length and one that isn't, for both index paths
+        for bytes in [8 * 4096, 8 * 1000] {
+            let initial = allocate_buffer_bytes(bytes, InitPattern::Sequential).unwrap();
+            let mut builtin = initial.clone();
+            stress_memory_bandwidth(2000, &mut builtin, &AccessPattern::default());
+            for access in &custom {
+                let mut latency = initial.clone();
+                stress_memory_latency_mlp(2000, &mut latency, 4, access);
+                let mut bandwidth = initial.clone();
+                stress_memory_bandwidth(2000, &mut bandwidth, access);
+                let mut prefetched = initial.clone();
+                stress_memory_bandwidth_prefetch(2000, &mut prefetched, 8, access);
+                assert_ne!(latency, initial, "{:?}", access);
+                assert_ne!(bandwidth, initial, "{:?}", access);
+                assert_ne!(prefetched, initial, "{:?}", access);
+                assert_ne!(bandwidth, builtin, "{:?} changed nothing", access);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stride_coverage_follows_the_gcd() {
+        let initial = vec![1u64; 64].into_boxed_slice();
+        let touched = |stride| {
+            let mut buffer = initial.clone();
+            let access = AccessPattern {
+                stride: Some(stride),
+                ..AccessPattern::default()
+            };
+            stress_memory_latency_mlp(640, &mut buffer, 1, &access);
+            buffer.iter().zip(&initial).filter(|(a, b)| a != b).count()
+        };
+        assert_eq!(touched(1), 64);
+        assert_eq!(touched(3), 64);
+        assert_eq!(touched(2), 32);
+        assert_eq!(touched(64), 1, "a multiple of the length stays put");
+    }
+
     #[test]
     fn test_stress_memory_bandwidth_modifies_buffer() {
         let mut buffer = vec![0u64; 16384].into_boxed_slice();
-        stress_memory_bandwidth(5000, &mut buffer);
+        stress_memory_bandwidth(5000, &mut buffer, &AccessPattern::default());
         let non_zero_count = buffer.iter().filter(|&&x| x != 0).count();
         assert!(non_zero_count > 0);
     }
@@ -152,7 +1377,7 @@ mod tests {
         let mut buffer = vec![0u64; 8192].into_boxed_slice();
 
         let initial_buffer = buffer.to_vec();
-        stress_memory_bandwidth(1000, &mut buffer);
+        stress_memory_bandwidth(1000, &mut buffer, &AccessPattern::default());
 
         let modified_count = buffer
             .iter()
@@ -167,9 +1392,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_memory_buffer_size_overflow_is_an_error() {
+        let huge = allocate_memory_buffer(usize::MAX, InitPattern::Sequential).unwrap_err();
+        assert!(huge.contains("exceeds addressable memory"), "{}", huge);
+
+        // A 32-bit target's limits: 4 GiB is one byte too many
+        let limit = u32::MAX as u64;
+        assert_eq!(addressable_bytes(limit, limit), Ok(u32::MAX as usize));
+        let over = addressable_bytes(4096 << 20, limit).unwrap_err();
+        assert!(over.contains("4 GiB") && over.contains("per buffer"), "{}", over);
+        assert!(addressable_bytes(2048 << 20, i32::MAX as u64).is_err());
+    }
+
+    #[test]
+    fn test_empty_buffer_is_an_error() {
+        let empty = allocate_memory_buffer(0, InitPattern::Sequential).unwrap_err();
+        assert!(empty.contains("0 B"));
+        assert!(allocate_buffer_bytes(7, InitPattern::Sequential).is_err());
+        assert_eq!(allocate_buffer_bytes(8, InitPattern::Sequential).unwrap().len(), 1);
+    }
+
     #[test]
     fn test_memory_buffer_allocation() {
-        let buffer = allocate_memory_buffer(1);
+        let buffer = allocate_memory_buffer(1, InitPattern::Sequential).unwrap();
         let expected_elements = 1024 * 1024 / 8;
         assert_eq!(buffer.len(), expected_elements);
 
@@ -205,7 +1451,7 @@ mod tests {
     fn test_memory_bandwidth_parallel_phases() {
         let mut buffer = vec![0u64; 8192].into_boxed_slice();
 
-        stress_memory_bandwidth(100, &mut buffer);
+        stress_memory_bandwidth(100, &mut buffer, &AccessPattern::default());
 
         // Verify buffer was modified
         let non_zero_count = buffer.iter().filter(|&&x| x != 0).count();
@@ -224,4 +1470,22 @@ mod tests {
             "Should modify multiple stream regions"
         );
     }
+
+    #[test]
+    fn test_cache_thrash_traverses_the_whole_buffer_each_batch() {
+        let mut buffer = vec![0u64; 1000].into_boxed_slice();
+        let mut cursor = 0;
+
+        stress_cache_thrash(1000, &mut buffer, &mut cursor);
+        assert!(buffer.iter().all(|&x| x == 1));
+        assert_eq!(cursor, 0);
+
+        // A batch that starts mid-buffer wraps around and still touches
+        // every word exactly once
+        stress_cache_thrash(400, &mut buffer, &mut cursor);
+        stress_cache_thrash(1000, &mut buffer, &mut cursor);
+        assert_eq!(cursor, 400);
+        assert!(buffer[..400].iter().all(|&x| x == 3));
+        assert!(buffer[400..].iter().all(|&x| x == 2));
+    }
 }
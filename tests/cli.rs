@@ -0,0 +1,818 @@
+//! The subcommands and the pre-subcommand flat form must resolve to the same
+//! run. `--dry-run` prints the header and plan without starting workers.
+
+use std::process::{Command, Output};
+
+fn locus(args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_locus"));
+    for (key, _) in std::env::vars().filter(|(key, _)| key.starts_with("LOCUS_")) {
+        command.env_remove(key);
+    }
+    command.args(args).output().expect("failed to run locus")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+fn assert_same_run(new: &[&str], legacy: &[&str]) {
+    let new_out = locus(new);
+    let legacy_out = locus(legacy);
+
+    assert!(new_out.status.success(), "{:?} failed: {:?}", new, new_out);
+    assert_eq!(new_out.status.code(), legacy_out.status.code());
+    assert_eq!(unstamped(&new_out), unstamped(&legacy_out));
+}
+
+/// Stdout without the lines that name one particular run.
+fn unstamped(output: &Output) -> String {
+    stdout(output)
+        .lines()
+        .filter(|line| !line.contains("\"run_id\"") && !line.contains("\"timestamp\""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn run_matches_bare_invocation() {
+    assert_same_run(
+        &["run", "-w", "float", "-d", "10", "-j", "2", "--dry-run"],
+        &["-w", "float", "-d", "10", "-j", "2", "--dry-run"],
+    );
+    assert_same_run(
+        &[
+            "run",
+            "--split",
+            "integer=1,memory-bandwidth=1",
+            "-j",
+            "2",
+            "-m",
+            "8",
+            "--dry-run",
+        ],
+        &[
+            "--split",
+            "integer=1,memory-bandwidth=1",
+            "-j",
+            "2",
+            "-m",
+            "8",
+            "--dry-run",
+        ],
+    );
+}
+
+#[test]
+fn bench_matches_benchmark_flag() {
+    assert_same_run(&["bench", "-d", "5", "-j", "2", "-m", "8", "--dry-run"], &[
+        "--benchmark",
+        "-d",
+        "5",
+        "-j",
+        "2",
+        "-m",
+        "8",
+        "--dry-run",
+    ]);
+    assert_same_run(
+        &[
+            "bench",
+            "-d",
+            "5",
+            "-j",
+            "2",
+            "-m",
+            "8",
+            "--dry-run",
+            "--format",
+            "json",
+        ],
+        &[
+            "-B",
+            "-d",
+            "5",
+            "-j",
+            "2",
+            "-m",
+            "8",
+            "--dry-run",
+            "--format",
+            "json",
+        ],
+    );
+}
+
+#[test]
+fn bench_total_time_counts_the_selected_set() {
+    for (set, workloads) in [("default", 5), ("memory", 4), ("all", 9)] {
+        let output = locus(&[
+            "bench",
+            "-d",
+            "3",
+            "-j",
+            "1",
+            "-m",
+            "8",
+            "--benchmark-set",
+            set,
+            "--dry-run",
+        ]);
+        assert!(output.status.success(), "{}: {:?}", set, output);
+        let expected = format!("Total time: ~{}s ({} workloads)", 3 * workloads, workloads);
+        assert!(stdout(&output).contains(&expected), "{}: {}", set, stdout(&output));
+    }
+
+    let outside = locus(&[
+        "bench",
+        "-d",
+        "3",
+        "--workload-durations",
+        "cache-thrash=5",
+        "--dry-run",
+    ]);
+    assert_eq!(outside.status.code(), Some(1), "{:?}", outside);
+}
+
+#[test]
+fn dump_config_matches_across_forms() {
+    assert_same_run(
+        &["run", "-w", "integer", "--stagger", "5", "--dump-config"],
+        &["-w", "integer", "--stagger", "5", "--dump-config"],
+    );
+}
+
+#[test]
+fn subcommands_reject_options_they_dont_take() {
+    let output = locus(&["run", "--benchmark"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not a `locus run` option"));
+
+    assert!(!locus(&["bench", "-w", "float", "-d", "5"]).status.success());
+}
+
+#[test]
+fn zero_batch_size_is_rejected_on_every_path() {
+    let flag = locus(&["-w", "integer", "-b", "0", "--once"]);
+    assert_eq!(flag.status.code(), Some(2), "{:?}", flag);
+    assert!(stderr(&flag).contains("must be at least 1"), "{:?}", flag);
+
+    let env = Command::new(env!("CARGO_BIN_EXE_locus"))
+        .args(["-w", "integer", "--once"])
+        .env("LOCUS_BATCH_SIZE", "0")
+        .output()
+        .expect("failed to run locus");
+    assert_eq!(env.status.code(), Some(2), "{:?}", env);
+}
+
+#[test]
+fn info_and_help_pages() {
+    let info = locus(&["info"]);
+    assert!(info.status.success());
+    assert!(stdout(&info).contains("memory-bandwidth"));
+
+    let help = stdout(&locus(&["bench", "--help"]));
+    assert!(help.contains("--bench-warmup"));
+    assert!(!help.contains("--split"));
+    assert!(stdout(&locus(&["--help"])).contains("COMMANDS:"));
+}
+
+#[test]
+fn memory_alias_warns_once_and_records_the_canonical_name() {
+    let output = locus(&[
+        "run",
+        "-w",
+        "memory",
+        "--split",
+        "memory=1",
+        "-j",
+        "1",
+        "-m",
+        "8",
+        "--format",
+        "json",
+        "--dry-run",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        stderr.matches("'memory' is deprecated").count(),
+        1,
+        "{}",
+        stderr
+    );
+    let json = stdout(&output);
+    assert!(json.contains("\"memory-latency\""), "{}", json);
+    assert!(!json.contains("\"memory\""), "{}", json);
+}
+
+#[test]
+fn silent_prints_nothing_on_success() {
+    let output = locus(&[
+        "run", "-w", "memory", "-d", "1", "-j", "1", "-m", "8", "--silent",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stdout.is_empty(), "stdout: {}", stdout(&output));
+    assert!(
+        output.stderr.is_empty(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The file still gets the report
+    let path = std::env::temp_dir().join(format!("locus-silent-{}.json", std::process::id()));
+    let output = locus(&[
+        "-w",
+        "integer",
+        "-d",
+        "1",
+        "-j",
+        "1",
+        "-s",
+        "--format",
+        "json",
+        "--out",
+        path.to_str().unwrap(),
+    ]);
+    let report = std::fs::read_to_string(&path).unwrap_or_default();
+    std::fs::remove_file(&path).ok();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(
+        output.stdout.is_empty() && output.stderr.is_empty(),
+        "{:?}",
+        output
+    );
+    assert!(report.contains("\"total_ops\""), "{}", report);
+}
+
+#[test]
+fn quiet_keeps_the_final_summary() {
+    let output = locus(&["run", "-w", "integer", "-d", "1", "-j", "1", "-q"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(
+        stdout(&output).contains("TEST COMPLETE"),
+        "{}",
+        stdout(&output)
+    );
+}
+
+#[test]
+fn min_rate_gates_the_exit_code() {
+    let run = |min_rate: &str| {
+        locus(&[
+            "run",
+            "-w",
+            "integer",
+            "-j",
+            "1",
+            "-d",
+            "1",
+            "-q",
+            "--min-rate",
+            min_rate,
+        ])
+    };
+
+    let low = run("1");
+    assert!(low.status.success(), "a 1 ops/s floor passes: {:?}", low);
+
+    let high = run("1000B");
+    assert_eq!(high.status.code(), Some(3), "{:?}", high);
+    let stderr = String::from_utf8_lossy(&high.stderr);
+    assert!(stderr.contains("below threshold"), "{}", stderr);
+    assert!(stderr.contains("--min-rate 1000.00B/s"), "{}", stderr);
+}
+
+#[test]
+fn ops_stops_after_a_fixed_amount_of_work() {
+    let output = locus(&[
+        "run", "-w", "integer", "-j", "2", "-b", "10000", "--ops", "2M", "-q", "--format",
+        "json",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let total_ops = report["total_ops"].as_u64().unwrap();
+    // Each worker may finish the batch it was in
+    assert!(
+        (2_000_000..=2_000_000 + 2 * 10_000).contains(&total_ops),
+        "{}",
+        total_ops
+    );
+
+    let both = locus(&["run", "--ops", "1M", "-d", "5"]);
+    assert_eq!(both.status.code(), Some(1), "{:?}", both);
+}
+
+#[test]
+fn reports_process_cpu_time_after_a_run() {
+    let output = locus(&[
+        "run", "-w", "integer", "-j", "1", "-d", "1", "-q", "--format", "json",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let cpu_secs = report["process_cpu_secs"].as_f64().unwrap();
+    assert!(cpu_secs > 0.0, "{}", cpu_secs);
+}
+
+#[test]
+fn json_report_names_the_run_and_its_config() {
+    let output = locus(&[
+        "run", "-w", "integer", "-j", "1", "--once", "-q", "--format", "json",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let metadata = &report["metadata"];
+    assert_eq!(metadata["run_id"].as_str().unwrap().len(), 36);
+    assert!(!metadata["hostname"].as_str().unwrap().is_empty());
+    let config = metadata["config"].as_str().unwrap();
+    assert!(config.contains("workload = \"integer\""), "{}", config);
+    assert!(config.contains("threads = 1"), "{}", config);
+}
+
+#[test]
+fn once_runs_every_workload_for_a_batch() {
+    let list = locus(&["--list-workloads"]);
+    let names: Vec<String> = stdout(&list)
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next().map(str::to_string))
+        .collect();
+    assert!(names.len() >= 8, "{:?}", names);
+
+    for name in &names {
+        let output = locus(&[
+            "run", "-w", name, "--once", "-j", "2", "-m", "8", "-q", "--format", "json",
+        ]);
+        assert!(output.status.success(), "{}: {:?}", name, output);
+        let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+        let total_ops = report["total_ops"].as_u64().unwrap();
+        assert!(total_ops > 0, "{} did no work", name);
+    }
+
+    let timed = locus(&["run", "--once", "-d", "5"]);
+    assert_eq!(timed.status.code(), Some(1), "{:?}", timed);
+
+    // The stagger outlasts integer's batch, so it's done before the window
+    // opens and the smoke test has nothing to show for it
+    let idle = locus(&[
+        "run", "--split", "integer=1,float=1", "-j", "2", "--once", "--stagger", "500", "-q",
+    ]);
+    assert_eq!(idle.status.code(), Some(1), "{:?}", idle);
+    assert!(stderr(&idle).contains("--once measured no ops for integer;"), "{:?}", idle);
+}
+
+#[test]
+fn reports_allocation_time_for_memory_workloads() {
+    let run = |workload| {
+        let output = locus(&[
+            "run", "-w", workload, "--once", "-j", "1", "-m", "16", "-q", "--format", "json",
+        ]);
+        assert!(output.status.success(), "{:?}", output);
+        serde_json::from_str::<serde_json::Value>(&stdout(&output)).unwrap()
+    };
+    let secs = run("memory-latency")["allocation_secs"].as_f64().unwrap();
+    assert!(secs > 0.0, "{}", secs);
+    assert!(run("integer")["allocation_secs"].is_null());
+}
+
+#[test]
+fn buffers_round_down_to_a_power_of_two_unless_exact() {
+    let run = |extra: &[&str]| {
+        let mut args = vec![
+            "run", "-w", "memory-latency", "--once", "-j", "2", "-m", "12", "-q", "--format",
+            "json",
+        ];
+        args.extend_from_slice(extra);
+        let output = locus(&args);
+        assert!(output.status.success(), "{:?}", output);
+        serde_json::from_str::<serde_json::Value>(&stdout(&output)).unwrap()
+    };
+    let rounded = run(&[]);
+    assert_eq!(rounded["buffer_rounding"]["requested_mb"], 12);
+    assert_eq!(rounded["buffer_rounding"]["effective_mb"], 8);
+    assert_eq!(rounded["planned_memory_mb"], 16);
+
+    let exact = run(&["--exact-buffer"]);
+    assert!(exact["buffer_rounding"].is_null());
+    assert_eq!(exact["planned_memory_mb"], 24);
+
+    let output = locus(&[
+        "bench",
+        "-j",
+        "1",
+        "-d",
+        "1",
+        "--bench-warmup",
+        "0",
+        "-m",
+        "12",
+        "--benchmark-set",
+        "memory",
+        "-q",
+        "--format",
+        "json",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let bench: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(bench["buffer_rounding"]["requested_mb"], 12);
+    assert_eq!(bench["buffer_rounding"]["effective_mb"], 8);
+}
+
+#[test]
+fn jsonl_progress_streams_snapshots_to_stderr() {
+    let output = locus(&[
+        "run",
+        "-w",
+        "integer",
+        "-j",
+        "1",
+        "-d",
+        "4",
+        "--progress-format",
+        "jsonl",
+        "--format",
+        "json",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let snapshots: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(snapshots.len() >= 2, "{:?}", snapshots);
+
+    for pair in snapshots.windows(2) {
+        assert!(pair[1]["total_ops"].as_u64() >= pair[0]["total_ops"].as_u64());
+        assert!(pair[1]["elapsed_secs"].as_f64() > pair[0]["elapsed_secs"].as_f64());
+    }
+    assert_eq!(snapshots[0]["workload"], "integer");
+    assert!(snapshots[0]["interval_ops_per_sec"].as_u64().unwrap() > 0);
+    assert!(snapshots[0].get("interval_gb_per_sec").is_none());
+    // The final report on stdout is still one JSON document
+    serde_json::from_str::<serde_json::Value>(&stdout(&output)).unwrap();
+}
+
+#[test]
+fn log_status_prints_blocks_on_its_own_lines() {
+    let output = locus(&[
+        "run", "-w", "integer", "-j", "1", "-d", "5", "--log-status", "2", "--quiet",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = stderr(&output);
+    // Due at 2s and 4s; the run stops just after 5
+    let blocks: Vec<&str> = stderr.split("[Status] ").skip(1).collect();
+    assert_eq!(blocks.len(), 2, "{}", stderr);
+    for block in blocks {
+        assert!(block.contains("\n  Total ops:   "), "{}", block);
+        assert!(block.contains("\n  Peak rate:   "), "{}", block);
+    }
+    assert!(!stderr.contains('\r'), "{:?}", stderr);
+}
+
+#[test]
+fn compare_flag_tables_saved_results() {
+    let dir = std::env::temp_dir().join(format!("locus-cli-compare-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let save = |name: &str, results: &[(&str, u64)]| {
+        let results: Vec<serde_json::Value> = results
+            .iter()
+            .map(|(workload, rate)| {
+                serde_json::json!({
+                    "workload": workload,
+                    "ops_per_sec": rate,
+                    "relative_to_mixed": 1.0,
+                    "per_thread_ops_per_sec": rate / 4,
+                })
+            })
+            .collect();
+        let report = serde_json::json!({
+            "version": "0.1.0",
+            "virtualization": "bare-metal",
+            "threads": 4,
+            "duration_secs": 5,
+            "results": results,
+            "events": [],
+        });
+        let path = dir.join(name);
+        std::fs::write(&path, report.to_string()).unwrap();
+        path.display().to_string()
+    };
+    let desktop = save("desktop.json", &[
+        ("integer", 4_000_000),
+        ("float", 2_000_000),
+    ]);
+    let laptop = save("laptop.json", &[("integer", 3_000_000)]);
+
+    let flag = locus(&["--compare", &desktop, &laptop]);
+    let command = locus(&["compare", &desktop, &laptop]);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(flag.status.success(), "{:?}", flag);
+    assert_eq!(stdout(&flag), stdout(&command));
+
+    let table = stdout(&flag);
+    let row = |name: &str| table.lines().find(|line| line.starts_with(name)).unwrap();
+    assert!(row("Workload").contains("desktop") && row("Workload").contains("laptop"));
+    assert!(row("Integer").contains("4.00M /s *") && row("Integer").contains("3.00M /s"));
+    assert!(row("Float").contains("n/a"));
+    // Nothing ran: no header, no progress
+    assert!(!table.contains("[Running]"));
+}
+
+/// The output with every number (and the unit glued to it) as `#`, runs of
+/// spaces collapsed, one line per `\r` redraw and repeats dropped, so runs
+/// on different machines compare equal.
+fn shape(text: &str) -> Vec<String> {
+    const UNITS: [&str; 10] = [
+        " KiB", " MiB", " GiB", " MB", " GB", "K", "M", "B", "T", "s",
+    ];
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.split(['\n', '\r']) {
+        let mut line = String::new();
+        let mut rest = raw;
+        while let Some(c) = rest.chars().next() {
+            if c.is_ascii_digit() {
+                rest = rest
+                    .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == ',');
+                if let Some(unit) = UNITS.iter().find(|unit| rest.starts_with(**unit)) {
+                    rest = &rest[unit.len()..];
+                }
+                line.push('#');
+            } else {
+                line.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() && lines.last() != Some(&line) {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn run_output_keeps_its_shape() {
+    let output = locus(&["run", "-w", "integer", "-j", "1", "-d", "3"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let progress = shape(&stderr(&output));
+    let from = progress
+        .iter()
+        .position(|l| l.starts_with("[Running]"))
+        .unwrap();
+    let progress: Vec<&str> = progress[from..]
+        .iter()
+        .map(String::as_str)
+        // Only there when the shared sandbox throttled the run
+        .filter(|line| !line.starts_with("[!] Rate down"))
+        .collect();
+    assert_eq!(progress, [
+        "[Running] Total ops: # | Rate: #/s (EMA #/s)",
+        "[✓] Time limit reached. Stopping...",
+    ]);
+
+    let lines = shape(&stdout(&output));
+    let rule = "═".repeat(60);
+    let from = lines.iter().position(|l| l == "TEST COMPLETE").unwrap() - 1;
+    let shown: Vec<&str> = lines[from..]
+        .iter()
+        .map(String::as_str)
+        // Only there when the shared sandbox throttled the run
+        .filter(|line| !line.contains("below peak"))
+        // The run id is random hex, which shape() can't pin down
+        .map(|line| if line.starts_with("Run: ") { "Run: …" } else { line })
+        .collect();
+    // How many bins a short run fills depends on how far its rates spread
+    let bins = shown.iter().filter(|line| line.starts_with("#-#/s")).count();
+    assert!((1..=10).contains(&bins), "{:?}", shown);
+    let shown: Vec<&str> = shown
+        .into_iter()
+        .filter(|line| !line.starts_with("#-#/s"))
+        .collect();
+    assert_eq!(shown, [
+        &rule,
+        "TEST COMPLETE",
+        &rule,
+        "Run: …",
+        "Elapsed: #",
+        "Total ops: #",
+        "Avg rate: #/s",
+        "Peak rate: #/s",
+        "Sustained: #/s (median of last half)",
+        "Percentiles: p# #/s | p# #/s | p# #/s",
+        "Histogram: # one-second rates",
+        "Swapping: none observed",
+        "Process CPU: # over # wall (# of # threads busy)",
+        "Peak RSS: #",
+        "CPU time: #% of wall time",
+        &rule,
+    ]);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn bench_output_keeps_its_shape() {
+    let output = locus(&[
+        "bench",
+        "-j",
+        "1",
+        "-d",
+        "1",
+        "--bench-warmup",
+        "0",
+        "-m",
+        "8",
+        "--benchmark-set",
+        "all",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let lines = shape(&stderr(&output));
+    let from = lines.iter().position(|l| l.starts_with("[→]")).unwrap();
+    let (progress, steps): (Vec<&str>, Vec<&str>) = lines[from..]
+        .iter()
+        .map(String::as_str)
+        .partition(|line| line.starts_with("[Running]"));
+    // Whether a one-second workload gets a progress tick in is a race
+    for line in progress {
+        assert!(
+            line == "[Running] Total ops: # | Rate: #/s"
+                || line == "[Running] Total ops: # | Rate: #/s, # ops/s",
+            "{}",
+            line
+        );
+    }
+
+    let mut expected = Vec::new();
+    for name in [
+        "integer",
+        "integer-latency",
+        "float",
+        "recursion",
+        "collatz",
+        "mixed",
+        "memory-latency",
+        "memory-bandwidth",
+        "cache-thrash",
+    ] {
+        expected.push(format!(
+            "[→] Running workload #/# ({}) — suite #% — ETA #",
+            name
+        ));
+        expected.push("[✓] Complete: # ops, setup #, measured #".to_string());
+    }
+    assert_eq!(steps, expected);
+
+    let lines = shape(&stdout(&output));
+    let from = lines.iter().position(|l| l == "BENCHMARK RESULTS").unwrap() - 1;
+    let shown: Vec<&str> = lines[from..].iter().map(String::as_str).collect();
+    let rule = "═".repeat(68);
+    assert_eq!(shown[..3], [rule.as_str(), "BENCHMARK RESULTS", &rule]);
+    assert!(shown[3].starts_with("Run: "));
+    assert!(shown.contains(&"│ Integer │ # /s │ #x │ # /s │"));
+    assert!(shown.contains(&"Baseline: Mixed = #x | Threads: #"));
+    assert!(shown.contains(&"Score: #/s (geometric mean of the rates)"));
+    assert_eq!(shown.last(), Some(&"Swapping: none observed"));
+}
+
+/// `locus ... > results.txt` keeps the header and report; warnings, notices
+/// and the live progress line all go to the terminal.
+#[test]
+#[cfg(target_os = "linux")]
+fn warnings_and_progress_stay_off_stdout() {
+    let output = locus(&[
+        "run",
+        "-w",
+        "integer",
+        "-j",
+        "1",
+        "-d",
+        "2",
+        "--affinity-reverse",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let (out, err) = (stdout(&output), stderr(&output));
+
+    assert!(err.contains("--affinity-reverse has no effect"), "{}", err);
+    assert!(err.contains("[Running]") && err.contains("WARNING"), "{}", err);
+    for chatter in ["[!]", "[Running]", "[✓]", "[Auto-detect]", "Warning", "WARNING", "\r"] {
+        assert!(!out.contains(chatter), "{:?} on stdout:\n{}", chatter, out);
+    }
+    assert!(out.contains("Locus v") && out.contains("TEST COMPLETE"), "{}", out);
+}
+
+#[test]
+fn interference_plans_both_sides_at_once() {
+    let output = locus(&[
+        "run",
+        "--interference",
+        "memory-bandwidth:2,integer:1",
+        "-m",
+        "8",
+        "-d",
+        "10",
+        "--dry-run",
+        "--format",
+        "json",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let plan: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(plan["mode"], "interference");
+    assert_eq!(plan["threads"], 3);
+    assert_eq!(plan["peak_memory_mb"], 16);
+
+    let unpaired = locus(&["run", "--interference", "integer:2", "-d", "5", "--dry-run"]);
+    assert_eq!(unpaired.status.code(), Some(1), "{:?}", unpaired);
+    let untimed = locus(&["run", "--interference", "integer:1,float:1", "--dry-run"]);
+    assert_eq!(untimed.status.code(), Some(1), "{:?}", untimed);
+}
+
+#[test]
+fn junit_gates_bench_testcases() {
+    let baseline =
+        std::env::temp_dir().join(format!("locus-baseline-{}.json", std::process::id()));
+    let saved = serde_json::json!({
+        "version": "0.1.0",
+        "virtualization": "bare-metal",
+        "threads": 1,
+        "duration_secs": 1,
+        "results": [
+            {"workload": "integer", "ops_per_sec": 999_000_000_000u64,
+             "relative_to_mixed": 1.0, "per_thread_ops_per_sec": 999_000_000_000u64},
+            {"workload": "float", "ops_per_sec": 1,
+             "relative_to_mixed": 1.0, "per_thread_ops_per_sec": 1},
+        ],
+        "events": [],
+    });
+    std::fs::write(&baseline, saved.to_string()).unwrap();
+    let output = locus(&[
+        "bench",
+        "-j",
+        "1",
+        "-d",
+        "1",
+        "--bench-warmup",
+        "0",
+        "-m",
+        "8",
+        "--workload-durations",
+        "memory-latency=1",
+        "--min-score",
+        "999B",
+        "--baseline",
+        baseline.to_str().unwrap(),
+        "--format",
+        "junit",
+    ]);
+    std::fs::remove_file(&baseline).unwrap();
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    let xml = stdout(&output);
+    assert!(xml.starts_with("<?xml "), "{}", xml);
+    assert!(xml.contains(" tests=\"6\" failures=\"2\" "), "{}", xml);
+    assert!(xml.contains("<testcase name=\"memory-latency\" "), "{}", xml);
+    assert!(xml.contains("<failure message=\"below threshold: score "), "{}", xml);
+    assert!(xml.contains("<failure message=\"below threshold: integer "), "{}", xml);
+    // Far above its baseline, and not in it at all
+    assert!(xml.contains("vs baseline: +"), "{}", xml);
+    assert_eq!(xml.matches("vs baseline: ").count(), 2, "{}", xml);
+    assert!(stderr(&output).contains("--fail-threshold 10%"), "{:?}", output);
+
+    let missing = locus(&["bench", "-d", "1", "--baseline", "/nonexistent/locus.json"]);
+    assert_eq!(missing.status.code(), Some(1), "{:?}", missing);
+    assert!(stderr(&missing).contains("cannot read"), "{:?}", missing);
+
+    let run = locus(&["run", "-d", "1", "--format", "junit"]);
+    assert_eq!(run.status.code(), Some(1), "{:?}", run);
+    assert!(stderr(&run).contains("use it with bench"), "{:?}", run);
+}
+
+#[test]
+fn bandwidth_scaling_plans_the_widest_step() {
+    let output = locus(&[
+        "bench",
+        "--bandwidth-scaling",
+        "-j",
+        "4",
+        "-m",
+        "8",
+        "-d",
+        "2",
+        "--dry-run",
+        "--format",
+        "json",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let plan: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(plan["mode"], "bandwidth-scaling");
+    assert_eq!(plan["threads"], 4);
+    assert_eq!(plan["peak_memory_mb"], 32);
+
+    let untimed = locus(&["bench", "--bandwidth-scaling", "--dry-run"]);
+    assert_eq!(untimed.status.code(), Some(1), "{:?}", untimed);
+    let scored = locus(&["bench", "--bandwidth-scaling", "-d", "2", "--min-score", "5"]);
+    assert_eq!(scored.status.code(), Some(1), "{:?}", scored);
+}